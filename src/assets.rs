@@ -0,0 +1,212 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::network::{format_size, RequestRecord};
+
+// A file a page declares as part of its identity, e.g. a favicon or a social preview image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageAsset {
+    // Where it was declared: icon, apple-touch-icon, mask-icon, manifest, og:image, twitter:image, or
+    // favicon.ico for the implicit fallback when no icon is declared
+    pub kind: String,
+    pub url: String,
+    // As declared: sizes="32x32 16x16", a manifest icon's sizes, or og:image:width x og:image:height
+    pub sizes: Option<String>,
+    #[serde(rename = "type")]
+    pub mime: Option<String>,
+    // Set once downloaded, or why the download failed
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+// A file name per asset for saving them side by side: the kind, then the URL's last path segment
+// ("apple-touch-icon-touch.png", "og-image-hero.jpg"), numbered when two would collide
+pub fn file_names(assets: &[PageAsset]) -> Vec<String> {
+    let mut taken = HashSet::new();
+    assets.iter()
+        .map(|asset| {
+            let kind = asset.kind.replace([':', '.'], "-");
+            let segment = url::Url::parse(&asset.url).ok()
+                .filter(|url| url.scheme() != "data")
+                .and_then(|url| url.path_segments()?.rfind(|segment| !segment.is_empty()).map(str::to_string))
+                .map(|segment| segment.replace(['\\', ':'], "_"));
+            let base = match segment {
+                Some(segment) if segment.starts_with(&kind) || segment.starts_with(asset.kind.as_str()) => segment,
+                Some(segment) => format!("{}-{}", kind, segment),
+                None => kind,
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while !taken.insert(name.clone()) {
+                name = match base.rsplit_once('.') {
+                    Some((stem, extension)) => format!("{}-{}.{}", stem, n, extension),
+                    None => format!("{}-{}", base, n),
+                };
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+// One line per asset: kind, declared size and type, URL, and where it was saved (or why it wasn't)
+pub fn print_icons(assets: &[PageAsset]) {
+    if assets.is_empty() {
+        status!("{} No icons or preview images declared", "🖼️".cyan());
+        return;
+    }
+    for asset in assets {
+        let declared: Vec<&str> = [asset.sizes.as_deref(), asset.mime.as_deref()].into_iter().flatten().collect();
+        let declared = if declared.is_empty() { String::new() } else { format!(" ({})", declared.join(", ")) };
+        let outcome = match (&asset.path, &asset.error) {
+            (Some(path), _) => format!(" → {}", path.display()).dimmed().to_string(),
+            (None, Some(error)) => format!(" ✗ {}", error).red().to_string(),
+            (None, None) => String::new(),
+        };
+        status!("  {}{} {}{}", asset.kind.cyan(), declared, asset.url, outcome);
+    }
+    let saved = assets.iter().filter(|asset| asset.path.is_some()).count();
+    if saved > 0 {
+        status!("{} Saved {} of {} files", "💾".green(), saved, assets.len());
+    }
+}
+
+// A font face as the page reports it: from document.fonts, with the src URLs of the @font-face rule
+// that declared it when the stylesheet is readable (cross-origin sheets without CORS aren't)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeclaredFont {
+    pub family: String,
+    pub weight: String,
+    pub style: String,
+    // loaded, loading, unloaded (declared but nothing on the page needed it) or error
+    pub status: String,
+    #[serde(default)]
+    pub urls: Vec<String>,
+}
+
+// A web font in the inventory: a declared face, a font file the page downloaded, or both
+#[derive(Debug, Clone, Serialize)]
+pub struct FontAsset {
+    // None for a downloaded file no readable @font-face accounts for
+    pub family: Option<String>,
+    pub weight: Option<String>,
+    pub style: Option<String>,
+    pub status: Option<String>,
+    pub url: Option<String>,
+    // Transferred size, when the file was downloaded during the captured page load
+    pub bytes: Option<u64>,
+}
+
+// Declared faces matched with the font requests of the page load by URL; font files no face claims are
+// listed after them without a family
+pub fn font_inventory(declared: &[DeclaredFont], requests: &[RequestRecord]) -> Vec<FontAsset> {
+    let mut downloaded: HashMap<&str, Option<u64>> = requests.iter()
+        .filter(|request| request.resource_type == "Font")
+        .map(|request| (request.url.as_str(), request.encoded_size.map(|bytes| bytes as u64)))
+        .collect();
+    let mut fonts: Vec<FontAsset> = declared.iter()
+        .map(|face| {
+            // The first src the browser actually fetched, else the first one listed
+            let url = face.urls.iter().find(|url| downloaded.contains_key(url.as_str())).or(face.urls.first());
+            let bytes = url.and_then(|url| downloaded.remove(url.as_str())).flatten();
+            FontAsset {
+                family: Some(face.family.clone()),
+                weight: Some(face.weight.clone()),
+                style: Some(face.style.clone()),
+                status: Some(face.status.clone()),
+                url: url.cloned(),
+                bytes,
+            }
+        })
+        .collect();
+    let mut unclaimed: Vec<FontAsset> = downloaded.into_iter()
+        .map(|(url, bytes)| FontAsset { family: None, weight: None, style: None, status: None, url: Some(url.to_string()), bytes })
+        .collect();
+    unclaimed.sort_by(|a, b| a.url.cmp(&b.url));
+    fonts.extend(unclaimed);
+    fonts
+}
+
+pub fn print_fonts(fonts: &[FontAsset]) {
+    if fonts.is_empty() {
+        status!("{} No web fonts", "🔤".cyan());
+        return;
+    }
+    for font in fonts {
+        let face = match &font.family {
+            Some(family) => format!("{} {} {}", family.bold(), font.weight.as_deref().unwrap_or_default(), font.style.as_deref().unwrap_or_default()),
+            None => "(unknown family)".dimmed().to_string(),
+        };
+        let status = match font.status.as_deref() {
+            Some("loaded") | None => String::new(),
+            Some(status) => format!(" [{}]", status).yellow().to_string(),
+        };
+        let bytes = font.bytes.map(|bytes| format!(" {}", format_size(bytes as f64))).unwrap_or_default();
+        status!("  {}{}{} {}", face, status, bytes.cyan(), font.url.as_deref().unwrap_or("(no URL)").dimmed());
+    }
+    let total: u64 = fonts.iter().filter_map(|font| font.bytes).sum();
+    status!("{} {} font faces, {} downloaded", "🔤".cyan(), fonts.len(), format_size(total as f64));
+}
+
+// Heaviest files `assets report` lists per resource type
+pub const DEFAULT_REPORT_TOP: usize = 5;
+
+// Transferred bytes of one resource type in a page load, with its heaviest files
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetGroup {
+    pub resource_type: String,
+    pub count: usize,
+    pub bytes: u64,
+    pub heaviest: Vec<SizedAsset>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SizedAsset {
+    pub url: String,
+    pub bytes: u64,
+}
+
+// Requests grouped by resource type (Image, Script, Font, ...), heaviest group first, each with its `top`
+// largest files
+pub fn group_by_type(requests: &[RequestRecord], top: usize) -> Vec<AssetGroup> {
+    let mut groups: HashMap<&str, Vec<SizedAsset>> = HashMap::new();
+    for request in requests {
+        let resource_type = if request.resource_type.is_empty() { "Other" } else { request.resource_type.as_str() };
+        groups.entry(resource_type).or_default().push(SizedAsset {
+            url: request.url.clone(),
+            bytes: request.encoded_size.unwrap_or(0.0) as u64,
+        });
+    }
+    let mut groups: Vec<AssetGroup> = groups.into_iter()
+        .map(|(resource_type, mut assets)| {
+            assets.sort_by_key(|asset| std::cmp::Reverse(asset.bytes));
+            AssetGroup {
+                resource_type: resource_type.to_string(),
+                count: assets.len(),
+                bytes: assets.iter().map(|asset| asset.bytes).sum(),
+                heaviest: assets.into_iter().take(top).collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.resource_type.cmp(&b.resource_type)));
+    groups
+}
+
+pub fn print_report(groups: &[AssetGroup]) {
+    if groups.is_empty() {
+        status!("{} No requests captured for this page load; reload the page and try again", "📦".cyan());
+        return;
+    }
+    for group in groups {
+        status!("{} {} ({} files)", group.resource_type.bold(), format_size(group.bytes as f64).cyan(), group.count);
+        for asset in &group.heaviest {
+            status!("  {:>8} {}", format_size(asset.bytes as f64), asset.url.dimmed());
+        }
+    }
+    let (files, total) = groups.iter().fold((0, 0), |(files, total), group| (files + group.count, total + group.bytes));
+    status!("{} {} files, {} transferred", "📦".cyan(), files, format_size(total as f64));
+}
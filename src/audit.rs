@@ -0,0 +1,323 @@
+use colored::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditFinding {
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    pub target: Option<String>,
+}
+
+// Result of one audit; every `audit <kind>` command produces one of these so reports look alike
+#[derive(Debug, Clone)]
+pub struct AuditReport {
+    pub audit: String,
+    pub url: String,
+    pub findings: Vec<AuditFinding>,
+}
+
+impl AuditReport {
+    pub fn new(audit: &str, url: &str) -> Self {
+        Self {
+            audit: audit.to_string(),
+            url: url.to_string(),
+            findings: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, severity: Severity, rule: &str, message: impl Into<String>, target: Option<String>) {
+        self.findings.push(AuditFinding {
+            rule: rule.to_string(),
+            severity,
+            message: message.into(),
+            target,
+        });
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings.iter().filter(|f| f.severity == severity).count()
+    }
+
+    pub fn print(&self) {
+        status!("{} audit {} | {}", "🔎".cyan(), self.audit.bold(), self.url);
+
+        let mut findings = self.findings.iter().collect::<Vec<_>>();
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.rule.cmp(&b.rule)));
+        for finding in findings {
+            let marker = match finding.severity {
+                Severity::Error => "✗".red(),
+                Severity::Warning => "!".yellow(),
+            };
+            let target = finding.target.as_ref().map(|t| format!(" {}", t.dimmed())).unwrap_or_default();
+            status!("  {} [{}] {}{}", marker, finding.rule.cyan(), finding.message, target);
+        }
+
+        let (errors, warnings) = (self.count(Severity::Error), self.count(Severity::Warning));
+        if errors == 0 && warnings == 0 {
+            status!("{} No issues found", "✓".green());
+        } else {
+            status!("{} {} error(s), {} warning(s)", if errors > 0 { "✗".red() } else { "!".yellow() }, errors, warnings);
+        }
+    }
+}
+
+// One stop of a keyboard focus walk
+#[derive(Debug, Clone)]
+pub struct FocusStop {
+    pub selector: String,
+    pub role: String,
+    pub name: String,
+    pub focus_visible: bool,
+    pub tab_index: i64,
+}
+
+// How a focus walk ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum WalkEnd {
+    // Focus came back to the first stop or left the document
+    Completed,
+    // Focus stopped moving, or cycled through a subset of the page
+    Trapped(String),
+    MaxSteps,
+}
+
+// WCAG 2 contrast ratio between two opaque sRGB colours (0-255 channels)
+pub fn contrast_ratio(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let luminance = |rgb: [f64; 3]| {
+        let channel = |c: f64| {
+            let c = c / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * channel(rgb[0]) + 0.7152 * channel(rgb[1]) + 0.0722 * channel(rgb[2])
+    };
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+// Minimum ratios for normal and large text: (AA, AAA)
+pub fn contrast_thresholds(large_text: bool) -> (f64, f64) {
+    if large_text { (3.0, 4.5) } else { (4.5, 7.0) }
+}
+
+pub fn hex_color(rgb: [f64; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0].round() as u8, rgb[1].round() as u8, rgb[2].round() as u8)
+}
+
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "textarea", "title"];
+const OPTIONAL_END_ELEMENTS: [&str; 19] = [
+    "p", "li", "dt", "dd", "tr", "td", "th", "option", "optgroup", "thead", "tbody", "tfoot", "colgroup",
+    "caption", "rp", "rt", "html", "head", "body",
+];
+// Elements whose start tag implicitly closes an open <p>
+const BLOCK_ELEMENTS: [&str; 29] = [
+    "address", "article", "aside", "blockquote", "details", "div", "dl", "fieldset", "figcaption", "figure",
+    "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "main", "menu", "nav", "ol", "p",
+    "pre", "section", "table", "ul",
+];
+const INTERACTIVE_ELEMENTS: [&str; 5] = ["a", "button", "select", "textarea", "input"];
+
+// Structural checks on the HTML as served, before the browser's parser silently repairs it:
+// blocks inside <p>, nested interactive content, nested forms, stray <li>, unclosed and stray tags
+pub fn check_nesting(source: &str, report: &mut AuditReport) {
+    let mut stack: Vec<(String, usize)> = Vec::new();
+    let mut rest = source;
+    let (mut line, mut counted) = (1, 0);
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        let offset = source.len() - rest.len();
+        line += source[counted..offset].matches('\n').count();
+        counted = offset;
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|end| &comment[end + 3..]).unwrap_or("");
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map(|end| &rest[end + 1..]).unwrap_or("");
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name_len = rest[name_start..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(rest.len() - name_start);
+        if name_len == 0 {
+            rest = &rest[1..];
+            continue;
+        }
+        let name = rest[name_start..name_start + name_len].to_ascii_lowercase();
+        let Some(tag_end) = find_tag_end(rest) else {
+            break;
+        };
+        let self_closing = rest[..tag_end].ends_with('/');
+        rest = &rest[tag_end + 1..];
+
+        if closing {
+            match stack.iter().rposition(|(open, _)| *open == name) {
+                Some(position) => {
+                    for (unclosed, opened_at) in stack.drain(position..).skip(1) {
+                        if !OPTIONAL_END_ELEMENTS.contains(&unclosed.as_str()) {
+                            report.add(Severity::Warning, "unclosed-tag",
+                                format!("<{}> is never closed before </{}>", unclosed, name), Some(format!("line {}", opened_at)));
+                        }
+                    }
+                }
+                // Browsers turn a stray </p> into an empty paragraph; other stray end tags are dropped
+                None if name == "p" || name == "br" => {}
+                None => report.add(Severity::Warning, "stray-end-tag",
+                    format!("</{}> has no matching start tag", name), Some(format!("line {}", line))),
+            }
+            continue;
+        }
+
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            if let Some(position) = stack.iter().rposition(|(open, _)| open == "p") {
+                if name != "p" {
+                    report.add(Severity::Error, "block-in-paragraph",
+                        format!("<{}> inside <p> closes the paragraph early", name), Some(format!("line {}", line)));
+                }
+                stack.truncate(position);
+            }
+        }
+        if let Some((top, _)) = stack.last() {
+            let implied_close = match name.as_str() {
+                "li" => top == "li",
+                "dt" | "dd" => top == "dt" || top == "dd",
+                "td" | "th" => top == "td" || top == "th",
+                "tr" => top == "tr",
+                "option" => top == "option",
+                _ => false,
+            };
+            if implied_close {
+                stack.pop();
+            }
+        }
+
+        if INTERACTIVE_ELEMENTS.contains(&name.as_str()) {
+            if let Some((outer, _)) = stack.iter().rev().find(|(open, _)| open == "a" || open == "button") {
+                report.add(Severity::Error, "nested-interactive",
+                    format!("<{}> inside <{}>", name, outer), Some(format!("line {}", line)));
+            }
+        }
+        if name == "form" && stack.iter().any(|(open, _)| open == "form") {
+            report.add(Severity::Error, "nested-form", "<form> inside another <form> is ignored by browsers", Some(format!("line {}", line)));
+        }
+        if name == "li" && !matches!(stack.last().map(|(open, _)| open.as_str()), Some("ul" | "ol" | "menu")) {
+            report.add(Severity::Warning, "li-outside-list", "<li> is not a direct child of <ul>, <ol> or <menu>", Some(format!("line {}", line)));
+        }
+
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            let end_tag = format!("</{}", name);
+            // Skip the contents and the end tag itself; raw text elements never go on the stack
+            rest = rest.to_ascii_lowercase().find(&end_tag)
+                .and_then(|end| find_tag_end(&rest[end..]).map(|close| &rest[end + close + 1..]))
+                .unwrap_or("");
+            continue;
+        }
+        if !VOID_ELEMENTS.contains(&name.as_str()) && !self_closing {
+            stack.push((name, line));
+        }
+    }
+
+    for (unclosed, opened_at) in stack {
+        if !OPTIONAL_END_ELEMENTS.contains(&unclosed.as_str()) {
+            report.add(Severity::Warning, "unclosed-tag", format!("<{}> is never closed", unclosed), Some(format!("line {}", opened_at)));
+        }
+    }
+}
+
+// Index of the `>` ending a tag, skipping over quoted attribute values
+fn find_tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nesting(source: &str) -> Vec<(String, Option<String>)> {
+        let mut report = AuditReport::new("html", "about:blank");
+        check_nesting(source, &mut report);
+        report.findings.into_iter().map(|finding| (finding.rule, finding.target)).collect()
+    }
+
+    fn rules(source: &str) -> Vec<String> {
+        nesting(source).into_iter().map(|(rule, _)| rule).collect()
+    }
+
+    #[test]
+    fn void_elements_need_no_end_tag() {
+        assert!(rules(r#"<div><img src="a.png"><br><input type="text"><hr><meta charset="utf-8"></div>"#).is_empty());
+        assert!(rules("<div><br/><img src='a.png' /></div>").is_empty());
+    }
+
+    #[test]
+    fn blocks_inside_a_paragraph_close_it() {
+        assert_eq!(nesting("<div>\n<p>One\n<div>Two</div>\n</div>"),
+            vec![("block-in-paragraph".to_string(), Some("line 3".to_string()))]);
+        // A new <p> closes the open one without complaint, and so does a stray </p>
+        assert!(rules("<div><p>One<p>Two</p></p></div>").is_empty());
+    }
+
+    #[test]
+    fn optional_end_tags_are_implied() {
+        assert!(rules("<ul><li>One<li>Two</ul>").is_empty());
+        assert!(rules("<dl><dt>Term<dd>Definition<dt>Other</dl>").is_empty());
+        assert!(rules("<table><tr><td>a<td>b<tr><th>c</table>").is_empty());
+        assert!(rules("<select><option>a<option>b</select>").is_empty());
+    }
+
+    #[test]
+    fn misnested_tags_are_reported() {
+        assert_eq!(nesting("<div><span>text</div>\n</span>"), vec![
+            ("unclosed-tag".to_string(), Some("line 1".to_string())),
+            ("stray-end-tag".to_string(), Some("line 2".to_string())),
+        ]);
+        assert_eq!(rules("<section><div>never closed</section>"), ["unclosed-tag"]);
+        assert_eq!(rules("<main>\n<article>"), ["unclosed-tag", "unclosed-tag"]);
+    }
+
+    #[test]
+    fn content_models_are_checked() {
+        assert_eq!(rules("<a href='/'><button>Go</button></a>"), ["nested-interactive"]);
+        assert_eq!(rules("<form><div><form></form></div></form>"), ["nested-form"]);
+        assert_eq!(rules("<div><li>Loose</li></div>"), ["li-outside-list"]);
+    }
+
+    #[test]
+    fn comments_and_script_contents_are_skipped() {
+        assert!(rules("<!DOCTYPE html><!-- <div> <p> </span> --><p>ok</p>").is_empty());
+        assert!(rules(r#"<script>if (a < b) { document.write("<div></span><p>"); }</SCRIPT><p>ok</p>"#).is_empty());
+        assert!(rules("<style>p > div { color: red }</style><title>a <b> title</title>").is_empty());
+        // Attribute values may hold `>` without ending the tag
+        assert!(rules(r#"<div data-rule="a > b"><span title='>'>x</span></div>"#).is_empty());
+    }
+
+    #[test]
+    fn findings_carry_their_line() {
+        assert_eq!(nesting("<p>\n\n<div>"), vec![
+            ("block-in-paragraph".to_string(), Some("line 3".to_string())),
+            ("unclosed-tag".to_string(), Some("line 3".to_string())),
+        ]);
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::Result;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+// What a token may do: read-only tokens can observe, interactive tokens can also drive the browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    ReadOnly,
+    Interactive,
+}
+
+impl Scope {
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "read" | "read-only" | "readonly" => Ok(Scope::ReadOnly),
+            "interactive" | "write" => Ok(Scope::Interactive),
+            other => Err(anyhow::anyhow!("Unknown token scope '{}'. Use read or interactive", other)),
+        }
+    }
+}
+
+// Why a request was refused, mapped onto an HTTP status
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Denied {
+    Unauthorized,
+    Forbidden,
+}
+
+impl Denied {
+    pub fn status_line(&self) -> &'static str {
+        match self {
+            Denied::Unauthorized => "401 Unauthorized",
+            Denied::Forbidden => "403 Forbidden",
+        }
+    }
+}
+
+// Access control for the HTTP endpoints: API tokens with scopes, bind allowlist, and optional TLS
+#[derive(Default)]
+pub struct AccessPolicy {
+    tokens: Vec<(String, Scope)>,
+    allowed_binds: Vec<IpAddr>,
+    tls: Option<TlsAcceptor>,
+}
+
+impl AccessPolicy {
+    // Tokens are given as `TOKEN` (interactive) or `TOKEN:read` / `TOKEN:interactive`
+    pub fn new(tokens: &[String], allowed_binds: &[String]) -> Result<Self> {
+        let mut parsed = Vec::new();
+        for spec in tokens {
+            let (token, scope) = match spec.rsplit_once(':') {
+                Some((token, scope)) => (token, Scope::parse(scope)?),
+                None => (spec.as_str(), Scope::Interactive),
+            };
+            if token.is_empty() {
+                return Err(anyhow::anyhow!("Empty API token"));
+            }
+            parsed.push((token.to_string(), scope));
+        }
+
+        let allowed_binds = allowed_binds.iter()
+            .map(|ip| ip.parse::<IpAddr>().map_err(|_| anyhow::anyhow!("Invalid bind address in allowlist: {}", ip)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { tokens: parsed, allowed_binds, tls: None })
+    }
+
+    pub fn with_tls(mut self, cert_path: &str, key_path: &str) -> Result<Self> {
+        let certs = CertificateDer::pem_file_iter(cert_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read TLS certificate {}: {}", cert_path, e))?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("Invalid TLS certificate {}: {}", cert_path, e))?;
+        let key = PrivateKeyDer::from_pem_file(key_path)
+            .map_err(|e| anyhow::anyhow!("Failed to read TLS key {}: {}", key_path, e))?;
+
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| anyhow::anyhow!("Invalid TLS configuration: {}", e))?;
+
+        self.tls = Some(TlsAcceptor::from(Arc::new(config)));
+        Ok(self)
+    }
+
+    pub fn tls(&self) -> Option<&TlsAcceptor> {
+        self.tls.as_ref()
+    }
+
+    // Loopback is always allowed; anything else must be allowlisted and protected by tokens
+    pub fn check_bind(&self, addr: &SocketAddr) -> Result<()> {
+        let ip = addr.ip();
+        if ip.is_loopback() {
+            return Ok(());
+        }
+        if !self.allowed_binds.iter().any(|allowed| *allowed == ip || allowed.is_unspecified()) {
+            return Err(anyhow::anyhow!(
+                "Refusing to bind {}: only loopback is allowed unless the address is passed with --allow-bind",
+                ip
+            ));
+        }
+        if self.tokens.is_empty() {
+            return Err(anyhow::anyhow!("Refusing to expose {} without authentication; add --token", ip));
+        }
+        Ok(())
+    }
+
+    // Authorize a request from its raw HTTP head (Authorization: Bearer header or ?token= query)
+    pub fn authorize(&self, request_head: &str, required: Scope) -> std::result::Result<(), Denied> {
+        // Without tokens anything that reaches the port may watch, but driving the browser always takes one:
+        // a web page open in any browser on the machine could otherwise POST to it
+        if self.tokens.is_empty() {
+            return match required {
+                Scope::ReadOnly => Ok(()),
+                Scope::Interactive => Err(Denied::Forbidden),
+            };
+        }
+
+        let Some(given) = bearer_token(request_head).or_else(|| query_token(request_head)) else {
+            return Err(Denied::Unauthorized);
+        };
+        // Every token is compared, so the time taken doesn't tell which one came close
+        let scope = self.tokens.iter()
+            .filter(|(token, _)| constant_time_eq(token, given))
+            .map(|(_, scope)| *scope)
+            .max();
+        match scope {
+            Some(scope) if scope >= required => Ok(()),
+            Some(_) => Err(Denied::Forbidden),
+            None => Err(Denied::Unauthorized),
+        }
+    }
+}
+
+fn bearer_token(request_head: &str) -> Option<&str> {
+    request_head.lines().skip(1).find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if !name.trim().eq_ignore_ascii_case("authorization") {
+            return None;
+        }
+        value.trim().strip_prefix("Bearer ").map(str::trim)
+    })
+}
+
+// EventSource clients in browsers can't set headers, so the token may also come in the query string
+fn query_token(request_head: &str) -> Option<&str> {
+    let target = request_head.lines().next()?.split_whitespace().nth(1)?;
+    let (_, query) = target.split_once('?')?;
+    query.split('&').find_map(|pair| pair.strip_prefix("token="))
+}
+
+// Compare secrets without returning early, so the time taken doesn't reveal how much of a guess matched
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(token: &str) -> String {
+        format!("GET /events HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", token)
+    }
+
+    #[test]
+    fn scopes_limit_what_a_token_may_do() {
+        let policy = AccessPolicy::new(&["watcher:read".to_string(), "driver".to_string()], &[]).unwrap();
+        assert_eq!(policy.authorize(&head("watcher"), Scope::ReadOnly), Ok(()));
+        assert_eq!(policy.authorize(&head("watcher"), Scope::Interactive), Err(Denied::Forbidden));
+        assert_eq!(policy.authorize(&head("driver"), Scope::Interactive), Ok(()));
+        assert_eq!(policy.authorize(&head("drive"), Scope::ReadOnly), Err(Denied::Unauthorized));
+        assert_eq!(policy.authorize("GET /events?token=driver HTTP/1.1\r\n\r\n", Scope::Interactive), Ok(()));
+        assert_eq!(policy.authorize("GET /events HTTP/1.1\r\n\r\n", Scope::ReadOnly), Err(Denied::Unauthorized));
+    }
+
+    #[test]
+    fn without_tokens_only_watching_is_open() {
+        let policy = AccessPolicy::new(&[], &[]).unwrap();
+        assert_eq!(policy.authorize("GET /events HTTP/1.1\r\n\r\n", Scope::ReadOnly), Ok(()));
+        assert_eq!(policy.authorize("POST /navigate HTTP/1.1\r\n\r\n", Scope::Interactive), Err(Denied::Forbidden));
+    }
+
+    #[test]
+    fn rejects_bad_token_specs() {
+        assert!(AccessPolicy::new(&[":read".to_string()], &[]).is_err());
+        assert!(AccessPolicy::new(&["secret:admin".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn constant_time_eq_matches_only_equal_strings() {
+        assert!(constant_time_eq("secret", "secret"));
+        assert!(!constant_time_eq("secret", "secreT"));
+        assert!(!constant_time_eq("secret", "secret2"));
+    }
+}
@@ -1,264 +1,4803 @@
 use anyhow::Result;
-use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotParams;
-use chromiumoxide::cdp::browser_protocol::input::{DispatchMouseEventParams, DispatchMouseEventType, MouseButton};
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::browser::{EventDownloadWillBegin, GrantPermissionsParams, PermissionType, SetDownloadBehaviorBehavior, SetDownloadBehaviorParams};
+use chromiumoxide::cdp::browser_protocol::emulation::{ClearDeviceMetricsOverrideParams, MediaFeature, SetDeviceMetricsOverrideParams, SetEmulatedMediaParams, SetLocaleOverrideParams, SetTimezoneOverrideParams, SetUserAgentOverrideParams};
+use chromiumoxide::cdp::browser_protocol::page::{AddScriptToEvaluateOnNewDocumentParams, CaptureScreenshotFormat, CaptureScreenshotParams, CreateIsolatedWorldParams, PrintToPdfParams, EventDomContentEventFired, EventFrameNavigated, FrameId, FrameTree, GetFrameTreeParams, EventJavascriptDialogOpening, HandleJavaScriptDialogParams, RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier, SetBypassCspParams};
+use chromiumoxide::cdp::browser_protocol::input::{DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams, DispatchMouseEventType, ImeSetCompositionParams, InsertTextParams, MouseButton};
+use chromiumoxide::cdp::browser_protocol::log::{DisableParams as LogDisableParams, EnableParams as LogEnableParams, EventEntryAdded, LogEntrySource};
+use chromiumoxide::cdp::browser_protocol::io::{CloseParams as IoCloseParams, ReadParams as IoReadParams};
+use chromiumoxide::cdp::browser_protocol::network::{CookieParam, LoadNetworkResourceOptions, LoadNetworkResourceParams, EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent, EventResponseReceived, Headers, ResourceType, SetExtraHttpHeadersParams};
+use chromiumoxide::cdp::browser_protocol::storage::SetCookiesParams as StorageSetCookiesParams;
+use chromiumoxide::cdp::browser_protocol::system_info::GetProcessInfoParams;
+use chromiumoxide::cdp::browser_protocol::target::{EventTargetInfoChanged, GetTargetsParams, TargetId};
+use chromiumoxide::cdp::browser_protocol::inspector::{EnableParams as InspectorEnableParams, EventTargetCrashed};
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, ConsoleApiCalledType, EventBindingCalled, EvaluateParams, ExecutionContextId, EventConsoleApiCalled, EventExceptionThrown};
+use chromiumoxide::element::Element;
+use chromiumoxide::error::CdpError;
+use chromiumoxide::js::{Evaluation, EvaluationResult};
+use chromiumoxide::types::{Command, CommandResponse};
+use chromiumoxide::page::ScreenshotParams;
 use chromiumoxide::{Browser, BrowserConfig, Page};
 use colored::*;
 use futures_util::StreamExt;
-use std::path::PathBuf;
-use std::fs;
-use chrono::{DateTime, Utc};
-use thirtyfour::prelude::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use chrono::{DateTime, FixedOffset, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
 use tokio::time::{sleep, Duration};
 
+use crate::assets::{self, DeclaredFont, FontAsset, PageAsset};
+use crate::audit::{self, AuditReport, FocusStop, Severity, WalkEnd};
+use crate::capture::{self, CaptureMeta};
+use crate::checkpoint::Checkpoint;
+use crate::chrome;
+use crate::compare::{self, CompareMode, CompareOptions, CompareReport, PathComparison};
+use crate::daemon::{self, AttachTarget, DaemonInfo};
+use crate::error::Error;
+use crate::paths::{self, Artifact};
+use crate::profiles;
+use crate::progress;
+use chromiumoxide::cdp::browser_protocol::page::Viewport;
+use crate::config::{FlagPreset, NavigationConfig};
+use crate::crawl::{self, normalize_url, CrawlOptions, CrawlPage, CrawlReport, Visited};
+use crate::handoff::Handoff;
+use crate::har;
+use crate::import::{self, ImportSummary, ImportedState};
+use crate::extract::{self, Article, ArticleSource};
+use crate::events::{BrowserEvent, DialogAction, EventPublisher, SessionEvent};
+use crate::keyboard::{self, KeyboardLayout, Segment};
+use chromiumoxide::keys::KeyDefinition;
+use crate::network::{self, BlockRule, HarMocks, Interception, NetworkLog, RequestRecord};
+use crate::policy::UrlPolicy;
+use crate::robots::RobotsRules;
+use crate::scrape::{self, ScrapeResult, ScrapeSpec};
+use crate::sitemap::{self, SitemapFilter};
+use crate::table::{self, Table, TableGrid};
+
+const DEFAULT_CDP_TIMEOUT_SECS: u64 = 30;
+// Target ids are 32 hex digits; this many are plenty to tell tabs apart
+const TAB_ID_LEN: usize = 8;
+// Longest single in-page wait before handing control back, so waits survive navigations and stay under the watchdog
+const OBSERVE_SLICE: Duration = Duration::from_secs(10);
+// Runtime binding the notification shim reports through; bindings survive navigations
+const NOTIFY_BINDING: &str = "__browserCliNotify";
+// Runtime binding the audio shim reports sound starting and stopping through
+const AUDIO_BINDING: &str = "__browserCliAudio";
+// Window event (and installed-flag) of the history hook waitroute puts in the page's main world
+const ROUTE_EVENT: &str = "__browserCliRoute";
+// JS function giving a readable, stable path for an element (id-anchored, nth-of-type otherwise)
+const ELEMENT_PATH_JS: &str = r#"(node) => {
+    const parts = [];
+    while (node && node.nodeType === 1) {
+        if (node.id) {
+            parts.unshift('#' + CSS.escape(node.id));
+            break;
+        }
+        let part = node.localName;
+        const parent = node.parentElement;
+        if (parent) {
+            const same = Array.from(parent.children).filter(child => child.localName === node.localName);
+            if (same.length > 1) part += `:nth-of-type(${same.indexOf(node) + 1})`;
+        }
+        parts.unshift(part);
+        node = parent || (node.getRootNode() && node.getRootNode().host);
+    }
+    return parts.join(' > ');
+}"#;
+// JS function handing out the element's stable ref (e1, e2, …) used by `elements`, `info --json` and
+// `[data-bcref="…"]` selectors. The counter lives in the DOM so every execution world hands out unique refs.
+const ELEMENT_REF_JS: &str = r#"(el) => {
+    let ref = el.getAttribute('data-bcref');
+    if (!ref) {
+        const root = document.documentElement;
+        const seq = parseInt(root.getAttribute('data-bcref-seq') || '0') + 1;
+        root.setAttribute('data-bcref-seq', seq);
+        ref = 'e' + seq;
+        el.setAttribute('data-bcref', ref);
+    }
+    return ref;
+}"#;
+// JS function looking for leads when a selector matches nothing: whether it parses at all, how many elements a
+// relaxed form of it matches (its last compound, then that without attributes and pseudo-classes, then the
+// bare tag), and spellings of its ids, classes and attribute values that do match something
+const SELECTOR_HINTS_JS: &str = r#"(selector) => {
+    const count = s => {
+        try {
+            return document.querySelectorAll(s).length;
+        } catch (e) {
+            return -1;
+        }
+    };
+    if (count(selector) < 0) return { invalid: true };
+
+    // Start of the last compound selector, outside brackets, parentheses and quotes
+    let depth = 0, quote = null, last = 0, list = false;
+    for (let i = 0; i < selector.length; i++) {
+        const ch = selector[i];
+        if (quote) {
+            if (ch === '\\') i++;
+            else if (ch === quote) quote = null;
+        } else if (ch === '"' || ch === "'") {
+            quote = ch;
+        } else if (ch === '[' || ch === '(') {
+            depth++;
+        } else if (ch === ']' || ch === ')') {
+            depth--;
+        } else if (depth === 0 && /[\s>+~,]/.test(ch)) {
+            last = i + 1;
+            if (ch === ',') list = true;
+        }
+    }
+    let relaxed = null;
+    if (!list) {
+        const compound = selector.slice(last).trim();
+        const bare = compound.replace(/\[[^\]]*\]/g, '').replace(/::?[\w-]+(\([^)]*\))?/g, '');
+        const tag = (compound.match(/^([a-zA-Z][\w-]*|\*)/) || [])[0];
+        for (const candidate of [compound, bare, tag]) {
+            if (!candidate || candidate === selector.trim()) continue;
+            const n = count(candidate);
+            if (n > 0) {
+                relaxed = { selector: candidate, count: n };
+                break;
+            }
+        }
+    }
+
+    const distance = (a, b) => {
+        let prev = Array.from({ length: b.length + 1 }, (_, j) => j);
+        for (let i = 1; i <= a.length; i++) {
+            const row = [i];
+            for (let j = 1; j <= b.length; j++) {
+                row[j] = Math.min(prev[j] + 1, row[j - 1] + 1, prev[j - 1] + (a[i - 1] === b[j - 1] ? 0 : 1));
+            }
+            prev = row;
+        }
+        return prev[b.length];
+    };
+    // Page values close to the word: a few edits away, or one containing the other; nearest first
+    const close = (word, values) => {
+        const lower = word.toLowerCase();
+        const limit = Math.max(2, Math.floor(word.length / 3));
+        return Array.from(new Set(values))
+            .filter(value => value && value !== word)
+            .map(value => {
+                const other = value.toLowerCase();
+                const d = distance(lower, other);
+                return { value, d: d <= limit ? d : (other.includes(lower) || lower.includes(other)) ? limit + 0.5 : Infinity };
+            })
+            .filter(match => match.d !== Infinity)
+            .sort((a, b) => a.d - b.d)
+            .map(match => match.value);
+    };
+    const elements = Array.from(document.querySelectorAll('*')).slice(0, 5000);
+    const ids = elements.map(el => el.id);
+    const classes = elements.flatMap(el => Array.from(el.classList));
+    const tokens = [];
+    for (const m of selector.matchAll(/#((?:\\.|[\w-])+)/g)) {
+        tokens.push({ at: m.index, text: m[0], values: close(m[1], ids).map(id => '#' + CSS.escape(id)) });
+    }
+    for (const m of selector.matchAll(/\.((?:\\.|[\w-])+)/g)) {
+        tokens.push({ at: m.index, text: m[0], values: close(m[1], classes).map(name => '.' + CSS.escape(name)) });
+    }
+    for (const m of selector.matchAll(/\[\s*([\w-]+)\s*([~|^$*]?=)\s*(["']?)([^"'\]]*)\3\s*\]/g)) {
+        let values = [];
+        try {
+            values = Array.from(document.querySelectorAll(`[${m[1]}]`), el => el.getAttribute(m[1]));
+        } catch (e) {}
+        tokens.push({ at: m.index, text: m[0], values: close(m[4], values).map(value => `[${m[1]}${m[2]}${JSON.stringify(value)}]`) });
+    }
+    // A spelling counts when swapping it in makes the whole selector match
+    const suggestions = [];
+    for (const token of tokens) {
+        for (const value of token.values) {
+            const candidate = selector.slice(0, token.at) + value + selector.slice(token.at + token.text.length);
+            if (count(candidate) > 0) {
+                suggestions.push(candidate);
+                break;
+            }
+        }
+    }
+    return { invalid: false, relaxed, suggestions: suggestions.slice(0, 3) };
+}"#;
+// Landmarks and interactive elements listed by `info --json`
+const PAGE_DETAILS_MAX_LANDMARKS: usize = 10;
+const PAGE_DETAILS_MAX_ELEMENTS: usize = 20;
+// Text elements checked by `audit contrast`, and how many of those on gradients/images get screenshot-sampled
+const CONTRAST_MAX_ELEMENTS: usize = 500;
+const CONTRAST_MAX_SAMPLED: usize = 30;
+// Viewport chromiumoxide emulates on pages of browsers it launches; device scale overrides keep it
+const LAUNCH_VIEWPORT: (i64, i64) = (800, 600);
+// How long JS errors keep counting against the error budget after a page has loaded
+const JS_ERROR_SETTLE_MS: u64 = 2000;
+
+// Quiet time waitfornetworkidle waits for when not given one
+pub const DEFAULT_NETWORK_IDLE_MS: u64 = 500;
+
+// Mouse moves between press and release when a drag doesn't say, and the pause after each (about a frame)
+pub const DEFAULT_DRAG_STEPS: u32 = 10;
+// Burst `ss-sequence` captures when not told otherwise
+pub const DEFAULT_SEQUENCE_FRAMES: usize = 10;
+pub const DEFAULT_SEQUENCE_INTERVAL_MS: u64 = 100;
+const DRAG_STEP_MS: u64 = 16;
+
+// Loads a batch job tries per page, and the wait before the first retry (doubled after each)
+const BATCH_ATTEMPTS: u32 = 3;
+const BATCH_RETRY_DELAY_MS: u64 = 2000;
+
+// CDP's answers when an evaluation outlives its document (a navigation or a reload)
+const STALE_CONTEXT_ERRORS: &[&str] = &[
+    "Cannot find context with specified id",
+    "Execution context was destroyed",
+    "Cannot find default execution context",
+    "Inspected target navigated or closed",
+];
+
+// Network errors worth another try; anything else (bad host, TLS, policy) fails the same way again
+const TRANSIENT_NET_ERRORS: &[&str] = &[
+    "ERR_CONNECTION_RESET",
+    "ERR_CONNECTION_CLOSED",
+    "ERR_CONNECTION_REFUSED",
+    "ERR_CONNECTION_TIMED_OUT",
+    "ERR_TIMED_OUT",
+    "ERR_EMPTY_RESPONSE",
+    "ERR_NETWORK_CHANGED",
+    "ERR_INTERNET_DISCONNECTED",
+    "ERR_HTTP2_PROTOCOL_ERROR",
+];
+
+// The watchdog for CDP calls made away from a controller's `cdp` (scratch browsers, the interception task):
+// the same timeout and error, without the page recovery
+pub(crate) async fn watchdog<T, F>(timeout: Duration, operation: &str, call: F) -> Result<T>
+where
+    F: Future<Output = chromiumoxide::error::Result<T>>,
+{
+    match tokio::time::timeout(timeout, call).await {
+        Ok(result) => Ok(result?),
+        Err(_) => Err(Error::CdpTimeout { operation: operation.to_string(), secs: timeout.as_secs(), crashed: false }.into()),
+    }
+}
+
+// Per-session resource limits; a session exceeding any of them has its browser reaped
+#[derive(Debug, Clone, Default)]
+pub struct SessionLimits {
+    pub idle_timeout: Option<Duration>,
+    pub max_lifetime: Option<Duration>,
+    pub max_memory_mb: Option<u64>,
+}
+
+// Media features `emulate-media` can force, with their values; forced-colors active renders the page the
+// way Windows High Contrast does, on any platform
+pub const MEDIA_FEATURES: &[(&str, &[&str])] = &[
+    ("forced-colors", &["active", "none"]),
+    ("prefers-contrast", &["more", "less", "custom", "no-preference"]),
+    ("prefers-color-scheme", &["light", "dark"]),
+    ("prefers-reduced-motion", &["reduce", "no-preference"]),
+];
+
+// CSS media emulated on every tab of a session, e.g. `print` to render what the browser's print preview shows
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MediaEmulation {
+    // print or screen; None keeps the real media type
+    pub media: Option<String>,
+    // Feature name to forced value, from MEDIA_FEATURES
+    pub features: BTreeMap<String, String>,
+}
+
+impl MediaEmulation {
+    pub fn is_active(&self) -> bool {
+        self.media.is_some() || !self.features.is_empty()
+    }
+
+    // Force a media feature to a value, or stop forcing it with None
+    pub fn set_feature(&mut self, name: &str, value: Option<&str>) -> Result<()> {
+        match value {
+            None => {
+                check_media_feature(name, None)?;
+                self.features.remove(name);
+            }
+            Some(value) => {
+                check_media_feature(name, Some(value))?;
+                self.features.insert(name.to_string(), value.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(other) = self.media.as_deref().filter(|media| !["print", "screen"].contains(media)) {
+            return Err(anyhow::anyhow!("Unknown media type '{}' (expected print or screen)", other));
+        }
+        for (name, value) in &self.features {
+            check_media_feature(name, Some(value))?;
+        }
+        Ok(())
+    }
+
+    fn params(&self) -> SetEmulatedMediaParams {
+        // An empty media type drops the override; every call replaces all feature overrides, so an empty
+        // list drops those
+        SetEmulatedMediaParams::builder()
+            .media(self.media.clone().unwrap_or_default())
+            .features(self.features.iter().map(|(name, value)| MediaFeature::new(name, value)))
+            .build()
+    }
+}
+
+fn check_media_feature(name: &str, value: Option<&str>) -> Result<()> {
+    let Some((_, values)) = MEDIA_FEATURES.iter().find(|(feature, _)| *feature == name) else {
+        let names: Vec<&str> = MEDIA_FEATURES.iter().map(|(feature, _)| *feature).collect();
+        return Err(anyhow::anyhow!("Unknown media feature '{}' (expected {})", name, names.join(", ")));
+    };
+    match value {
+        Some(value) if !values.contains(&value) => {
+            Err(anyhow::anyhow!("Invalid {} value '{}' (expected {})", name, value, values.join(", ")))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl std::fmt::Display for MediaEmulation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts: Vec<String> = self.media.iter().map(|media| format!("media {}", media)).collect();
+        parts.extend(self.features.iter().map(|(name, value)| format!("{}: {}", name, value)));
+        if parts.is_empty() {
+            write!(f, "no media emulation")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+// What SELECTOR_HINTS_JS found for a selector that matched nothing
+#[derive(Debug, Default, Deserialize)]
+struct SelectorHints {
+    #[serde(default)]
+    invalid: bool,
+    relaxed: Option<RelaxedMatch>,
+    #[serde(default)]
+    suggestions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RelaxedMatch {
+    selector: String,
+    count: usize,
+}
+
+// One screenshot of an `ss-sequence` burst
+#[derive(Debug, Clone, Serialize)]
+pub struct SequenceFrame {
+    pub path: String,
+    // When it was taken, from the start of the burst
+    pub offset_ms: u64,
+}
+
+// Outcome of BrowserController::load_resource
+enum Loaded {
+    // HTTP status (after redirects) and body
+    Response(u16, String),
+    // Network error name, e.g. net::ERR_NAME_NOT_RESOLVED
+    Unreachable(String),
+}
+
+// What re-runs an observe_until check inside the page between slices
+#[derive(Debug, Clone, Copy)]
+enum Wake {
+    // DOM mutations; `poll` adds a 250ms timer for state no mutation reveals (opacity, covering, scrolling)
+    Mutations { poll: bool },
+    // History API route changes (through the route_hook_js hook), popstate and hashchange
+    Route,
+}
+
+impl Wake {
+    // JS declaring `arm(recheck)` and `disarm()` for the check's promise
+    fn js(self) -> String {
+        match self {
+            Self::Mutations { poll } => format!(
+                r#"let observer = null, timer = null;
+                    const arm = (recheck) => {{
+                        observer = new MutationObserver(recheck);
+                        observer.observe(document, {{ childList: true, subtree: true, attributes: true, characterData: true }});
+                        if ({poll}) timer = setInterval(recheck, 250);
+                    }};
+                    const disarm = () => {{
+                        if (observer) observer.disconnect();
+                        clearInterval(timer);
+                    }};"#,
+                poll = poll
+            ),
+            Self::Route => format!(
+                r#"const types = ['{event}', 'popstate', 'hashchange'];
+                    let listener = null;
+                    const arm = (recheck) => {{
+                        listener = recheck;
+                        types.forEach(type => window.addEventListener(type, listener));
+                    }};
+                    const disarm = () => types.forEach(type => window.removeEventListener(type, listener));"#,
+                event = ROUTE_EVENT
+            ),
+        }
+    }
+}
+
+// An anchor on the page, as `links` lists it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageLink {
+    // Resolved against the document's base URL
+    pub url: String,
+    // Visible text, else aria-label, an image's alt or the title
+    pub text: String,
+    pub rel: Vec<String>,
+    pub target: Option<String>,
+}
+
+impl std::fmt::Display for PageLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.url)?;
+        if !self.text.is_empty() {
+            write!(f, "  {}", self.text)?;
+        }
+        if !self.rel.is_empty() {
+            write!(f, "  [rel={}]", self.rel.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+// Which anchors `links` keeps
+#[derive(Debug, Clone, Default)]
+pub struct LinkFilter {
+    pub same_origin: bool,
+    // JavaScript regular expression the resolved URL must match
+    pub pattern: Option<String>,
+}
+
+// What `navigate` and `pageinfo` report about a page; displays as the one-line summary agents read
+#[derive(Debug, Clone, Serialize)]
+pub struct PageSummary {
+    pub title: String,
+    pub url: String,
+    pub inputs: u64,
+    pub buttons: u64,
+    pub links: u64,
+}
+
+impl std::fmt::Display for PageSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} | {}",
+            self.title.chars().take(40).collect::<String>(),
+            self.url.replace("https://", "").replace("http://", "")
+        )?;
+        if self.inputs > 0 || self.buttons > 0 || self.links > 0 {
+            write!(f, " | i:{} b:{} l:{}", self.inputs, self.buttons, self.links)?;
+        }
+        Ok(())
+    }
+}
+
+// What `version --full` and the console's `about` report when triaging a bug
+#[derive(Debug, Clone, Serialize)]
+pub struct AboutInfo {
+    pub cli_version: String,
+    pub product: String,
+    pub revision: String,
+    pub protocol_version: String,
+    pub js_version: String,
+    pub user_agent: String,
+    // None when attached to a daemon's browser, which another process launched
+    pub executable: Option<PathBuf>,
+    pub executable_source: Option<String>,
+    pub daemon_pid: Option<u32>,
+    // Session options that change how pages behave
+    pub features: Vec<&'static str>,
+}
+
+impl AboutInfo {
+    pub fn print(&self) {
+        status!("{} browser-cli {}", "ℹ️".blue(), self.cli_version.bold());
+        status!("  {:<10} {} (revision {})", "Browser:", self.product, self.revision);
+        status!("  {:<10} {}", "Protocol:", self.protocol_version);
+        status!("  {:<10} {}", "V8:", self.js_version);
+        status!("  {:<10} {}", "UA:", self.user_agent);
+        match (&self.executable, &self.executable_source, self.daemon_pid) {
+            (Some(path), Some(source), _) => status!("  {:<10} {} (from {})", "Binary:", path.display(), source),
+            (_, _, Some(pid)) => status!("  {:<10} attached to daemon (pid {})", "Binary:", pid),
+            _ => {}
+        }
+        let features = match self.features.is_empty() {
+            true => "none".dimmed().to_string(),
+            false => self.features.join(", "),
+        };
+        status!("  {:<10} {}", "Features:", features);
+    }
+}
+
+// `info --json`: the page summary plus its landmarks and first interactive elements, all carrying the
+// refs `elements` uses so agents can act on them with `[data-bcref="eN"]`
+#[derive(Debug, Clone, Serialize)]
+pub struct PageDetails {
+    #[serde(flatten)]
+    pub summary: PageSummary,
+    pub landmarks: Vec<serde_json::Value>,
+    pub elements: Vec<serde_json::Value>,
+    // Interactive elements beyond the ones listed; page through them with `elements --offset`
+    pub more_elements: u64,
+}
+
+// Per-call navigation settings; unset fields fall back to the session's configured defaults
+#[derive(Debug, Clone, Default)]
+pub struct NavigateOptions {
+    pub max_js_errors: Option<usize>,
+    pub wait_until: Option<WaitUntil>,
+}
+
+// When navigate considers the page loaded
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum WaitUntil {
+    // The load event: the document and its subresources have loaded
+    #[default]
+    Load,
+    // DOMContentLoaded: the document is parsed; images and stylesheets may still be loading
+    DomContentLoaded,
+    // load, then DEFAULT_NETWORK_IDLE_MS without requests, for apps that fetch their content afterwards
+    NetworkIdle,
+    // load, then until an element matches the selector
+    Selector(String),
+}
+
+impl WaitUntil {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "load" => Ok(Self::Load),
+            "domcontentloaded" => Ok(Self::DomContentLoaded),
+            "networkidle" => Ok(Self::NetworkIdle),
+            _ => match name.strip_prefix("selector:") {
+                Some(selector) if !selector.trim().is_empty() => Ok(Self::Selector(selector.trim().to_string())),
+                _ => Err(anyhow::anyhow!(
+                    "Unknown wait strategy '{}' (expected load, domcontentloaded, networkidle or selector:<css>)", name
+                )),
+            },
+        }
+    }
+}
+
+// The config file spells strategies the way --wait-until does
+impl TryFrom<String> for WaitUntil {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        Self::parse(&name)
+    }
+}
+
+// Where `js` evaluates code: another tab, an iframe, and/or an isolated world instead of the current page
+#[derive(Debug, Clone, Default)]
+pub struct JsTarget {
+    pub tab: Option<usize>,
+    // Index among the page's iframes (depth-first, 0-based) or a selector for the <iframe> element
+    pub frame: Option<String>,
+    pub isolated: bool,
+}
+
+// A notification or push subscription seen by the notification shim
+#[derive(Debug, Clone)]
+pub struct NotificationRecord {
+    pub at: DateTime<Utc>,
+    // page (Notification constructor), registration (showNotification), service-worker, push-subscribe
+    pub source: String,
+    pub title: String,
+    pub body: String,
+    pub tag: Option<String>,
+}
+
+// Hardware values reported to pages by `spoof hw`; unset fields keep the real values
+#[derive(Debug, Clone, Default)]
+pub struct HardwareProfile {
+    pub cores: Option<u32>,
+    pub memory_gb: Option<f64>,
+    // Battery level from 0.0 to 1.0
+    pub battery: Option<f64>,
+    pub charging: bool,
+}
+
+// One row of `tabs`
+#[derive(Debug, Clone, Serialize)]
+pub struct TabInfo {
+    // 1-based position in opening order
+    pub index: usize,
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub active: bool,
+}
+
+// `* 2 [1a2b3c4d] Title | url`, the star marking the active tab
+impl std::fmt::Display for TabInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} [{}] {} | {}",
+            if self.active { "*" } else { " " },
+            self.index,
+            self.id,
+            self.title.chars().take(40).collect::<String>(),
+            self.url
+        )
+    }
+}
+
+// A browser the controller attached to rather than launched
+#[derive(Debug, Clone)]
+pub enum Attachment {
+    // The session's daemon, another browser-cli process
+    Daemon(DaemonInfo),
+    // A browser started outside browser-cli, given with --connect or --connect-port
+    External(String),
+}
+
+// Fake camera/microphone for WebRTC flows; without files Chrome generates a test pattern and a beep
+#[derive(Debug, Clone, Default)]
+pub struct FakeMedia {
+    pub video_file: Option<PathBuf>,
+    pub audio_file: Option<PathBuf>,
+}
+
+// How type_text delivers characters that have no US key
+#[derive(Debug, Clone, Default)]
+pub struct TypeOptions {
+    pub layout: KeyboardLayout,
+    // Send non-layout text through IME composition events instead of a plain insert
+    pub ime: bool,
+}
+
+// Which interactive elements `elements` lists, and which page of them
+#[derive(Debug, Clone, Default)]
+pub struct ElementQuery {
+    // buttons, links or inputs; all three when unset
+    pub kind: Option<String>,
+    // Case-insensitive match against text, id, name, placeholder, href, aria-label and title
+    pub contains: Option<String>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub scope: ElementScope,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ElementScope {
+    // Rendered and perceivable somewhere on the page (not hidden by display, visibility, opacity or clipping)
+    #[default]
+    Visible,
+    // Visible and at least partly inside the current viewport
+    InViewport,
+    // Everything in the DOM, hidden elements included with the reason they're hidden
+    All,
+}
+
+// Output shape of `text`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TextFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+}
+
+impl TextFormat {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "text" | "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "md" | "markdown" => Ok(Self::Markdown),
+            _ => Err(anyhow::anyhow!("Unknown format '{}' (expected text, json or md)", name)),
+        }
+    }
+}
+
+// Element state required by `waitfor` on top of the selector matching
+#[derive(Debug, Clone, Default)]
+pub struct WaitCondition {
+    pub visible: bool,
+    pub enabled: bool,
+    // Visible, enabled and not covered by another element at its center
+    pub clickable: bool,
+    pub attr: Option<(String, String)>,
+}
+
+impl WaitCondition {
+    // `name=value` as given to --attr
+    pub fn parse_attr(spec: &str) -> Result<(String, String)> {
+        match spec.split_once('=') {
+            Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+            _ => Err(anyhow::anyhow!("Invalid attribute condition '{}' (expected name=value)", spec)),
+        }
+    }
+
+    pub fn is_presence(&self) -> bool {
+        !self.visible && !self.enabled && !self.clickable && self.attr.is_none()
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.visible {
+            parts.push("visible".to_string());
+        }
+        if self.enabled {
+            parts.push("enabled".to_string());
+        }
+        if self.clickable {
+            parts.push("clickable".to_string());
+        }
+        if let Some((name, value)) = &self.attr {
+            parts.push(format!("{}=\"{}\"", name, value));
+        }
+        parts.join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TickerOptions {
+    // One JSON change record per line instead of the human-readable log
+    pub json: bool,
+    // Console command to run after each reported change; a leading `!` runs it through the shell instead
+    pub on_change: Option<String>,
+    // Text changes smaller than this many characters are ignored
+    pub min_change_chars: usize,
+    // How long a change has to hold before it's reported
+    pub debounce: Duration,
+    pub ignore_selectors: Vec<String>,
+}
+
+// What `ticker` watches, and the state it last reported
+pub struct Ticker {
+    selector: Option<String>,
+    options: TickerOptions,
+    script: String,
+    reported: Option<serde_json::Map<String, serde_json::Value>>,
+    last_seen: Option<serde_json::Map<String, serde_json::Value>>,
+    // When the current unreported change was first seen in its present form
+    settling_since: Option<Instant>,
+}
+
+impl Ticker {
+    pub fn new(selector: Option<&str>, options: TickerOptions) -> Result<Self> {
+        // Elements under --ignore-selectors (clocks, ads, carousels) are left out of both text and counts
+        let ignored = serde_json::to_string(&options.ignore_selectors.join(", "))?;
+        let script = if let Some(sel) = selector {
+            format!(
+                r#"
+                (() => {{
+                    const ignored = {ignored};
+                    const kept = el => !ignored || !el.closest(ignored);
+                    const text = el => {{
+                        if (!ignored) return el.textContent.trim();
+                        const copy = el.cloneNode(true);
+                        copy.querySelectorAll(ignored).forEach(node => node.remove());
+                        return copy.textContent.trim();
+                    }};
+                    const matches = Array.from(document.querySelectorAll({selector})).filter(kept);
+                    return {{
+                        count: matches.length,
+                        text: matches.map(text).join(' | '),
+                    }};
+                }})()
+                "#,
+                ignored = ignored,
+                selector = serde_json::to_string(sel)?
+            )
+        } else {
+            format!(
+                r#"
+                (() => {{
+                    const ignored = {ignored};
+                    const count = selector => Array.from(document.querySelectorAll(selector))
+                        .filter(el => !ignored || !el.closest(ignored)).length;
+                    return {{
+                        url: window.location.href,
+                        title: document.title,
+                        inputs: count('input:not([type="hidden"]), textarea'),
+                        buttons: count('button, input[type="submit"], input[type="button"]'),
+                        forms: count('form'),
+                    }};
+                }})()
+                "#,
+                ignored = ignored
+            )
+        };
+        
+        Ok(Self {
+            selector: selector.map(String::from),
+            options,
+            script,
+            reported: None,
+            last_seen: None,
+            settling_since: None,
+        })
+    }
+}
+
+pub enum TickerCheck {
+    // First check; the state every later change is measured against
+    Baseline(serde_json::Value),
+    Unchanged,
+    Changed(Vec<FieldChange>),
+}
+
+// One monitored field that differs between two ticker checks
+#[derive(Debug, Clone)]
+pub struct FieldChange {
+    pub ts: DateTime<Utc>,
+    pub selector: Option<String>,
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+impl FieldChange {
+    // Characters that differ between the two values once the common prefix and suffix are set aside;
+    // non-text changes (counts, missing fields) always count as large
+    pub fn size(&self) -> usize {
+        let (Some(before), Some(after)) = (self.before.as_str(), self.after.as_str()) else {
+            return usize::MAX;
+        };
+        let before: Vec<char> = before.chars().collect();
+        let after: Vec<char> = after.chars().collect();
+        let prefix = before.iter().zip(&after).take_while(|(a, b)| a == b).count();
+        let suffix = before[prefix..].iter().rev().zip(after[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+        (before.len() - prefix - suffix).max(after.len() - prefix - suffix)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut record = serde_json::json!({
+            "ts": self.ts.to_rfc3339(),
+            "field": self.field,
+            "before": self.before,
+            "after": self.after,
+        });
+        if let Some(selector) = &self.selector {
+            record["selector"] = serde_json::Value::String(selector.clone());
+        }
+        record
+    }
+}
+
+// Where `waitenhanced` found an element
+#[derive(Debug, Clone)]
+pub struct ElementLocation {
+    // URL of the containing frame, None for the top document
+    pub frame: Option<String>,
+    // Shadow hosts from the outermost in; empty when the element is in the light DOM
+    pub shadow_hosts: Vec<String>,
+    pub path: String,
+}
+
+impl ElementLocation {
+    pub fn describe(&self) -> String {
+        let mut place = match &self.frame {
+            Some(url) => format!("in frame {}", url),
+            None => "in top document".to_string(),
+        };
+        if !self.shadow_hosts.is_empty() {
+            place.push_str(&format!(", shadow root of {}", self.shadow_hosts.join(" >> ")));
+        }
+        format!("{} at {}", place, self.path)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextOptions {
+    pub format: TextFormat,
+    // Collapse runs of whitespace and blank lines
+    pub trim: bool,
+    pub max_chars: Option<usize>,
+    // Every match instead of the first
+    pub all: bool,
+}
+
+// What submit_form waits for before returning
+#[derive(Debug, Clone, Default)]
+pub enum SubmitWait {
+    #[default]
+    None,
+    Navigation,
+    Selector(String),
+}
+
+// Resource usage of a session's browser from CDP Performance metrics and SystemInfo
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    pub uptime: Duration,
+    pub idle: Duration,
+    pub js_heap_used_mb: f64,
+    pub js_heap_total_mb: f64,
+    pub dom_nodes: u64,
+    pub cpu_seconds: f64,
+}
+
+// Builder-style configuration for a controller, applied before the browser is launched:
+// `BrowserController::builder().session("work").cdp_timeout(30).auto_recover(true).build()?`
+#[derive(Default)]
+pub struct BrowserControllerBuilder {
+    session: Option<String>,
+    bus: Option<broadcast::Sender<SessionEvent>>,
+    cdp_timeout: Option<u64>,
+    auto_recover: bool,
+    isolated_helpers: bool,
+    bypass_csp: bool,
+    strict_csp: bool,
+    fake_media: Option<FakeMedia>,
+    limits: SessionLimits,
+    url_policy: UrlPolicy,
+    block_urls: Vec<String>,
+    flag_presets: BTreeMap<String, FlagPreset>,
+    navigation: NavigationConfig,
+    connect: Option<String>,
+    device_scale: Option<f64>,
+    media_emulation: MediaEmulation,
+}
+
+impl BrowserControllerBuilder {
+    // Named session; sessions get their own profile, daemon and event name
+    pub fn session(mut self, name: impl Into<String>) -> Self {
+        self.session = Some(name.into());
+        self
+    }
+
+    // Event bus to publish on, for sharing one bus between several controllers
+    pub fn event_bus(mut self, bus: broadcast::Sender<SessionEvent>) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    pub fn cdp_timeout(mut self, secs: u64) -> Self {
+        self.cdp_timeout = Some(secs);
+        self
+    }
+
+    pub fn auto_recover(mut self, enabled: bool) -> Self {
+        self.auto_recover = enabled;
+        self
+    }
+
+    pub fn isolated_helpers(mut self, enabled: bool) -> Self {
+        self.isolated_helpers = enabled;
+        self
+    }
+
+    pub fn bypass_csp(mut self, enabled: bool) -> Self {
+        self.bypass_csp = enabled;
+        self
+    }
+
+    pub fn strict_csp(mut self, enabled: bool) -> Self {
+        self.strict_csp = enabled;
+        self
+    }
+
+    pub fn fake_media(mut self, media: Option<FakeMedia>) -> Self {
+        self.fake_media = media;
+        self
+    }
+
+    pub fn limits(mut self, limits: SessionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn url_policy(mut self, policy: UrlPolicy) -> Self {
+        self.url_policy = policy;
+        self
+    }
+
+    pub fn block_urls(mut self, patterns: Vec<String>) -> Self {
+        self.block_urls = patterns;
+        self
+    }
+
+    pub fn flag_presets(mut self, presets: BTreeMap<String, FlagPreset>) -> Self {
+        self.flag_presets = presets;
+        self
+    }
+
+    pub fn navigation(mut self, navigation: NavigationConfig) -> Self {
+        self.navigation = navigation;
+        self
+    }
+
+    // Attach to this running browser's DevTools endpoint instead of launching one
+    pub fn connect(mut self, endpoint: impl Into<String>) -> Self {
+        self.connect = Some(endpoint.into());
+        self
+    }
+
+    // Fixed deviceScaleFactor for pixel-consistent screenshots
+    pub fn device_scale(mut self, scale: f64) -> Self {
+        self.device_scale = Some(scale);
+        self
+    }
+
+    // CSS media type (and features) emulated on every tab
+    pub fn media_emulation(mut self, emulation: MediaEmulation) -> Self {
+        self.media_emulation = emulation;
+        self
+    }
+
+    // Checks conflicting settings and missing fake media files; nothing is launched until init()
+    pub fn build(self) -> Result<BrowserController> {
+        if self.bypass_csp && self.strict_csp {
+            return Err(anyhow::anyhow!("CSP bypass can't be enabled in strict CSP mode"));
+        }
+        if let Some(name) = &self.session {
+            profiles::check_session_name(name)?;
+        }
+        let bus = self.bus.unwrap_or_else(|| broadcast::channel(256).0);
+        let mut controller = BrowserController::with_session(self.session.as_deref().unwrap_or("default"), bus);
+        if let Some(secs) = self.cdp_timeout {
+            controller.set_cdp_timeout(secs);
+        }
+        controller.set_auto_recover(self.auto_recover);
+        controller.set_isolated_helpers(self.isolated_helpers);
+        controller.bypass_csp = self.bypass_csp;
+        controller.set_strict_csp(self.strict_csp)?;
+        controller.set_fake_media(self.fake_media)?;
+        controller.set_limits(self.limits);
+        controller.set_url_policy(self.url_policy);
+        controller.interception.lock().unwrap().blocks = self.block_urls.iter().map(|pattern| BlockRule::new(pattern)).collect();
+        controller.set_flag_presets(self.flag_presets);
+        controller.set_navigation_defaults(self.navigation);
+        controller.set_connect(self.connect);
+        if self.device_scale.is_some_and(|scale| !(scale > 0.0 && scale <= 10.0)) {
+            return Err(anyhow::anyhow!("Device scale must be between 0 and 10"));
+        }
+        controller.device_scale = self.device_scale;
+        self.media_emulation.validate()?;
+        controller.media_emulation = self.media_emulation;
+        Ok(controller)
+    }
+}
+
 pub struct BrowserController {
     browser: Option<Browser>,
     page: Option<Page>,
-    temp_dir: Option<String>,
+    temp_dir: Option<PathBuf>,
+    cdp_timeout: Duration,
+    auto_recover: bool,
+    crashed: Arc<AtomicBool>,
+    events: EventPublisher,
+    dialog_action: Arc<std::sync::Mutex<Option<DialogAction>>>,
+    limits: SessionLimits,
+    // Where a running `har start` capture goes
+    har_path: Option<PathBuf>,
+    // robots.txt rules per origin, and when a batch last visited each
+    robots: HashMap<String, RobotsRules>,
+    robots_visits: HashMap<String, Instant>,
+    started_at: Option<Instant>,
+    last_activity: std::sync::Mutex<Instant>,
+    url_policy: Arc<UrlPolicy>,
+    // Init script sources by name, and what each tab registered them as
+    init_sources: BTreeMap<String, String>,
+    init_scripts: HashMap<TargetId, HashMap<String, ScriptIdentifier>>,
+    // Every tab in opening order; `page` is the active one
+    tabs: Vec<Page>,
+    flag_presets: BTreeMap<String, FlagPreset>,
+    navigation: NavigationConfig,
+    extra_headers: BTreeMap<String, String>,
+    network: Arc<std::sync::Mutex<NetworkLog>>,
+    interception: Arc<std::sync::Mutex<Interception>>,
+    intercepting: bool,
+    isolated_helpers: bool,
+    bypass_csp: bool,
+    strict_csp: bool,
+    // Keys pressed with `keydown` and not yet released, in press order
+    held_keys: std::sync::Mutex<Vec<&'static KeyDefinition>>,
+    fake_media: Option<FakeMedia>,
+    notifications: Arc<std::sync::Mutex<Vec<NotificationRecord>>>,
+    watching_notifications: bool,
+    watching_titles: bool,
+    watching_audio: bool,
+    helper_world: std::sync::Mutex<Option<ExecutionContextId>>,
+    // Existing browser to attach to instead of launching one (--connect)
+    connect: Option<String>,
+    // deviceScaleFactor forced on the page; launched browsers render at 1 without it
+    device_scale: Option<f64>,
+    media_emulation: MediaEmulation,
+    // Set when the browser belongs to someone else: closing only detaches from it
+    attached: Option<Attachment>,
+}
+
+impl Default for BrowserController {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BrowserController {
+    pub fn builder() -> BrowserControllerBuilder {
+        BrowserControllerBuilder::default()
+    }
+
     pub fn new() -> Self {
+        Self::with_session("default", broadcast::channel(256).0)
+    }
+
+    // A named browser session publishing onto a shared event bus; each session gets its own profile
+    pub fn with_session(name: &str, bus: broadcast::Sender<SessionEvent>) -> Self {
         Self {
             browser: None,
             page: None,
             temp_dir: None,
+            cdp_timeout: Duration::from_secs(DEFAULT_CDP_TIMEOUT_SECS),
+            auto_recover: false,
+            crashed: Arc::new(AtomicBool::new(false)),
+            events: EventPublisher::new(name, bus),
+            dialog_action: Arc::new(std::sync::Mutex::new(None)),
+            connect: None,
+            device_scale: None,
+            media_emulation: MediaEmulation::default(),
+            attached: None,
+            limits: SessionLimits::default(),
+            har_path: None,
+            robots: HashMap::new(),
+            robots_visits: HashMap::new(),
+            started_at: None,
+            last_activity: std::sync::Mutex::new(Instant::now()),
+            url_policy: Arc::new(UrlPolicy::default()),
+            init_sources: BTreeMap::new(),
+            init_scripts: HashMap::new(),
+            tabs: Vec::new(),
+            flag_presets: BTreeMap::new(),
+            navigation: NavigationConfig::default(),
+            extra_headers: BTreeMap::new(),
+            network: Arc::new(std::sync::Mutex::new(NetworkLog::default())),
+            interception: Arc::new(std::sync::Mutex::new(Interception::default())),
+            intercepting: false,
+            isolated_helpers: false,
+            bypass_csp: false,
+            strict_csp: false,
+            held_keys: std::sync::Mutex::new(Vec::new()),
+            fake_media: None,
+            notifications: Arc::new(std::sync::Mutex::new(Vec::new())),
+            watching_notifications: false,
+            watching_titles: false,
+            watching_audio: false,
+            helper_world: std::sync::Mutex::new(None),
         }
     }
 
-    pub async fn init(&mut self) -> Result<()> {
-        if self.browser.is_some() {
-            return Ok(());
-        }
+    // Another named session on the same event bus, inheriting this session's settings
+    pub fn new_session(&self, name: &str) -> Self {
+        let mut session = Self::with_session(name, self.event_bus());
+        session.cdp_timeout = self.cdp_timeout;
+        session.auto_recover = self.auto_recover;
+        session.limits = self.limits.clone();
+        session.set_url_policy(self.url_policy().clone());
+        // Block rules carry over, their hit counts don't
+        session.interception.lock().unwrap().blocks = self.block_rules().iter().map(|rule| BlockRule::new(&rule.pattern)).collect();
+        session.flag_presets = self.flag_presets.clone();
+        session.navigation = self.navigation.clone();
+        session.isolated_helpers = self.isolated_helpers;
+        session.bypass_csp = self.bypass_csp;
+        session.strict_csp = self.strict_csp;
+        session.fake_media = self.fake_media.clone();
+        session.connect = self.connect.clone();
+        session.device_scale = self.device_scale;
+        session.media_emulation = self.media_emulation.clone();
+        session
+    }
 
-        // Create a temporary user data directory to avoid conflicts with existing Chrome sessions
-        let temp_dir = format!("/tmp/browser-cli-{}-{}", std::process::id(), chrono::Utc::now().timestamp());
-        
+    pub fn set_cdp_timeout(&mut self, secs: u64) {
+        self.cdp_timeout = Duration::from_secs(secs.max(1));
+    }
+
+    pub fn cdp_timeout_secs(&self) -> u64 {
+        self.cdp_timeout.as_secs()
+    }
+
+    // Run fill/highlight/elements/ticker helpers in an isolated world, out of reach of page overrides
+    pub fn set_isolated_helpers(&mut self, enabled: bool) {
+        self.isolated_helpers = enabled;
+        *self.helper_world.lock().unwrap() = None;
+    }
+
+    pub fn isolated_helpers(&self) -> bool {
+        self.isolated_helpers
+    }
+
+    // Ignore the page's Content-Security-Policy so init scripts and injected audits aren't silently refused.
+    // Chrome applies this from the next document on.
+    pub async fn set_bypass_csp(&mut self, enabled: bool) -> Result<()> {
+        if enabled && self.strict_csp {
+            return Err(anyhow::anyhow!("CSP bypass can't be enabled in strict CSP mode"));
+        }
+        self.bypass_csp = enabled;
+        if self.page.is_some() {
+            self.execute(SetBypassCspParams::new(enabled)).await?;
+        }
+        Ok(())
+    }
+
+    // Render at a fixed deviceScaleFactor so screenshots have the same pixel size on every machine;
+    // None drops the override (launched browsers then fall back to 1, attached ones to their screen's)
+    pub async fn set_device_scale(&mut self, scale: Option<f64>) -> Result<()> {
+        if scale.is_some_and(|scale| !(scale > 0.0 && scale <= 10.0)) {
+            return Err(anyhow::anyhow!("Device scale must be between 0 and 10"));
+        }
+        self.device_scale = scale;
+        if let Some(page) = self.page.clone() {
+            self.apply_device_scale(&page).await?;
+        }
+        Ok(())
+    }
+
+    pub fn device_scale(&self) -> Option<f64> {
+        self.device_scale
+    }
+
+    async fn apply_device_scale(&self, page: &Page) -> Result<()> {
+        // Width and height 0 leave the size alone; launched pages already have an emulated viewport to keep
+        let (width, height) = match self.attached {
+            Some(_) => (0, 0),
+            None => LAUNCH_VIEWPORT,
+        };
+        if self.device_scale.is_none() && self.attached.is_some() {
+            self.cdp("device_scale", page.execute(ClearDeviceMetricsOverrideParams::default())).await?;
+        } else {
+            let scale = self.device_scale.unwrap_or(1.0);
+            self.cdp("device_scale", page.execute(SetDeviceMetricsOverrideParams::new(width, height, scale, false))).await?;
+        }
+        Ok(())
+    }
+
+    // Emulate CSS media on every tab; the default clears the emulation. Takes effect immediately, no reload
+    pub async fn set_media_emulation(&mut self, emulation: MediaEmulation) -> Result<()> {
+        emulation.validate()?;
+        self.media_emulation = emulation;
+        if let Some(page) = self.page.clone() {
+            self.cdp("emulate_media", page.execute(self.media_emulation.params())).await?;
+        }
+        Ok(())
+    }
+
+    pub fn media_emulation(&self) -> &MediaEmulation {
+        &self.media_emulation
+    }
+
+    pub fn bypass_csp(&self) -> bool {
+        self.bypass_csp
+    }
+
+    // Keep the page's CSP fully enforced (e.g. when testing the CSP itself) and report refused injections
+    pub fn set_strict_csp(&mut self, enabled: bool) -> Result<()> {
+        if enabled && self.bypass_csp {
+            return Err(anyhow::anyhow!("Strict CSP mode can't be enabled while CSP bypass is on"));
+        }
+        self.strict_csp = enabled;
+        Ok(())
+    }
+
+    pub fn strict_csp(&self) -> bool {
+        self.strict_csp
+    }
+
+    // Takes effect when the browser is launched
+    pub fn set_fake_media(&mut self, media: Option<FakeMedia>) -> Result<()> {
+        for file in media.iter().flat_map(|media| media.video_file.iter().chain(media.audio_file.iter())) {
+            if !file.is_file() {
+                return Err(anyhow::anyhow!("Fake media file not found: {}", file.display()));
+            }
+        }
+        self.fake_media = media;
+        Ok(())
+    }
+
+    pub fn set_auto_recover(&mut self, enabled: bool) {
+        self.auto_recover = enabled;
+    }
+
+    pub fn auto_recover(&self) -> bool {
+        self.auto_recover
+    }
+
+    pub fn event_bus(&self) -> broadcast::Sender<SessionEvent> {
+        self.events.bus()
+    }
+
+    pub fn session_name(&self) -> &str {
+        self.events.session()
+    }
+
+    pub fn set_limits(&mut self, limits: SessionLimits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &SessionLimits {
+        &self.limits
+    }
+
+    pub fn set_url_policy(&mut self, policy: UrlPolicy) {
+        self.url_policy = Arc::new(policy);
+        self.interception.lock().unwrap().policy = Arc::clone(&self.url_policy);
+    }
+
+    pub fn url_policy(&self) -> &UrlPolicy {
+        &self.url_policy
+    }
+
+    pub fn set_flag_presets(&mut self, presets: BTreeMap<String, FlagPreset>) {
+        self.flag_presets = presets;
+    }
+
+    pub fn flag_presets(&self) -> &BTreeMap<String, FlagPreset> {
+        &self.flag_presets
+    }
+
+    pub fn set_navigation_defaults(&mut self, navigation: NavigationConfig) {
+        self.navigation = navigation;
+    }
+
+    // DevTools endpoint of a running browser (ws://…/devtools/browser/… or http://host:port) to use
+    // instead of launching one; its profile is left alone and the browser keeps running on close
+    pub fn set_connect(&mut self, endpoint: Option<String>) {
+        self.connect = endpoint;
+    }
+
+    pub fn attachment(&self) -> Option<&Attachment> {
+        self.attached.as_ref()
+    }
+
+    pub fn set_dialog_action(&self, action: Option<DialogAction>) {
+        *self.dialog_action.lock().unwrap() = action;
+    }
+
+    pub async fn init(&mut self) -> Result<()> {
+        if self.browser.is_some() {
+            return Ok(());
+        }
+
+        // Use the browser given with --connect, else reuse the session's daemon browser when one is running,
+        // otherwise launch our own
+        let daemon_target = match self.connect {
+            Some(_) => None,
+            None => daemon::attach_target(self.session_name()).await,
+        };
+        let (browser, page, temp_dir) = match (self.connect.clone(), daemon_target) {
+            (Some(endpoint), _) => {
+                let (browser, page) = self.connect_external(&endpoint).await?;
+                let url = self.cdp("url", page.url()).await?.unwrap_or_default();
+                status!("{} Connected to browser at {} ({})", "🔗".green(), endpoint, url);
+                self.attached = Some(Attachment::External(endpoint));
+                (browser, page, None)
+            }
+            (None, Some((info, target))) => {
+                let (browser, page) = self.attach(&target).await?;
+                status!("{} Attached to daemon for session '{}' (pid {}) at {}", "🔗".green(), info.session, info.pid, target.url);
+                self.attached = Some(Attachment::Daemon(info));
+                (browser, page, None)
+            }
+            (None, None) => {
+                let (browser, page, temp_dir) = self.launch().await?;
+                (browser, page, Some(temp_dir))
+            }
+        };
+        if self.fake_media.is_some() {
+            // The fake UI flag accepts prompts; granting up front also covers permissions.query() checks
+            self.cdp("grant_permissions", browser.execute(GrantPermissionsParams::new(vec![PermissionType::AudioCapture, PermissionType::VideoCapture]))).await?;
+        }
+        self.crashed.store(false, Ordering::SeqCst);
+        self.watch_browser_events(&browser).await?;
+        self.network.lock().unwrap().clear();
+        self.prepare_page(&page).await?;
+        self.intercepting = self.interception.lock().unwrap().is_active();
+        
+        self.browser = Some(browser);
+        self.tabs = vec![page.clone()];
+        self.page = Some(page);
+        self.temp_dir = temp_dir;
+        self.started_at = Some(Instant::now());
+        *self.last_activity.lock().unwrap() = Instant::now();
+        
+        if self.attached.is_none() {
+            status!("{} Browser ready", "🚀".green());
+        }
+        Ok(())
+    }
+
+    // Launch a fresh browser with a throwaway profile
+    async fn launch(&self) -> Result<(Browser, Page, PathBuf)> {
+        // Create a temporary user data directory to avoid conflicts with existing Chrome sessions
+        let temp_dir = profiles::new_profile_dir(self.session_name());
+        
+        let (executable, _) = chrome::find_executable().ok_or_else(|| Error::BrowserNotFound(chrome::not_found_help()))?;
+        let mut config = BrowserConfig::builder()
+            .chrome_executable(&executable)
+            .window_size(1280, 800)
+            .user_data_dir(&temp_dir);
+        if let Some(media) = &self.fake_media {
+            config = config
+                .arg("--use-fake-device-for-media-stream")
+                .arg("--use-fake-ui-for-media-stream");
+            if let Some(video) = &media.video_file {
+                config = config.arg(format!("--use-file-for-fake-video-capture={}", video.display()));
+            }
+            if let Some(audio) = &media.audio_file {
+                config = config.arg(format!("--use-file-for-fake-audio-capture={}", audio.display()));
+            }
+        }
+        
+        let launching = progress::spinner("Launching browser");
         let (browser, mut handler) = Browser::launch(
-            BrowserConfig::builder()
-                .window_size(1280, 800)
-                .user_data_dir(&temp_dir)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build browser config: {}", e))?,
+            config.build().map_err(|e| anyhow::anyhow!("Failed to build browser config: {}", e))?,
         )
         .await
-        .map_err(|e| anyhow::anyhow!("Failed to launch browser. Make sure Chrome is installed. Error: {}", e))?;
+        .map_err(|e| Error::Launch { path: executable.clone(), reason: e.to_string() })?;
+        launching.finish_and_clear();
 
         let _handle = tokio::task::spawn(async move {
             while let Some(h) = handler.next().await {
-                if let Err(_) = h {
+                if h.is_err() {
                     // Suppress handler errors
                 }
             }
         });
 
         let page = browser.new_page("about:blank").await?;
+        Ok((browser, page, temp_dir))
+    }
+
+    // Connect to a daemon's browser and take over its current page
+    async fn attach(&self, target: &AttachTarget) -> Result<(Browser, Page)> {
+        let (mut browser, mut handler) = Browser::connect(target.websocket.clone()).await
+            .map_err(|e| anyhow::anyhow!("Failed to attach to daemon browser at {}: {}", target.websocket, e))?;
+        tokio::task::spawn(async move { while handler.next().await.is_some() {} });
         
-        self.browser = Some(browser);
+        // Existing targets are only tracked after fetching them, and take a moment to attach
+        self.cdp("fetch_targets", browser.fetch_targets()).await?;
+        let target_id = TargetId::from(target.target_id.clone());
+        for _ in 0..20 {
+            if let Ok(page) = browser.get_page(target_id.clone()).await {
+                return Ok((browser, page));
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        Err(anyhow::anyhow!("Daemon page {} did not become available", target.target_id))
+    }
+
+    // Connect to a browser someone else started and drive its first open tab (or a new one if it has none)
+    async fn connect_external(&self, endpoint: &str) -> Result<(Browser, Page)> {
+        let connecting = progress::spinner(format!("Connecting to {}", endpoint));
+        let connected = tokio::time::timeout(self.cdp_timeout, Browser::connect(endpoint.to_string())).await;
+        connecting.finish_and_clear();
+        let (mut browser, mut handler) = connected
+            .map_err(|_| anyhow::anyhow!("Timed out connecting to browser at {}", endpoint))?
+            .map_err(|e| anyhow::anyhow!("Failed to connect to browser at {}: {} (is it running with --remote-debugging-port?)", endpoint, e))?;
+        tokio::task::spawn(async move { while handler.next().await.is_some() {} });
+        
+        let targets = self.cdp("fetch_targets", browser.fetch_targets()).await?;
+        let tab = targets.into_iter().find(|target| target.r#type == "page" && !target.url.starts_with("devtools://"));
+        if let Some(tab) = tab {
+            for _ in 0..20 {
+                if let Ok(page) = browser.get_page(tab.target_id.clone()).await {
+                    return Ok((browser, page));
+                }
+                sleep(Duration::from_millis(100)).await;
+            }
+        }
+        let page = self.cdp("new_page", browser.new_page("about:blank")).await?;
+        Ok((browser, page))
+    }
+
+    // DevTools endpoint and current page for clients attaching through the daemon
+    pub async fn attach_target(&self) -> Result<AttachTarget> {
+        self.ensure_page()?;
+        let (Some(browser), Some(page)) = (self.browser.as_ref(), self.page.as_ref()) else {
+            return Err(Error::NotInitialized.into());
+        };
+        Ok(AttachTarget {
+            websocket: browser.websocket_address().clone(),
+            target_id: page.target_id().as_ref().to_string(),
+            url: self.cdp("url", page.url()).await?.unwrap_or_default(),
+        })
+    }
+
+    // Start already signed in: copy the given domains' cookies (and localStorage) out of a real Chrome profile
+    pub async fn import_chrome_profile(&mut self, profile: Option<&str>, domains: &[String], local_storage: bool) -> Result<ImportSummary> {
+        self.ensure_page()?;
+        
+        let (profile, state) = import::read_chrome_profile(profile, domains, local_storage, self.cdp_timeout).await?;
+        let local_storage_items = self.import_state(&state).await?;
+        let summary = ImportSummary { profile, cookies: state.cookies.len(), local_storage_items };
+        status!("{} Imported {} cookies and {} localStorage items from Chrome profile '{}' ({})", "📥".green(),
+            summary.cookies, summary.local_storage_items, summary.profile, domains.join(", "));
+        Ok(summary)
+    }
+
+    // Put cookies and localStorage read from another profile into this browser. localStorage is written
+    // from a scratch tab that loads each origin offline, so the current page is left alone.
+    pub async fn import_state(&mut self, state: &ImportedState) -> Result<usize> {
+        self.ensure_page()?;
+        
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        if !state.cookies.is_empty() {
+            let cookies = state.cookies.iter().map(import::cookie_param).collect();
+            self.cdp("set_cookies", browser.execute(StorageSetCookiesParams::new(cookies))).await?;
+        }
+        
+        let mut items = 0;
+        if !state.local_storage.is_empty() {
+            let scratch = self.cdp("new_page", browser.new_page("about:blank")).await?;
+            let written = async {
+                for (origin, entries) in &state.local_storage {
+                    import::open_blank_origin(&scratch, origin, self.cdp_timeout).await?;
+                    let script = format!(
+                        "(() => {{ for (const [key, value] of {}) localStorage.setItem(key, value); }})()",
+                        serde_json::to_string(entries)?
+                    );
+                    self.cdp("evaluate", scratch.evaluate(script)).await?;
+                    items += entries.len();
+                }
+                Ok::<(), anyhow::Error>(())
+            }.await;
+            self.cdp("close_tab", scratch.close()).await?;
+            written?;
+        }
+        Ok(items)
+    }
+
+    // Links for a human to take over the current page, see handoff::Handoff
+    pub async fn handoff(&self) -> Result<Handoff> {
+        self.ensure_page()?;
+        
+        let (Some(browser), Some(page)) = (self.browser.as_ref(), self.page.as_ref()) else {
+            return Err(Error::NotInitialized.into());
+        };
+        Ok(Handoff::new(
+            self.cdp("url", page.url()).await?.unwrap_or_default(),
+            self.cdp("get_title", page.get_title()).await?.unwrap_or_default(),
+            browser.websocket_address().clone(),
+            page.target_id().as_ref(),
+        ))
+    }
+
+    // Whether the browser still answers; false once it has exited or was closed from elsewhere
+    pub async fn is_responsive(&self) -> bool {
+        let Some(browser) = self.browser.as_ref() else {
+            return false;
+        };
+        matches!(tokio::time::timeout(Duration::from_secs(5), browser.version()).await, Ok(Ok(_)))
+    }
+
+    // Pick up tabs the page opened itself (target=_blank, window.open) and forget ones closed elsewhere;
+    // if the active tab is gone the most recently opened one takes over
+    async fn sync_tabs(&mut self) -> Result<()> {
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        let pages = self.cdp("pages", browser.pages()).await?;
+        let open: Vec<TargetId> = pages.iter().map(|page| page.target_id().clone()).collect();
+        self.tabs.retain(|tab| open.contains(tab.target_id()));
+        self.init_scripts.retain(|id, _| open.contains(id));
+        for page in pages {
+            if !self.tabs.iter().any(|tab| tab.target_id() == page.target_id()) {
+                self.prepare_page(&page).await?;
+                self.catch_up_tab(&page).await?;
+                self.tabs.push(page);
+            }
+        }
+        
+        let active_open = self.page.as_ref().is_some_and(|page| open.contains(page.target_id()));
+        if !active_open {
+            let Some(last) = self.tabs.last().cloned() else {
+                return Err(anyhow::anyhow!("All tabs were closed, run 'close' to restart the browser"));
+            };
+            self.activate(last);
+        }
+        Ok(())
+    }
+
+    // Make a tab the one commands act on; per-page state of the previous tab doesn't carry over
+    fn activate(&mut self, page: Page) {
         self.page = Some(page);
-        self.temp_dir = Some(temp_dir);
+        *self.helper_world.lock().unwrap() = None;
+        self.held_keys.lock().unwrap().clear();
+        self.watching_notifications = false;
+        self.watching_audio = false;
+    }
+
+    // Find a tab by 1-based index or (a prefix of) its target id
+    fn find_tab(&self, which: &str) -> Result<usize> {
+        if let Ok(index) = which.parse::<usize>() {
+            if index >= 1 && index <= self.tabs.len() {
+                return Ok(index - 1);
+            }
+            return Err(anyhow::anyhow!("No tab {} (there are {} tabs)", index, self.tabs.len()));
+        }
+        let matches: Vec<usize> = self.tabs.iter().enumerate()
+            .filter(|(_, tab)| tab.target_id().as_ref().to_lowercase().starts_with(&which.to_lowercase()))
+            .map(|(position, _)| position)
+            .collect();
+        match matches.as_slice() {
+            [position] => Ok(*position),
+            [] => Err(anyhow::anyhow!("No tab with id '{}'", which)),
+            _ => Err(anyhow::anyhow!("Tab id '{}' is ambiguous, give more of it", which)),
+        }
+    }
+
+    fn active_tab(&self) -> Option<usize> {
+        let page = self.page.as_ref()?;
+        self.tabs.iter().position(|tab| tab.target_id() == page.target_id())
+    }
+
+    pub async fn tabs(&mut self) -> Result<Vec<TabInfo>> {
+        self.ensure_page()?;
+        self.sync_tabs().await?;
+        
+        let mut tabs = Vec::new();
+        for position in 0..self.tabs.len() {
+            tabs.push(self.tab_info(position).await?);
+        }
+        Ok(tabs)
+    }
+
+    // Open a tab with the session's settings, load the URL in it and make it active
+    pub async fn new_tab(&mut self, url: &str) -> Result<TabInfo> {
+        self.ensure_initialized().await?;
+        self.url_policy.check(url).await?;
+        self.sync_tabs().await?;
+        
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        let page = self.cdp("new_page", browser.new_page("about:blank")).await?;
+        self.prepare_page(&page).await?;
+        self.catch_up_tab(&page).await?;
+        self.tabs.push(page.clone());
+        self.activate(page.clone());
+        if url != "about:blank" {
+            self.cdp("goto", page.goto(url)).await?;
+            self.cdp("wait_for_navigation", page.wait_for_navigation()).await?;
+        }
+        
+        let info = self.tab_info(self.tabs.len() - 1).await?;
+        status!("{} Opened tab {} ({}): {}", "🗂️".green(), info.index, info.id, info.url);
+        Ok(info)
+    }
+
+    pub async fn switch_tab(&mut self, which: &str) -> Result<TabInfo> {
+        self.ensure_page()?;
+        self.sync_tabs().await?;
+        
+        let position = self.find_tab(which)?;
+        let page = self.tabs[position].clone();
+        self.cdp("bring_to_front", page.bring_to_front()).await?;
+        self.activate(page);
+        if let Some(Attachment::Daemon(info)) = &self.attached {
+            // Later commands attach to whatever tab the daemon reports, so keep it in step
+            daemon::activate(&info.session, self.tabs[position].target_id().as_ref()).await?;
+        }
+        
+        let info = self.tab_info(position).await?;
+        status!("{} Switched to tab {} ({}): {}", "🗂️".green(), info.index, info.id, info.url);
+        Ok(info)
+    }
+
+    // Close a tab (the active one by default); the last tab stays open, use 'close' for the browser
+    pub async fn close_tab(&mut self, which: Option<&str>) -> Result<TabInfo> {
+        self.ensure_page()?;
+        self.sync_tabs().await?;
+        
+        let position = match which {
+            Some(which) => self.find_tab(which)?,
+            None => self.active_tab().ok_or(Error::NotInitialized)?,
+        };
+        if self.tabs.len() == 1 {
+            return Err(anyhow::anyhow!("Refusing to close the last tab, use 'close' to close the browser"));
+        }
+        let closed = self.tab_info(position).await?;
+        let page = self.tabs.remove(position);
+        self.init_scripts.remove(page.target_id());
+        self.cdp("close_tab", page.close()).await?;
+        if closed.active {
+            // Like a browser, fall back to the tab that was next to it
+            let next = self.tabs[position.min(self.tabs.len() - 1)].clone();
+            self.cdp("bring_to_front", next.bring_to_front()).await?;
+            self.activate(next);
+            if let Some(Attachment::Daemon(info)) = &self.attached {
+                daemon::activate(&info.session, self.page.as_ref().unwrap().target_id().as_ref()).await?;
+            }
+        }
+        
+        status!("{} Closed tab {} ({}): {}", "🗂️".green(), closed.index, closed.id, closed.url);
+        Ok(closed)
+    }
+
+    async fn tab_info(&self, position: usize) -> Result<TabInfo> {
+        let tab = &self.tabs[position];
+        Ok(TabInfo {
+            index: position + 1,
+            id: tab.target_id().as_ref().chars().take(TAB_ID_LEN).collect(),
+            url: self.cdp("url", tab.url()).await?.unwrap_or_default(),
+            title: self.cdp("get_title", tab.get_title()).await?.unwrap_or_default(),
+            active: self.active_tab() == Some(position),
+        })
+    }
+
+    pub async fn navigate(&mut self, url: &str, options: NavigateOptions) -> Result<PageSummary> {
+        self.ensure_initialized().await?;
+        
+        self.url_policy.check(url).await?;
+        let max_js_errors = options.max_js_errors.or(self.navigation.max_js_errors);
+        let wait_until = options.wait_until.or_else(|| self.navigation.wait_until.clone()).unwrap_or_default();
+        // Subscribe before navigating so errors thrown during load are counted too. The bus also carries every
+        // response and log line, so it's read as events arrive rather than afterwards, when a busy page would
+        // have pushed the earliest errors out of it.
+        let js_errors = max_js_errors.map(|_| {
+            let mut receiver = self.events.bus().subscribe();
+            let session = self.session_name().to_string();
+            let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = Arc::clone(&errors);
+            let collector = tokio::spawn(async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(SessionEvent { session: from, event: BrowserEvent::ConsoleError { message } }) if from == session => {
+                            sink.lock().unwrap().push(message);
+                        }
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+            (errors, collector)
+        });
+        progress!("{}", format!("Navigating to: {}", url).blue());
+        
+        let arrived: Result<()> = async {
+            let loading = progress::spinner(format!("Loading {}", url));
+            let loaded = match wait_until {
+                WaitUntil::DomContentLoaded => self.goto_dom_content_loaded(url).await,
+                _ => self.cdp("goto", self.page.as_ref().unwrap().goto(url)).await.map(|_| ()),
+            };
+            loading.finish_and_clear();
+            // Timeouts and policy refusals keep their own error; anything else failed the navigation itself
+            loaded.map_err(|e| match e.downcast_ref::<Error>() {
+                Some(_) => e,
+                None => Error::Navigation { url: url.to_string(), reason: e.to_string() }.into(),
+            })?;
+            
+            match &wait_until {
+                WaitUntil::NetworkIdle => self.wait_for_network_idle(Duration::from_millis(DEFAULT_NETWORK_IDLE_MS), None).await?,
+                WaitUntil::Selector(selector) => self.wait_for_selector(selector, None, &WaitCondition::default()).await?,
+                WaitUntil::Load | WaitUntil::DomContentLoaded => {}
+            }
+            Ok(())
+        }.await;
+        if let (Err(_), Some((_, collector))) = (&arrived, &js_errors) {
+            collector.abort();
+        }
+        arrived?;
+        
+        if let (Some(budget), Some((errors, collector))) = (max_js_errors, js_errors) {
+            tokio::time::sleep(Duration::from_millis(JS_ERROR_SETTLE_MS)).await;
+            collector.abort();
+            let errors = std::mem::take(&mut *errors.lock().unwrap());
+            if errors.len() > budget {
+                for message in &errors {
+                    status!("  {} {}", "✗".red(), message.lines().next().unwrap_or_default());
+                }
+                return Err(anyhow::anyhow!("{} JS error(s) after loading {} (budget {})", errors.len(), url, budget));
+            }
+        }
+        
+        // Get concise page information for AI/agents
+        let summary = self.page_summary().await?;
+        status!("{} {}", "✓".green(), summary);
+        
+        Ok(summary)
+    }
+
+    // goto resolves at the load event; stop waiting at DOMContentLoaded instead and let the rest of the load
+    // carry on. A navigation that fails outright still reports its error.
+    async fn goto_dom_content_loaded(&self, url: &str) -> Result<()> {
+        let page = self.page.as_ref().unwrap();
+        let mut ready = self.cdp("event_listener", page.event_listener::<EventDomContentEventFired>()).await?;
+        let loaded = self.cdp("goto", page.goto(url));
+        tokio::select! {
+            biased;
+            loaded = loaded => loaded.map(|_| ()),
+            Some(_) = ready.next() => Ok(()),
+        }
+    }
+
+    // navigate for batch jobs: transient failures (dropped connections, timeouts) are retried with exponential
+    // backoff before the page counts as failed
+    pub async fn navigate_retrying(&mut self, url: &str) -> Result<PageSummary> {
+        let (mut attempt, mut delay) = (1, Duration::from_millis(BATCH_RETRY_DELAY_MS));
+        loop {
+            match self.navigate(url, NavigateOptions::default()).await {
+                Err(e) if attempt < BATCH_ATTEMPTS && is_transient(&e) => {
+                    warning!("Loading {} failed ({}); retrying in {}s", url, e, delay.as_secs_f64());
+                    sleep(delay).await;
+                    attempt += 1;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn screenshot(&self, filename: Option<&str>) -> Result<String> {
+        self.ensure_page()?;
+        
+        let path = if let Some(name) = filename {
+            paths::capture_path(name)?
+        } else {
+            // Generate filename based on route and timestamp
+            let page = self.page.as_ref().unwrap();
+            let url = self.cdp("url", page.url()).await?.unwrap_or_default();
+            let route = self.url_to_route(&url);
+            let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+            paths::capture_path(&format!("{}_{}.png", route, timestamp))?
+        };
+        let final_filename = path.display().to_string();
+        
+        let page = self.page.as_ref().unwrap();
+        self.save_screenshot(page, &path).await?;
+        
+        status!("{} Screenshot: {}", "📸".cyan(), final_filename);
+        Ok(final_filename)
+    }
+
+    // What the browser's print preview shows: a PDF when `filename` ends in .pdf (backgrounds printed, @page
+    // sizes honoured), otherwise a full-page PNG rendered with print media. The session's own media
+    // emulation is put back afterwards.
+    pub async fn print_capture(&self, filename: Option<&str>) -> Result<String> {
+        self.ensure_page()?;
+        
+        let page = self.page.as_ref().unwrap();
+        let path = if let Some(name) = filename {
+            paths::capture_path(name)?
+        } else {
+            let url = self.cdp("url", page.url()).await?.unwrap_or_default();
+            let route = self.url_to_route(&url);
+            let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+            paths::capture_path(&format!("{}_{}_print.png", route, timestamp))?
+        };
+        let final_filename = path.display().to_string();
+        
+        if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("pdf")) {
+            let params = PrintToPdfParams::builder().print_background(true).prefer_css_page_size(true).build();
+            let pdf = self.cdp("print_to_pdf", page.pdf(params)).await?;
+            tokio::fs::write(&path, pdf).await?;
+            status!("{} PDF: {}", "🖨️".cyan(), final_filename);
+        } else {
+            let mut print = self.media_emulation.clone();
+            print.media = Some("print".to_string());
+            self.cdp("emulate_media", page.execute(print.params())).await?;
+            let saved = self.save_capture(page, &path, true).await;
+            self.cdp("emulate_media", page.execute(self.media_emulation.params())).await?;
+            saved?;
+            status!("{} Print preview: {}", "🖨️".cyan(), final_filename);
+        }
+        Ok(final_filename)
+    }
+
+    // Visit every path on both hosts, screenshot each at the same viewport and scroll position, and diff
+    // the pair. Files and an HTML report go to `out` (reports/compare-envs in the run by default). Progress is
+    // saved per path, so `resume` continues a run in `out` (or the latest run) instead of starting over.
+    pub async fn compare_envs(&mut self, base: &str, target: &str, paths: &[String], options: &CompareOptions) -> Result<CompareReport> {
+        self.ensure_initialized().await?;
+        
+        let mode = options.mode;
+        let dir = match (&options.out, options.resume.then(compare::latest_run_dir).flatten()) {
+            (Some(dir), _) => {
+                std::fs::create_dir_all(dir)?;
+                dir.clone()
+            }
+            (None, Some(latest)) => latest,
+            (None, None) => paths::run_subdir(Artifact::Reports, "compare-envs")?,
+        };
+        let job = format!("compare-envs {} {} {:?}", base, target, mode);
+        let mut checkpoint = Checkpoint::<PathComparison>::open(&dir.join(compare::PROGRESS_FILE), &job, options.resume)?;
+        if checkpoint.completed_count() > 0 {
+            status!("{} Resuming {}: {} of {} paths already compared, retrying {} that failed", "⏯".cyan(),
+                dir.display(), checkpoint.completed_count(), paths.len(), checkpoint.failed_count());
+        }
+        // Diffing happens in a blank tab of its own, where no page CSP stands in the way of data: images
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        let scratch = self.cdp("new_page", browser.new_page("about:blank")).await?;
+        
+        // Paths that only differ in query order or fragment are the same page
+        let mut visited = Visited::default();
+        let paths: Vec<&String> = paths.iter().filter(|path| visited.insert(&compare::join_url(base, path))).collect();
+        let comparing = progress::items(paths.len() as u64, "Comparing");
+        let mut results = Vec::new();
+        for (index, path) in paths.iter().enumerate() {
+            if let Some(done) = checkpoint.completed(path) {
+                // The threshold may have changed since
+                let mut done = done.clone();
+                done.passed = done.mismatch <= options.threshold;
+                results.push(done);
+                comparing.inc(1);
+                continue;
+            }
+            comparing.set_message(path.to_string());
+            let stem = compare::file_stem(index, path);
+            let extension = if mode == CompareMode::Text { "txt" } else { "png" };
+            let mut result = PathComparison::new(
+                path,
+                compare::join_url(base, path),
+                compare::join_url(target, path),
+                dir.join(format!("{}-base.{}", stem, extension)),
+                dir.join(format!("{}-target.{}", stem, extension)),
+            );
+            let compared = async {
+                if !options.ignore_robots {
+                    self.robots_allow(&result.base_url).await?;
+                    self.robots_allow(&result.target_url).await?;
+                }
+                match mode {
+                    CompareMode::Visual => self.compare_visual(&scratch, &mut result, &dir.join(format!("{}-diff.png", stem))).await,
+                    CompareMode::Text => self.compare_text(&mut result, &dir.join(format!("{}.diff", stem))).await,
+                }
+            }.await;
+            match compared {
+                Ok(()) => {
+                    result.passed = result.mismatch <= options.threshold;
+                    checkpoint.complete(path, result.clone())?;
+                }
+                Err(e) => {
+                    result.error = Some(e.to_string());
+                    checkpoint.fail(path, &e.to_string())?;
+                }
+            }
+            results.push(result);
+            comparing.inc(1);
+        }
+        comparing.finish_and_clear();
+        self.cdp("close_tab", scratch.close()).await?;
+        
+        let report = CompareReport {
+            mode,
+            base: base.to_string(),
+            target: target.to_string(),
+            threshold: options.threshold,
+            report: dir.join("report.html"),
+            dir,
+            paths: results,
+        };
+        compare::write_html_report(&report)?;
+        std::fs::write(report.dir.join("report.json"), serde_json::to_string_pretty(&report)?)?;
+        Ok(report)
+    }
+
+    // Breadth-first crawl from `start` and/or the pages of `options.from_sitemap`: visit each page (robots.txt
+    // and a politeness delay permitting), save its text, HTML and a screenshot, and queue the http(s) links not
+    // seen yet, up to `depth` links away from where it started. Stops after `max_pages` visits; report.json
+    // goes beside the page files. Every page is checkpointed as it's done, so `resume` replays the finished
+    // ones (re-queueing their links) and carries on where an interrupted crawl stopped.
+    pub async fn crawl(&mut self, start: Option<&str>, options: &CrawlOptions) -> Result<CrawlReport> {
+        self.ensure_initialized().await?;
+        
+        let mut seeds = Vec::new();
+        if let Some(start) = start {
+            let start_url = url::Url::parse(start).map_err(|e| anyhow::anyhow!("Invalid start URL {}: {}", start, e))?;
+            seeds.push(start_url.to_string());
+        }
+        if let Some(sitemap) = &options.from_sitemap {
+            seeds.extend(self.sitemap_urls(sitemap, &options.sitemap_filter).await?);
+        }
+        let Some(first) = seeds.first() else {
+            return Err(anyhow::anyhow!("crawl needs a start URL or --from-sitemap"));
+        };
+        let origin = url::Url::parse(first).map_err(|e| anyhow::anyhow!("Invalid URL {}: {}", first, e))?.origin();
+        let dir = match (&options.out, options.resume.then(crawl::latest_run_dir).flatten()) {
+            (Some(dir), _) => {
+                std::fs::create_dir_all(dir)?;
+                dir.clone()
+            }
+            (None, Some(latest)) => latest,
+            (None, None) => paths::run_subdir(Artifact::Reports, "crawl")?,
+        };
+        let job = format!(
+            "crawl {} {} depth {}{}",
+            start.unwrap_or("-"),
+            options.from_sitemap.as_deref().unwrap_or("-"),
+            options.depth,
+            if options.same_origin { " same-origin" } else { "" }
+        );
+        let mut checkpoint = Checkpoint::<CrawlPage>::open(&dir.join(crawl::PROGRESS_FILE), &job, options.resume)?;
+        if checkpoint.completed_count() > 0 {
+            status!("{} Resuming {}: {} pages already crawled, retrying {} that failed", "⏯".cyan(),
+                dir.display(), checkpoint.completed_count(), checkpoint.failed_count());
+        }
+        
+        // Queued: every URL ever put in the frontier. Visited: pages loaded, including where they redirected
+        // to and their canonical URLs, so the same page isn't saved twice.
+        let (mut queued, mut visited) = (Visited::default(), Visited::default());
+        let mut frontier = VecDeque::new();
+        for seed in seeds {
+            if queued.insert(&seed) {
+                frontier.push_back((seed, 0));
+            }
+        }
+        let mut pages: Vec<CrawlPage> = Vec::new();
+        let mut last_visit: Option<Instant> = None;
+        let crawling = progress::items(1, "Crawling");
+        while pages.len() < options.max_pages {
+            let Some((url, depth)) = frontier.pop_front() else {
+                break;
+            };
+            if !visited.insert(&url) {
+                continue;
+            }
+            // Finished before an interruption: take the page as it was, queueing what it queued then
+            if let Some(done) = checkpoint.completed(&url) {
+                let done = done.clone();
+                for same in done.final_url.iter().chain(&done.duplicate_of) {
+                    visited.insert(same);
+                }
+                for link in &done.queued {
+                    if queued.insert(link) {
+                        frontier.push_back((link.clone(), depth + 1));
+                    }
+                }
+                pages.push(done);
+                crawling.set_length((pages.len() + frontier.len()).min(options.max_pages) as u64);
+                crawling.set_position(pages.len() as u64);
+                continue;
+            }
+            crawling.set_message(url.clone());
+            if let Some(wait) = last_visit.map(|last| options.delay.saturating_sub(last.elapsed())) {
+                sleep(wait).await;
+            }
+            last_visit = Some(Instant::now());
+            
+            let mut page = CrawlPage::new(&url, depth);
+            let stem = compare::file_stem(pages.len(), url.split_once("://").map_or(url.as_str(), |(_, rest)| rest));
+            let crawled = async {
+                if !options.ignore_robots {
+                    self.robots_allow(&url).await?;
+                }
+                let summary = self.navigate_retrying(&url).await?;
+                page.final_url = Some(summary.url.clone());
+                page.title = Some(summary.title.clone());
+                let canonical = self.canonical_url().await?;
+                for same in [Some(summary.url), canonical].into_iter().flatten() {
+                    if normalize_url(&same) != normalize_url(&url) && !visited.insert(&same) {
+                        page.duplicate_of = Some(same);
+                        return Ok(());
+                    }
+                }
+                
+                let text = self.evaluate("document.body ? document.body.innerText : ''").await?.into_value::<String>().unwrap_or_default();
+                let text_file = dir.join(format!("{}.txt", stem));
+                tokio::fs::write(&text_file, tidy_text(&text)).await?;
+                page.text_file = Some(text_file);
+                let html_file = dir.join(format!("{}.html", stem));
+                tokio::fs::write(&html_file, self.get_html(None, false).await?).await?;
+                page.html_file = Some(html_file);
+                let screenshot = dir.join(format!("{}.png", stem));
+                self.save_screenshot(self.page.as_ref().ok_or(Error::NotInitialized)?, &screenshot).await?;
+                page.screenshot = Some(screenshot);
+                
+                if depth < options.depth {
+                    for link in self.get_links(&LinkFilter::default()).await? {
+                        let Ok(mut next) = url::Url::parse(&link.url) else {
+                            continue;
+                        };
+                        if !matches!(next.scheme(), "http" | "https") || (options.same_origin && next.origin() != origin) {
+                            continue;
+                        }
+                        next.set_fragment(None);
+                        if queued.insert(next.as_str()) {
+                            frontier.push_back((next.to_string(), depth + 1));
+                            page.queued.push(next.to_string());
+                            page.new_links += 1;
+                        }
+                    }
+                }
+                Ok::<(), anyhow::Error>(())
+            }.await;
+            match crawled {
+                Ok(()) => checkpoint.complete(&url, page.clone())?,
+                Err(e) => {
+                    page.error = Some(e.to_string());
+                    checkpoint.fail(&url, &e.to_string())?;
+                }
+            }
+            pages.push(page);
+            crawling.set_length((pages.len() + frontier.len()).min(options.max_pages) as u64);
+            crawling.set_position(pages.len() as u64);
+        }
+        crawling.finish_and_clear();
+        
+        let report = CrawlReport {
+            start: start.or(options.from_sitemap.as_deref()).unwrap_or_default().to_string(),
+            depth: options.depth,
+            same_origin: options.same_origin,
+            report: dir.join("report.json"),
+            dir,
+            pages,
+            unvisited: frontier.len(),
+        };
+        std::fs::write(&report.report, serde_json::to_string_pretty(&report)?)?;
+        Ok(report)
+    }
+
+    // Page URLs a sitemap lists, following sitemap indexes, in sitemap order without duplicates. Sitemaps are
+    // fetched from a scratch tab with the session's cookies; gzipped ones (.xml.gz) are unpacked in the page.
+    pub async fn sitemap_urls(&mut self, url: &str, filter: &SitemapFilter) -> Result<Vec<String>> {
+        self.ensure_initialized().await?;
+        
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        let scratch = self.cdp("new_page", browser.new_page("about:blank")).await?;
+        let reading = progress::spinner(format!("Reading sitemap {}", url));
+        let this = &*self;
+        let result = sitemap::collect(url, filter, |sitemap_url| {
+            let (scratch, reading) = (&scratch, &reading);
+            async move {
+                reading.set_message(format!("Reading sitemap {}", sitemap_url));
+                match this.fetch_from_origin(scratch, &sitemap_url).await? {
+                    (200..=299, xml) => Ok(xml),
+                    (status, _) => Err(anyhow::anyhow!("Failed to fetch sitemap {}: HTTP {}", sitemap_url, status)),
+                }
+            }
+        }).await;
+        reading.finish_and_clear();
+        self.cdp("close_tab", scratch.close()).await?;
+        
+        let mut visited = Visited::default();
+        let urls: Vec<String> = result?.into_iter().filter(|url| visited.insert(url)).collect();
+        if urls.is_empty() {
+            return Err(anyhow::anyhow!("No pages in {} match the filter", url));
+        }
+        status!("{} {} pages from {}", "🗺️".cyan(), urls.len(), url);
+        Ok(urls)
+    }
+
+    // Status and text of a URL, fetched by a scratch tab parked on the URL's own origin so the request is
+    // same-origin and carries the session's cookies
+    async fn fetch_from_origin(&self, scratch: &Page, url: &str) -> Result<(u16, String)> {
+        self.url_policy.check(url).await?;
+        let origin = url::Url::parse(url)
+            .map_err(|e| anyhow::anyhow!("Invalid URL {}: {}", url, e))?
+            .origin()
+            .ascii_serialization();
+        import::open_blank_origin(scratch, &origin, self.cdp_timeout).await?;
+        let script = format!(
+            r#"
+            (async () => {{
+                const response = await fetch({}, {{ credentials: 'include' }});
+                const bytes = new Uint8Array(await response.arrayBuffer());
+                // Servers that don't set Content-Encoding hand out .gz files (sitemaps) still compressed
+                if (bytes[0] === 0x1f && bytes[1] === 0x8b) {{
+                    const stream = new Blob([bytes]).stream().pipeThrough(new DecompressionStream('gzip'));
+                    return [response.status, await new Response(stream).text()];
+                }}
+                return [response.status, new TextDecoder().decode(bytes)];
+            }})()
+            "#,
+            serde_json::to_string(url)?
+        );
+        let fetched = self.cdp("evaluate", scratch.evaluate(script)).await
+            .map_err(|e| anyhow::anyhow!("Failed to fetch {}: {}", url, e))?;
+        fetched.into_value().map_err(|e| anyhow::anyhow!("Failed to fetch {}: {}", url, e))
+    }
+
+    // Gate for batch visits: refuse a URL its origin's robots.txt disallows for browser-cli, and wait out the
+    // crawl-delay since the previous visit there. robots.txt is read once per origin and session.
+    pub async fn robots_allow(&mut self, url: &str) -> Result<()> {
+        self.ensure_initialized().await?;
+        
+        let Ok(parsed) = url::Url::parse(url) else {
+            return Ok(());
+        };
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Ok(());
+        }
+        let origin = parsed.origin().ascii_serialization();
+        if !self.robots.contains_key(&origin) {
+            let rules = self.read_robots(&origin).await?;
+            if let Some(delay) = rules.crawl_delay {
+                status!("{} {} asks for {}s between visits (robots.txt)", "🤖".cyan(), origin, delay);
+            }
+            self.robots.insert(origin.clone(), rules);
+        }
+        
+        let rules = &self.robots[&origin];
+        if !rules.allowed(url) {
+            return Err(anyhow::anyhow!("{} is disallowed by robots.txt (--ignore-robots visits it anyway)", url));
+        }
+        if let (Some(delay), Some(last)) = (rules.crawl_delay, self.robots_visits.get(&origin)) {
+            let wait = Duration::from_secs_f64(delay).saturating_sub(last.elapsed());
+            if !wait.is_zero() {
+                sleep(wait).await;
+            }
+        }
+        self.robots_visits.insert(origin, Instant::now());
+        Ok(())
+    }
+
+    async fn read_robots(&self, origin: &str) -> Result<RobotsRules> {
+        let url = format!("{}/robots.txt", origin);
+        self.url_policy.check(&url).await?;
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        let scratch = self.cdp("new_page", browser.new_page("about:blank")).await?;
+        let loaded = async {
+            import::open_blank_origin(&scratch, origin, self.cdp_timeout).await?;
+            self.load_resource(&scratch, &url).await
+        }.await;
+        self.cdp("close_tab", scratch.close()).await?;
+        Ok(match loaded? {
+            Loaded::Response(200..=299, text) => RobotsRules::parse(&text),
+            // No robots.txt (or one we may not read) puts no limits on visits
+            Loaded::Response(400..=499, _) => RobotsRules::default(),
+            Loaded::Response(status, _) => {
+                warning!("robots.txt of {} answered HTTP {}; treating the site as off limits", origin, status);
+                RobotsRules::disallow_all()
+            }
+            Loaded::Unreachable(reason) => {
+                warning!("Could not reach robots.txt of {} ({}); treating the site as off limits", origin, reason);
+                RobotsRules::disallow_all()
+            }
+        })
+    }
+
+    // Load a URL from the browser process (Network.loadNetworkResource) for `page`'s frame, with its cookies.
+    // Unlike an in-page fetch this follows redirects to other origins (http to https, apex to www) without
+    // CORS getting in the way; Chrome gives up after 20 hops. Errors are for DevTools failures; a load that
+    // never got an HTTP response is Loaded::Unreachable.
+    async fn load_resource(&self, page: &Page, url: &str) -> Result<Loaded> {
+        let frame_id = self.cdp("mainframe", page.mainframe()).await?;
+        let params = LoadNetworkResourceParams {
+            frame_id,
+            url: url.to_string(),
+            options: LoadNetworkResourceOptions::new(true, true),
+        };
+        let resource = self.cdp("load_network_resource", page.execute(params)).await?.result.resource;
+        let Some(status) = resource.http_status_code else {
+            return Ok(Loaded::Unreachable(resource.net_error_name.unwrap_or_else(|| "no response".to_string())));
+        };
+        let mut body = Vec::new();
+        if let Some(handle) = resource.stream {
+            loop {
+                let chunk = self.cdp("io_read", page.execute(IoReadParams::new(handle.clone()))).await?.result;
+                if chunk.base64_encoded.unwrap_or(false) {
+                    body.extend(base64::engine::general_purpose::STANDARD.decode(&chunk.data)?);
+                } else {
+                    body.extend(chunk.data.into_bytes());
+                }
+                if chunk.eof {
+                    break;
+                }
+            }
+            self.cdp("io_close", page.execute(IoCloseParams::new(handle))).await?;
+        }
+        Ok(Loaded::Response(status as u16, String::from_utf8_lossy(&body).into_owned()))
+    }
+
+    async fn compare_visual(&mut self, scratch: &Page, result: &mut PathComparison, diff_file: &std::path::Path) -> Result<()> {
+        self.capture_settled(&result.base_url, &result.base_file).await?;
+        self.capture_settled(&result.target_url, &result.target_file).await?;
+        let (width, height, different) = self.pixel_diff(scratch, &result.base_file, &result.target_file, diff_file).await?;
+        result.width = width;
+        result.height = height;
+        result.different_pixels = different;
+        result.mismatch = different as f64 * 100.0 / (width as u64 * height as u64).max(1) as f64;
+        result.diff_file = Some(diff_file.to_path_buf());
+        Ok(())
+    }
+
+    // Unified diff of both sides' main text; the diff file is only written when they differ
+    async fn compare_text(&mut self, result: &mut PathComparison, diff_file: &std::path::Path) -> Result<()> {
+        let base = self.capture_text(&result.base_url, &result.base_file).await?;
+        let target = self.capture_text(&result.target_url, &result.target_file).await?;
+        let (diff, added, removed) = compare::unified_diff(&base, &target, &result.base_url, &result.target_url);
+        result.added_lines = added;
+        result.removed_lines = removed;
+        result.mismatch = (added + removed) as f64 * 100.0 / (base.len() + target.len()).max(1) as f64;
+        if !diff.is_empty() {
+            std::fs::write(diff_file, diff)?;
+            result.diff_file = Some(diff_file.to_path_buf());
+        }
+        Ok(())
+    }
+
+    // Load a URL and save the readable text of its main content, normalized one line per block. Site chrome
+    // (navigation, header, footer, asides) and anything not rendered is left out, as a reader view would.
+    async fn capture_text(&mut self, url: &str, path: &std::path::Path) -> Result<Vec<String>> {
+        self.navigate_retrying(url).await?;
+        let text: String = self.evaluate(r#"
+            (() => {
+                const root = document.querySelector('main, [role="main"], article') || document.body;
+                if (!root) return '';
+                // The page is discarded after this, so the chrome can simply be taken out
+                root.querySelectorAll('nav, header, footer, aside, script, style, noscript, template, svg, [aria-hidden="true"], [role="navigation"], [role="banner"], [role="contentinfo"]')
+                    .forEach(el => el.remove());
+                return root.innerText || '';
+            })()
+        "#).await?.into_value().unwrap_or_default();
+        let lines = compare::normalize_text(&text);
+        std::fs::write(path, lines.iter().map(|line| format!("{}\n", line)).collect::<String>())
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(lines)
+    }
+
+    // Load a URL and screenshot it once web fonts are in and the page is scrolled to the top
+    async fn capture_settled(&mut self, url: &str, path: &std::path::Path) -> Result<()> {
+        self.navigate_retrying(url).await?;
+        self.evaluate("document.fonts.ready.then(() => window.scrollTo(0, 0))").await?;
+        sleep(Duration::from_millis(300)).await;
+        let page = self.page.as_ref().ok_or(Error::NotInitialized)?;
+        self.save_screenshot(page, path).await
+    }
+
+    // Compare two PNGs pixel by pixel in a blank page and save the differences highlighted in red over a
+    // faded copy of the first. Returns the compared size and how many pixels differ; size differences count.
+    async fn pixel_diff(&self, scratch: &Page, a: &std::path::Path, b: &std::path::Path, out: &std::path::Path) -> Result<(u32, u32, u64)> {
+        let encode = |path: &std::path::Path| -> Result<String> {
+            let bytes = std::fs::read(path).map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+        };
+        let script = format!(
+            r#"
+            (async () => {{
+                const load = (png) => new Promise((resolve, reject) => {{
+                    const image = new Image();
+                    image.onload = () => resolve(image);
+                    image.onerror = () => reject(new Error('Could not decode screenshot'));
+                    image.src = 'data:image/png;base64,' + png;
+                }});
+                const [a, b] = await Promise.all([load({a}), load({b})]);
+                const width = Math.max(a.width, b.width), height = Math.max(a.height, b.height);
+                const pixels = (image) => {{
+                    const canvas = document.createElement('canvas');
+                    canvas.width = width;
+                    canvas.height = height;
+                    const context = canvas.getContext('2d');
+                    context.drawImage(image, 0, 0);
+                    return context.getImageData(0, 0, width, height).data;
+                }};
+                const pa = pixels(a), pb = pixels(b);
+                const canvas = document.createElement('canvas');
+                canvas.width = width;
+                canvas.height = height;
+                const context = canvas.getContext('2d');
+                const diff = context.createImageData(width, height);
+                let different = 0;
+                for (let i = 0; i < pa.length; i += 4) {{
+                    const delta = Math.abs(pa[i] - pb[i]) + Math.abs(pa[i + 1] - pb[i + 1])
+                        + Math.abs(pa[i + 2] - pb[i + 2]) + Math.abs(pa[i + 3] - pb[i + 3]);
+                    if (delta > {tolerance}) {{
+                        different++;
+                        diff.data.set([255, 0, 0, 255], i);
+                    }} else {{
+                        const gray = (pa[i] + pa[i + 1] + pa[i + 2]) / 3;
+                        diff.data.set([gray, gray, gray, 64], i);
+                    }}
+                }}
+                context.putImageData(diff, 0, 0);
+                return {{ width, height, different, png: canvas.toDataURL('image/png').split(',')[1] }};
+            }})()
+            "#,
+            a = serde_json::to_string(&encode(a)?)?,
+            b = serde_json::to_string(&encode(b)?)?,
+            tolerance = compare::PIXEL_TOLERANCE,
+        );
+        let result = self.evaluate_in_context(scratch, &script, None).await?;
+        let result = result.value().cloned().unwrap_or_default();
+        let png = base64::engine::general_purpose::STANDARD.decode(result["png"].as_str().unwrap_or_default())?;
+        tokio::fs::write(out, png).await?;
+        Ok((
+            result["width"].as_u64().unwrap_or_default() as u32,
+            result["height"].as_u64().unwrap_or_default() as u32,
+            result["different"].as_u64().unwrap_or_default(),
+        ))
+    }
+
+    // Capture the viewport to a file, with the URL, time, viewport and scroll position embedded
+    async fn save_screenshot(&self, page: &Page, path: &std::path::Path) -> Result<()> {
+        self.save_capture(page, path, false).await
+    }
+
+    // Screenshot plus the capture metadata; `full_page` captures the whole document instead of the viewport
+    async fn save_capture(&self, page: &Page, path: &std::path::Path, full_page: bool) -> Result<()> {
+        let meta = self.capture_meta(page).await?;
+        let shot = self.cdp("screenshot", page.screenshot(ScreenshotParams::builder().full_page(full_page).build())).await?;
+        capture::write(path, shot, &meta).await
+    }
+
+    // Where and when a screenshot is being taken, as recorded in the file
+    async fn capture_meta(&self, page: &Page) -> Result<CaptureMeta> {
+        let metrics = self.cdp("evaluate", page.evaluate(
+            "({ url: location.href, width: innerWidth, height: innerHeight, x: scrollX, y: scrollY, dpr: devicePixelRatio })"
+        )).await?;
+        let metrics = metrics.value().cloned().unwrap_or_default();
+        Ok(CaptureMeta {
+            url: metrics["url"].as_str().unwrap_or_default().to_string(),
+            captured_at: Utc::now().to_rfc3339(),
+            viewport_width: metrics["width"].as_u64().unwrap_or_default() as u32,
+            viewport_height: metrics["height"].as_u64().unwrap_or_default() as u32,
+            scroll_x: metrics["x"].as_f64().unwrap_or_default(),
+            scroll_y: metrics["y"].as_f64().unwrap_or_default(),
+            device_scale: metrics["dpr"].as_f64().unwrap_or(1.0),
+        })
+    }
+
+    // A burst of `frames` viewport screenshots `interval` apart, for stepping through an animation or
+    // transition frame by frame. Frames are held in memory until the burst ends so writing them doesn't
+    // eat into the interval; a capture slower than the interval skips ticks, and each frame's real offset
+    // is in its name. Saved as NNN-<offset>ms.png in --out, by default a new run screenshot subdirectory.
+    pub async fn screenshot_sequence(&self, frames: usize, interval: Duration, out: Option<&Path>) -> Result<(String, Vec<SequenceFrame>)> {
+        self.ensure_page()?;
+        
+        if frames == 0 {
+            return Err(anyhow::anyhow!("--frames must be at least 1"));
+        }
+        if interval.is_zero() {
+            return Err(anyhow::anyhow!("--interval must be at least 1ms"));
+        }
+        let dir = match out {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                dir.to_path_buf()
+            }
+            None => paths::run_subdir(Artifact::Screenshots, "sequence")?,
+        };
+        
+        let page = self.page.as_ref().unwrap();
+        let meta = self.capture_meta(page).await?;
+        progress!("{}", format!("Capturing {} frames {}ms apart", frames, interval.as_millis()).blue());
+        
+        let capturing = progress::items(frames as u64, "Capturing frames");
+        let mut shots = Vec::with_capacity(frames);
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let start = Instant::now();
+        for _ in 0..frames {
+            ticks.tick().await;
+            let offset = start.elapsed();
+            let captured_at = Utc::now().to_rfc3339();
+            let shot = self.cdp("screenshot", page.screenshot(ScreenshotParams::builder().build())).await;
+            match shot {
+                Ok(shot) => shots.push((offset, captured_at, shot)),
+                Err(e) => {
+                    capturing.finish_and_clear();
+                    return Err(e);
+                }
+            }
+            capturing.inc(1);
+        }
+        capturing.finish_and_clear();
+        
+        let mut saved = Vec::with_capacity(shots.len());
+        let mut late = 0;
+        for (index, (offset, captured_at, shot)) in shots.into_iter().enumerate() {
+            let offset_ms = offset.as_millis() as u64;
+            if offset_ms > index as u64 * interval.as_millis() as u64 + interval.as_millis() as u64 / 2 {
+                late += 1;
+            }
+            let path = dir.join(format!("{:03}-{}ms.png", index + 1, offset_ms));
+            capture::write(&path, shot, &CaptureMeta { captured_at, ..meta.clone() }).await?;
+            saved.push(SequenceFrame { path: path.display().to_string(), offset_ms });
+        }
+        if late > 0 {
+            status!("{} {} of {} frames came late: a capture takes longer than {}ms here; see the offsets in the file names", "⚠️".yellow(), late, saved.len(), interval.as_millis());
+        }
+        status!("{} {} frames: {}", "📸".cyan(), saved.len(), dir.display());
+        Ok((dir.display().to_string(), saved))
+    }
+
+    // Save a canvas's own pixels. toDataURL is tried first; WebGL canvases whose drawing buffer was already
+    // cleared come back blank, so the next frame is grabbed from captureStream, then a clipped screenshot.
+    pub async fn canvas_dump(&self, selector: &str, out: Option<&str>) -> Result<String> {
+        self.ensure_page()?;
+        
+        let dump_script = format!(
+            r#"
+            (async () => {{
+                const canvas = document.querySelector({selector});
+                if (!canvas) return {{ error: 'missing' }};
+                if (!(canvas instanceof HTMLCanvasElement)) return {{ error: 'not-canvas' }};
+                if (!canvas.width || !canvas.height) return {{ error: 'empty' }};
+                
+                const copy = document.createElement('canvas');
+                copy.width = canvas.width;
+                copy.height = canvas.height;
+                const ctx = copy.getContext('2d', {{ willReadFrequently: true }});
+                const isBlank = () => {{
+                    const data = ctx.getImageData(0, 0, copy.width, copy.height).data;
+                    for (let i = 3; i < data.length; i += 4) if (data[i] !== 0) return false;
+                    return true;
+                }};
+                
+                try {{
+                    ctx.drawImage(canvas, 0, 0);
+                    if (!isBlank()) return {{ method: 'toDataURL', data: canvas.toDataURL('image/png') }};
+                }} catch (e) {{
+                    if (e.name === 'SecurityError') return {{ error: 'tainted' }};
+                }}
+                
+                if (canvas.captureStream && window.ImageCapture) {{
+                    const track = canvas.captureStream().getVideoTracks()[0];
+                    try {{
+                        const frame = await Promise.race([
+                            new ImageCapture(track).grabFrame(),
+                            new Promise((_, reject) => setTimeout(() => reject(new Error('timeout')), 2000)),
+                        ]);
+                        ctx.clearRect(0, 0, copy.width, copy.height);
+                        ctx.drawImage(frame, 0, 0, copy.width, copy.height);
+                        if (!isBlank()) return {{ method: 'captureStream', data: copy.toDataURL('image/png') }};
+                    }} catch (e) {{
+                    }} finally {{
+                        track.stop();
+                    }}
+                }}
+                return {{ method: 'blank' }};
+            }})()
+            "#,
+            selector = serde_json::to_string(selector)?
+        );
+        
+        let result = self.evaluate_helper(dump_script).await?;
+        let result = result.value().cloned().unwrap_or_default();
+        let (bytes, method) = match (result["error"].as_str(), result["method"].as_str(), result["data"].as_str()) {
+            (Some("missing"), ..) => return Err(Error::element_not_found(selector).into()),
+            (Some("not-canvas"), ..) => return Err(anyhow::anyhow!("{} is not a <canvas>", selector)),
+            (Some("empty"), ..) => return Err(anyhow::anyhow!("{} has zero width or height", selector)),
+            (Some("tainted"), ..) => return Err(anyhow::anyhow!("{} is tainted by cross-origin content and can't be read", selector)),
+            (_, Some(method), Some(data)) => {
+                let encoded = data.split_once(',').map(|(_, encoded)| encoded).unwrap_or_default();
+                (base64::engine::general_purpose::STANDARD.decode(encoded)?, method)
+            }
+            _ => {
+                let element = self.find_element(selector).await?;
+                (self.cdp("screenshot", element.screenshot(CaptureScreenshotFormat::Png)).await?, "screenshot")
+            }
+        };
+        
+        let path = match out {
+            Some(path) => path.to_string(),
+            None => paths::capture_path(&format!("canvas_{}.png", Utc::now().format("%Y%m%d_%H%M%S")))?.display().to_string(),
+        };
+        tokio::fs::write(&path, &bytes).await?;
+        
+        status!("{} Canvas saved to {} (via {})", "🎨".cyan(), path, method);
+        Ok(path)
+    }
+
+    // Download a resource with the page's cookies and session by fetching it in-page and streaming the body out in chunks
+    pub async fn fetch_to_file(&self, url: &str, out: Option<&str>) -> Result<String> {
+        self.ensure_page()?;
+
+        // Relative URLs resolve against the current page, just like they would in the page itself
+        let page_url = self.get_url().await?;
+        let absolute_url = url::Url::parse(&page_url)
+            .and_then(|base| base.join(url))
+            .map(|resolved| resolved.to_string())
+            .unwrap_or_else(|_| url.to_string());
+        self.url_policy.check(&absolute_url).await?;
+
+        progress!("{}", format!("Fetching: {}", absolute_url).blue());
+
+        let start_script = format!(
+            r#"
+            (async function() {{
+                const response = await fetch({}, {{ credentials: 'include' }});
+                window.__browserCliFetch = response.body ? response.body.getReader() : null;
+                return JSON.stringify({{
+                    status: response.status,
+                    url: response.url,
+                    contentType: response.headers.get('content-type'),
+                    disposition: response.headers.get('content-disposition')
+                }});
+            }})()
+            "#,
+            serde_json::to_string(&absolute_url)?
+        );
+        let result = self.evaluate(start_script).await
+            .map_err(|e| anyhow::anyhow!("Fetch failed for {}: {}", absolute_url, e))?;
+        let info: serde_json::Value = result.value()
+            .and_then(|value| value.as_str())
+            .and_then(|json| serde_json::from_str(json).ok())
+            .ok_or_else(|| anyhow::anyhow!("Fetch failed for {}", absolute_url))?;
+
+        let status = info.get("status").and_then(|v| v.as_i64()).unwrap_or(0);
+        if !(200..300).contains(&status) {
+            self.evaluate("delete window.__browserCliFetch").await.ok();
+            return Err(anyhow::anyhow!("Fetch returned HTTP {} for {}", status, absolute_url));
+        }
+
+        let final_url = info.get("url").and_then(|v| v.as_str()).unwrap_or(&absolute_url);
+        let filename = match out {
+            Some(path) => path.to_string(),
+            None => info.get("disposition")
+                .and_then(|v| v.as_str())
+                .and_then(filename_from_disposition)
+                .or_else(|| filename_from_url(final_url))
+                .unwrap_or_else(|| "download".to_string()),
+        };
+
+        // Each read returns one base64-encoded chunk; null marks the end of the body
+        let read_script = r#"
+            (async function() {
+                const reader = window.__browserCliFetch;
+                if (!reader) return null;
+                const { done, value } = await reader.read();
+                if (done) {
+                    delete window.__browserCliFetch;
+                    return null;
+                }
+                let binary = '';
+                for (let i = 0; i < value.length; i += 0x8000) {
+                    binary += String.fromCharCode.apply(null, value.subarray(i, i + 0x8000));
+                }
+                return btoa(binary);
+            })()
+        "#;
+
+        let mut file = tokio::fs::File::create(&filename).await
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", filename, e))?;
+        let mut total = 0usize;
+        loop {
+            let chunk = self.evaluate(read_script).await?;
+            let Some(encoded) = chunk.value().and_then(|value| value.as_str()) else {
+                break;
+            };
+            let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)
+                .map_err(|e| anyhow::anyhow!("Corrupt chunk while downloading {}: {}", absolute_url, e))?;
+            file.write_all(&bytes).await?;
+            total += bytes.len();
+        }
+        file.flush().await?;
+
+        let content_type = info.get("contentType").and_then(|v| v.as_str()).unwrap_or("unknown type");
+        status!("{} Saved {} ({} bytes, {})", "✓".green(), filename, total, content_type);
+        Ok(filename)
+    }
+
+    // The page's favicon set (link rel icon, apple-touch-icon, mask-icon and the web app manifest's icons)
+    // and social preview images (og:image, twitter:image), with URLs resolved and sizes as declared.
+    // Without any declared icon, browsers fall back to /favicon.ico, so that is listed instead.
+    pub async fn page_icons(&self) -> Result<Vec<PageAsset>> {
+        self.ensure_page()?;
+        
+        progress!("{}", "Collecting icons and preview images".blue());
+        let icons = self.evaluate(
+            r#"
+            (async () => {
+                const assets = [];
+                const add = (kind, url, sizes, type) => {
+                    if (!url) return;
+                    const resolved = new URL(url, document.baseURI).href;
+                    if (!assets.some(a => a.kind === kind && a.url === resolved)) {
+                        assets.push({ kind, url: resolved, sizes: sizes || null, type: type || null });
+                    }
+                };
+                for (const link of document.querySelectorAll('link[rel][href]')) {
+                    const rels = link.rel.toLowerCase().split(/\s+/);
+                    const kind = ['apple-touch-icon', 'apple-touch-icon-precomposed', 'mask-icon'].find(r => rels.includes(r))
+                        || (rels.includes('icon') ? 'icon' : null);
+                    if (kind) add(kind, link.getAttribute('href'), link.getAttribute('sizes'), link.getAttribute('type'));
+                }
+                const manifest = document.querySelector('link[rel~="manifest"][href]');
+                if (manifest) {
+                    try {
+                        const response = await fetch(manifest.href, { credentials: 'include' });
+                        const json = await response.json();
+                        for (const icon of json.icons || []) {
+                            if (icon.src) add('manifest', new URL(icon.src, manifest.href).href, icon.sizes, icon.type);
+                        }
+                    } catch (e) {}
+                }
+                if (!assets.length) add('favicon.ico', '/favicon.ico', null, null);
+                const meta = name => {
+                    const el = document.querySelector(`meta[property="${name}"], meta[name="${name}"]`);
+                    return el ? el.getAttribute('content') : null;
+                };
+                for (const name of ['og:image', 'og:image:url', 'twitter:image', 'twitter:image:src']) {
+                    const kind = name.startsWith('og:') ? 'og:image' : 'twitter:image';
+                    const width = meta(`${kind}:width`), height = meta(`${kind}:height`);
+                    add(kind, meta(name), width && height ? `${width}x${height}` : null, meta(`${kind}:type`));
+                }
+                return assets;
+            })()
+            "#
+        ).await?.into_value::<Vec<PageAsset>>().unwrap_or_default();
+        Ok(icons)
+    }
+
+    // Web fonts of the page: every face in document.fonts with the URL its @font-face rule points at,
+    // sized from the font requests of the last page load (see assets::font_inventory)
+    pub async fn font_inventory(&self) -> Result<Vec<FontAsset>> {
+        self.ensure_page()?;
+        
+        progress!("{}", "Collecting web fonts".blue());
+        let declared = self.evaluate(
+            r#"
+            (async () => {
+                await Promise.race([document.fonts.ready, new Promise(resolve => setTimeout(resolve, 3000))]);
+                const unquote = text => text.trim().replace(/^["']|["']$/g, '');
+                const rules = [];
+                const visit = (list, base) => {
+                    for (const rule of list) {
+                        if (rule instanceof CSSFontFaceRule) {
+                            const src = rule.style.getPropertyValue('src');
+                            rules.push({
+                                family: unquote(rule.style.getPropertyValue('font-family')),
+                                weight: rule.style.getPropertyValue('font-weight') || 'normal',
+                                style: rule.style.getPropertyValue('font-style') || 'normal',
+                                urls: [...src.matchAll(/url\(\s*(['"]?)(.*?)\1\s*\)/g)].map(m => new URL(m[2], base).href),
+                            });
+                        } else if (rule instanceof CSSImportRule) {
+                            try { visit(rule.styleSheet.cssRules, rule.styleSheet.href || base); } catch (e) {}
+                        } else if (rule.cssRules) {
+                            visit(rule.cssRules, base);
+                        }
+                    }
+                };
+                // Cross-origin stylesheets without CORS throw on cssRules
+                for (const sheet of document.styleSheets) {
+                    try { visit(sheet.cssRules, sheet.href || document.baseURI); } catch (e) {}
+                }
+                const fonts = [];
+                for (const face of document.fonts) {
+                    const family = unquote(face.family);
+                    const sameFamily = rules.filter(r => r.family === family);
+                    const rule = sameFamily.find(r => r.weight === face.weight && r.style === face.style)
+                        || (sameFamily.length === 1 ? sameFamily[0] : null);
+                    fonts.push({ family, weight: face.weight, style: face.style, status: face.status, urls: rule ? rule.urls : [] });
+                }
+                return fonts;
+            })()
+            "#
+        ).await?.into_value::<Vec<DeclaredFont>>().unwrap_or_default();
+        Ok(assets::font_inventory(&declared, &self.network_requests(false)))
+    }
+
+    // Download assets into a directory under names from assets::file_names, recording each one's path or
+    // why it failed; one failing doesn't stop the rest
+    pub async fn download_assets(&self, assets: &mut [PageAsset], dir: &Path) -> Result<()> {
+        self.ensure_page()?;
+        
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", dir.display(), e))?;
+        let names = assets::file_names(assets);
+        for (asset, name) in assets.iter_mut().zip(names) {
+            let target = dir.join(name);
+            match self.fetch_to_file(&asset.url, Some(&target.display().to_string())).await {
+                Ok(path) => asset.path = Some(PathBuf::from(path)),
+                Err(e) => {
+                    warning!("Downloading {} failed: {:#}", asset.url, e);
+                    asset.error = Some(format!("{:#}", e));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn click(&self, selector: &str) -> Result<()> {
+        self.ensure_page()?;
+        
+        let element = self.find_element(selector).await?;
+        // Element::click can't carry held modifiers, so shift/ctrl-clicks go through raw mouse events
+        if self.modifiers() != 0 {
+            let point = self.cdp("clickable_point", element.clickable_point()).await?;
+            return self.click_at_coordinates(point.x, point.y).await;
+        }
+        self.cdp("click", element.click()).await?;
+        
+        status!("{} Clicked: {}", "✓".green(), selector);
+        Ok(())
+    }
+
+    pub async fn type_text(&self, selector: &str, text: &str, options: &TypeOptions) -> Result<()> {
+        self.ensure_page()?;
+        
+        let element = self.find_element(selector).await?;
+        self.cdp("click", element.click()).await?;
+        for segment in keyboard::segments(text, options.layout) {
+            match segment {
+                Segment::Keys(keys) => {
+                    self.cdp("type", element.type_str(keys)).await?;
+                }
+                Segment::LayoutKey(c) => {
+                    let key = c.to_string();
+                    for event_type in [DispatchKeyEventType::KeyDown, DispatchKeyEventType::KeyUp] {
+                        let mut event = DispatchKeyEventParams::builder().r#type(event_type.clone()).key(key.clone());
+                        if event_type == DispatchKeyEventType::KeyDown {
+                            event = event.text(key.clone());
+                        }
+                        self.execute(event.build().map_err(|e| anyhow::anyhow!(e))?).await?;
+                    }
+                }
+                Segment::Text(text) if options.ime => {
+                    // Grow the composition a character at a time, as an IME would, then commit it
+                    let mut composed = String::new();
+                    for c in text.chars() {
+                        composed.push(c);
+                        let len = composed.encode_utf16().count() as i64;
+                        self.execute(ImeSetCompositionParams::new(composed.clone(), len, len)).await?;
+                    }
+                    self.execute(InsertTextParams::new(text)).await?;
+                }
+                Segment::Text(text) => {
+                    self.execute(InsertTextParams::new(text)).await?;
+                }
+            }
+        }
+        
+        status!("{} Typed into {}", "✓".green(), selector);
+        Ok(())
+    }
+
+    pub async fn scroll(&self, direction: &str, amount: Option<i32>) -> Result<()> {
+        self.ensure_page()?;
+        
+        match direction {
+            "up" => {
+                let scroll_amount = -(amount.unwrap_or(300));
+                self.evaluate(format!("window.scrollBy(0, {})", scroll_amount)).await?;
+            }
+            "down" => {
+                let scroll_amount = amount.unwrap_or(300);
+                self.evaluate(format!("window.scrollBy(0, {})", scroll_amount)).await?;
+            }
+            "top" => {
+                self.evaluate("window.scrollTo(0, 0)").await?;
+            }
+            "bottom" => {
+                self.evaluate("window.scrollTo(0, document.body.scrollHeight)").await?;
+            }
+            _ => return Err(anyhow::anyhow!("Invalid scroll direction")),
+        }
+        
+        status!("{} Scrolled {}", "✓".green(), direction);
+        Ok(())
+    }
+
+    // Types the query into the page's search box and submits it; returns the selector of the box used
+    pub async fn search(&self, query: &str) -> Result<String> {
+        self.ensure_page()?;
+        
+        progress!("{}", format!("Searching for: '{}'", query).blue());
+        
+        let search_selectors = vec![
+            "input[type=\"search\"]",
+            "input[placeholder*=\"search\" i]",
+            "input[name*=\"search\" i]",
+            "input[id*=\"search\" i]",
+            ".search input",
+            "#search input",
+        ];
+        
+        // Probing, so the bare lookup: misses here need no explaining
+        let page = self.page.as_ref().unwrap();
+        for selector in search_selectors {
+            if let Ok(element) = self.cdp("find_element", page.find_element(selector)).await {
+                self.cdp("click", element.click()).await?;
+                self.cdp("type", element.type_str(query)).await?;
+                self.press_key("Enter").await?;
+                status!("{} Search: {}", "✓".green(), query);
+                return Ok(selector.to_string());
+            }
+        }
+        
+        Err(anyhow::anyhow!("No search input found on page"))
+    }
+
+    pub async fn get_text(&self, selector: Option<&str>, options: &TextOptions) -> Result<String> {
+        self.ensure_page()?;
+        
+        let page = self.page.as_ref().unwrap();
+        
+        let Some(sel) = selector else {
+            let title = self.cdp("get_title", page.get_title()).await?.unwrap_or_default();
+            let url = self.cdp("url", page.url()).await?.unwrap_or_default();
+            return Ok(match options.format {
+                TextFormat::Text => format!("Title: {}\nURL: {}", title, url),
+                TextFormat::Json => serde_json::to_string_pretty(&serde_json::json!({ "title": title, "url": url }))?,
+                TextFormat::Markdown => format!("# {}\n\n<{}>", title, url),
+            });
+        };
+        
+        if options.format == TextFormat::Text {
+            progress!("{}", format!("Getting text from: {}", sel).blue());
+        }
+        let texts: Vec<String> = self.evaluate(format!(
+            "Array.from(document.querySelectorAll({})).slice(0, {}).map(el => el.innerText ?? el.textContent ?? '')",
+            serde_json::to_string(sel)?,
+            if options.all { "undefined" } else { "1" }
+        )).await?.into_value().unwrap_or_default();
+        if texts.is_empty() {
+            return Err(Error::element_not_found(sel).into());
+        }
+        
+        // (text, truncated) per match
+        let texts: Vec<(String, bool)> = texts.into_iter()
+            .map(|text| if options.trim { tidy_text(&text) } else { text })
+            .map(|text| match options.max_chars {
+                Some(max) if text.chars().count() > max => (format!("{}…", text.chars().take(max).collect::<String>()), true),
+                _ => (text, false),
+            })
+            .collect();
+        
+        Ok(match options.format {
+            TextFormat::Text => texts.into_iter().map(|(text, _)| text).collect::<Vec<_>>().join("\n"),
+            TextFormat::Json if options.all => serde_json::to_string_pretty(&serde_json::json!({
+                "selector": sel,
+                "count": texts.len(),
+                "matches": texts.iter().map(|(text, truncated)| serde_json::json!({ "text": text, "truncated": truncated })).collect::<Vec<_>>(),
+            }))?,
+            TextFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                "selector": sel,
+                "text": texts[0].0,
+                "truncated": texts[0].1,
+            }))?,
+            TextFormat::Markdown if options.all => texts.iter().enumerate()
+                .map(|(index, (text, _))| format!("{}. {}", index + 1, text.replace('\n', "\n   ")))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            TextFormat::Markdown => texts[0].0.clone(),
+        })
+    }
+
+    // Text as a user would copy it: select the element's contents with a Range and read the selection,
+    // which drops hidden content and applies the browser's copy line-break rules
+    pub async fn copy_text(&self, selector: &str) -> Result<String> {
+        self.ensure_page()?;
+        
+        progress!("{}", format!("Copying text from: {}", selector).blue());
+        let copy_script = format!(
+            r#"
+            (function() {{
+                const element = document.querySelector({});
+                if (!element) return null;
+                const selection = window.getSelection();
+                const saved = [];
+                for (let i = 0; i < selection.rangeCount; i++) saved.push(selection.getRangeAt(i));
+                
+                const range = document.createRange();
+                range.selectNodeContents(element);
+                selection.removeAllRanges();
+                selection.addRange(range);
+                const text = selection.toString();
+                
+                selection.removeAllRanges();
+                saved.forEach(r => selection.addRange(r));
+                return text;
+            }})()
+            "#,
+            serde_json::to_string(selector)?
+        );
+        
+        let result = self.evaluate(copy_script).await?;
+        match result.value().and_then(|v| v.as_str()) {
+            Some(text) => Ok(text.to_string()),
+            None => Err(Error::element_not_found(selector).into()),
+        }
+    }
+
+    // The page body (or the first element matching the selector) as Markdown; see extract::to_markdown
+    pub async fn get_markdown(&self, selector: Option<&str>) -> Result<String> {
+        self.ensure_page()?;
+        
+        if let Some(sel) = selector {
+            progress!("{}", format!("Converting to Markdown: {}", sel).blue());
+        }
+        let tree_script = format!(
+            r#"
+            (function() {{
+                const selector = {selector};
+                const root = selector === null ? document.body : document.querySelector(selector);
+                return root ? ({tree})(root) : null;
+            }})()
+            "#,
+            selector = serde_json::to_string(&selector)?,
+            tree = extract::EXTRACT_TREE_JS
+        );
+        let tree = self.evaluate_helper(tree_script).await?.into_value::<Option<extract::Node>>()
+            .map_err(|e| anyhow::anyhow!("Failed to read the page's content: {}", e))?;
+        match tree {
+            Some(tree) => Ok(extract::to_markdown(&tree)),
+            None => Err(Error::element_not_found(selector.unwrap_or("body")).into()),
+        }
+    }
+
+    // The page's main content with its title, byline and publication date, boilerplate stripped; see
+    // extract::ARTICLE_JS for how the content is picked
+    pub async fn get_article(&self) -> Result<Article> {
+        self.ensure_page()?;
+        
+        progress!("{}", "Extracting the article".blue());
+        let article_script = format!("({})({})", extract::ARTICLE_JS, extract::EXTRACT_TREE_JS);
+        let source = self.evaluate_helper(article_script).await?.into_value::<ArticleSource>()
+            .map_err(|e| anyhow::anyhow!("Failed to read the page's content: {}", e))?;
+        Ok(Article::from_source(source))
+    }
+
+    // Apply a scrape spec to the page: one JSON document with the text or attribute each field's selector
+    // points at, lists and nested objects as the spec asks
+    pub async fn scrape(&self, spec: &ScrapeSpec) -> Result<ScrapeResult> {
+        self.ensure_page()?;
+        
+        progress!("{}", "Scraping the page".blue());
+        let scrape_script = format!("({})({})", scrape::SCRAPE_JS, serde_json::to_string(spec)?);
+        let result = self.evaluate_helper(scrape_script).await?.into_value::<serde_json::Value>()
+            .map_err(|e| anyhow::anyhow!("Failed to scrape the page: {}", e))?;
+        if let Some(error) = result["error"].as_str() {
+            return Err(anyhow::anyhow!("Invalid scrape spec: {}", error));
+        }
+        Ok(serde_json::from_value(result)?)
+    }
+
+    // Every anchor with an href, in document order, duplicates included
+    pub async fn get_links(&self, filter: &LinkFilter) -> Result<Vec<PageLink>> {
+        self.ensure_page()?;
+        
+        let links_script = format!(
+            r#"
+            (function() {{
+                let pattern = null;
+                try {{
+                    pattern = {pattern} === null ? null : new RegExp({pattern});
+                }} catch (e) {{
+                    return {{ error: e.message }};
+                }}
+                const links = [];
+                for (const a of document.querySelectorAll('a[href]')) {{
+                    const url = a.href;
+                    if ({same_origin}) {{
+                        try {{
+                            if (new URL(url).origin !== location.origin) continue;
+                        }} catch (e) {{
+                            continue;
+                        }}
+                    }}
+                    if (pattern && !pattern.test(url)) continue;
+                    const img = a.querySelector('img[alt]');
+                    const text = (a.innerText || '').replace(/\s+/g, ' ').trim()
+                        || a.getAttribute('aria-label') || (img && img.alt) || a.title || '';
+                    links.push({{
+                        url,
+                        text: text.trim(),
+                        rel: (a.getAttribute('rel') || '').split(/\s+/).filter(Boolean),
+                        target: a.getAttribute('target')
+                    }});
+                }}
+                return {{ links }};
+            }})()
+            "#,
+            pattern = serde_json::to_string(&filter.pattern)?,
+            same_origin = filter.same_origin
+        );
+        let result = self.evaluate_helper(links_script).await?.into_value::<serde_json::Value>()
+            .map_err(|e| anyhow::anyhow!("Failed to read the page's links: {}", e))?;
+        if let Some(error) = result["error"].as_str() {
+            return Err(anyhow::anyhow!("Invalid --pattern: {}", error));
+        }
+        Ok(serde_json::from_value(result["links"].clone())?)
+    }
+
+    // How many elements match the selector, for `count` and `exists`; one querySelectorAll, no waiting
+    pub async fn count_elements(&self, selector: &str) -> Result<usize> {
+        self.ensure_page()?;
+        
+        let count_script = format!(
+            r#"
+            (() => {{
+                try {{
+                    return {{ count: document.querySelectorAll({selector}).length }};
+                }} catch (e) {{
+                    return {{ error: e.message }};
+                }}
+            }})()
+            "#,
+            selector = serde_json::to_string(selector)?
+        );
+        let result = self.evaluate_helper(count_script).await?.into_value::<serde_json::Value>()
+            .map_err(|e| anyhow::anyhow!("Failed to count matches: {}", e))?;
+        if result["error"].is_string() {
+            return Err(anyhow::anyhow!("Invalid selector: {}", selector));
+        }
+        Ok(result["count"].as_u64().unwrap_or_default() as usize)
+    }
+
+    // Rows of the table the selector matches (or the first table inside it), with colspan and rowspan
+    // cells repeated across the columns and rows they cover
+    pub async fn get_table(&self, selector: &str) -> Result<Table> {
+        self.ensure_page()?;
+        
+        progress!("{}", format!("Reading table: {}", selector).blue());
+        let table_script = format!(
+            r#"
+            (function() {{
+                const el = document.querySelector({selector});
+                if (!el) return {{ error: 'missing' }};
+                const table = el instanceof HTMLTableElement ? el : el.querySelector('table');
+                if (!table) return {{ error: 'not-table' }};
+                return ({table})(table);
+            }})()
+            "#,
+            selector = serde_json::to_string(selector)?,
+            table = table::TABLE_JS
+        );
+        let result = self.evaluate_helper(table_script).await?.into_value::<serde_json::Value>()
+            .map_err(|e| anyhow::anyhow!("Failed to read the table: {}", e))?;
+        match result["error"].as_str() {
+            Some("missing") => Err(Error::element_not_found(selector).into()),
+            Some(_) => Err(anyhow::anyhow!("{} is not a table and has none inside it", selector)),
+            None => Ok(Table::from_grid(serde_json::from_value::<TableGrid>(result)?)),
+        }
+    }
+
+    // The page's HTML source as it is now (doctype and documentElement), or the outerHTML of the first
+    // element matching the selector. With pretty, every element starts its own indented line; script,
+    // style, pre and textarea contents are left as they are, since whitespace there is significant.
+    pub async fn get_html(&self, selector: Option<&str>, pretty: bool) -> Result<String> {
+        self.ensure_page()?;
+        
+        if let Some(sel) = selector {
+            progress!("{}", format!("Getting HTML from: {}", sel).blue());
+        }
+        let html_script = format!(
+            r#"
+            (function() {{
+                const selector = {};
+                const root = selector === null ? document.documentElement : document.querySelector(selector);
+                if (!root) return null;
+                const doctype = selector === null && document.doctype ? `<!DOCTYPE ${{document.doctype.name}}>` : null;
+                if (!{}) return [doctype, root.outerHTML].filter(part => part !== null).join('\n');
+                
+                const VOID = new Set(['area', 'base', 'br', 'col', 'embed', 'hr', 'img', 'input', 'link', 'meta', 'source', 'track', 'wbr']);
+                const VERBATIM = new Set(['script', 'style', 'pre', 'textarea', 'template']);
+                const escape = text => text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/\u00a0/g, '&nbsp;');
+                const lines = doctype ? [doctype] : [];
+                const walk = (node, depth) => {{
+                    const pad = '  '.repeat(depth);
+                    if (node.nodeType === Node.TEXT_NODE) {{
+                        const text = node.textContent.replace(/[ \t\n\r\f]+/g, ' ').trim();
+                        if (text) lines.push(pad + escape(text));
+                        return;
+                    }}
+                    if (node.nodeType === Node.COMMENT_NODE) {{
+                        lines.push(`${{pad}}<!--${{node.data}}-->`);
+                        return;
+                    }}
+                    if (node.nodeType !== Node.ELEMENT_NODE) return;
+                    const tag = node.localName;
+                    const shallow = node.cloneNode(false).outerHTML;
+                    if (VOID.has(tag)) {{
+                        lines.push(pad + shallow);
+                        return;
+                    }}
+                    if (VERBATIM.has(tag)) {{
+                        lines.push(pad + node.outerHTML);
+                        return;
+                    }}
+                    const close = `</${{tag}}>`;
+                    const open = shallow.slice(0, shallow.length - close.length);
+                    const children = Array.from(node.childNodes)
+                        .filter(child => child.nodeType !== Node.TEXT_NODE || child.textContent.trim());
+                    const text = children.length === 1 && children[0].nodeType === Node.TEXT_NODE
+                        ? children[0].textContent.replace(/[ \t\n\r\f]+/g, ' ').trim()
+                        : null;
+                    if (children.length === 0 || (text !== null && text.length <= 80)) {{
+                        lines.push(pad + open + escape(text ?? '') + close);
+                        return;
+                    }}
+                    lines.push(pad + open);
+                    children.forEach(child => walk(child, depth + 1));
+                    lines.push(pad + close);
+                }};
+                walk(root, 0);
+                return lines.join('\n');
+            }})()
+            "#,
+            serde_json::to_string(&selector)?,
+            pretty
+        );
+        
+        let result = self.evaluate(html_script).await?;
+        match result.value().and_then(|v| v.as_str()) {
+            Some(html) => Ok(html.to_string()),
+            None => Err(Error::element_not_found(selector.unwrap_or("html")).into()),
+        }
+    }
+
+    pub async fn close(&mut self) -> Result<()> {
+        if let (Some(attachment), Some(_)) = (self.attached.take(), self.browser.take()) {
+            // The daemon or whoever started the browser owns it; just let go of it
+            self.reset_page_state();
+            match attachment {
+                Attachment::Daemon(info) => status!("{} Detached from daemon for session '{}'", "🔗".green(), info.session),
+                Attachment::External(endpoint) => status!("{} Detached from browser at {}; it keeps running", "🔗".green(), endpoint),
+            }
+            return Ok(());
+        }
+        if let Some(mut browser) = self.browser.take() {
+            status!("{}", "Closing browser...".yellow());
+            // Dropped first so a timed-out close doesn't try to recover a page that's going away
+            self.reset_page_state();
+            // Closing pages first lets unload handlers run and pending writes land before the process goes
+            for page in self.cdp("pages", browser.pages()).await.unwrap_or_default() {
+                self.cdp("close_page", page.close()).await.ok();
+            }
+            // A hung browser still has to go, or the process and its profile outlive the session
+            let closed = self.cdp("close_browser", browser.close()).await;
+            if closed.is_err() {
+                browser.kill().await;
+            }
+            
+            // Clean up temporary directory
+            if let Some(temp_dir) = &self.temp_dir {
+                if let Err(e) = std::fs::remove_dir_all(temp_dir) {
+                    warning!("Warning: Failed to remove temp directory {}: {}", temp_dir.display(), e);
+                }
+            }
+            self.temp_dir = None;
+            closed?;
+            
+            status!("{}", "Browser closed".green());
+        }
+        Ok(())
+    }
+
+    // Forget everything tied to the browser's pages once it's gone
+    fn reset_page_state(&mut self) {
+        self.page = None;
+        self.tabs.clear();
+        self.started_at = None;
+        self.init_scripts.clear();
+        self.init_sources.clear();
+        self.extra_headers.clear();
+        self.intercepting = false;
+        *self.helper_world.lock().unwrap() = None;
+        self.held_keys.lock().unwrap().clear();
+        self.watching_notifications = false;
+        self.watching_titles = false;
+        self.watching_audio = false;
+    }
+
+    async fn ensure_initialized(&mut self) -> Result<()> {
+        if self.browser.is_none() {
+            self.init().await?;
+        }
+        Ok(())
+    }
+
+    fn ensure_page(&self) -> Result<()> {
+        if self.page.is_none() {
+            return Err(Error::NotInitialized.into());
+        }
+        Ok(())
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.browser.is_some() && self.page.is_some()
+    }
+
+    // Session settings every tab gets: CSP bypass, device scale, media emulation, crash tracking, events, interception
+    // and the network log
+    async fn prepare_page(&self, page: &Page) -> Result<()> {
+        if self.bypass_csp {
+            self.cdp("bypass_csp", page.execute(SetBypassCspParams::new(true))).await?;
+        }
+        if self.device_scale.is_some() {
+            self.apply_device_scale(page).await?;
+        }
+        if self.media_emulation.is_active() {
+            self.cdp("emulate_media", page.execute(self.media_emulation.params())).await?;
+        }
+        self.watch_for_crashes(page).await?;
+        self.watch_page_events(page).await?;
+        if self.interception.lock().unwrap().is_active() {
+            network::intercept(page, Arc::clone(&self.interception), self.cdp_timeout).await?;
+        }
+        network::record(page, Arc::clone(&self.network)).await?;
+        Ok(())
+    }
+
+    // Track renderer crashes so timed-out calls can report them and trigger recovery
+    async fn watch_for_crashes(&self, page: &Page) -> Result<()> {
+        self.cdp("inspector_enable", page.execute(InspectorEnableParams::default())).await?;
+        
+        let mut crashes = self.cdp("event_listener", page.event_listener::<EventTargetCrashed>()).await?;
+        let crashed = Arc::clone(&self.crashed);
+        tokio::spawn(async move {
+            while crashes.next().await.is_some() {
+                crashed.store(true, Ordering::SeqCst);
+            }
+        });
+        
+        Ok(())
+    }
+
+    // Publish browser-wide events (downloads) on the event bus
+    async fn watch_browser_events(&self, browser: &Browser) -> Result<()> {
+        let download_behavior = SetDownloadBehaviorParams::builder()
+            .behavior(SetDownloadBehaviorBehavior::Default)
+            .events_enabled(true)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build download behavior command: {}", e))?;
+        self.cdp("download_behavior", browser.execute(download_behavior)).await?;
+        
+        let mut downloads = self.cdp("event_listener", browser.event_listener::<EventDownloadWillBegin>()).await?;
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            while let Some(event) = downloads.next().await {
+                events.publish(BrowserEvent::Download {
+                    url: event.url.clone(),
+                    filename: event.suggested_filename.clone(),
+                });
+            }
+        });
+        
+        Ok(())
+    }
+
+    // Publish page lifecycle events on the event bus and answer dialogs when a dialog action is set
+    async fn watch_page_events(&self, page: &Page) -> Result<()> {
+        let mut navigations = self.cdp("event_listener", page.event_listener::<EventFrameNavigated>()).await?;
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            while let Some(event) = navigations.next().await {
+                if event.frame.parent_id.is_none() {
+                    events.publish(BrowserEvent::Navigated { url: event.frame.url.clone() });
+                }
+            }
+        });
+        
+        let mut dialogs = self.cdp("event_listener", page.event_listener::<EventJavascriptDialogOpening>()).await?;
+        let events = self.events.clone();
+        let dialog_action = Arc::clone(&self.dialog_action);
+        let dialog_page = page.clone();
+        let timeout = self.cdp_timeout;
+        tokio::spawn(async move {
+            while let Some(event) = dialogs.next().await {
+                events.publish(BrowserEvent::Dialog {
+                    kind: event.r#type.as_ref().to_string(),
+                    message: event.message.clone(),
+                });
+                
+                let action = dialog_action.lock().unwrap().clone();
+                let params = match action {
+                    Some(DialogAction::Accept(prompt_text)) => HandleJavaScriptDialogParams { accept: true, prompt_text },
+                    Some(DialogAction::Dismiss) => HandleJavaScriptDialogParams { accept: false, prompt_text: None },
+                    None => continue,
+                };
+                watchdog(timeout, "handle_dialog", dialog_page.execute(params)).await.ok();
+            }
+        });
+        
+        let mut console_calls = self.cdp("event_listener", page.event_listener::<EventConsoleApiCalled>()).await?;
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            while let Some(event) = console_calls.next().await {
+                let message = event.args.iter()
+                    .map(|arg| match &arg.value {
+                        Some(serde_json::Value::String(text)) => text.clone(),
+                        Some(value) => value.to_string(),
+                        None => arg.description.clone().unwrap_or_default(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if event.r#type == ConsoleApiCalledType::Error {
+                    events.publish(BrowserEvent::ConsoleError { message });
+                } else {
+                    events.publish(BrowserEvent::ConsoleLog { level: event.r#type.as_ref().to_string(), message });
+                }
+            }
+        });
+        
+        let mut exceptions = self.cdp("event_listener", page.event_listener::<EventExceptionThrown>()).await?;
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            while let Some(event) = exceptions.next().await {
+                let details = &event.exception_details;
+                let message = details.exception.as_ref()
+                    .and_then(|exception| exception.description.clone())
+                    .unwrap_or_else(|| details.text.clone());
+                events.publish(BrowserEvent::ConsoleError { message });
+            }
+        });
+        
+        let mut responses = self.cdp("event_listener", page.event_listener::<EventResponseReceived>()).await?;
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            while let Some(event) = responses.next().await {
+                events.publish(BrowserEvent::Network {
+                    url: event.response.url.clone(),
+                    status: event.response.status,
+                    resource_type: event.r#type.as_ref().to_string(),
+                });
+            }
+        });
+        
+        Ok(())
+    }
+
+    // Watchdog around every CDP call: a stuck renderer must not hang the tool while holding the browser lock
+    async fn cdp<T, F>(&self, operation: &str, call: F) -> Result<T>
+    where
+        F: Future<Output = chromiumoxide::error::Result<T>>,
+    {
+        *self.last_activity.lock().unwrap() = Instant::now();
+        match tokio::time::timeout(self.cdp_timeout, call).await {
+            Ok(result) => Ok(result?),
+            Err(_) => {
+                let crashed = self.crashed.load(Ordering::SeqCst);
+                if self.auto_recover {
+                    self.recover_page().await;
+                }
+                Err(Error::CdpTimeout {
+                    operation: operation.to_string(),
+                    secs: self.cdp_timeout.as_secs(),
+                    crashed,
+                }.into())
+            }
+        }
+    }
+
+    // Reload a crashed or unresponsive page so the session stays usable after a watchdog timeout
+    async fn recover_page(&self) {
+        let Some(page) = self.page.as_ref() else {
+            return;
+        };
+        
+        status!("{} Page unresponsive, attempting recovery...", "🩹".yellow());
+        match tokio::time::timeout(self.cdp_timeout, page.reload()).await {
+            Ok(Ok(_)) => {
+                self.crashed.store(false, Ordering::SeqCst);
+                status!("{} Page reloaded", "✓".green());
+            }
+            Ok(Err(e)) => status!("{} Recovery failed: {}", "⚠️".yellow(), e),
+            Err(_) => status!("{} Recovery timed out, restart the browser with 'close'", "⚠️".yellow()),
+        }
+    }
+
+    async fn evaluate(&self, expression: impl Into<Evaluation>) -> Result<EvaluationResult> {
+        let page = self.page.as_ref().ok_or(Error::NotInitialized)?;
+        self.cdp("evaluate", page.evaluate(expression)).await
+            .map_err(|e| self.explain_injection_error(e))
+    }
+
+    // A failed lookup comes back as ElementNotFound with whatever leads explain_missing turns up
+    async fn find_element(&self, selector: &str) -> Result<Element> {
+        let page = self.page.as_ref().ok_or(Error::NotInitialized)?;
+        match self.cdp("find_element", page.find_element(selector)).await {
+            Err(e) if matches!(e.downcast_ref::<CdpError>(), Some(CdpError::Chrome(_) | CdpError::NotFound)) => {
+                Err(self.explain_missing(selector).await.into())
+            }
+            result => result,
+        }
+    }
+
+    // ElementNotFound for a selector that matched nothing, with near misses found on the page (see
+    // SELECTOR_HINTS_JS) and a pointer to `elements`
+    async fn explain_missing(&self, selector: &str) -> Error {
+        let script = format!("({})({})", SELECTOR_HINTS_JS, serde_json::to_string(selector).unwrap_or_default());
+        let hints = match self.evaluate_helper(script).await {
+            Ok(result) => result.into_value::<SelectorHints>().unwrap_or_default(),
+            Err(_) => SelectorHints::default(),
+        };
+        let mut leads = Vec::new();
+        if hints.invalid {
+            leads.push("not a valid CSS selector".to_string());
+        }
+        if !hints.suggestions.is_empty() {
+            let quoted: Vec<String> = hints.suggestions.iter().map(|s| format!("'{}'", s)).collect();
+            leads.push(format!("did you mean {}?", quoted.join(" or ")));
+        }
+        if let Some(relaxed) = &hints.relaxed {
+            leads.push(match relaxed.count {
+                1 => format!("1 element matches '{}'", relaxed.selector),
+                n => format!("{} elements match '{}'", n, relaxed.selector),
+            });
+        }
+        if !hints.invalid {
+            leads.push("run 'elements' to list the page's interactive elements".to_string());
+        }
+        Error::ElementNotFound { selector: selector.to_string(), detail: format!(" ({})", leads.join("; ")) }
+    }
+
+    async fn execute<C: Command>(&self, cmd: C) -> Result<CommandResponse<C::Response>> {
+        let page = self.page.as_ref().ok_or(Error::NotInitialized)?;
+        self.cdp("execute", page.execute(cmd)).await
+    }
+
+    pub async fn execute_javascript(&self, code: &str, target: &JsTarget) -> Result<()> {
+        self.ensure_page()?;
+        
+        let result = match target {
+            JsTarget { tab: None, frame: None, isolated: false } => self.evaluate(code).await?,
+            target => self.evaluate_in(code, target).await?,
+        };
+        
+        if let Some(value) = result.value() {
+            status!("{}", serde_json::to_string_pretty(value)?);
+        }
+        
+        Ok(())
+    }
+
+    async fn evaluate_in(&self, code: &str, target: &JsTarget) -> Result<EvaluationResult> {
+        let page = match target.tab {
+            Some(index) => {
+                let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+                let pages = self.cdp("pages", browser.pages()).await?;
+                let count = pages.len();
+                pages.into_iter().nth(index)
+                    .ok_or_else(|| anyhow::anyhow!("No tab {} (there are {} tabs)", index, count))?
+            }
+            None => self.page.clone().ok_or(Error::NotInitialized)?,
+        };
+
+        let frame_id = self.resolve_frame(&page, target.frame.as_deref()).await?;
+        let context_id = if target.isolated {
+            let world = CreateIsolatedWorldParams {
+                frame_id,
+                world_name: Some("browser-cli".to_string()),
+                grant_univeral_access: None,
+            };
+            Some(self.cdp("create_isolated_world", page.execute(world)).await?.result.execution_context_id)
+        } else if target.frame.is_some() {
+            let context = self.cdp("frame_execution_context", page.frame_execution_context(frame_id)).await?;
+            Some(context.ok_or_else(|| anyhow::anyhow!("Frame has no JavaScript context yet (still loading?)"))?)
+        } else {
+            None
+        };
+
+        self.evaluate_in_context(&page, code, context_id).await
+    }
+
+    async fn evaluate_in_context(&self, page: &Page, code: &str, context_id: Option<ExecutionContextId>) -> Result<EvaluationResult> {
+        let mut params = EvaluateParams::builder().expression(code).await_promise(true).return_by_value(true);
+        if let Some(context_id) = context_id {
+            params = params.context_id(context_id);
+        }
+        let params = params.build().map_err(|e| anyhow::anyhow!(e))?;
+        self.cdp("evaluate", page.evaluate(params)).await
+            .map_err(|e| self.explain_injection_error(e))
+    }
+
+    // Scripts are only ever injected over CDP (Runtime.evaluate, addScriptToEvaluateOnNewDocument), which CSP
+    // doesn't gate, but what they do inside the page (eval, HTML sinks under Trusted Types) still is
+    fn explain_injection_error(&self, error: anyhow::Error) -> anyhow::Error {
+        let message = error.to_string();
+        if !is_csp_violation(&message) {
+            return error;
+        }
+        if self.strict_csp {
+            anyhow::anyhow!("Injected script was blocked by the page's Content-Security-Policy (strict CSP mode keeps it enforced): {}", message)
+        } else {
+            anyhow::anyhow!("Injected script was blocked by the page's Content-Security-Policy; use --bypass-csp to allow it: {}", message)
+        }
+    }
+
+    // Evaluate one of the tool's own helper scripts, in the helper world when isolation is on.
+    // The world dies with its document, so a stale one is replaced once and the script retried.
+    async fn evaluate_helper(&self, script: impl Into<String>) -> Result<EvaluationResult> {
+        let script = script.into();
+        if !self.isolated_helpers {
+            return self.evaluate(script).await;
+        }
+        let page = self.page.as_ref().ok_or(Error::NotInitialized)?;
+
+        let cached = *self.helper_world.lock().unwrap();
+        if let Some(context_id) = cached {
+            match self.evaluate_in_context(page, &script, Some(context_id)).await {
+                Err(e) if is_stale_context(&e) => {}
+                result => return result,
+            }
+        }
+
+        let frame_id = self.resolve_frame(page, None).await?;
+        let world = CreateIsolatedWorldParams {
+            frame_id,
+            world_name: Some("browser-cli-helpers".to_string()),
+            grant_univeral_access: None,
+        };
+        let context_id = self.cdp("create_isolated_world", page.execute(world)).await?.result.execution_context_id;
+        *self.helper_world.lock().unwrap() = Some(context_id);
+        self.evaluate_in_context(page, &script, Some(context_id)).await
+    }
+
+    // Frame id for `--frame`: the main frame when unset, else an iframe by index or by its element's selector
+    async fn resolve_frame(&self, page: &Page, frame: Option<&str>) -> Result<FrameId> {
+        let tree = self.cdp("get_frame_tree", page.execute(GetFrameTreeParams::default())).await?.result.frame_tree;
+        let Some(frame) = frame else {
+            return Ok(tree.frame.id);
+        };
+
+        if let Ok(index) = frame.parse::<usize>() {
+            fn flatten(tree: FrameTree, out: &mut Vec<FrameId>) {
+                for child in tree.child_frames.into_iter().flatten() {
+                    out.push(child.frame.id.clone());
+                    flatten(child, out);
+                }
+            }
+            let mut frames = Vec::new();
+            flatten(tree, &mut frames);
+            let count = frames.len();
+            return frames.into_iter().nth(index)
+                .ok_or_else(|| anyhow::anyhow!("No frame {} (the page has {} iframes)", index, count));
+        }
+
+        let element = self.cdp("find_element", page.find_element(frame)).await?;
+        let node = self.cdp("describe_node", element.description()).await?;
+        node.frame_id.ok_or_else(|| anyhow::anyhow!("'{}' is not an iframe", frame))
+    }
+
+    // Evaluate an expression and compare it with an expected JSON value, or check it is truthy
+    // Returns the actual value when the assertion holds
+    pub async fn assert_js(&self, expression: &str, expected: Option<&str>) -> Result<serde_json::Value> {
+        self.ensure_page()?;
+        
+        let result = self.evaluate(format!("({})", expression)).await?;
+        let actual = result.value().cloned().unwrap_or(serde_json::Value::Null);
+        
+        match expected {
+            // Bare words that aren't JSON compare as strings, so `assert-js document.title Home` works
+            Some(expected) => {
+                let expected = serde_json::from_str(expected)
+                    .unwrap_or_else(|_| serde_json::Value::String(expected.to_string()));
+                if actual != expected {
+                    return Err(anyhow::anyhow!("Assertion failed: {} => {} (expected {})", expression, actual, expected));
+                }
+            }
+            None => {
+                if !is_truthy(&actual) {
+                    return Err(anyhow::anyhow!("Assertion failed: {} => {} (expected a truthy value)", expression, actual));
+                }
+            }
+        }
+        
+        status!("{} {} => {}", "✓".green(), expression, actual);
+        Ok(actual)
+    }
+
+    // Freeze Date and performance.now at an instant (and emulate its timezone) so countdowns and
+    // relative timestamps render the same on every run; None restores the real clock for new documents
+    pub async fn freeze_time(&mut self, at: Option<DateTime<FixedOffset>>, timezone: Option<&str>) -> Result<()> {
+        self.ensure_page()?;
+        
+        // Chrome refuses a new override while one is active, so always clear it first
+        self.execute(SetTimezoneOverrideParams::new("")).await.ok();
+        
+        let Some(at) = at else {
+            self.set_init_script("freeze-time", None).await?;
+            status!("{} Time unfrozen (reload to restore the real clock in the current page)", "🕒".cyan());
+            return Ok(());
+        };
+        
+        let shim = format!(
+            r#"
+            (function() {{
+                const frozen = {};
+                const NativeDate = Date;
+                function FrozenDate(...args) {{
+                    if (!new.target) return new NativeDate(frozen).toString();
+                    return Reflect.construct(NativeDate, args.length ? args : [frozen], new.target);
+                }}
+                Object.setPrototypeOf(FrozenDate, NativeDate);
+                FrozenDate.prototype = NativeDate.prototype;
+                FrozenDate.now = () => frozen;
+                window.Date = FrozenDate;
+                const perfNow = performance.now();
+                performance.now = () => perfNow;
+            }})()
+            "#,
+            at.timestamp_millis()
+        );
+        self.set_init_script("freeze-time", Some(shim)).await?;
+        
+        let timezone = timezone.map(str::to_string).or_else(|| timezone_for_offset(at.offset()));
+        if let Some(timezone) = &timezone {
+            self.execute(SetTimezoneOverrideParams::new(timezone.clone())).await
+                .map_err(|e| anyhow::anyhow!("Invalid timezone '{}': {}", timezone, e))?;
+        }
+        
+        status!("{} Time frozen at {}{}", "🕒".cyan(), at.to_rfc3339(),
+            timezone.map(|tz| format!(" ({})", tz)).unwrap_or_default());
+        Ok(())
+    }
+
+    // Replace Math.random (and optionally crypto.getRandomValues) with a seeded PRNG so randomized
+    // content and A/B assignment repeat across runs; None restores native randomness for new documents
+    pub async fn seed_random(&mut self, seed: Option<u32>, include_crypto: bool) -> Result<()> {
+        self.ensure_page()?;
+        
+        let Some(seed) = seed else {
+            self.set_init_script("seed", None).await?;
+            status!("{} Randomness unseeded (reload to restore it in the current page)", "🎲".cyan());
+            return Ok(());
+        };
+        
+        // mulberry32: tiny, fast, and good enough to make page randomness deterministic
+        let shim = format!(
+            r#"
+            (function() {{
+                let state = {} >>> 0;
+                const next = () => {{
+                    state = (state + 0x6D2B79F5) >>> 0;
+                    let t = state;
+                    t = Math.imul(t ^ (t >>> 15), t | 1);
+                    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+                    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+                }};
+                Math.random = next;
+                if ({}) {{
+                    crypto.getRandomValues = function(array) {{
+                        const bytes = new Uint8Array(array.buffer, array.byteOffset, array.byteLength);
+                        for (let i = 0; i < bytes.length; i++) bytes[i] = Math.floor(next() * 256);
+                        return array;
+                    }};
+                }}
+            }})()
+            "#,
+            seed, include_crypto
+        );
+        self.set_init_script("seed", Some(shim)).await?;
+        
+        status!("{} Math.random seeded with {}{}", "🎲".cyan(), seed,
+            if include_crypto { " (crypto.getRandomValues too)" } else { "" });
+        Ok(())
+    }
+
+    // Reload the page once per language (Accept-Language + Intl locale) and report the selector's
+    // strings side by side; values identical to the first language are flagged as untranslated
+    pub async fn i18n_check(&self, langs: &[String], selector: &str) -> Result<()> {
+        self.ensure_page()?;
+        
+        let user_agent = self.evaluate("navigator.userAgent").await?
+            .value().and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let extract_script = format!(
+            "Array.from(document.querySelectorAll({})).map(el => el.innerText.trim())",
+            serde_json::to_string(selector)?
+        );
+        
+        let mut columns: Vec<Vec<String>> = Vec::new();
+        for lang in langs {
+            progress!("{}", format!("Loading page in {}...", lang).blue());
+            self.execute(SetLocaleOverrideParams::default()).await.ok();
+            self.execute(SetLocaleOverrideParams { locale: Some(lang.clone()) }).await
+                .map_err(|e| anyhow::anyhow!("Unsupported locale '{}': {}", lang, e))?;
+            self.execute(SetUserAgentOverrideParams {
+                accept_language: Some(lang.clone()),
+                ..SetUserAgentOverrideParams::new(user_agent.clone())
+            }).await?;
+            
+            let page = self.page.as_ref().unwrap();
+            self.cdp("reload", page.reload()).await?;
+            sleep(Duration::from_millis(500)).await;
+            
+            let values = self.evaluate(extract_script.as_str()).await?
+                .into_value::<Vec<String>>()
+                .unwrap_or_default();
+            columns.push(values);
+        }
+        
+        // Put the browser's own language back before reporting
+        self.execute(SetLocaleOverrideParams::default()).await.ok();
+        self.execute(SetUserAgentOverrideParams::new(user_agent)).await.ok();
+        let page = self.page.as_ref().unwrap();
+        self.cdp("reload", page.reload()).await?;
+        
+        let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+        if rows == 0 {
+            return Err(anyhow::anyhow!("No elements matched '{}' in any language", selector));
+        }
+        
+        let mut untranslated = 0;
+        for row in 0..rows {
+            status!("{}", format!("{}[{}]", selector, row).bold());
+            let base = columns[0].get(row);
+            for (index, (lang, values)) in langs.iter().zip(&columns).enumerate() {
+                let value = values.get(row);
+                let text = value.map(String::as_str).unwrap_or("(missing)");
+                if index > 0 && value.is_some() && value == base {
+                    untranslated += 1;
+                    status!("  {:<6} {} {}", lang.cyan(), text, "⚠️ untranslated".yellow());
+                } else {
+                    status!("  {:<6} {}", lang.cyan(), text);
+                }
+            }
+        }
+        
+        if untranslated > 0 {
+            status!("{} {} value(s) identical to {}", "⚠️".yellow(), untranslated, langs[0]);
+        } else {
+            status!("{} All {} string(s) differ across {} languages", "✓".green(), rows, langs.len());
+        }
+        Ok(())
+    }
+
+    // Reload the page collecting deprecation warnings, then check the served HTML's structure and the DOM
+    pub async fn audit_html(&self) -> Result<AuditReport> {
+        self.ensure_page()?;
+        
+        let page = self.page.as_ref().unwrap();
+        let mut log_entries = self.cdp("event_listener", page.event_listener::<EventEntryAdded>()).await?;
+        let mut console_calls = self.cdp("event_listener", page.event_listener::<EventConsoleApiCalled>()).await?;
+        self.execute(LogEnableParams::default()).await?;
+        
+        progress!("{}", "Reloading page to collect console warnings...".blue());
+        self.cdp("reload", page.reload()).await?;
+        
+        let mut warnings = Vec::new();
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(1500);
+        loop {
+            tokio::select! {
+                Some(event) = log_entries.next() => {
+                    let entry = &event.entry;
+                    if matches!(entry.source, LogEntrySource::Deprecation | LogEntrySource::Intervention | LogEntrySource::Violation) {
+                        warnings.push((entry.source.as_ref().to_string(), entry.text.clone(), entry.url.clone()));
+                    }
+                }
+                Some(event) = console_calls.next() => {
+                    let message = event.args.iter()
+                        .filter_map(|arg| arg.value.as_ref().and_then(|v| v.as_str()).map(str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if event.r#type == ConsoleApiCalledType::Warning && message.to_lowercase().contains("deprecat") {
+                        warnings.push(("deprecation".to_string(), message, None));
+                    }
+                }
+                _ = tokio::time::sleep_until(deadline) => break,
+            }
+        }
+        self.execute(LogDisableParams::default()).await.ok();
+        
+        let url = self.get_url().await?;
+        let mut report = AuditReport::new("html", &url);
+        for (source, text, location) in warnings {
+            let rule = if source == "deprecation" { "deprecation" } else { "console-warning" };
+            report.add(Severity::Warning, rule, text, location);
+        }
+        
+        // Check the source as served: the live DOM has already been repaired by the parser
+        let source = self.evaluate(
+            "fetch(location.href, { credentials: 'include' }).then(r => r.text()).catch(() => document.documentElement.outerHTML)"
+        ).await?.into_value::<String>().unwrap_or_default();
+        audit::check_nesting(&source, &mut report);
+        
+        let dom_checks = self.evaluate(
+            r#"
+            (function() {
+                const describe = el => el.tagName.toLowerCase()
+                    + (el.id ? '#' + el.id : '')
+                    + (el.classList.length ? '.' + Array.from(el.classList).join('.') : '');
+                const ids = {};
+                document.querySelectorAll('[id]').forEach(el => { ids[el.id] = (ids[el.id] || 0) + 1; });
+                return JSON.stringify({
+                    duplicateIds: Object.entries(ids).filter(([, count]) => count > 1),
+                    missingAlt: Array.from(document.querySelectorAll('img:not([alt]), area:not([alt]), input[type="image"]:not([alt])')).map(describe),
+                    missingLang: !document.documentElement.getAttribute('lang'),
+                    missingTitle: !document.title.trim(),
+                    missingDoctype: !document.doctype
+                });
+            })()
+            "#
+        ).await?;
+        let dom: serde_json::Value = dom_checks.value()
+            .and_then(|v| v.as_str())
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        
+        for entry in dom["duplicateIds"].as_array().into_iter().flatten() {
+            report.add(Severity::Error, "duplicate-id",
+                format!("id \"{}\" is used {} times", entry[0].as_str().unwrap_or_default(), entry[1]), None);
+        }
+        for target in dom["missingAlt"].as_array().into_iter().flatten() {
+            report.add(Severity::Error, "missing-alt", "Image without alt text", target.as_str().map(str::to_string));
+        }
+        if dom["missingLang"].as_bool().unwrap_or(false) {
+            report.add(Severity::Warning, "missing-lang", "<html> has no lang attribute", None);
+        }
+        if dom["missingTitle"].as_bool().unwrap_or(false) {
+            report.add(Severity::Warning, "missing-title", "Document has no <title>", None);
+        }
+        if dom["missingDoctype"].as_bool().unwrap_or(false) {
+            report.add(Severity::Warning, "missing-doctype", "No <!DOCTYPE html>; the page renders in quirks mode", None);
+        }
+        
+        Ok(report)
+    }
+
+    // Captured requests: the latest page load, or everything still in the capture buffer
+    pub fn network_requests(&self, all: bool) -> Vec<RequestRecord> {
+        let log = self.network.lock().unwrap();
+        if all {
+            log.entries()
+        } else {
+            log.last_page_load()
+        }
+    }
+
+    pub fn clear_network(&self) {
+        self.network.lock().unwrap().clear();
+    }
+
+    // Keep every request from now on (in all tabs) until har_stop writes them out
+    pub fn har_start(&mut self, path: PathBuf) -> Result<()> {
+        self.ensure_page()?;
+        if let Some(running) = &self.har_path {
+            return Err(anyhow::anyhow!("Already capturing to {}; run 'har stop' first", running.display()));
+        }
+        
+        self.network.lock().unwrap().start_capture();
+        status!("{} Capturing network traffic for {}", "🔴".red(), path.display());
+        self.har_path = Some(path);
+        Ok(())
+    }
+
+    pub fn har_capturing(&self) -> bool {
+        self.har_path.is_some()
+    }
+
+    // Write the capture as HAR 1.2; returns the file and how many requests it holds
+    pub fn har_stop(&mut self) -> Result<(PathBuf, usize)> {
+        let path = self.har_path.take().ok_or_else(|| anyhow::anyhow!("No HAR capture running; start one with 'har start <file.har>'"))?;
+        let records = self.network.lock().unwrap().take_capture().unwrap_or_default();
+        let count = har::write(&path, &records)?;
+        
+        status!("{} Saved {} requests to {}", "💾".green(), count, path.display());
+        Ok((path, count))
+    }
+
+    // Answer requests from a recorded HAR; unmatched requests pass through or are blocked
+    pub async fn load_har_mocks(&mut self, path: &str, block_unmatched: bool) -> Result<()> {
+        self.ensure_page()?;
+        
+        let mocks = HarMocks::from_har(std::path::Path::new(path), block_unmatched)?;
+        let count = mocks.len();
+        self.interception.lock().unwrap().mocks = Some(mocks);
+        self.ensure_intercepting().await?;
+        
+        status!("{} Mocking {} responses from {} (unmatched requests {})", "🎭".cyan(), count, path,
+            if block_unmatched { "blocked" } else { "pass through" });
+        Ok(())
+    }
+
+    // Pause requests on every tab once some interception rule exists; they stay paused-and-continued after
+    async fn ensure_intercepting(&mut self) -> Result<()> {
+        if !self.intercepting {
+            for tab in &self.tabs {
+                network::intercept(tab, Arc::clone(&self.interception), self.cdp_timeout).await?;
+            }
+            self.intercepting = true;
+        }
+        Ok(())
+    }
+
+    // Abort requests whose URL matches any of the globs, see network::BlockRule
+    pub async fn block_urls(&mut self, patterns: &[String]) -> Result<()> {
+        self.ensure_page()?;
+        
+        {
+            let mut interception = self.interception.lock().unwrap();
+            for pattern in patterns {
+                if !interception.blocks.iter().any(|rule| &rule.pattern == pattern) {
+                    interception.blocks.push(BlockRule::new(pattern));
+                }
+            }
+        }
+        self.ensure_intercepting().await?;
+        
+        status!("{} Blocking {}", "🚫".cyan(), patterns.join(", "));
+        Ok(())
+    }
+
+    // Drop one block rule, or all of them with None; returns how many were removed
+    pub fn unblock_urls(&self, pattern: Option<&str>) -> Result<usize> {
+        let mut interception = self.interception.lock().unwrap();
+        let before = interception.blocks.len();
+        match pattern {
+            Some(pattern) => {
+                interception.blocks.retain(|rule| rule.pattern != pattern);
+                if interception.blocks.len() == before {
+                    return Err(anyhow::anyhow!("No block rule '{}'", pattern));
+                }
+            }
+            None => interception.blocks.clear(),
+        }
+        Ok(before - interception.blocks.len())
+    }
+
+    pub fn block_rules(&self) -> Vec<BlockRule> {
+        self.interception.lock().unwrap().blocks.clone()
+    }
+
+    pub fn clear_mocks(&self) {
+        self.interception.lock().unwrap().mocks = None;
+    }
+
+    pub fn mock_status(&self) -> Option<String> {
+        self.interception.lock().unwrap().mocks.as_ref().map(HarMocks::describe)
+    }
+
+    // Press a key and keep it down; held modifiers apply to later clicks and key presses
+    pub async fn key_down(&self, key: &str) -> Result<()> {
+        self.ensure_page()?;
+        
+        let definition = keyboard::key_definition(key)?;
+        if self.held_keys.lock().unwrap().iter().any(|held| held.key == definition.key) {
+            return Err(anyhow::anyhow!("{} is already held", definition.key));
+        }
+        self.held_keys.lock().unwrap().push(definition);
+        
+        self.press_down(definition, self.modifiers()).await?;
+        
+        status!("{} Holding {}", "⬇".cyan(), definition.key);
+        Ok(())
+    }
+
+    pub async fn key_up(&self, key: &str) -> Result<()> {
+        self.ensure_page()?;
+        
+        let definition = keyboard::key_definition(key)?;
+        let modifiers = self.modifiers();
+        {
+            let mut held = self.held_keys.lock().unwrap();
+            let position = held.iter().position(|held| held.key == definition.key)
+                .ok_or_else(|| anyhow::anyhow!("{} is not held", definition.key))?;
+            held.remove(position);
+        }
+        
+        self.dispatch_key(DispatchKeyEventType::KeyUp, definition, modifiers & !keyboard::modifier_bit(definition.key), None).await?;
+        
+        status!("{} Released {}", "⬆".cyan(), definition.key);
+        Ok(())
+    }
+
+    // Press a key combo (`Enter`, `ctrl+a`, `shift+Tab`) with real key events: modifiers go down in order,
+    // the key is pressed and released, then the modifiers come up again. Keys already held still apply.
+    pub async fn press_key(&self, combo: &str) -> Result<()> {
+        self.ensure_page()?;
+        
+        let (modifiers, key) = keyboard::parse_combo(combo)?;
+        let mut bits = self.modifiers();
+        for modifier in &modifiers {
+            bits |= keyboard::modifier_bit(modifier.key);
+            self.dispatch_key(DispatchKeyEventType::RawKeyDown, modifier, bits, None).await?;
+        }
+        
+        self.press_down(key, bits).await?;
+        self.dispatch_key(DispatchKeyEventType::KeyUp, key, bits, None).await?;
+        
+        for modifier in modifiers.iter().rev() {
+            bits &= !keyboard::modifier_bit(modifier.key);
+            self.dispatch_key(DispatchKeyEventType::KeyUp, modifier, bits | self.modifiers(), None).await?;
+        }
+        
+        status!("{} Pressed {}", "⌨️".cyan(), combo);
+        Ok(())
+    }
+
+    // Keys with text type it unless a shortcut modifier turns the press into a command
+    async fn press_down(&self, key: &KeyDefinition, modifiers: i64) -> Result<()> {
+        let text = key.text.or((key.key.chars().count() == 1).then_some(key.key))
+            .filter(|_| modifiers & (2 | 4) == 0);
+        let kind = if text.is_some() { DispatchKeyEventType::KeyDown } else { DispatchKeyEventType::RawKeyDown };
+        self.dispatch_key(kind, key, modifiers, text).await
+    }
+
+    async fn dispatch_key(&self, kind: DispatchKeyEventType, key: &KeyDefinition, modifiers: i64, text: Option<&str>) -> Result<()> {
+        let event = DispatchKeyEventParams::builder()
+            .r#type(kind)
+            .modifiers(modifiers)
+            .key(key.key)
+            .code(key.code)
+            .windows_virtual_key_code(key.key_code)
+            .native_virtual_key_code(key.key_code);
+        let event = match text {
+            Some(text) => event.text(text),
+            None => event,
+        };
+        self.execute(event.build().map_err(|e| anyhow::anyhow!(e))?).await?;
+        Ok(())
+    }
+
+    pub fn held_keys(&self) -> Vec<&'static str> {
+        self.held_keys.lock().unwrap().iter().map(|held| held.key).collect()
+    }
+
+    fn modifiers(&self) -> i64 {
+        self.held_keys.lock().unwrap().iter().fold(0, |bits, held| bits | keyboard::modifier_bit(held.key))
+    }
+
+    // Override navigator.hardwareConcurrency, deviceMemory and getBattery() in every document.
+    // Getters go on Navigator.prototype, where the real ones live, so the override doesn't stand out.
+    pub async fn spoof_hardware(&mut self, profile: Option<HardwareProfile>) -> Result<()> {
+        self.ensure_page()?;
+        
+        let Some(profile) = profile else {
+            self.set_init_script("hardware", None).await?;
+            status!("{} Hardware spoofing off (reload to restore real values in the current page)", "🖥️".cyan());
+            return Ok(());
+        };
+        if profile.battery.is_some_and(|level| !(0.0..=1.0).contains(&level)) {
+            return Err(anyhow::anyhow!("Battery level must be between 0 and 1"));
+        }
+        
+        let shim = format!(
+            r#"
+            (function() {{
+                const override = (name, value) => {{
+                    if (value === null) return;
+                    Object.defineProperty(Navigator.prototype, name, {{ get: () => value, configurable: true, enumerable: true }});
+                }};
+                override('hardwareConcurrency', {cores});
+                override('deviceMemory', {memory});
+                
+                const level = {battery};
+                if (level !== null && navigator.getBattery) {{
+                    const battery = Object.assign(new EventTarget(), {{
+                        charging: {charging},
+                        chargingTime: {charging} ? 0 : Infinity,
+                        dischargingTime: {charging} ? Infinity : Math.round(level * 36000),
+                        level,
+                        onchargingchange: null,
+                        onchargingtimechange: null,
+                        ondischargingtimechange: null,
+                        onlevelchange: null,
+                    }});
+                    Navigator.prototype.getBattery = function getBattery() {{ return Promise.resolve(battery); }};
+                }}
+            }})()
+            "#,
+            cores = profile.cores.map(|c| c.to_string()).unwrap_or("null".to_string()),
+            memory = profile.memory_gb.map(|m| m.to_string()).unwrap_or("null".to_string()),
+            battery = profile.battery.map(|b| b.to_string()).unwrap_or("null".to_string()),
+            charging = profile.charging,
+        );
+        self.set_init_script("hardware", Some(shim)).await?;
         
-        println!("{} Browser ready", "🚀".green());
+        let mut spoofed = Vec::new();
+        if let Some(cores) = profile.cores {
+            spoofed.push(format!("{} cores", cores));
+        }
+        if let Some(memory) = profile.memory_gb {
+            spoofed.push(format!("{}GB memory", memory));
+        }
+        if let Some(battery) = profile.battery {
+            spoofed.push(format!("battery {:.0}%{}", battery * 100.0, if profile.charging { " charging" } else { "" }));
+        }
+        status!("{} Spoofing {}", "🖥️".cyan(), spoofed.join(", "));
         Ok(())
     }
 
-    pub async fn navigate(&mut self, url: &str) -> Result<()> {
-        self.ensure_initialized().await?;
+    // Start capturing notifications: the Notification constructor and showNotification are wrapped in
+    // every document and report over a runtime binding, so nothing is lost across navigations
+    pub async fn watch_notifications(&mut self) -> Result<()> {
+        self.ensure_page()?;
+        if self.watching_notifications {
+            return Ok(());
+        }
         
-        println!("{}", format!("Navigating to: {}", url).blue());
+        let browser = self.browser.as_ref().unwrap();
+        self.cdp("grant_permissions", browser.execute(GrantPermissionsParams::new(vec![PermissionType::Notifications]))).await?;
         
         let page = self.page.as_ref().unwrap();
-        page.goto(url).await?;
-        
-        // Wait for navigation to complete
-        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        let mut calls = self.cdp("event_listener", page.event_listener::<EventBindingCalled>()).await?;
+        self.execute(AddBindingParams::new(NOTIFY_BINDING)).await?;
+        let (records, events) = (Arc::clone(&self.notifications), self.events.clone());
+        tokio::spawn(async move {
+            while let Some(call) = calls.next().await {
+                if call.name != NOTIFY_BINDING {
+                    continue;
+                }
+                let Ok(payload) = serde_json::from_str::<serde_json::Value>(&call.payload) else {
+                    continue;
+                };
+                let record = NotificationRecord {
+                    at: Utc::now(),
+                    source: payload["source"].as_str().unwrap_or("page").to_string(),
+                    title: payload["title"].as_str().unwrap_or_default().to_string(),
+                    body: payload["body"].as_str().unwrap_or_default().to_string(),
+                    tag: payload["tag"].as_str().map(str::to_string),
+                };
+                events.publish(BrowserEvent::Notification {
+                    source: record.source.clone(),
+                    title: record.title.clone(),
+                    body: record.body.clone(),
+                });
+                records.lock().unwrap().push(record);
+            }
+        });
         
-        // Get concise page information for AI/agents
-        let page_info = self.get_concise_page_info().await?;
-        println!("{} {}", "✓".green(), page_info);
+        let shim = format!(
+            r#"
+            (function() {{
+                if (window.__browserCliNotificationShim) return;
+                window.__browserCliNotificationShim = true;
+                const report = (source, title, options) => {{
+                    try {{
+                        window[{binding}](JSON.stringify({{
+                            source,
+                            title: String(title ?? ''),
+                            body: String((options && options.body) ?? ''),
+                            tag: (options && options.tag) || null,
+                        }}));
+                    }} catch (e) {{}}
+                }};
+                
+                if (window.Notification) {{
+                    const Original = window.Notification;
+                    const Wrapped = function Notification(title, options) {{
+                        report('page', title, options);
+                        return new Original(title, options);
+                    }};
+                    Wrapped.prototype = Original.prototype;
+                    Object.defineProperty(Wrapped, 'permission', {{ get: () => Original.permission }});
+                    Wrapped.requestPermission = (...args) => Original.requestPermission(...args);
+                    window.Notification = Wrapped;
+                }}
+                if (window.ServiceWorkerRegistration) {{
+                    const show = ServiceWorkerRegistration.prototype.showNotification;
+                    ServiceWorkerRegistration.prototype.showNotification = function(title, options) {{
+                        report('registration', title, options);
+                        return show.call(this, title, options);
+                    }};
+                }}
+                if (window.PushManager) {{
+                    const subscribe = PushManager.prototype.subscribe;
+                    PushManager.prototype.subscribe = function(...args) {{
+                        return subscribe.apply(this, args).then(subscription => {{
+                            report('push-subscribe', subscription.endpoint, {{}});
+                            return subscription;
+                        }});
+                    }};
+                }}
+            }})()
+            "#,
+            binding = serde_json::to_string(NOTIFY_BINDING)?
+        );
+        self.set_init_script("notifications", Some(shim)).await?;
+        self.watching_notifications = true;
         
+        status!("{} Capturing notifications", "🔔".cyan());
         Ok(())
     }
 
-    pub async fn screenshot(&self, filename: Option<&str>) -> Result<String> {
+    // Publish a title event whenever any tab's title changes, from the targetInfoChanged events the
+    // browser sends for every tab (so background tabs count too)
+    pub async fn watch_titles(&mut self) -> Result<()> {
         self.ensure_page()?;
-        
-        // Create browser-ss directory if it doesn't exist
-        let screenshots_dir = "browser-ss";
-        if let Err(_) = fs::metadata(screenshots_dir) {
-            fs::create_dir_all(screenshots_dir)?;
+        if self.watching_titles {
+            return Ok(());
         }
         
-        let final_filename = if let Some(name) = filename {
-            // If user provides filename, use it directly
-            if name.starts_with('/') || name.contains('/') {
-                name.to_string()
-            } else {
-                format!("{}/{}", screenshots_dir, name)
+        let browser = self.browser.as_ref().unwrap();
+        let mut changes = self.cdp("event_listener", browser.event_listener::<EventTargetInfoChanged>()).await?;
+        // Current titles, so only changes from here on are reported
+        let targets = self.cdp("get_targets", browser.execute(GetTargetsParams::default())).await?;
+        let mut titles: HashMap<TargetId, String> = targets.result.target_infos.iter()
+            .map(|info| (info.target_id.clone(), info.title.clone()))
+            .collect();
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            while let Some(event) = changes.next().await {
+                let info = &event.target_info;
+                if info.r#type != "page" || titles.get(&info.target_id) == Some(&info.title) {
+                    continue;
+                }
+                titles.insert(info.target_id.clone(), info.title.clone());
+                events.publish(BrowserEvent::TitleChanged { url: info.url.clone(), title: info.title.clone() });
             }
-        } else {
-            // Generate filename based on route and timestamp
-            let page = self.page.as_ref().unwrap();
-            let url = page.url().await?.unwrap_or_default();
-            let route = self.url_to_route(&url);
-            let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-            format!("{}/{}_{}.png", screenshots_dir, route, timestamp)
-        };
-        
-        let path = PathBuf::from(&final_filename);
-        
-        let page = self.page.as_ref().unwrap();
-        let screenshot = page.screenshot(CaptureScreenshotParams::builder().build()).await?;
-        tokio::fs::write(&path, screenshot).await?;
+        });
+        self.watching_titles = true;
         
-        println!("{} Screenshot: {}", "📸".cyan(), final_filename);
-        Ok(final_filename)
+        status!("{} Watching tab titles", "👀".cyan());
+        Ok(())
     }
 
-    pub async fn click(&self, selector: &str) -> Result<()> {
+    // Publish an audio event when the active tab starts or stops playing sound. The protocol doesn't
+    // expose the tab's audible state, so a shim in every document watches unmuted media elements and
+    // running Web Audio contexts instead; a running context counts as sound even when it plays silence.
+    pub async fn watch_audio(&mut self) -> Result<()> {
         self.ensure_page()?;
+        if self.watching_audio {
+            return Ok(());
+        }
         
         let page = self.page.as_ref().unwrap();
-        let element = page.find_element(selector).await?;
-        element.click().await?;
+        let mut calls = self.cdp("event_listener", page.event_listener::<EventBindingCalled>()).await?;
+        self.execute(AddBindingParams::new(AUDIO_BINDING)).await?;
+        let (events, audio_page) = (self.events.clone(), page.clone());
+        tokio::spawn(async move {
+            while let Some(call) = calls.next().await {
+                if call.name != AUDIO_BINDING {
+                    continue;
+                }
+                let url = audio_page.url().await.ok().flatten().unwrap_or_default();
+                events.publish(BrowserEvent::Audio { url, audible: call.payload == "true" });
+            }
+        });
+        
+        let shim = format!(
+            r#"
+            (function() {{
+                if (window.__browserCliAudioShim) return;
+                window.__browserCliAudioShim = true;
+                const contexts = new Set();
+                let audible = false;
+                const check = () => {{
+                    const media = Array.from(document.querySelectorAll('audio, video'))
+                        .some(m => !m.paused && !m.ended && !m.muted && m.volume > 0);
+                    const webAudio = Array.from(contexts).some(c => c.state === 'running');
+                    if ((media || webAudio) === audible) return;
+                    audible = media || webAudio;
+                    try {{ window[{binding}](String(audible)); }} catch (e) {{}}
+                }};
+                // Media events don't bubble, so listen in the capture phase
+                for (const type of ['playing', 'pause', 'ended', 'volumechange', 'emptied']) {{
+                    document.addEventListener(type, check, true);
+                }}
+                const Original = window.AudioContext || window.webkitAudioContext;
+                if (Original) {{
+                    const Wrapped = class AudioContext extends Original {{
+                        constructor(...args) {{
+                            super(...args);
+                            contexts.add(this);
+                            this.addEventListener('statechange', check);
+                            check();
+                        }}
+                    }};
+                    window.AudioContext = Wrapped;
+                    if (window.webkitAudioContext) window.webkitAudioContext = Wrapped;
+                }}
+                check();
+            }})()
+            "#,
+            binding = serde_json::to_string(AUDIO_BINDING)?
+        );
+        self.set_init_script("audio", Some(shim)).await?;
+        self.watching_audio = true;
         
-        println!("{} Clicked: {}", "✓".green(), selector);
+        status!("{} Watching for sound", "🔊".cyan());
         Ok(())
     }
 
-    pub async fn type_text(&self, selector: &str, text: &str) -> Result<()> {
+    // Captured notifications, plus ones a service worker showed on its own (e.g. from a push event),
+    // which only the registrations' getNotifications() can see
+    pub async fn notifications(&self) -> Result<Vec<NotificationRecord>> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
-        let element = page.find_element(selector).await?;
-        element.click().await?;
-        element.type_str(text).await?;
+        let shown = self.evaluate(
+            r#"
+            (async () => {
+                if (!navigator.serviceWorker) return [];
+                const shown = [];
+                for (const registration of await navigator.serviceWorker.getRegistrations()) {
+                    for (const n of await registration.getNotifications()) {
+                        shown.push({ title: n.title, body: n.body, tag: n.tag || null });
+                    }
+                }
+                return shown;
+            })()
+            "#
+        ).await?;
         
-        println!("{} Typed into {}", "✓".green(), selector);
+        let mut records = self.notifications.lock().unwrap();
+        for shown in shown.value().and_then(|v| v.as_array()).into_iter().flatten() {
+            let (title, body) = (shown["title"].as_str().unwrap_or_default(), shown["body"].as_str().unwrap_or_default());
+            let tag = shown["tag"].as_str().map(str::to_string);
+            if !records.iter().any(|r| r.title == title && r.body == body && r.tag == tag) {
+                records.push(NotificationRecord {
+                    at: Utc::now(),
+                    source: "service-worker".to_string(),
+                    title: title.to_string(),
+                    body: body.to_string(),
+                    tag,
+                });
+            }
+        }
+        Ok(records.clone())
+    }
+
+    pub fn clear_notifications(&self) {
+        self.notifications.lock().unwrap().clear();
+    }
+
+    // Press and release a single key without printing, honouring held modifiers
+    async fn tap_key(&self, key: &str) -> Result<()> {
+        let definition = keyboard::key_definition(key)?;
+        for event_type in [DispatchKeyEventType::RawKeyDown, DispatchKeyEventType::KeyUp] {
+            let event = DispatchKeyEventParams::builder()
+                .r#type(event_type)
+                .modifiers(self.modifiers())
+                .key(definition.key)
+                .code(definition.code)
+                .windows_virtual_key_code(definition.key_code)
+                .native_virtual_key_code(definition.key_code)
+                .build()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            self.execute(event).await?;
+        }
         Ok(())
     }
 
-    pub async fn scroll(&self, direction: &str, amount: Option<i32>) -> Result<()> {
+    // Press Tab from the top of the document until focus wraps around, gets stuck, or max_steps is hit.
+    // Each stop is handed to `on_stop` (e.g. to screenshot it) while it has focus.
+    async fn tab_walk<F, Fut>(&self, max_steps: usize, mut on_stop: F) -> Result<(Vec<FocusStop>, WalkEnd)>
+    where
+        F: FnMut(usize, FocusStop) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        self.evaluate("document.activeElement && document.activeElement.blur(); window.scrollTo(0, 0)").await?;
+        let describe_script = format!(
+            r#"
+            (() => {{
+                const path = {path};
+                let el = document.activeElement;
+                while (el && el.shadowRoot && el.shadowRoot.activeElement) el = el.shadowRoot.activeElement;
+                if (!el || el === document.body || el === document.documentElement) return null;
+                
+                const implicitRoles = {{ a: 'link', button: 'button', select: 'combobox', textarea: 'textbox', summary: 'button', iframe: 'document' }};
+                const inputRoles = {{ checkbox: 'checkbox', radio: 'radio', range: 'slider', submit: 'button', button: 'button', reset: 'button', image: 'button' }};
+                const role = el.getAttribute('role')
+                    || (el.localName === 'input' ? (inputRoles[el.type] || 'textbox') : implicitRoles[el.localName])
+                    || el.localName;
+                const labelledBy = (el.getAttribute('aria-labelledby') || '').split(/\s+/)
+                    .map(id => (document.getElementById(id) || {{}}).innerText || '').join(' ');
+                const name = (el.getAttribute('aria-label') || labelledBy.trim()
+                    || (el.labels && el.labels[0] && el.labels[0].innerText) || el.innerText
+                    || el.getAttribute('alt') || el.getAttribute('title') || el.getAttribute('placeholder') || '')
+                    .trim().replace(/\s+/g, ' ').slice(0, 60);
+                
+                // A focus indicator is an outline, or any visual difference from the unfocused state
+                const style = () => {{
+                    const cs = getComputedStyle(el);
+                    return [cs.outlineStyle, cs.outlineWidth, cs.outlineColor, cs.boxShadow, cs.borderColor, cs.backgroundColor, cs.color, cs.textDecorationLine].join('|');
+                }};
+                const cs = getComputedStyle(el);
+                const outline = cs.outlineStyle !== 'none' && parseFloat(cs.outlineWidth) > 0;
+                const focused = style();
+                el.blur();
+                const unfocused = style();
+                el.focus({{ focusVisible: true }});
+                
+                return {{ selector: path(el), role, name, focusVisible: outline || focused !== unfocused, tabIndex: el.tabIndex }};
+            }})()
+            "#,
+            path = ELEMENT_PATH_JS
+        );
+        
+        let mut stops: Vec<FocusStop> = Vec::new();
+        for _ in 0..max_steps {
+            self.tap_key("Tab").await?;
+            let described = self.evaluate_helper(describe_script.clone()).await?;
+            let Some(stop) = described.value().filter(|v| !v.is_null()).map(|v| FocusStop {
+                selector: v["selector"].as_str().unwrap_or_default().to_string(),
+                role: v["role"].as_str().unwrap_or_default().to_string(),
+                name: v["name"].as_str().unwrap_or_default().to_string(),
+                focus_visible: v["focusVisible"].as_bool().unwrap_or(false),
+                tab_index: v["tabIndex"].as_i64().unwrap_or(0),
+            }) else {
+                // Focus left the document: the walk has passed the last stop
+                return Ok((stops, WalkEnd::Completed));
+            };
+            
+            if let Some(previous) = stops.iter().position(|s| s.selector == stop.selector) {
+                if previous == 0 {
+                    return Ok((stops, WalkEnd::Completed));
+                }
+                let end = if previous == stops.len() - 1 {
+                    format!("focus stays on {} when Tab is pressed", stop.selector)
+                } else {
+                    format!("focus cycles back to {} after {} stops", stop.selector, stops.len() - previous)
+                };
+                return Ok((stops, WalkEnd::Trapped(end)));
+            }
+            on_stop(stops.len() + 1, stop.clone()).await?;
+            stops.push(stop);
+        }
+        Ok((stops, WalkEnd::MaxSteps))
+    }
+
+    // Walk the tab order and flag traps, unreachable controls, missing focus indicators and names
+    pub async fn audit_tab_order(&self, max_steps: usize) -> Result<(Vec<FocusStop>, AuditReport)> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
+        let url = self.get_url().await?;
+        let mut report = AuditReport::new("tab-order", &url);
+        let (stops, end) = self.tab_walk(max_steps, |_, _| async { Ok(()) }).await?;
         
-        match direction {
-            "up" => {
-                let scroll_amount = -(amount.unwrap_or(300));
-                page.evaluate(format!("window.scrollBy(0, {})", scroll_amount)).await?;
+        match &end {
+            WalkEnd::Trapped(reason) => report.add(Severity::Error, "focus-trap", reason.clone(), None),
+            WalkEnd::MaxSteps => report.add(Severity::Warning, "max-steps",
+                format!("Stopped after {} stops before focus wrapped around; raise --max-steps", max_steps), None),
+            WalkEnd::Completed => {}
+        }
+        for stop in &stops {
+            if !stop.focus_visible {
+                report.add(Severity::Warning, "no-focus-indicator", "No visible change when focused", Some(stop.selector.clone()));
             }
-            "down" => {
-                let scroll_amount = amount.unwrap_or(300);
-                page.evaluate(format!("window.scrollBy(0, {})", scroll_amount)).await?;
+            if stop.name.is_empty() {
+                report.add(Severity::Warning, "missing-name", format!("Focusable {} has no accessible name", stop.role), Some(stop.selector.clone()));
             }
-            "top" => {
-                page.evaluate("window.scrollTo(0, 0)").await?;
+            if stop.tab_index > 0 {
+                report.add(Severity::Warning, "positive-tabindex",
+                    format!("tabindex={} overrides the document order", stop.tab_index), Some(stop.selector.clone()));
             }
-            "bottom" => {
-                page.evaluate("window.scrollTo(0, document.body.scrollHeight)").await?;
+        }
+        
+        // Only meaningful when the walk saw the whole cycle (or proved it can't)
+        if end != WalkEnd::MaxSteps {
+            let candidates = self.evaluate_helper(format!(
+                r#"
+                (() => {{
+                    const path = {path};
+                    const selector = 'a[href], button, input:not([type="hidden"]), select, textarea, summary, iframe, [tabindex], [contenteditable=""], [contenteditable="true"]';
+                    return Array.from(document.querySelectorAll(selector))
+                        .filter(el => !el.disabled && el.tabIndex >= 0 && !el.closest('[inert]'))
+                        .filter(el => el.getClientRects().length > 0 && getComputedStyle(el).visibility !== 'hidden')
+                        .map(path);
+                }})()
+                "#,
+                path = ELEMENT_PATH_JS
+            )).await?;
+            let candidates: Vec<String> = candidates.into_value().unwrap_or_default();
+            for candidate in candidates {
+                if !stops.iter().any(|stop| stop.selector == candidate) {
+                    report.add(Severity::Error, "unreachable", "Interactive element never receives focus via Tab", Some(candidate));
+                }
             }
-            _ => return Err(anyhow::anyhow!("Invalid scroll direction")),
         }
         
-        println!("{} Scrolled {}", "✓".green(), direction);
-        Ok(())
+        Ok((stops, report))
     }
 
-    pub async fn search(&self, query: &str) -> Result<()> {
+    // Tab through the page saving a viewport screenshot at every focus stop, as focus-ring evidence
+    pub async fn focus_walk_screenshots(&self, max_steps: usize) -> Result<(String, Vec<FocusStop>)> {
         self.ensure_page()?;
         
-        println!("{}", format!("Searching for: '{}'", query).blue());
+        let dir = paths::run_subdir(Artifact::Screenshots, "focus-walk")?;
         
         let page = self.page.as_ref().unwrap();
-        
-        let search_selectors = vec![
-            "input[type=\"search\"]",
-            "input[placeholder*=\"search\" i]",
-            "input[name*=\"search\" i]",
-            "input[id*=\"search\" i]",
-            ".search input",
-            "#search input",
-        ];
-        
-        for selector in search_selectors {
-            if let Ok(element) = page.find_element(selector).await {
-                element.click().await?;
-                element.type_str(query).await?;
-                page.evaluate("document.activeElement.dispatchEvent(new KeyboardEvent('keydown', {key: 'Enter', code: 'Enter'}))").await?;
-                println!("{} Search: {}", "✓".green(), query);
-                return Ok(());
+        let (stops, end) = self.tab_walk(max_steps, |step, stop| {
+            let path = dir.join(format!("{:03}-{}.png", step, sanitize_filename(&stop.role).unwrap_or_else(|| "stop".to_string())));
+            async move {
+                self.save_screenshot(page, &path).await?;
+                let indicator = if stop.focus_visible { "◉".green() } else { "○".red() };
+                status!("  {:>3}. {} {} {}", step, indicator, stop.selector.dimmed(), path.display());
+                Ok(())
             }
-        }
+        }).await?;
         
-        Err(anyhow::anyhow!("No search input found on page"))
+        match end {
+            WalkEnd::Trapped(reason) => status!("{} Focus trap: {}", "⚠️".yellow(), reason),
+            WalkEnd::MaxSteps => status!("{} Stopped after {} stops; raise --max to continue", "⚠️".yellow(), max_steps),
+            WalkEnd::Completed => {}
+        }
+        Ok((dir.display().to_string(), stops))
     }
 
-    pub async fn get_text(&self, selector: Option<&str>) -> Result<String> {
+    // Contrast of every visible text element in scope against its rendered background. Solid backgrounds
+    // are composited from computed styles; gradients and images are sampled from a screenshot with the text hidden.
+    pub async fn audit_contrast(&self, scope: Option<&str>) -> Result<(usize, AuditReport)> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
+        let url = self.get_url().await?;
+        let mut report = AuditReport::new("contrast", &url);
+        let collect_script = format!(
+            r#"
+            (() => {{
+                const path = {path};
+                const scope = document.querySelector({scope});
+                if (!scope) return null;
+                const parse = (color) => {{
+                    const match = color.match(/rgba?\(([^)]+)\)/);
+                    if (!match) return null;
+                    const parts = match[1].split(/[\s,\/]+/).filter(Boolean).map(parseFloat);
+                    return [parts[0], parts[1], parts[2], parts.length > 3 ? parts[3] : 1];
+                }};
+                const blend = (top, bottom) => [0, 1, 2].map(i => top[i] * top[3] + bottom[i] * (1 - top[3])).concat(1);
+                // Composite background colours up the tree; null when an image or gradient is involved
+                const background = (el) => {{
+                    const layers = [];
+                    for (let node = el; node; node = node.parentElement) {{
+                        const cs = getComputedStyle(node);
+                        if (cs.backgroundImage !== 'none') return null;
+                        const bg = parse(cs.backgroundColor);
+                        if (bg && bg[3] > 0) {{
+                            layers.push(bg);
+                            if (bg[3] >= 1) break;
+                        }}
+                    }}
+                    return layers.reverse().reduce((under, layer) => blend(layer, under), [255, 255, 255, 1]);
+                }};
+                
+                const seen = new Set();
+                const results = [];
+                const walker = document.createTreeWalker(scope, NodeFilter.SHOW_TEXT);
+                while (walker.nextNode() && results.length < {max}) {{
+                    const text = walker.currentNode.textContent.trim();
+                    const el = walker.currentNode.parentElement;
+                    if (!text || !el || seen.has(el)) continue;
+                    seen.add(el);
+                    if (['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE'].includes(el.tagName)) continue;
+                    const cs = getComputedStyle(el);
+                    if (cs.visibility === 'hidden' || cs.display === 'none' || parseFloat(cs.opacity) === 0) continue;
+                    const rect = el.getBoundingClientRect();
+                    if (!rect.width || !rect.height) continue;
+                    const fg = parse(cs.color);
+                    if (!fg || fg[3] === 0) continue;
+                    
+                    const size = parseFloat(cs.fontSize);
+                    const weight = parseInt(cs.fontWeight) || 400;
+                    const bg = background(el);
+                    if (!bg) el.setAttribute('data-browser-cli-contrast', results.length);
+                    results.push({{
+                        selector: path(el),
+                        text: text.replace(/\s+/g, ' ').slice(0, 40),
+                        fg,
+                        bg,
+                        large: size >= 24 || (size >= 18.66 && weight >= 700),
+                        rect: {{ x: rect.left + scrollX, y: rect.top + scrollY, width: rect.width, height: rect.height }},
+                    }});
+                }}
+                return results;
+            }})()
+            "#,
+            path = ELEMENT_PATH_JS,
+            scope = serde_json::to_string(scope.unwrap_or("body"))?,
+            max = CONTRAST_MAX_ELEMENTS
+        );
+        let collected = self.evaluate_helper(collect_script).await?;
+        let Some(elements) = collected.value().and_then(|v| v.as_array()).cloned() else {
+            return Err(Error::element_not_found(scope.unwrap_or("body")).into());
+        };
         
-        if let Some(sel) = selector {
-            println!("{}", format!("Getting text from: {}", sel).blue());
-            let element = page.find_element(sel).await?;
-            let text = element.inner_text().await?;
-            Ok(text.unwrap_or_default())
-        } else {
-            println!("{}", "Getting page title and URL".blue());
-            let title = page.get_title().await?.unwrap_or_default();
-            let url = page.url().await?;
-            Ok(format!("Title: {}\nURL: {}", title, url.unwrap_or_default()))
-        }
-    }
-
-    pub async fn close(&mut self) -> Result<()> {
-        if let Some(mut browser) = self.browser.take() {
-            println!("{}", "Closing browser...".yellow());
-            browser.close().await?;
-            self.page = None;
+        let rgb = |value: &serde_json::Value| -> Option<[f64; 4]> {
+            let channels = value.as_array()?;
+            Some([channels.first()?.as_f64()?, channels.get(1)?.as_f64()?, channels.get(2)?.as_f64()?, channels.get(3)?.as_f64()?])
+        };
+        let mut sampled = 0;
+        let checking = progress::items(elements.len() as u64, "Checking contrast");
+        for (index, element) in elements.iter().enumerate() {
+            checking.inc(1);
+            let (Some(fg), selector) = (rgb(&element["fg"]), element["selector"].as_str().unwrap_or_default()) else {
+                continue;
+            };
+            let large = element["large"].as_bool().unwrap_or(false);
             
-            // Clean up temporary directory
-            if let Some(temp_dir) = &self.temp_dir {
-                if let Err(e) = std::fs::remove_dir_all(temp_dir) {
-                    eprintln!("Warning: Failed to remove temp directory {}: {}", temp_dir, e);
+            // Worst case over the background: the solid colour, or every sampled pixel behind the text
+            let backgrounds = match rgb(&element["bg"]) {
+                Some(bg) => vec![[bg[0], bg[1], bg[2]]],
+                None if sampled < CONTRAST_MAX_SAMPLED => {
+                    sampled += 1;
+                    self.sample_background(index, &element["rect"]).await.unwrap_or_default()
                 }
+                None => Vec::new(),
+            };
+            if backgrounds.is_empty() {
+                report.add(Severity::Warning, "contrast-unknown", "Background is an image or gradient that couldn't be sampled",
+                    Some(selector.to_string()));
+                continue;
             }
-            self.temp_dir = None;
             
-            println!("{}", "Browser closed".green());
+            let (ratio, bg) = backgrounds.iter()
+                .map(|bg| {
+                    let text = [0, 1, 2].map(|i| fg[i] * fg[3] + bg[i] * (1.0 - fg[3]));
+                    (audit::contrast_ratio(text, *bg), *bg)
+                })
+                .fold((f64::INFINITY, [0.0; 3]), |worst, candidate| if candidate.0 < worst.0 { candidate } else { worst });
+            let (aa, aaa) = audit::contrast_thresholds(large);
+            let message = |needed: f64| format!(
+                "{:.2}:1 (needs {}:1{}) {} on {} \"{}\"",
+                ratio, needed, if large { ", large text" } else { "" },
+                audit::hex_color([fg[0], fg[1], fg[2]]), audit::hex_color(bg), element["text"].as_str().unwrap_or_default()
+            );
+            if ratio < aa {
+                report.add(Severity::Error, "contrast-aa", message(aa), Some(selector.to_string()));
+            } else if ratio < aaa {
+                report.add(Severity::Warning, "contrast-aaa", message(aaa), Some(selector.to_string()));
+            }
         }
-        Ok(())
+        checking.finish_and_clear();
+        
+        self.evaluate_helper("document.querySelectorAll('[data-browser-cli-contrast]').forEach(el => el.removeAttribute('data-browser-cli-contrast'))").await?;
+        Ok((elements.len(), report))
     }
 
-    async fn ensure_initialized(&mut self) -> Result<()> {
-        if self.browser.is_none() {
-            self.init().await?;
-        }
-        Ok(())
+    // Screenshot an element with its text made transparent and return a grid of background pixels
+    async fn sample_background(&self, index: usize, rect: &serde_json::Value) -> Result<Vec<[f64; 3]>> {
+        let target = format!("document.querySelector('[data-browser-cli-contrast=\"{}\"]')", index);
+        self.evaluate_helper(format!(
+            "(el => {{ el.dataset.browserCliColor = el.style.getPropertyValue('color') + '|' + el.style.getPropertyPriority('color'); el.style.setProperty('color', 'transparent', 'important'); }})({})",
+            target
+        )).await?;
+        
+        let clip = Viewport {
+            x: rect["x"].as_f64().unwrap_or_default(),
+            y: rect["y"].as_f64().unwrap_or_default(),
+            width: rect["width"].as_f64().unwrap_or(1.0).max(1.0),
+            height: rect["height"].as_f64().unwrap_or(1.0).max(1.0),
+            scale: 1.0,
+        };
+        let page = self.page.as_ref().unwrap();
+        let params = CaptureScreenshotParams::builder()
+            .format(CaptureScreenshotFormat::Png)
+            .clip(clip)
+            .capture_beyond_viewport(true)
+            .build();
+        let shot = self.cdp("screenshot", page.screenshot(params)).await;
+        
+        self.evaluate_helper(format!(
+            "(el => {{ const [value, priority] = el.dataset.browserCliColor.split('|'); el.style.setProperty('color', value, priority); if (!value) el.style.removeProperty('color'); delete el.dataset.browserCliColor; }})({})",
+            target
+        )).await?;
+        
+        // Decoded in the page: a data: URL image never taints the canvas
+        let sampler = format!(
+            r#"
+            (async () => {{
+                const img = new Image();
+                img.src = 'data:image/png;base64,{png}';
+                await img.decode();
+                const canvas = document.createElement('canvas');
+                canvas.width = img.width;
+                canvas.height = img.height;
+                const ctx = canvas.getContext('2d');
+                ctx.drawImage(img, 0, 0);
+                const data = ctx.getImageData(0, 0, img.width, img.height).data;
+                const step = Math.max(1, Math.floor(Math.sqrt(img.width * img.height / 400)));
+                const pixels = [];
+                for (let y = 0; y < img.height; y += step) {{
+                    for (let x = 0; x < img.width; x += step) {{
+                        const i = (y * img.width + x) * 4;
+                        pixels.push([data[i], data[i + 1], data[i + 2]]);
+                    }}
+                }}
+                return pixels;
+            }})()
+            "#,
+            png = base64::engine::general_purpose::STANDARD.encode(shot?)
+        );
+        Ok(self.evaluate_helper(sampler).await?.into_value().unwrap_or_default())
     }
 
-    fn ensure_page(&self) -> Result<()> {
-        if self.page.is_none() {
-            return Err(anyhow::anyhow!("Browser not initialized"));
+    // Install a named script in every tab so it runs before page scripts in each new document, and run it in
+    // the active tab's current document right away; an existing script of that name is replaced, None removes it
+    async fn set_init_script(&mut self, name: &str, source: Option<String>) -> Result<()> {
+        for tab in self.tabs.clone() {
+            let registered = self.init_scripts.entry(tab.target_id().clone()).or_default().remove(name);
+            if let Some(identifier) = registered {
+                self.cdp("remove_init_script", tab.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(identifier))).await?;
+            }
+            if let Some(source) = &source {
+                let added = self.cdp("add_init_script", tab.execute(AddScriptToEvaluateOnNewDocumentParams::new(source.clone()))).await?;
+                self.init_scripts.entry(tab.target_id().clone()).or_default()
+                    .insert(name.to_string(), added.result.identifier.clone());
+            }
+        }
+        self.init_sources.remove(name);
+        if let Some(source) = source {
+            self.init_sources.insert(name.to_string(), source.clone());
+            self.evaluate(source).await?;
         }
         Ok(())
     }
 
-    pub fn is_initialized(&self) -> bool {
-        self.browser.is_some() && self.page.is_some()
+    // Give a tab opened mid-session the init scripts and extra headers the others already have
+    async fn catch_up_tab(&mut self, page: &Page) -> Result<()> {
+        let mut registered = HashMap::new();
+        for (name, source) in &self.init_sources {
+            let added = self.cdp("add_init_script", page.execute(AddScriptToEvaluateOnNewDocumentParams::new(source.clone()))).await?;
+            registered.insert(name.clone(), added.result.identifier.clone());
+        }
+        self.init_scripts.insert(page.target_id().clone(), registered);
+        self.send_extra_headers(page).await
     }
 
-    pub async fn execute_javascript(&self, code: &str) -> Result<()> {
-        self.ensure_page()?;
-        
-        let page = self.page.as_ref().unwrap();
-        let result = page.evaluate(code).await?;
-        
-        if let Some(value) = result.value() {
-            println!("{}", serde_json::to_string_pretty(value)?);
+    // Extra headers replace the previous set, so always send every header applied so far
+    async fn send_extra_headers(&self, page: &Page) -> Result<()> {
+        if !self.extra_headers.is_empty() {
+            let headers = serde_json::to_value(&self.extra_headers)?;
+            self.cdp("set_extra_headers", page.execute(SetExtraHttpHeadersParams::new(Headers::new(headers)))).await?;
         }
-        
         Ok(())
     }
 
@@ -266,7 +4805,7 @@ impl BrowserController {
         self.ensure_page()?;
         
         let page = self.page.as_ref().unwrap();
-        let url = page.url().await?;
+        let url = self.cdp("url", page.url()).await?;
         Ok(url.unwrap_or_default())
     }
 
@@ -274,61 +4813,82 @@ impl BrowserController {
         self.ensure_page()?;
         
         let page = self.page.as_ref().unwrap();
-        let title = page.get_title().await?;
+        let title = self.cdp("get_title", page.get_title()).await?;
         Ok(title.unwrap_or_default())
     }
 
     pub async fn reload(&self) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", "Reloading page...".blue());
+        progress!("{}", "Reloading page...".blue());
         
         let page = self.page.as_ref().unwrap();
-        page.reload().await?;
+        self.cdp("reload", page.reload()).await?;
         
-        println!("{}", "Page reloaded".green());
+        status!("{}", "Page reloaded".green());
         Ok(())
     }
 
     pub async fn go_back(&self) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", "Going back...".blue());
+        progress!("{}", "Going back...".blue());
         
-        let page = self.page.as_ref().unwrap();
-        page.evaluate("window.history.back()").await?;
+        self.evaluate("window.history.back()").await?;
         
-        println!("{}", "Navigated back".green());
+        status!("{}", "Navigated back".green());
         Ok(())
     }
 
     pub async fn go_forward(&self) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", "Going forward...".blue());
+        progress!("{}", "Going forward...".blue());
         
-        let page = self.page.as_ref().unwrap();
-        page.evaluate("window.history.forward()").await?;
+        self.evaluate("window.history.forward()").await?;
         
-        println!("{}", "Navigated forward".green());
+        status!("{}", "Navigated forward".green());
         Ok(())
     }
 
-    pub async fn click_at_coordinates(&self, x: f64, y: f64) -> Result<()> {
+    // Screenshot pixels per CSS pixel, for turning positions found on a screenshot into input coordinates.
+    // With the screenshot at hand it's the PNG's width over the viewport's (as recorded in the capture when
+    // it has metadata), which also covers zoom and device metrics changed since; otherwise devicePixelRatio,
+    // what a capture now would use.
+    pub async fn screenshot_scale(&self, screenshot: Option<&std::path::Path>) -> Result<f64> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
+        let metrics = self.evaluate("({ dpr: window.devicePixelRatio, width: window.innerWidth })").await?;
+        let metrics = metrics.value().cloned().unwrap_or_default();
+        let dpr = metrics.get("dpr").and_then(|v| v.as_f64()).filter(|dpr| *dpr > 0.0).unwrap_or(1.0);
+        let Some(path) = screenshot else {
+            return Ok(dpr);
+        };
+        
+        let width = capture::png_width(path)?;
+        if let Some(recorded) = capture::read(path).ok().filter(|meta| meta.viewport_width > 0) {
+            return Ok(width as f64 / recorded.viewport_width as f64);
+        }
+        let viewport = metrics.get("width").and_then(|v| v.as_f64()).filter(|w| *w > 0.0)
+            .ok_or_else(|| anyhow::anyhow!("Couldn't read the viewport width"))?;
+        Ok(width as f64 / viewport)
+    }
+
+    pub async fn click_at_coordinates(&self, x: f64, y: f64) -> Result<()> {
+        self.ensure_page()?;
         
         // Perform click sequence
         let move_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .r#type(DispatchMouseEventType::MouseMoved)
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse move command: {}", e))?;
-        page.execute(move_cmd).await?;
+        self.execute(move_cmd).await?;
         
         let down_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .button(MouseButton::Left)
@@ -336,9 +4896,10 @@ impl BrowserController {
             .click_count(1)
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse down command: {}", e))?;
-        page.execute(down_cmd).await?;
+        self.execute(down_cmd).await?;
         
         let up_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .button(MouseButton::Left)
@@ -346,31 +4907,161 @@ impl BrowserController {
             .click_count(1)
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse up command: {}", e))?;
-        page.execute(up_cmd).await?;
+        self.execute(up_cmd).await?;
         
-        println!("{} Clicked: ({}, {})", "✓".green(), x, y);
+        status!("{} Clicked: ({}, {})", "✓".green(), x, y);
         Ok(())
     }
 
-    pub async fn double_click_at_coordinates(&self, x: f64, y: f64) -> Result<()> {
+    // Press the left button, keep it down for the duration, then release (long-press affordances)
+    pub async fn press_hold_at(&self, x: f64, y: f64, duration: Duration) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", format!("Double-clicking at coordinates: ({}, {})", x, y).blue());
+        for (event_type, button) in [
+            (DispatchMouseEventType::MouseMoved, None),
+            (DispatchMouseEventType::MousePressed, Some(MouseButton::Left)),
+        ] {
+            let mut event = DispatchMouseEventParams::builder()
+                .modifiers(self.modifiers())
+                .x(x)
+                .y(y)
+                .r#type(event_type);
+            if let Some(button) = button {
+                event = event.button(button).click_count(1);
+            }
+            self.execute(event.build().map_err(|e| anyhow::anyhow!("Failed to build mouse command: {}", e))?).await?;
+        }
+        
+        sleep(duration).await;
         
-        let page = self.page.as_ref().unwrap();
+        let up_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
+            .x(x)
+            .y(y)
+            .button(MouseButton::Left)
+            .r#type(DispatchMouseEventType::MouseReleased)
+            .click_count(1)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build mouse up command: {}", e))?;
+        self.execute(up_cmd).await?;
+        
+        status!("{} Held ({}, {}) for {}ms", "✓".green(), x, y, duration.as_millis());
+        Ok(())
+    }
+
+    pub async fn press_hold(&self, selector: &str, duration: Duration) -> Result<()> {
+        self.ensure_page()?;
+        
+        let element = self.find_element(selector).await?;
+        self.cdp("scroll_into_view", element.scroll_into_view()).await?;
+        let point = self.cdp("clickable_point", element.clickable_point()).await?;
+        self.press_hold_at(point.x, point.y, duration).await
+    }
+
+    // Move the mouse over a point without pressing anything, so :hover styles, tooltips and hover menus open
+    pub async fn hover_at(&self, x: f64, y: f64) -> Result<()> {
+        self.ensure_page()?;
+        
+        self.move_mouse(x, y).await?;
+        
+        status!("{} Hovering: ({}, {})", "✓".green(), x, y);
+        Ok(())
+    }
+
+    // Hover an element's center, scrolled into view first
+    pub async fn hover(&self, selector: &str) -> Result<()> {
+        self.ensure_page()?;
+        
+        let element = self.find_element(selector).await?;
+        self.cdp("scroll_into_view", element.scroll_into_view()).await?;
+        let point = self.cdp("clickable_point", element.clickable_point()).await?;
+        self.move_mouse(point.x, point.y).await?;
+        
+        status!("{} Hovering: {}", "✓".green(), selector);
+        Ok(())
+    }
+
+    // Press at one point, move to the other in `steps` evenly spaced moves with the button held, then release
+    // there: sortable lists, sliders and drag handles see a real pointer drag
+    pub async fn drag_at(&self, from: (f64, f64), to: (f64, f64), steps: u32) -> Result<()> {
+        self.ensure_page()?;
+        
+        let steps = steps.max(1);
+        self.move_mouse(from.0, from.1).await?;
+        self.drag_event(DispatchMouseEventType::MousePressed, from).await?;
+        for step in 1..=steps {
+            let progress = step as f64 / steps as f64;
+            let point = (from.0 + (to.0 - from.0) * progress, from.1 + (to.1 - from.1) * progress);
+            self.drag_event(DispatchMouseEventType::MouseMoved, point).await?;
+            sleep(Duration::from_millis(DRAG_STEP_MS)).await;
+        }
+        self.drag_event(DispatchMouseEventType::MouseReleased, to).await?;
+        
+        status!("{} Dragged ({}, {}) → ({}, {}) in {} steps", "✓".green(), from.0, from.1, to.0, to.1, steps);
+        Ok(())
+    }
+
+    // Drag from the center of one element to the center of another
+    pub async fn drag(&self, from: &str, to: &str, steps: u32) -> Result<()> {
+        self.ensure_page()?;
+        
+        let source = self.find_element(from).await?;
+        self.cdp("scroll_into_view", source.scroll_into_view()).await?;
+        let start = self.cdp("clickable_point", source.clickable_point()).await?;
+        // The target is measured after the source is in view, since scrolling moves it too
+        let target = self.find_element(to).await?;
+        let end = self.cdp("clickable_point", target.clickable_point()).await?;
+        self.drag_at((start.x, start.y), (end.x, end.y), steps).await
+    }
+
+    // Mouse event with the left button down (pressed, moved while held) or just let go (released)
+    async fn drag_event(&self, kind: DispatchMouseEventType, (x, y): (f64, f64)) -> Result<()> {
+        let buttons = if kind == DispatchMouseEventType::MouseReleased { 0 } else { 1 };
+        let event = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
+            .x(x)
+            .y(y)
+            .button(MouseButton::Left)
+            .buttons(buttons)
+            .r#type(kind.clone())
+            .click_count(if kind == DispatchMouseEventType::MouseMoved { 0 } else { 1 })
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build mouse command: {}", e))?;
+        self.execute(event).await?;
+        Ok(())
+    }
+
+    async fn move_mouse(&self, x: f64, y: f64) -> Result<()> {
+        let move_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
+            .x(x)
+            .y(y)
+            .r#type(DispatchMouseEventType::MouseMoved)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build mouse move command: {}", e))?;
+        self.execute(move_cmd).await?;
+        Ok(())
+    }
+
+    pub async fn double_click_at_coordinates(&self, x: f64, y: f64) -> Result<()> {
+        self.ensure_page()?;
+        
+        progress!("{}", format!("Double-clicking at coordinates: ({}, {})", x, y).blue());
         
         // Move mouse to coordinates
         let move_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .r#type(DispatchMouseEventType::MouseMoved)
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse move command: {}", e))?;
         
-        page.execute(move_cmd).await?;
+        self.execute(move_cmd).await?;
         
         // Double click (mouse down with click_count=2)
         let down_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .button(MouseButton::Left)
@@ -379,10 +5070,11 @@ impl BrowserController {
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse down command: {}", e))?;
         
-        page.execute(down_cmd).await?;
+        self.execute(down_cmd).await?;
         
         // Mouse up with click_count=2
         let up_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .button(MouseButton::Left)
@@ -391,31 +5083,31 @@ impl BrowserController {
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse up command: {}", e))?;
         
-        page.execute(up_cmd).await?;
+        self.execute(up_cmd).await?;
         
-        println!("{}", format!("Double-clicked at ({}, {})", x, y).green());
+        status!("{}", format!("Double-clicked at ({}, {})", x, y).green());
         Ok(())
     }
 
     pub async fn right_click_at_coordinates(&self, x: f64, y: f64) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", format!("Right-clicking at coordinates: ({}, {})", x, y).blue());
-        
-        let page = self.page.as_ref().unwrap();
+        progress!("{}", format!("Right-clicking at coordinates: ({}, {})", x, y).blue());
         
         // Move mouse to coordinates
         let move_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .r#type(DispatchMouseEventType::MouseMoved)
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse move command: {}", e))?;
         
-        page.execute(move_cmd).await?;
+        self.execute(move_cmd).await?;
         
         // Right click (mouse down)
         let down_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .button(MouseButton::Right)
@@ -424,10 +5116,11 @@ impl BrowserController {
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse down command: {}", e))?;
         
-        page.execute(down_cmd).await?;
+        self.execute(down_cmd).await?;
         
         // Mouse up
         let up_cmd = DispatchMouseEventParams::builder()
+            .modifiers(self.modifiers())
             .x(x)
             .y(y)
             .button(MouseButton::Right)
@@ -436,86 +5129,285 @@ impl BrowserController {
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build mouse up command: {}", e))?;
         
-        page.execute(up_cmd).await?;
+        self.execute(up_cmd).await?;
         
-        println!("{}", format!("Right-clicked at ({}, {})", x, y).green());
+        status!("{}", format!("Right-clicked at ({}, {})", x, y).green());
         Ok(())
     }
 
-    pub async fn wait_for_selector(&self, selector: &str, timeout_secs: Option<u64>) -> Result<()> {
+    pub async fn wait_for_selector(&self, selector: &str, timeout_secs: Option<u64>, condition: &WaitCondition) -> Result<()> {
         self.ensure_page()?;
         
         let timeout = timeout_secs.unwrap_or(10);
-        println!("{}", format!("Waiting for selector '{}' (timeout: {}s)", selector, timeout).blue());
+        if condition.is_presence() {
+            progress!("{}", format!("Waiting for selector '{}' (timeout: {}s)", selector, timeout).blue());
+        } else {
+            progress!("{}", format!("Waiting for selector '{}' to be {} (timeout: {}s)", selector, condition.describe(), timeout).blue());
+        }
         
-        let page = self.page.as_ref().unwrap();
-        let start = std::time::Instant::now();
+        // Opacity, covering and scrolling changes don't always show up as mutations, so state conditions also re-check on a timer
+        let check = Self::condition_check_js(selector, condition)?;
+        match self.observe_until(&check, Wake::Mutations { poll: !condition.is_presence() }, Duration::from_secs(timeout)).await? {
+            None => {
+                status!("{}", format!("Element '{}' found", selector).green());
+                Ok(())
+            }
+            Some(state) => Err(Error::Timeout { what: format!("selector: '{}'", selector), secs: timeout, detail: format!(" ({})", state) }.into()),
+        }
+    }
+
+    // JS function returning the first condition the element doesn't satisfy yet, or null once it's ready
+    fn condition_check_js(selector: &str, condition: &WaitCondition) -> Result<String> {
+        let (attr_name, attr_value) = match &condition.attr {
+            Some((name, value)) => (Some(name.as_str()), Some(value.as_str())),
+            None => (None, None),
+        };
+        Ok(format!(
+            r#"() => {{
+                const el = document.querySelector({selector});
+                if (!el) return 'not found';
+                const wantVisible = {visible} || {clickable};
+                const wantEnabled = {enabled} || {clickable};
+                if (wantVisible) {{
+                    if (!el.getClientRects().length) return 'not rendered';
+                    const style = getComputedStyle(el);
+                    if (style.visibility === 'hidden' || style.visibility === 'collapse') return 'visibility hidden';
+                    for (let node = el; node; node = node.parentElement) {{
+                        if (parseFloat(getComputedStyle(node).opacity) === 0) return 'opacity 0';
+                    }}
+                    const rect = el.getBoundingClientRect();
+                    if (rect.width === 0 || rect.height === 0) return 'zero size';
+                }}
+                if (wantEnabled) {{
+                    if (el.disabled || el.closest('fieldset[disabled]')) return 'disabled';
+                    if (el.getAttribute('aria-disabled') === 'true') return 'aria-disabled';
+                }}
+                if ({clickable}) {{
+                    let rect = el.getBoundingClientRect();
+                    if (rect.bottom < 0 || rect.right < 0 || rect.top > innerHeight || rect.left > innerWidth) {{
+                        el.scrollIntoView({{ block: 'center', inline: 'center' }});
+                        rect = el.getBoundingClientRect();
+                    }}
+                    if (getComputedStyle(el).pointerEvents === 'none') return 'pointer-events none';
+                    const hit = document.elementFromPoint(rect.left + rect.width / 2, rect.top + rect.height / 2);
+                    if (!hit || (hit !== el && !el.contains(hit))) {{
+                        return 'covered by ' + (hit ? hit.tagName.toLowerCase() + (hit.id ? '#' + hit.id : '') : 'nothing');
+                    }}
+                }}
+                const attrName = {attr_name};
+                if (attrName !== null) {{
+                    const actual = el.getAttribute(attrName);
+                    if (actual !== {attr_value}) return attrName + '=' + JSON.stringify(actual);
+                }}
+                return null;
+            }}"#,
+            selector = serde_json::to_string(selector)?,
+            visible = condition.visible,
+            enabled = condition.enabled,
+            clickable = condition.clickable,
+            attr_name = serde_json::to_string(&attr_name)?,
+            attr_value = serde_json::to_string(&attr_value)?
+        ))
+    }
+
+    // Run `check` (a JS function returning null when satisfied, otherwise a short state string) inside the page,
+    // re-running it whenever `wake` fires instead of polling over CDP. Returns None once satisfied, or the
+    // last state on timeout. Each evaluation is bounded below the CDP watchdog; navigations just restart it.
+    async fn observe_until(&self, check: &str, wake: Wake, timeout: Duration) -> Result<Option<String>> {
+        let start = Instant::now();
+        let slice = self.cdp_timeout.saturating_sub(Duration::from_secs(1)).clamp(Duration::from_secs(1), OBSERVE_SLICE);
+        let mut last_state = "not found".to_string();
+        let waiting = progress::spinner(format!("Waiting (timeout: {}s)", timeout.as_secs()));
         
-        while start.elapsed().as_secs() < timeout {
-            if let Ok(element) = page.find_element(selector).await {
-                println!("{}", format!("Element '{}' found", selector).green());
-                return Ok(());
+        while start.elapsed() < timeout {
+            waiting.set_message(format!("Waiting: {} (timeout: {}s)", last_state, timeout.as_secs()));
+            let wait_ms = (timeout - start.elapsed()).min(slice).as_millis();
+            let script = format!(
+                r#"
+                new Promise(resolve => {{
+                    const check = {check};
+                    let state = check();
+                    if (state === null) return resolve(null);
+                    let deadline = null;
+                    {wake}
+                    const settle = (value) => {{
+                        disarm();
+                        clearTimeout(deadline);
+                        resolve(value);
+                    }};
+                    const recheck = () => {{
+                        state = check();
+                        if (state === null) settle(null);
+                    }};
+                    arm(recheck);
+                    deadline = setTimeout(() => settle(state), {wait_ms});
+                }})
+                "#,
+                check = check,
+                wake = wake.js(),
+                wait_ms = wait_ms
+            );
+            
+            // The page's own History API calls only go through its own world's history object, so the route
+            // hook goes into the main world; events dispatched on window reach listeners in every world
+            let result = match wake {
+                Wake::Route => match self.evaluate(route_hook_js()).await {
+                    Ok(_) => self.evaluate_helper(script).await,
+                    Err(e) => Err(e),
+                },
+                Wake::Mutations { .. } => self.evaluate_helper(script).await,
+            };
+            match result {
+                Ok(result) => match result.value() {
+                    Some(serde_json::Value::String(state)) => last_state = state.clone(),
+                    _ => {
+                        waiting.finish_and_clear();
+                        return Ok(None);
+                    }
+                },
+                // The document was replaced mid-wait; give the new one a moment to get a context
+                Err(e) if is_stale_context(&e) => {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                Err(e) => {
+                    waiting.finish_and_clear();
+                    return Err(e);
+                }
             }
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
         }
         
-        Err(anyhow::anyhow!("Timeout waiting for selector: '{}' after {} seconds", selector, timeout))
+        waiting.finish_and_clear();
+        Ok(Some(last_state))
     }
 
     pub async fn wait_for_text(&self, text: &str, timeout_secs: Option<u64>) -> Result<()> {
         self.ensure_page()?;
         
         let timeout = timeout_secs.unwrap_or(10);
-        println!("{}", format!("Waiting for text '{}' (timeout: {}s)", text, timeout).blue());
+        progress!("{}", format!("Waiting for text '{}' (timeout: {}s)", text, timeout).blue());
+        
+        let check = format!(
+            "() => document.body && document.body.innerText.includes({}) ? null : 'not found'",
+            serde_json::to_string(text)?
+        );
+        match self.observe_until(&check, Wake::Mutations { poll: false }, Duration::from_secs(timeout)).await? {
+            None => {
+                status!("{}", format!("Text '{}' found", text).green());
+                Ok(())
+            }
+            Some(_) => Err(Error::timeout(format!("text: '{}'", text), timeout).into()),
+        }
+    }
+
+    pub async fn wait_for_navigation(&self, timeout_secs: Option<u64>) -> Result<()> {
+        self.ensure_page()?;
+        
+        let timeout = timeout_secs.unwrap_or(30);
+        progress!("{}", format!("Waiting for navigation to complete (timeout: {}s)", timeout).blue());
         
-        let page = self.page.as_ref().unwrap();
         let start = std::time::Instant::now();
+        let waiting = progress::spinner("Waiting for document.readyState");
         
         while start.elapsed().as_secs() < timeout {
-            let body_text = page.evaluate("document.body.innerText").await?;
-            if let Some(body_content) = body_text.value() {
-                let content_str = body_content.to_string();
-                if content_str.contains(text) {
-                    println!("{}", format!("Text '{}' found", text).green());
+            let ready_state = self.evaluate("document.readyState").await?;
+            if let Some(state) = ready_state.value() {
+                if state == "complete" {
+                    waiting.finish_and_clear();
+                    status!("{}", "Navigation completed".green());
                     return Ok(());
                 }
+                waiting.set_message(format!("Waiting for document.readyState ({})", state.as_str().unwrap_or_default()));
             }
             tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
         }
+        waiting.finish_and_clear();
         
-        Err(anyhow::anyhow!("Timeout waiting for text: '{}' after {} seconds", text, timeout))
+        Err(Error::timeout("navigation", timeout).into())
     }
 
-    pub async fn wait_for_navigation(&self, timeout_secs: Option<u64>) -> Result<()> {
+    // Wait for a client-side route change to a URL matching `pattern` (network::pattern_matches: a substring,
+    // or `*` globs over the whole URL). SPA routers change the URL through the History API without loading a
+    // document, so instead of watching loads this hooks pushState/replaceState in the page and wakes on their
+    // event, popstate and hashchange. Returns the matching URL, at once if it already matches.
+    pub async fn wait_for_route(&self, pattern: &str, timeout_secs: Option<u64>) -> Result<String> {
+        self.ensure_page()?;
+        
+        let timeout = timeout_secs.unwrap_or(30);
+        progress!("{}", format!("Waiting for route '{}' (timeout: {}s)", pattern, timeout).blue());
+        
+        let check = format!("() => {} ? null : location.href", network::pattern_matches_js(pattern, "location.href"));
+        match self.observe_until(&check, Wake::Route, Duration::from_secs(timeout)).await? {
+            None => {
+                let url = self.evaluate_helper("location.href").await?.into_value::<String>().unwrap_or_default();
+                status!("{} Route: {}", "✓".green(), url);
+                Ok(url)
+            }
+            Some(url) => Err(Error::Timeout {
+                what: format!("route '{}'", pattern),
+                secs: timeout,
+                detail: format!(" (still at {})", url),
+            }.into()),
+        }
+    }
+
+    // Wait until no request has been in flight for `idle`. EventSource streams never finish, so they
+    // don't count; a request already running when the wait starts is only noticed when it ends.
+    pub async fn wait_for_network_idle(&self, idle: Duration, timeout_secs: Option<u64>) -> Result<()> {
         self.ensure_page()?;
         
         let timeout = timeout_secs.unwrap_or(30);
-        println!("{}", format!("Waiting for navigation to complete (timeout: {}s)", timeout).blue());
+        progress!("{}", format!("Waiting for {}ms without network requests (timeout: {}s)", idle.as_millis(), timeout).blue());
         
         let page = self.page.as_ref().unwrap();
-        let start = std::time::Instant::now();
+        let mut requests = self.cdp("event_listener", page.event_listener::<EventRequestWillBeSent>()).await?;
+        let mut finished = self.cdp("event_listener", page.event_listener::<EventLoadingFinished>()).await?;
+        let mut failed = self.cdp("event_listener", page.event_listener::<EventLoadingFailed>()).await?;
         
-        while start.elapsed().as_secs() < timeout {
-            let ready_state = page.evaluate("document.readyState").await?;
-            if let Some(state) = ready_state.value() {
-                if state == "complete" {
-                    println!("{}", "Navigation completed".green());
+        let waiting = progress::spinner("Waiting for network idle");
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout);
+        let mut in_flight = HashSet::new();
+        let mut last_activity = tokio::time::Instant::now();
+        loop {
+            tokio::select! {
+                Some(event) = requests.next() => {
+                    // A redirect reuses the request id, so it stays a single entry
+                    if event.r#type != Some(ResourceType::EventSource) {
+                        in_flight.insert(event.request_id.inner().clone());
+                    }
+                    last_activity = tokio::time::Instant::now();
+                }
+                Some(event) = finished.next() => {
+                    in_flight.remove(event.request_id.inner());
+                    last_activity = tokio::time::Instant::now();
+                }
+                Some(event) = failed.next() => {
+                    in_flight.remove(event.request_id.inner());
+                    last_activity = tokio::time::Instant::now();
+                }
+                _ = tokio::time::sleep_until(last_activity + idle), if in_flight.is_empty() => {
+                    waiting.finish_and_clear();
+                    status!("{}", "Network idle".green());
                     return Ok(());
                 }
+                _ = tokio::time::sleep_until(deadline) => break,
             }
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+            waiting.set_message(format!("Waiting for network idle ({} in flight)", in_flight.len()));
         }
+        waiting.finish_and_clear();
         
-        Err(anyhow::anyhow!("Timeout waiting for navigation after {} seconds", timeout))
+        Err(Error::Timeout {
+            what: "network idle".to_string(),
+            secs: timeout,
+            detail: format!(" ({} requests still in flight)", in_flight.len()),
+        }.into())
     }
 
     pub async fn highlight_element(&self, selector: &str) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", format!("Highlighting element: {}", selector).blue());
+        progress!("{}", format!("Highlighting element: {}", selector).blue());
         
-        let page = self.page.as_ref().unwrap();
-        let element = page.find_element(selector).await?;
+        self.find_element(selector).await?;
         
         // Add temporary highlight border
         let highlight_script = format!(
@@ -537,12 +5429,12 @@ impl BrowserController {
             selector
         );
         
-        let result = page.evaluate(highlight_script).await?;
+        let result = self.evaluate_helper(highlight_script).await?;
         if let Some(found) = result.value() {
             if found.as_bool().unwrap_or(false) {
-                println!("{}", format!("Highlighted element: {}", selector).green());
+                status!("{}", format!("Highlighted element: {}", selector).green());
             } else {
-                return Err(anyhow::anyhow!("Element not found: {}", selector));
+                return Err(Error::element_not_found(selector).into());
             }
         }
         
@@ -553,7 +5445,7 @@ impl BrowserController {
         self.ensure_page()?;
         
         let page = self.page.as_ref().unwrap();
-        let cookies = page.get_cookies().await?;
+        let cookies = self.cdp("get_cookies", page.get_cookies()).await?;
         
         let cookie_json = serde_json::to_string_pretty(&cookies)?;
         Ok(cookie_json)
@@ -562,8 +5454,7 @@ impl BrowserController {
     pub async fn get_local_storage(&self) -> Result<String> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
-        let local_storage = page.evaluate("JSON.stringify(Object.entries(localStorage))").await?;
+        let local_storage = self.evaluate("JSON.stringify(Object.entries(localStorage))").await?;
         
         if let Some(storage_data) = local_storage.value() {
             Ok(storage_data.to_string())
@@ -575,8 +5466,7 @@ impl BrowserController {
     pub async fn get_session_storage(&self) -> Result<String> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
-        let session_storage = page.evaluate("JSON.stringify(Object.entries(sessionStorage))").await?;
+        let session_storage = self.evaluate("JSON.stringify(Object.entries(sessionStorage))").await?;
         
         if let Some(storage_data) = session_storage.value() {
             Ok(storage_data.to_string())
@@ -588,12 +5478,11 @@ impl BrowserController {
     pub async fn clear_cookies(&self) -> Result<()> {
         self.ensure_page()?;
         
-        println!("{}", "Clearing all cookies...".blue());
+        progress!("{}", "Clearing all cookies...".blue());
         
-        let page = self.page.as_ref().unwrap();
-        page.evaluate("document.cookie.split(';').forEach(cookie => { document.cookie = cookie.replace(/^ +/, '').replace(/=.*/, '=;expires=' + new Date().toUTCString() + ';path=/'); });").await?;
+        self.evaluate("document.cookie.split(';').forEach(cookie => { document.cookie = cookie.replace(/^ +/, '').replace(/=.*/, '=;expires=' + new Date().toUTCString() + ';path=/'); });").await?;
         
-        println!("{}", "Cookies cleared".green());
+        status!("{}", "Cookies cleared".green());
         Ok(())
     }
 
@@ -601,37 +5490,106 @@ impl BrowserController {
         self.ensure_page()?;
         
         let page = self.page.as_ref().unwrap();
-        let current_url = page.url().await?;
+        let current_url = self.cdp("url", page.url()).await?;
         let default_domain = "".to_string();
         let current_domain = current_url.as_ref().unwrap_or(&default_domain);
         
         let domain_str = domain.unwrap_or(current_domain);
         
-        println!("{}", format!("Setting cookie: {}={} for domain: {}", name, value, domain_str).blue());
+        progress!("{}", format!("Setting cookie: {}={} for domain: {}", name, value, domain_str).blue());
         
-        page.evaluate(format!(
+        self.evaluate(format!(
             "document.cookie = '{}={};domain={};path=/;'",
             name, value, domain_str
         )).await?;
         
-        println!("{}", format!("Cookie set: {}={}", name, value).green());
+        status!("{}", format!("Cookie set: {}={}", name, value).green());
+        Ok(())
+    }
+
+    // Force experiment variants using the project's configured conventions (cookie, localStorage, header)
+    pub async fn apply_flags(&mut self, assignments: &[(String, String)]) -> Result<()> {
+        self.ensure_page()?;
+        
+        let url = self.get_url().await?;
+        let is_web_page = url.starts_with("http://") || url.starts_with("https://");
+        
+        for (name, value) in assignments {
+            let preset = self.flag_presets.get(name).cloned().ok_or_else(|| {
+                if self.flag_presets.is_empty() {
+                    anyhow::anyhow!("No flags configured; add a [flags.{}] section to browser-cli.toml", name)
+                } else {
+                    let known = self.flag_presets.keys().cloned().collect::<Vec<_>>().join(", ");
+                    anyhow::anyhow!("Unknown flag '{}'. Configured flags: {}", name, known)
+                }
+            })?;
+            if preset.cookie.is_none() && preset.local_storage.is_none() && preset.header.is_none() {
+                return Err(anyhow::anyhow!("Flag '{}' has no cookie, local_storage or header configured", name));
+            }
+            
+            let stored = preset.format.as_deref().unwrap_or("{value}").replace("{value}", value);
+            let mut applied = Vec::new();
+            
+            if let Some(cookie_name) = &preset.cookie {
+                let mut cookie = CookieParam::new(cookie_name.clone(), stored.clone());
+                cookie.path = Some("/".to_string());
+                match &preset.domain {
+                    Some(domain) => cookie.domain = Some(domain.clone()),
+                    None if is_web_page => cookie.url = Some(url.clone()),
+                    None => return Err(anyhow::anyhow!("Navigate to the site first or set a domain for flag '{}'", name)),
+                }
+                let page = self.page.as_ref().unwrap();
+                self.cdp("set_cookie", page.set_cookie(cookie)).await?;
+                applied.push(format!("cookie {}", cookie_name));
+            }
+            
+            if let Some(key) = &preset.local_storage {
+                if !is_web_page {
+                    return Err(anyhow::anyhow!("Navigate to the site first to set localStorage for flag '{}'", name));
+                }
+                self.evaluate(format!(
+                    "localStorage.setItem({}, {})",
+                    serde_json::to_string(key)?,
+                    serde_json::to_string(&stored)?
+                )).await?;
+                applied.push(format!("localStorage {}", key));
+            }
+            
+            if let Some(header) = &preset.header {
+                self.extra_headers.insert(header.clone(), stored.clone());
+                applied.push(format!("header {}", header));
+            }
+            
+            status!("{} Flag {}={} ({})", "🚩".cyan(), name, value, applied.join(", "));
+        }
+        
+        for tab in &self.tabs {
+            self.send_extra_headers(tab).await?;
+        }
+        
+        status!("{} Reload or navigate to land in the forced variants", "💡".yellow());
         Ok(())
     }
 
     // Get concise page information for AI/agents
     pub async fn get_concise_page_info(&self) -> Result<String> {
+        Ok(self.page_summary().await?.to_string())
+    }
+
+    // Title, URL and interactive element counts of the current page
+    pub async fn page_summary(&self) -> Result<PageSummary> {
         self.ensure_page()?;
         
         let page = self.page.as_ref().unwrap();
         
         // Get essential info only
-        let title = page.get_title().await?.unwrap_or("Unknown".to_string());
-        let url = page.url().await?.unwrap_or("Unknown".to_string());
+        let title = self.cdp("get_title", page.get_title()).await?.unwrap_or("Unknown".to_string());
+        let url = self.cdp("url", page.url()).await?.unwrap_or("Unknown".to_string());
         
         // Count key interactive elements only
-        let element_counts = page.evaluate(
+        let element_counts = self.evaluate_helper(
             r#"
-            JSON.stringify({
+            ({
                 inputs: document.querySelectorAll('input:not([type="hidden"]), textarea, select').length,
                 buttons: document.querySelectorAll('button, input[type="submit"], input[type="button"]').length,
                 links: document.querySelectorAll('a[href]').length
@@ -639,24 +5597,83 @@ impl BrowserController {
             "#
         ).await?;
         
-        let mut info = format!("{} | {}", 
-            title.chars().take(40).collect::<String>(),
-            url.replace("https://", "").replace("http://", "")
-        );
+        let count = |key: &str| element_counts.value().and_then(|counts| counts.get(key)).and_then(|v| v.as_u64()).unwrap_or(0);
+        Ok(PageSummary {
+            inputs: count("inputs"),
+            buttons: count("buttons"),
+            links: count("links"),
+            title,
+            url,
+        })
+    }
+
+    // The page's rel=canonical URL, when it names one
+    pub async fn canonical_url(&self) -> Result<Option<String>> {
+        self.ensure_page()?;
         
-        if let Some(counts) = element_counts.value() {
-            if let Ok(parsed) = serde_json::from_value::<serde_json::Map<String, serde_json::Value>>(counts.clone()) {
-                let inputs = parsed.get("inputs").and_then(|v| v.as_u64()).unwrap_or(0);
-                let buttons = parsed.get("buttons").and_then(|v| v.as_u64()).unwrap_or(0);
-                let links = parsed.get("links").and_then(|v| v.as_u64()).unwrap_or(0);
-                
-                if inputs > 0 || buttons > 0 || links > 0 {
-                    info.push_str(&format!(" | i:{} b:{} l:{}", inputs, buttons, links));
-                }
-            }
-        }
+        let canonical = self.evaluate_helper(
+            r#"(document.querySelector('link[rel~="canonical" i][href]') || {}).href || null"#
+        ).await?;
+        Ok(canonical.value().and_then(|value| value.as_str()).map(str::to_string))
+    }
+
+    // Structured counterpart of get_concise_page_info for agents that parse rather than read
+    pub async fn page_details(&self) -> Result<PageDetails> {
+        let summary = self.page_summary().await?;
         
-        Ok(info)
+        let landmarks = self.evaluate_helper(format!(
+            r#"
+            (() => {{
+                const refOf = {ref_of};
+                const roles = [
+                    ['banner', 'header, [role="banner"]'],
+                    ['navigation', 'nav, [role="navigation"]'],
+                    ['search', '[role="search"], search'],
+                    ['main', 'main, [role="main"]'],
+                    ['complementary', 'aside, [role="complementary"]'],
+                    ['form', 'form[aria-label], form[aria-labelledby], [role="form"]'],
+                    ['region', 'section[aria-label], section[aria-labelledby], [role="region"]'],
+                    ['contentinfo', 'footer, [role="contentinfo"]'],
+                ];
+                const labelOf = el => {{
+                    const labelledBy = el.getAttribute('aria-labelledby');
+                    const byId = labelledBy && labelledBy.split(/\s+/).map(id => document.getElementById(id)).filter(Boolean)
+                        .map(node => node.textContent.trim()).join(' ');
+                    const heading = el.querySelector('h1, h2, h3, h4, h5, h6');
+                    return (el.getAttribute('aria-label') || byId || (heading && heading.textContent) || '').trim().replace(/\s+/g, ' ').slice(0, 60);
+                }};
+                const seen = new Set();
+                const found = [];
+                for (const [role, selector] of roles) {{
+                    for (const el of document.querySelectorAll(selector)) {{
+                        // header/footer inside articles and sections aren't page landmarks
+                        if (seen.has(el) || (!el.getAttribute('role') && ['header', 'footer'].includes(el.localName) && el.closest('article, aside, main, nav, section'))) continue;
+                        if (!el.getClientRects().length) continue;
+                        seen.add(el);
+                        const ref = refOf(el);
+                        const item = {{ ref, selector: `[data-bcref="${{ref}}"]`, role }};
+                        const label = labelOf(el);
+                        if (label) item.label = label;
+                        found.push(item);
+                    }}
+                }}
+                return found.slice(0, {max});
+            }})()
+            "#,
+            ref_of = ELEMENT_REF_JS,
+            max = PAGE_DETAILS_MAX_LANDMARKS
+        )).await?.into_value::<Vec<serde_json::Value>>().unwrap_or_default();
+        
+        let listed = self.interactive_elements(&ElementQuery { limit: Some(PAGE_DETAILS_MAX_ELEMENTS), ..Default::default() }).await?;
+        let elements: Vec<serde_json::Value> = listed.get("elements").and_then(|e| e.as_array()).cloned().unwrap_or_default();
+        let total = listed.get("total").and_then(|t| t.as_u64()).unwrap_or(0);
+        
+        Ok(PageDetails {
+            summary,
+            landmarks,
+            more_elements: total.saturating_sub(elements.len() as u64),
+            elements,
+        })
     }
 
     // Helper function to convert URL to route for screenshot naming
@@ -703,6 +5720,52 @@ impl BrowserController {
         }
     }
 
+    // Versions of the CLI and the connected browser (Browser.getVersion), plus the session features in effect
+    pub async fn about(&self) -> Result<AboutInfo> {
+        let browser = self.browser.as_ref().ok_or(Error::NotInitialized)?;
+        let version = self.cdp("version", browser.version()).await?;
+        let executable = match self.attached {
+            Some(_) => None,
+            None => chrome::find_executable(),
+        };
+        
+        let mut features = Vec::new();
+        for (enabled, name) in [
+            (self.auto_recover, "auto-recover"),
+            (self.isolated_helpers, "isolated-helpers"),
+            (self.bypass_csp, "bypass-csp"),
+            (self.strict_csp, "strict-csp"),
+            (self.media_emulation.is_active(), "media-emulation"),
+            (self.fake_media.is_some(), "fake-media"),
+            (self.url_policy.is_active(), "url-policy"),
+            (self.intercepting, "interception"),
+            (self.har_path.is_some(), "har-capture"),
+            (self.watching_notifications, "notifications"),
+            (self.watching_titles, "title-watch"),
+            (self.watching_audio, "audio-watch"),
+        ] {
+            if enabled {
+                features.push(name);
+            }
+        }
+        
+        Ok(AboutInfo {
+            cli_version: crate::VERSION.to_string(),
+            product: version.product,
+            revision: version.revision,
+            protocol_version: version.protocol_version,
+            js_version: version.js_version,
+            user_agent: version.user_agent,
+            executable_source: executable.as_ref().map(|(_, source)| source.to_string()),
+            executable: executable.map(|(path, _)| path),
+            daemon_pid: match &self.attached {
+                Some(Attachment::Daemon(info)) => Some(info.pid),
+                _ => None,
+            },
+            features,
+        })
+    }
+
     // Get concise status for AI/agents
     pub async fn get_status(&self) -> Result<String> {
         if !self.is_initialized() {
@@ -713,52 +5776,168 @@ impl BrowserController {
         Ok(page_info)
     }
 
-    // Get key interactive elements for AI/agents (concise)
-    pub async fn get_interactive_elements(&self) -> Result<String> {
+    // Resource usage for `session stats`; deliberately bypasses cdp() so polling doesn't count as activity
+    pub async fn resource_stats(&self) -> Result<SessionStats> {
         self.ensure_page()?;
         
         let page = self.page.as_ref().unwrap();
+        let browser = self.browser.as_ref().unwrap();
         
-        let elements_info = page.evaluate(
-            r#"
-            JSON.stringify({
-                inputs: Array.from(document.querySelectorAll('input:not([type="hidden"]), select, textarea')).filter(el => el.offsetParent !== null).map(el => ({
-                    type: el.type || el.tagName.toLowerCase(),
-                    id: el.id,
-                    name: el.name,
-                    placeholder: el.placeholder
-                })).slice(0, 10),
-                buttons: Array.from(document.querySelectorAll('button, input[type="submit"], input[type="button"]')).filter(el => el.offsetParent !== null).map(el => ({
-                    text: (el.textContent || el.value || '').trim().substring(0, 30),
-                    id: el.id
-                })).slice(0, 8),
-                links: Array.from(document.querySelectorAll('a[href]')).filter(el => el.offsetParent !== null && el.textContent.trim()).map(el => ({
-                    text: el.textContent.trim().substring(0, 30),
-                    href: el.href.substring(0, 50)
-                })).slice(0, 8)
-            })
-            "#
-        ).await?;
+        let metrics = tokio::time::timeout(self.cdp_timeout, page.metrics()).await
+            .map_err(|_| anyhow::anyhow!("Timed out reading performance metrics"))??;
+        let metric = |name: &str| metrics.iter().find(|m| m.name == name).map(|m| m.value).unwrap_or(0.0);
+        
+        // Each session is its own browser process tree, so all of its processes count towards it
+        let cpu_seconds = match tokio::time::timeout(self.cdp_timeout, browser.execute(GetProcessInfoParams::default())).await {
+            Ok(Ok(info)) => info.result.process_info.iter().map(|p| p.cpu_time).sum(),
+            _ => 0.0,
+        };
+        
+        Ok(SessionStats {
+            uptime: self.started_at.map(|t| t.elapsed()).unwrap_or_default(),
+            idle: self.last_activity.lock().unwrap().elapsed(),
+            js_heap_used_mb: metric("JSHeapUsedSize") / 1_048_576.0,
+            js_heap_total_mb: metric("JSHeapTotalSize") / 1_048_576.0,
+            dom_nodes: metric("Nodes") as u64,
+            cpu_seconds,
+        })
+    }
+
+    // Close the browser if the session broke one of its limits, returning why
+    pub async fn enforce_limits(&mut self) -> Option<String> {
+        if !self.is_initialized() {
+            return None;
+        }
         
-        if let Some(elements) = elements_info.value() {
-            Ok(serde_json::to_string_pretty(elements)?)
+        let idle = self.last_activity.lock().unwrap().elapsed();
+        let uptime = self.started_at.map(|t| t.elapsed()).unwrap_or_default();
+        
+        let reason = if self.limits.idle_timeout.is_some_and(|limit| idle > limit) {
+            Some(format!("idle for {}s", idle.as_secs()))
+        } else if self.limits.max_lifetime.is_some_and(|limit| uptime > limit) {
+            Some(format!("exceeded max lifetime after {}s", uptime.as_secs()))
+        } else if let Some(max_mb) = self.limits.max_memory_mb {
+            match self.resource_stats().await {
+                Ok(stats) if stats.js_heap_total_mb > max_mb as f64 => {
+                    Some(format!("JS heap {:.0}MB exceeds {}MB", stats.js_heap_total_mb, max_mb))
+                }
+                _ => None,
+            }
         } else {
-            Ok("No elements found".to_string())
+            None
+        };
+        
+        if reason.is_some() {
+            self.close().await.ok();
+        }
+        reason
+    }
+
+    // Interactive elements in document order with bounding boxes (viewport CSS pixels) and refs.
+    // A ref is stamped on the element as data-bcref, so its `selector` works with every other command.
+    pub async fn get_interactive_elements(&self, query: &ElementQuery) -> Result<String> {
+        match self.interactive_elements(query).await? {
+            serde_json::Value::Null => Ok("No elements found".to_string()),
+            elements => Ok(serde_json::to_string_pretty(&elements)?),
+        }
+    }
+
+    // `{ total, offset, returned, elements: [{ ref, selector, kind, tag, text, bbox, … }] }`
+    pub async fn interactive_elements(&self, query: &ElementQuery) -> Result<serde_json::Value> {
+        self.ensure_page()?;
+        
+        if let Some(kind) = &query.kind {
+            if !["buttons", "links", "inputs"].contains(&kind.as_str()) {
+                return Err(anyhow::anyhow!("Unknown filter '{}' (expected buttons, links or inputs)", kind));
+            }
         }
+        let script = format!(
+            r#"
+            (() => {{
+                const kinds = {{
+                    inputs: 'input:not([type="hidden"]):not([type="submit"]):not([type="button"]):not([type="reset"]), select, textarea',
+                    buttons: 'button, input[type="submit"], input[type="button"], input[type="reset"], [role="button"]',
+                    links: 'a[href]',
+                }};
+                const wanted = {kind} ? [{kind}] : Object.keys(kinds);
+                const kindOf = el => wanted.find(kind => el.matches(kinds[kind]));
+                const needle = {contains};
+                const offset = {offset};
+                const limit = {limit};
+                const scope = {scope};
+                
+                // Why an element can't be seen, or null. offsetParent alone misses fixed elements and
+                // opacity/clip tricks such as screen-reader-only text.
+                const hiddenReason = (el) => {{
+                    if (!el.getClientRects().length) return 'display-none';
+                    const cs = getComputedStyle(el);
+                    if (cs.visibility === 'hidden' || cs.visibility === 'collapse') return 'visibility-hidden';
+                    for (let node = el; node; node = node.parentElement) {{
+                        const style = getComputedStyle(node);
+                        if (parseFloat(style.opacity) === 0) return 'opacity-0';
+                        if (/^rect\(0(px)?,? 0(px)?,? 0(px)?,? 0(px)?\)$/.test(style.clip) || /inset\(50%|inset\(100%/.test(style.clipPath)) return 'clipped';
+                    }}
+                    const rect = el.getBoundingClientRect();
+                    if (rect.width <= 1 && rect.height <= 1) return 'zero-size';
+                    if (rect.right + scrollX <= 0 || rect.bottom + scrollY <= 0) return 'offscreen';
+                    return null;
+                }};
+                const inViewport = (rect) => rect.bottom > 0 && rect.right > 0 && rect.top < innerHeight && rect.left < innerWidth;
+                
+                const matches = Array.from(document.querySelectorAll(wanted.map(kind => kinds[kind]).join(', ')))
+                    .map(el => ({{ el, hidden: hiddenReason(el) }}))
+                    .filter(({{ el, hidden }}) => scope === 'all' || (!hidden && (scope !== 'viewport' || inViewport(el.getBoundingClientRect()))))
+                    .map(({{ el, hidden }}) => {{ el.__bcHidden = hidden; return el; }})
+                    .map(el => ({{ el, text: (el.innerText || el.value || el.getAttribute('aria-label') || '').trim().replace(/\s+/g, ' ') }}))
+                    .filter(({{ el, text }}) => !needle || [text, el.id, el.name, el.placeholder, el.href, el.getAttribute('aria-label'), el.title]
+                        .some(value => value && String(value).toLowerCase().includes(needle)));
+                
+                const refOf = {ref_of};
+                const elements = matches.slice(offset, limit === null ? undefined : offset + limit).map(({{ el, text }}) => {{
+                    const ref = refOf(el);
+                    const rect = el.getBoundingClientRect();
+                    const item = {{ ref, selector: `[data-bcref="${{ref}}"]`, kind: kindOf(el), tag: el.localName }};
+                    if (text) item.text = text.slice(0, 80);
+                    if (el.type && el.localName === 'input') item.type = el.type;
+                    if (el.id) item.id = el.id;
+                    if (el.name) item.name = el.name;
+                    if (el.placeholder) item.placeholder = el.placeholder;
+                    if (el.href) item.href = el.href;
+                    item.bbox = {{ x: Math.round(rect.x), y: Math.round(rect.y), width: Math.round(rect.width), height: Math.round(rect.height) }};
+                    item.in_viewport = !el.__bcHidden && inViewport(rect);
+                    if (el.__bcHidden) item.hidden = el.__bcHidden;
+                    delete el.__bcHidden;
+                    return item;
+                }});
+                return {{ total: matches.length, offset, returned: elements.length, elements }};
+            }})()
+            "#,
+            ref_of = ELEMENT_REF_JS,
+            kind = serde_json::to_string(&query.kind)?,
+            contains = serde_json::to_string(&query.contains.as_ref().map(|c| c.to_lowercase()))?,
+            offset = query.offset,
+            limit = serde_json::to_string(&query.limit)?,
+            scope = serde_json::to_string(match query.scope {
+                ElementScope::Visible => "visible",
+                ElementScope::InViewport => "viewport",
+                ElementScope::All => "all",
+            })?
+        );
+        
+        let elements_info = self.evaluate_helper(script).await?;
+        Ok(elements_info.value().cloned().unwrap_or(serde_json::Value::Null))
     }
 
     // Robust form filling method for tricky forms
     pub async fn fill_form_field(&self, selector: &str, value: &str) -> Result<()> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
-        
         // Multi-step approach to ensure form field is properly filled
         let fill_script = format!(
             r#"
             (function() {{
                 const element = document.querySelector('{}');
-                if (!element) return false;
+                if (!element) return null;
                 
                 // Focus the element first
                 element.focus();
@@ -784,199 +5963,323 @@ impl BrowserController {
             selector, value, value, value
         );
         
-        let result = page.evaluate(fill_script).await?;
+        let result = self.evaluate_helper(fill_script).await?;
         
-        if let Some(success) = result.value() {
-            if success.as_bool().unwrap_or(false) {
-                println!("✓ Filled: {} = {}", selector, value);
+        match result.value().and_then(|success| success.as_bool()) {
+            Some(true) => {
+                status!("✓ Filled: {} = {}", selector, value);
                 Ok(())
-            } else {
-                Err(anyhow::anyhow!("Failed to fill field: {}", selector))
             }
-        } else {
-            Err(anyhow::anyhow!("Field not found: {}", selector))
+            Some(false) => Err(anyhow::anyhow!("Failed to fill field: {}", selector)),
+            None => Err(Error::element_not_found(selector).into()),
         }
     }
 
-    // Submit form with validation bypass if needed
-    pub async fn submit_form(&self, form_selector: Option<&str>) -> Result<()> {
+    // Submit a form the way a user would: click its submit button (running validation and JS handlers),
+    // falling back to requestSubmit()/submit(), then optionally wait for the result to be observable
+    pub async fn submit_form(&self, form_selector: Option<&str>, wait: SubmitWait) -> Result<()> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
-        
-        let submit_script = if let Some(selector) = form_selector {
-            format!(
-                r#"
-                (function() {{
-                    const form = document.querySelector('{}');
-                    if (form) {{
-                        form.submit();
-                        return true;
-                    }}
-                    return false;
-                }})()
-                "#,
-                selector
-            )
-        } else {
+        let submit_script = format!(
             r#"
-            (function() {
-                const form = document.querySelector('form');
-                if (form) {
-                    form.submit();
-                    return true;
-                }
-                return false;
-            })()
-            "#.to_string()
+            (function() {{
+                const form = document.querySelector({});
+                if (!form) return 'missing';
+                const valid = form.checkValidity();
+                const button = Array.from(form.elements).find(el => el.type === 'submit' && !el.disabled);
+                if (button) {{
+                    button.click();
+                    return valid ? 'button' : 'invalid';
+                }}
+                if (!valid) {{
+                    form.reportValidity();
+                    return 'invalid';
+                }}
+                if (typeof form.requestSubmit === 'function') {{
+                    form.requestSubmit();
+                    return 'requestSubmit';
+                }}
+                form.submit();
+                return 'submit';
+            }})()
+            "#,
+            serde_json::to_string(form_selector.unwrap_or("form"))?
+        );
+        
+        // Subscribe before submitting so a fast navigation isn't missed
+        let page = self.page.as_ref().unwrap();
+        let navigations = match wait {
+            SubmitWait::Navigation => Some(self.cdp("event_listener", page.event_listener::<EventFrameNavigated>()).await?),
+            _ => None,
         };
         
-        let result = page.evaluate(submit_script).await?;
+        let result = self.evaluate_helper(submit_script).await?;
+        let method = result.value().and_then(|v| v.as_str()).unwrap_or("missing").to_string();
+        match method.as_str() {
+            "missing" => return Err(anyhow::anyhow!("Form not found: {}", form_selector.unwrap_or("form"))),
+            "invalid" => return Err(anyhow::anyhow!("Form validation failed; the browser refused to submit")),
+            _ => status!("✓ Form submitted (via {})", method),
+        }
         
-        if let Some(success) = result.value() {
-            if success.as_bool().unwrap_or(false) {
-                println!("✓ Form submitted");
-                Ok(())
-            } else {
-                Err(anyhow::anyhow!("Form not found or submission failed"))
+        match (wait, navigations) {
+            (SubmitWait::Selector(selector), _) => self.wait_for_selector(&selector, None, &WaitCondition::default()).await,
+            (SubmitWait::Navigation, Some(mut navigations)) => {
+                let timeout = Duration::from_secs(30);
+                let navigated = tokio::time::timeout(timeout, async {
+                    while let Some(event) = navigations.next().await {
+                        if event.frame.parent_id.is_none() {
+                            return true;
+                        }
+                    }
+                    false
+                }).await.unwrap_or(false);
+                if !navigated {
+                    return Err(anyhow::anyhow!("Form was submitted but no navigation happened within {}s", timeout.as_secs()));
+                }
+                self.wait_for_navigation(Some(timeout.as_secs())).await
             }
-        } else {
-            Err(anyhow::anyhow!("Form submission failed"))
+            _ => Ok(()),
         }
     }
 
-    // Ticker functionality for monitoring page changes
-    pub async fn start_ticker(&self, selector: Option<&str>, interval_secs: u64, max_iterations: Option<u64>) -> Result<()> {
+    // One ticker check: evaluate the monitored state and report changes that cleared the noise filters
+    pub async fn ticker_check(&self, ticker: &mut Ticker) -> Result<TickerCheck> {
         self.ensure_page()?;
         
-        let page = self.page.as_ref().unwrap();
-        let mut previous_state = HashMap::new();
-        let mut iteration = 0;
-        
-        println!("{} Starting ticker ({}s intervals)...", "⏱️".cyan(), interval_secs);
+        let result = self.evaluate_helper(ticker.script.clone()).await?;
+        let Some(serde_json::Value::Object(current)) = result.value() else {
+            return Err(anyhow::anyhow!("Ticker script returned no state"));
+        };
         
-        // Determine what to monitor
-        let monitor_script = if let Some(sel) = selector {
-            format!(
-                r#"
-                JSON.stringify({{
-                    selector: '{}',
-                    count: document.querySelectorAll('{}').length,
-                    text: Array.from(document.querySelectorAll('{}')).map(el => el.textContent.trim()).join(' | '),
-                    timestamp: Date.now()
-                }})
-                "#,
-                sel, sel, sel
-            )
-        } else {
-            r#"
-            JSON.stringify({
-                url: window.location.href,
-                title: document.title,
-                inputs: document.querySelectorAll('input:not([type="hidden"]), textarea').length,
-                buttons: document.querySelectorAll('button, input[type="submit"], input[type="button"]').length,
-                forms: document.querySelectorAll('form').length,
-                timestamp: Date.now()
-            })
-            "#.to_string()
+        let Some(before) = &ticker.reported else {
+            ticker.reported = Some(current.clone());
+            ticker.last_seen = Some(current.clone());
+            return Ok(TickerCheck::Baseline(serde_json::Value::Object(current.clone())));
         };
         
-        loop {
-            // Check if we should stop
-            if let Some(max) = max_iterations {
-                if iteration >= max {
-                    println!("{} Ticker completed {} iterations", "✓".green(), iteration);
-                    break;
-                }
-            }
-            
-            // Get current state
-            match page.evaluate(monitor_script.clone()).await {
-                Ok(result) => {
-                    if let Some(state_json) = result.value() {
-                        if let Ok(state_str) = serde_json::to_string(state_json) {
-                            let current_hash = format!("{:x}", md5::compute(&state_str));
-                            
-                            if let Some(prev_hash) = previous_state.get("hash") {
-                                if prev_hash != &current_hash {
-                                    println!("{} {} Change detected!", 
-                                        "🔄".yellow(), 
-                                        chrono::Utc::now().format("%H:%M:%S")
-                                    );
-                                    
-                                    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&state_str) {
-                                        println!("  {}", parsed.to_string().dimmed());
-                                    }
-                                    
-                                    previous_state.insert("hash".to_string(), current_hash);
-                                } else {
-                                    print!(".");
-                                    std::io::Write::flush(&mut std::io::stdout()).ok();
-                                }
-                            } else {
-                                // First iteration
-                                println!("{} Baseline established", "📊".cyan());
-                                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&state_str) {
-                                    println!("  {}", parsed.to_string().dimmed());
-                                }
-                                previous_state.insert("hash".to_string(), current_hash);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    println!("{} Ticker error: {}", "⚠️".yellow(), e);
-                }
-            }
-            
-            iteration += 1;
-            sleep(Duration::from_secs(interval_secs)).await;
+        let changes: Vec<FieldChange> = field_changes(ticker.selector.as_deref(), before, current).into_iter()
+            .filter(|change| change.size() >= ticker.options.min_change_chars)
+            .collect();
+        // A change only counts once the state has held still for the debounce window
+        if changes.is_empty() {
+            ticker.settling_since = None;
+        } else if ticker.last_seen.as_ref() != Some(current) || ticker.settling_since.is_none() {
+            ticker.settling_since = Some(Instant::now());
         }
+        ticker.last_seen = Some(current.clone());
         
-        Ok(())
+        let settled = ticker.settling_since.is_some_and(|since| since.elapsed() >= ticker.options.debounce);
+        if !settled {
+            return Ok(TickerCheck::Unchanged);
+        }
+        ticker.settling_since = None;
+        ticker.reported = Some(current.clone());
+        Ok(TickerCheck::Changed(changes))
     }
 
-    // Enhanced wait-for with thirtyfour integration for better reliability
-    pub async fn wait_for_element_enhanced(&self, selector: &str, timeout_secs: u64) -> Result<bool> {
+    // Wait for an element in the top document, its open shadow roots and its in-process frames, each frame
+    // scanned in its own context. Cross-origin frames that Chrome runs out of process never show up in the
+    // page's frame tree here, so they aren't searched. Reports where the element was found.
+    pub async fn wait_for_element_enhanced(&self, selector: &str, timeout_secs: u64) -> Result<Option<ElementLocation>> {
         self.ensure_page()?;
+        let page = self.page.as_ref().ok_or(Error::NotInitialized)?;
         
-        let page = self.page.as_ref().unwrap();
         let start_time = std::time::Instant::now();
         let timeout = Duration::from_secs(timeout_secs);
         
-        println!("{} Waiting for element: {} ({}s timeout)", "⏳".yellow(), selector, timeout_secs);
+        status!("{} Waiting for element: {} ({}s timeout)", "⏳".yellow(), selector, timeout_secs);
+        
+        let scan_script = format!(
+            r#"
+            (() => {{
+                const selector = {selector};
+                const path = {path};
+                const search = (root, hosts) => {{
+                    const found = root.querySelector(selector);
+                    if (found) return {{ hosts, path: path(found) }};
+                    for (const el of root.querySelectorAll('*')) {{
+                        if (!el.shadowRoot) continue;
+                        const inner = search(el.shadowRoot, hosts.concat([path(el)]));
+                        if (inner) return inner;
+                    }}
+                    return null;
+                }};
+                return search(document, []);
+            }})()
+            "#,
+            selector = serde_json::to_string(selector)?,
+            path = ELEMENT_PATH_JS
+        );
         
+        let waiting = progress::spinner(format!("Scanning frames and shadow roots for {}", selector));
         while start_time.elapsed() < timeout {
-            // Use chromiumoxide to check for element
-            match page.find_element(selector).await {
-                Ok(_) => {
-                    println!("{} Element found: {}", "✓".green(), selector);
-                    return Ok(true);
-                }
-                Err(_) => {
-                    // Also try with JavaScript evaluation as backup
-                    let check_script = format!(
-                        "document.querySelector('{}') !== null",
-                        selector
-                    );
-                    
-                    if let Ok(result) = page.evaluate(check_script).await {
-                        if let Some(exists) = result.value() {
-                            if exists.as_bool().unwrap_or(false) {
-                                println!("{} Element found (via JS): {}", "✓".green(), selector);
-                                return Ok(true);
-                            }
-                        }
-                    }
-                }
+            let main_frame = self.cdp("mainframe", page.mainframe()).await?;
+            let mut frames = self.cdp("frames", page.frames()).await?;
+            // Top document first; an invalid selector fails there instead of timing out
+            frames.sort_by_key(|frame| Some(frame) != main_frame.as_ref());
+            
+            for frame_id in frames {
+                let is_main = Some(&frame_id) == main_frame.as_ref();
+                let Ok(Some(context_id)) = self.cdp("frame_execution_context", page.frame_execution_context(frame_id.clone())).await else {
+                    continue;
+                };
+                let found = match self.evaluate_in_context(page, &scan_script, Some(context_id)).await {
+                    Ok(result) => result.value().cloned().filter(|v| !v.is_null()),
+                    Err(e) if is_main && !is_stale_context(&e) => return Err(e),
+                    Err(_) => None,
+                };
+                let Some(found) = found else {
+                    continue;
+                };
+                
+                let frame = if is_main {
+                    None
+                } else {
+                    Some(self.cdp("frame_url", page.frame_url(frame_id)).await.ok().flatten().unwrap_or_default())
+                };
+                let location = ElementLocation {
+                    frame,
+                    shadow_hosts: found["hosts"].as_array()
+                        .map(|hosts| hosts.iter().filter_map(|h| h.as_str().map(String::from)).collect())
+                        .unwrap_or_default(),
+                    path: found["path"].as_str().unwrap_or_default().to_string(),
+                };
+                waiting.finish_and_clear();
+                status!("{} Element found: {} ({})", "✓".green(), selector, location.describe());
+                return Ok(Some(location));
             }
             
-            print!(".");
-            std::io::Write::flush(&mut std::io::stdout()).ok();
             sleep(Duration::from_millis(500)).await;
         }
         
-        println!("\n{} Timeout waiting for: {}", "❌".red(), selector);
-        Ok(false)
+        waiting.finish_and_clear();
+        status!("{} Timeout waiting for: {}", "❌".red(), selector);
+        Ok(None)
+    }
+}
+// Fields whose value differs between two ticker states, in the order the current state lists them
+fn field_changes(
+    selector: Option<&str>,
+    before: &serde_json::Map<String, serde_json::Value>,
+    after: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<FieldChange> {
+    let ts = Utc::now();
+    after.iter()
+        .filter(|(field, value)| before.get(*field) != Some(*value))
+        .map(|(field, value)| FieldChange {
+            ts,
+            selector: selector.map(String::from),
+            field: field.clone(),
+            before: before.get(field).cloned().unwrap_or(serde_json::Value::Null),
+            after: value.clone(),
+        })
+        .collect()
+}
+
+// IANA zone for a fixed UTC offset; Etc/GMT zones only cover whole hours and use inverted signs
+fn timezone_for_offset(offset: &FixedOffset) -> Option<String> {
+    let seconds = offset.local_minus_utc();
+    if seconds == 0 {
+        return Some("UTC".to_string());
+    }
+    if seconds % 3600 != 0 {
+        return None;
+    }
+    Some(format!("Etc/GMT{:+}", -seconds / 3600))
+}
+
+// Wraps the page's pushState/replaceState to fire ROUTE_EVENT on window; installs once per document
+fn route_hook_js() -> String {
+    format!(
+        r#"
+        (() => {{
+            if (window.{event}) return;
+            window.{event} = true;
+            for (const name of ['pushState', 'replaceState']) {{
+                const original = history[name];
+                history[name] = function (...args) {{
+                    const result = original.apply(this, args);
+                    window.dispatchEvent(new Event('{event}'));
+                    return result;
+                }};
+            }}
+        }})()
+        "#,
+        event = ROUTE_EVENT
+    )
+}
+
+// Exceptions raised when CSP or Trusted Types refuse something a script does
+fn is_csp_violation(message: &str) -> bool {
+    ["Content Security Policy", "unsafe-eval", "TrustedHTML", "TrustedScript", "Trusted Type"]
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+// The evaluation's execution context went away with its document, as opposed to the script itself failing
+fn is_stale_context(error: &anyhow::Error) -> bool {
+    let message = error.to_string();
+    STALE_CONTEXT_ERRORS.iter().any(|marker| message.contains(marker))
+}
+
+// Failures worth retrying: timeouts and the network errors a flaky connection produces
+fn is_transient(error: &anyhow::Error) -> bool {
+    let message = match error.downcast_ref::<Error>() {
+        Some(Error::Timeout { .. } | Error::CdpTimeout { .. }) => return true,
+        Some(Error::Navigation { reason, .. }) => reason.clone(),
+        Some(_) => return false,
+        None => error.to_string(),
+    };
+    TRANSIENT_NET_ERRORS.iter().any(|code| message.contains(code))
+}
+
+// Whitespace runs collapsed within lines, blank lines collapsed to one, ends trimmed
+fn tidy_text(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines().map(|line| line.split_whitespace().collect::<Vec<_>>().join(" ")) {
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+// JavaScript truthiness of a value returned by value over CDP
+fn is_truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0 && !n.is_nan()),
+        serde_json::Value::String(s) => !s.is_empty(),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => true,
     }
-}
\ No newline at end of file
+}
+
+// Suggested name from `Content-Disposition: attachment; filename="report.csv"`
+fn filename_from_disposition(disposition: &str) -> Option<String> {
+    disposition.split(';')
+        .filter_map(|part| part.trim().strip_prefix("filename="))
+        .map(|name| name.trim_matches('"').to_string())
+        .find(|name| !name.is_empty())
+        .and_then(|name| sanitize_filename(&name))
+}
+
+fn filename_from_url(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let segment = parsed.path_segments()?.rfind(|segment| !segment.is_empty())?;
+    sanitize_filename(segment)
+}
+
+// Server-provided names must not escape the working directory
+fn sanitize_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// Where and how a screenshot was taken, kept with the image so archived captures stay interpretable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureMeta {
+    pub url: String,
+    // RFC 3339
+    pub captured_at: String,
+    pub viewport_width: u32,
+    pub viewport_height: u32,
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    pub device_scale: f64,
+}
+
+impl std::fmt::Display for CaptureMeta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "URL:       {}", self.url)?;
+        writeln!(f, "Captured:  {}", self.captured_at)?;
+        writeln!(f, "Viewport:  {}x{} CSS px at scale {}", self.viewport_width, self.viewport_height, self.device_scale)?;
+        write!(f, "Scroll:    {},{}", self.scroll_x, self.scroll_y)
+    }
+}
+
+// Save a screenshot with its metadata: PNGs carry it in tEXt chunks, anything else gets a sidecar JSON
+pub async fn write(path: &Path, image: Vec<u8>, meta: &CaptureMeta) -> Result<()> {
+    if image.starts_with(PNG_SIGNATURE) {
+        tokio::fs::write(path, embed(image, meta)).await?;
+    } else {
+        tokio::fs::write(path, image).await?;
+        tokio::fs::write(sidecar_path(path), serde_json::to_string_pretty(meta)?).await?;
+    }
+    Ok(())
+}
+
+// Metadata of a screenshot, from its tEXt chunks or its sidecar JSON
+pub fn read(path: &Path) -> Result<CaptureMeta> {
+    let image = std::fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open screenshot {}: {}", path.display(), e))?;
+    if image.starts_with(PNG_SIGNATURE) {
+        if let Some(meta) = from_text_chunks(&image) {
+            return Ok(meta);
+        }
+    }
+    let sidecar = sidecar_path(path);
+    match std::fs::read_to_string(&sidecar) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid screenshot metadata in {}: {}", sidecar.display(), e)),
+        Err(_) => Err(anyhow::anyhow!("{} has no browser-cli capture metadata", path.display())),
+    }
+}
+
+// Pixel width from a PNG's IHDR chunk, without decoding the image
+pub fn png_width(path: &Path) -> Result<u32> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open screenshot {}: {}", path.display(), e))?;
+    let mut header = [0u8; 24];
+    let is_png = std::io::Read::read_exact(&mut file, &mut header).is_ok()
+        && header.starts_with(PNG_SIGNATURE)
+        && &header[12..16] == b"IHDR";
+    if !is_png {
+        return Err(anyhow::anyhow!("{} is not a PNG screenshot", path.display()));
+    }
+    Ok(u32::from_be_bytes([header[16], header[17], header[18], header[19]]))
+}
+
+fn sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".json");
+    PathBuf::from(name)
+}
+
+// Standard keywords where PNG defines one, so other image tools show them too
+fn text_entries(meta: &CaptureMeta) -> Vec<(&'static str, String)> {
+    vec![
+        ("Software", format!("browser-cli {}", crate::VERSION)),
+        ("Source", meta.url.clone()),
+        ("Creation Time", meta.captured_at.clone()),
+        ("Viewport", format!("{}x{}", meta.viewport_width, meta.viewport_height)),
+        ("Scroll", format!("{},{}", meta.scroll_x, meta.scroll_y)),
+        ("Device Scale", meta.device_scale.to_string()),
+    ]
+}
+
+// Insert the tEXt chunks right after IHDR, ahead of the image data
+fn embed(image: Vec<u8>, meta: &CaptureMeta) -> Vec<u8> {
+    let ihdr_end = PNG_SIGNATURE.len() + 8 + 13 + 4;
+    if image.len() < ihdr_end {
+        return image;
+    }
+    let mut out = Vec::with_capacity(image.len() + 512);
+    out.extend_from_slice(&image[..ihdr_end]);
+    for (keyword, text) in text_entries(meta) {
+        let mut data = keyword.as_bytes().to_vec();
+        data.push(0);
+        // tEXt is Latin-1; browsers serialize URLs as ASCII, anything else outside Latin-1 is replaced
+        data.extend(text.chars().map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?')));
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        let chunk_start = out.len();
+        out.extend_from_slice(b"tEXt");
+        out.extend_from_slice(&data);
+        let crc = crc32(&out[chunk_start..]);
+        out.extend_from_slice(&crc.to_be_bytes());
+    }
+    out.extend_from_slice(&image[ihdr_end..]);
+    out
+}
+
+fn from_text_chunks(image: &[u8]) -> Option<CaptureMeta> {
+    let mut entries = std::collections::HashMap::new();
+    let mut offset = PNG_SIGNATURE.len();
+    while offset + 8 <= image.len() {
+        let length = u32::from_be_bytes(image[offset..offset + 4].try_into().ok()?) as usize;
+        let kind = &image[offset + 4..offset + 8];
+        let data = image.get(offset + 8..offset + 8 + length)?;
+        if kind == b"IDAT" || kind == b"IEND" {
+            break;
+        }
+        if kind == b"tEXt" {
+            if let Some(split) = data.iter().position(|b| *b == 0) {
+                let keyword = String::from_utf8_lossy(&data[..split]).to_string();
+                let text: String = data[split + 1..].iter().map(|b| char::from(*b)).collect();
+                entries.insert(keyword, text);
+            }
+        }
+        offset += 12 + length;
+    }
+
+    let (viewport_width, viewport_height) = entries.get("Viewport")?.split_once('x')?;
+    let (scroll_x, scroll_y) = entries.get("Scroll")?.split_once(',')?;
+    Some(CaptureMeta {
+        url: entries.get("Source")?.clone(),
+        captured_at: entries.get("Creation Time")?.clone(),
+        viewport_width: viewport_width.parse().ok()?,
+        viewport_height: viewport_height.parse().ok()?,
+        scroll_x: scroll_x.parse().ok()?,
+        scroll_y: scroll_y.parse().ok()?,
+        device_scale: entries.get("Device Scale").and_then(|scale| scale.parse().ok()).unwrap_or(1.0),
+    })
+}
+
+// CRC-32 as PNG chunks use it (ISO-HDLC polynomial)
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signature, a 3x2 IHDR, an empty IDAT and IEND, each chunk with its CRC
+    fn tiny_png() -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        let mut ihdr = 3u32.to_be_bytes().to_vec();
+        ihdr.extend_from_slice(&2u32.to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+        for (kind, data) in [(&b"IHDR"[..], ihdr), (&b"IDAT"[..], Vec::new()), (&b"IEND"[..], Vec::new())] {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            let start = png.len();
+            png.extend_from_slice(kind);
+            png.extend_from_slice(&data);
+            let crc = crc32(&png[start..]);
+            png.extend_from_slice(&crc.to_be_bytes());
+        }
+        png
+    }
+
+    // Every chunk's type, after checking its CRC
+    fn chunk_kinds(png: &[u8]) -> Vec<String> {
+        let mut kinds = Vec::new();
+        let mut offset = PNG_SIGNATURE.len();
+        while offset < png.len() {
+            let length = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+            let body = &png[offset + 4..offset + 8 + length];
+            let crc = u32::from_be_bytes(png[offset + 8 + length..offset + 12 + length].try_into().unwrap());
+            let kind = String::from_utf8_lossy(&body[..4]).to_string();
+            assert_eq!(crc32(body), crc, "bad CRC on {}", kind);
+            kinds.push(kind);
+            offset += 12 + length;
+        }
+        kinds
+    }
+
+    fn meta() -> CaptureMeta {
+        CaptureMeta {
+            url: "https://example.com/café?q=1".to_string(),
+            captured_at: "2024-01-31T15:45:02+01:00".to_string(),
+            viewport_width: 1280,
+            viewport_height: 720,
+            scroll_x: 0.0,
+            scroll_y: 412.5,
+            device_scale: 2.0,
+        }
+    }
+
+    #[test]
+    fn crc32_matches_the_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    }
+
+    #[test]
+    fn metadata_round_trips_through_text_chunks() {
+        let png = embed(tiny_png(), &meta());
+        assert_eq!(chunk_kinds(&png), ["IHDR", "tEXt", "tEXt", "tEXt", "tEXt", "tEXt", "tEXt", "IDAT", "IEND"]);
+
+        let read = from_text_chunks(&png).unwrap();
+        assert_eq!(read.url, meta().url);
+        assert_eq!(read.captured_at, meta().captured_at);
+        assert_eq!((read.viewport_width, read.viewport_height), (1280, 720));
+        assert_eq!((read.scroll_x, read.scroll_y), (0.0, 412.5));
+        assert_eq!(read.device_scale, 2.0);
+    }
+
+    #[test]
+    fn pngs_without_metadata_read_as_none() {
+        assert!(from_text_chunks(&tiny_png()).is_none());
+        // Too short to hold an IHDR: left alone
+        assert_eq!(embed(PNG_SIGNATURE.to_vec(), &meta()), PNG_SIGNATURE);
+    }
+
+    #[test]
+    fn files_keep_their_metadata() {
+        let dir = crate::paths::temp_root().join(format!("browser-cli-capture-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        let png = dir.join("shot.png");
+        runtime.block_on(write(&png, tiny_png(), &meta())).unwrap();
+        assert_eq!(read(&png).unwrap().url, meta().url);
+        assert_eq!(png_width(&png).unwrap(), 3);
+        assert!(!sidecar_path(&png).exists());
+
+        let jpeg = dir.join("shot.jpg");
+        runtime.block_on(write(&jpeg, b"\xff\xd8\xff\xe0 not really a jpeg".to_vec(), &meta())).unwrap();
+        assert!(sidecar_path(&jpeg).exists());
+        assert_eq!(read(&jpeg).unwrap().scroll_y, 412.5);
+        assert!(png_width(&jpeg).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// One line of a progress file. Lines are only ever appended; for an item named twice the later line wins.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Line<T> {
+    Job { job: String },
+    Done { item: String, result: T },
+    Failed { item: String, error: String },
+}
+
+// Progress of a batch job (a compare-envs run, a sitemap audit), written as each item finishes so a rerun
+// with --resume skips what's done. Finished items keep their result; failed ones keep their error and are
+// tried again on resume.
+pub struct Checkpoint<T> {
+    path: PathBuf,
+    file: std::fs::File,
+    completed: HashMap<String, T>,
+    failed: BTreeMap<String, String>,
+}
+
+impl<T: Serialize + DeserializeOwned> Checkpoint<T> {
+    // `job` describes the run (command and what it works on) so a progress file isn't resumed by another
+    // job. Without `resume` any earlier progress in the file is discarded.
+    pub fn open(path: &Path, job: &str, resume: bool) -> Result<Self> {
+        let (mut completed, mut failed, mut torn) = (HashMap::new(), BTreeMap::new(), false);
+        let resumable = resume && path.exists();
+        if resume && !resumable {
+            warning!("No progress to resume in {}; starting from the beginning", path.display());
+        }
+        if resumable {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("Failed to read progress file {}: {}", path.display(), e))?;
+            torn = !contents.is_empty() && !contents.ends_with('\n');
+            for (number, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                // A job killed mid-write leaves a partial last line, which is simply redone
+                let Ok(line) = serde_json::from_str::<Line<T>>(line) else {
+                    warning!("Skipping unreadable line {} of {}", number + 1, path.display());
+                    continue;
+                };
+                match line {
+                    Line::Job { job: recorded } if recorded != job => {
+                        return Err(anyhow::anyhow!(
+                            "{} holds the progress of another job ({}); run without --resume to start over", path.display(), recorded
+                        ));
+                    }
+                    Line::Job { .. } => {}
+                    Line::Done { item, result } => {
+                        failed.remove(&item);
+                        completed.insert(item, result);
+                    }
+                    Line::Failed { item, error } => {
+                        completed.remove(&item);
+                        failed.insert(item, error);
+                    }
+                }
+            }
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(resumable)
+            .write(true)
+            .truncate(!resumable)
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open progress file {}: {}", path.display(), e))?;
+        let mut checkpoint = Self { path: path.to_path_buf(), file, completed, failed };
+        if torn {
+            checkpoint.file.write_all(b"\n")?;
+        }
+        if !resumable {
+            checkpoint.append(&Line::<T>::Job { job: job.to_string() })?;
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Result of an item finished in an earlier run
+    pub fn completed(&self, item: &str) -> Option<&T> {
+        self.completed.get(item)
+    }
+
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    // Items that failed in an earlier run; they are tried again
+    pub fn failed_count(&self) -> usize {
+        self.failed.len()
+    }
+
+    pub fn complete(&mut self, item: &str, result: T) -> Result<()> {
+        self.append(&Line::Done { item: item.to_string(), result })
+    }
+
+    pub fn fail(&mut self, item: &str, error: &str) -> Result<()> {
+        self.append(&Line::<T>::Failed { item: item.to_string(), error: error.to_string() })
+    }
+
+    fn append(&mut self, line: &Line<T>) -> Result<()> {
+        let mut json = serde_json::to_string(line)?;
+        json.push('\n');
+        self.file.write_all(json.as_bytes())
+            .and_then(|_| self.file.flush())
+            .map_err(|e| anyhow::anyhow!("Failed to write progress file {}: {}", self.path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress_file(test: &str) -> PathBuf {
+        let dir = crate::paths::temp_root().join(format!("browser-cli-checkpoint-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("progress.jsonl")
+    }
+
+    #[test]
+    fn resume_recovers_from_a_torn_last_line() {
+        let path = progress_file("torn");
+        let mut checkpoint = Checkpoint::<u32>::open(&path, "audit", false).unwrap();
+        checkpoint.complete("a", 1).unwrap();
+        checkpoint.fail("b", "timed out").unwrap();
+        checkpoint.complete("c", 3).unwrap();
+        drop(checkpoint);
+
+        // Killed while writing c's line
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &contents[..contents.len() - 6]).unwrap();
+
+        let mut checkpoint = Checkpoint::<u32>::open(&path, "audit", true).unwrap();
+        assert_eq!(checkpoint.completed("a"), Some(&1));
+        assert_eq!(checkpoint.completed("c"), None);
+        assert_eq!((checkpoint.completed_count(), checkpoint.failed_count()), (1, 1));
+        checkpoint.complete("b", 2).unwrap();
+        checkpoint.complete("c", 3).unwrap();
+        drop(checkpoint);
+
+        // The redone items start on a line of their own and are read back
+        let checkpoint = Checkpoint::<u32>::open(&path, "audit", true).unwrap();
+        let results: Vec<_> = ["a", "b", "c"].iter().map(|item| checkpoint.completed(item).copied()).collect();
+        assert_eq!(results, [Some(1), Some(2), Some(3)]);
+        assert_eq!(checkpoint.failed_count(), 0);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn starting_over_discards_earlier_progress() {
+        let path = progress_file("restart");
+        let mut checkpoint = Checkpoint::<u32>::open(&path, "audit", false).unwrap();
+        checkpoint.complete("a", 1).unwrap();
+        drop(checkpoint);
+
+        let checkpoint = Checkpoint::<u32>::open(&path, "audit", false).unwrap();
+        assert_eq!(checkpoint.completed_count(), 0);
+        drop(checkpoint);
+        assert_eq!(Checkpoint::<u32>::open(&path, "audit", true).unwrap().completed_count(), 0);
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn another_jobs_progress_is_not_resumed() {
+        let path = progress_file("job");
+        Checkpoint::<u32>::open(&path, "audit https://a.test", false).unwrap().complete("a", 1).unwrap();
+        assert!(Checkpoint::<u32>::open(&path, "audit https://b.test", true).is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn resuming_without_progress_starts_fresh() {
+        let path = progress_file("missing");
+        let checkpoint = Checkpoint::<u32>::open(&path, "audit", true).unwrap();
+        assert_eq!(checkpoint.completed_count(), 0);
+        drop(checkpoint);
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with(r#"{"kind":"job","job":"audit"}"#));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::Result;
+use chromiumoxide::fetcher::{BrowserFetcher, BrowserFetcherOptions};
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+use crate::progress;
+
+// Records where `install-browser` put its executable, inside the managed install dir
+const MANAGED_MARKER: &str = "executable";
+
+// Executable names looked up on PATH, most preferred first
+#[cfg(target_os = "windows")]
+const PATH_NAMES: &[&str] = &["chrome.exe", "chromium.exe", "msedge.exe"];
+#[cfg(not(target_os = "windows"))]
+const PATH_NAMES: &[&str] = &[
+    "google-chrome",
+    "google-chrome-stable",
+    "chromium",
+    "chromium-browser",
+    "chrome",
+    "microsoft-edge",
+    "microsoft-edge-stable",
+];
+
+// Where the browser comes from, for messages and `version --full`
+#[derive(Debug, Clone)]
+pub enum Source {
+    EnvVar,
+    Managed,
+    Path,
+    Standard,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Source::EnvVar => "$CHROME",
+            Source::Managed => "install-browser",
+            Source::Path => "PATH",
+            Source::Standard => "standard install location",
+        })
+    }
+}
+
+// The browser to launch: $CHROME, then a managed install, then PATH, then the usual install locations
+pub fn find_executable() -> Option<(PathBuf, Source)> {
+    if let Some(path) = std::env::var_os("CHROME").map(PathBuf::from).filter(|path| path.is_file()) {
+        return Some((path, Source::EnvVar));
+    }
+    if let Some(path) = managed_executable() {
+        return Some((path, Source::Managed));
+    }
+    if let Some(path) = on_path() {
+        return Some((path, Source::Path));
+    }
+    standard_locations().into_iter().find(|path| path.is_file()).map(|path| (path, Source::Standard))
+}
+
+// What to tell someone when nothing was found
+pub fn not_found_help() -> String {
+    match std::env::var_os("CHROME") {
+        Some(path) => format!(
+            "CHROME is set to {} but that file doesn't exist. Fix it, or run `browser-cli install-browser` to download Chromium",
+            PathBuf::from(path).display()
+        ),
+        None => "No Chrome, Chromium or Edge found. Install one, set CHROME=/path/to/browser, \
+                 or run `browser-cli install-browser` to download Chromium".to_string(),
+    }
+}
+
+fn managed_dir() -> PathBuf {
+    paths::data_dir().join("chromium")
+}
+
+fn managed_executable() -> Option<PathBuf> {
+    let recorded = std::fs::read_to_string(managed_dir().join(MANAGED_MARKER)).ok()?;
+    Some(PathBuf::from(recorded.trim())).filter(|path| path.is_file())
+}
+
+fn on_path() -> Option<PathBuf> {
+    let dirs = std::env::var_os("PATH")?;
+    std::env::split_paths(&dirs)
+        .flat_map(|dir| PATH_NAMES.iter().map(move |name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+#[cfg(target_os = "macos")]
+fn standard_locations() -> Vec<PathBuf> {
+    let apps = [
+        "Google Chrome.app/Contents/MacOS/Google Chrome",
+        "Google Chrome Canary.app/Contents/MacOS/Google Chrome Canary",
+        "Chromium.app/Contents/MacOS/Chromium",
+        "Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+    ];
+    let mut roots = vec![PathBuf::from("/Applications")];
+    if let Some(home) = directories::BaseDirs::new() {
+        roots.push(home.home_dir().join("Applications"));
+    }
+    roots.iter().flat_map(|root| apps.iter().map(move |app| root.join(app))).collect()
+}
+
+#[cfg(target_os = "windows")]
+fn standard_locations() -> Vec<PathBuf> {
+    let installs = [
+        r"Google\Chrome\Application\chrome.exe",
+        r"Chromium\Application\chrome.exe",
+        r"Microsoft\Edge\Application\msedge.exe",
+    ];
+    ["ProgramFiles", "ProgramFiles(x86)", "LocalAppData"].iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .flat_map(|root| installs.iter().map(move |install| root.join(install)))
+        .collect()
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn standard_locations() -> Vec<PathBuf> {
+    [
+        "/usr/bin/google-chrome",
+        "/usr/bin/google-chrome-stable",
+        "/usr/bin/chromium",
+        "/usr/bin/chromium-browser",
+        "/snap/bin/chromium",
+        "/opt/google/chrome/chrome",
+        "/usr/bin/microsoft-edge",
+        "/opt/microsoft/msedge/msedge",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect()
+}
+
+// The user's own Chrome (not Chromium or Edge), whose profiles only it can decrypt
+pub fn google_chrome_executable() -> Option<PathBuf> {
+    let is_chrome = |path: &PathBuf| {
+        let path = path.display().to_string();
+        ["google-chrome", "Google Chrome.app", r"Google\Chrome", "/opt/google/chrome/"].iter().any(|name| path.contains(name))
+    };
+    let on_path = std::env::var_os("PATH").into_iter()
+        .flat_map(|dirs| std::env::split_paths(&dirs).collect::<Vec<_>>())
+        .flat_map(|dir| PATH_NAMES.iter().map(move |name| dir.join(name)));
+    on_path.chain(standard_locations())
+        .find(|path| path.is_file() && is_chrome(path))
+}
+
+// Where Chrome keeps the user's profiles
+pub fn user_data_dir() -> Option<PathBuf> {
+    let base = directories::BaseDirs::new()?;
+    #[cfg(target_os = "macos")]
+    let dir = base.data_dir().join("Google/Chrome");
+    #[cfg(target_os = "windows")]
+    let dir = base.data_local_dir().join(r"Google\Chrome\User Data");
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let dir = base.config_dir().join("google-chrome");
+    Some(dir).filter(|dir| dir.is_dir())
+}
+
+// Download the Chromium build pinned by chromiumoxide into the data dir; later launches pick it up
+// ahead of PATH. Returns the executable. Already-installed builds aren't downloaded again.
+pub async fn install() -> Result<PathBuf> {
+    let dir = managed_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    let options = BrowserFetcherOptions::builder()
+        .with_path(&dir)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Unsupported platform for managed Chromium: {}", e))?;
+
+    let downloading = progress::spinner(format!("Downloading Chromium into {}", dir.display()));
+    let fetched = BrowserFetcher::new(options).fetch().await;
+    downloading.finish_and_clear();
+    let info = fetched.map_err(|e| {
+        // A partial archive would otherwise sit in the data dir until the next attempt overwrites it
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            if entry.path().extension().is_some_and(|ext| ext == "zip") {
+                std::fs::remove_file(entry.path()).ok();
+            }
+        }
+        anyhow::anyhow!("Failed to install Chromium: {}", e)
+    })?;
+
+    record_managed(&dir, &info.executable_path)?;
+    Ok(info.executable_path)
+}
+
+fn record_managed(dir: &Path, executable: &Path) -> Result<()> {
+    std::fs::write(dir.join(MANAGED_MARKER), executable.display().to_string())?;
+    Ok(())
+}
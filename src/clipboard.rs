@@ -0,0 +1,65 @@
+use anyhow::Result;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Place a PNG image on the OS clipboard using the platform's native tooling
+pub fn copy_png(path: &Path) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Screenshot not found at {}: {}", path.display(), e))?;
+    copy_png_native(&path)
+}
+
+#[cfg(target_os = "macos")]
+fn copy_png_native(path: &Path) -> Result<()> {
+    let script = format!(
+        "set the clipboard to (read (POSIX file \"{}\") as «class PNGf»)",
+        path.display()
+    );
+    run("osascript", &["-e", &script], None)
+}
+
+#[cfg(target_os = "windows")]
+fn copy_png_native(path: &Path) -> Result<()> {
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms; Add-Type -AssemblyName System.Drawing; \
+         [System.Windows.Forms.Clipboard]::SetImage([System.Drawing.Image]::FromFile('{}'))",
+        path.display().to_string().replace('\'', "''")
+    );
+    run("powershell", &["-NoProfile", "-STA", "-Command", &script], None)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn copy_png_native(path: &Path) -> Result<()> {
+    // Prefer Wayland's wl-copy when running under a Wayland session, otherwise fall back to xclip
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let data = std::fs::read(path)?;
+        return run("wl-copy", &["--type", "image/png"], Some(&data));
+    }
+
+    let path_str = path.display().to_string();
+    run("xclip", &["-selection", "clipboard", "-t", "image/png", "-i", &path_str], None)
+}
+
+fn run(program: &str, args: &[&str], stdin: Option<&[u8]>) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        // Clipboard helpers like xclip fork to keep serving the selection, so don't wait on their output pipes
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to run '{}' for clipboard access (is it installed?): {}", program, e))?;
+
+    if let (Some(data), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(data)?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("'{}' failed to copy image to clipboard ({})", program, status));
+    }
+
+    Ok(())
+}
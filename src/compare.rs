@@ -0,0 +1,409 @@
+use anyhow::Result;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Per-path progress of a run, for --resume
+pub const PROGRESS_FILE: &str = "progress.jsonl";
+
+// Unchanged lines shown around each change in a text diff
+const DIFF_CONTEXT: usize = 3;
+
+// Channel deltas (summed over RGBA) up to this count as equal, so anti-aliasing noise isn't a difference
+pub const PIXEL_TOLERANCE: u32 = 32;
+
+// What compare-envs compares per path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareMode {
+    // Screenshots, pixel by pixel
+    #[default]
+    Visual,
+    // The page's main content as normalized text, line by line
+    Text,
+}
+
+impl CompareMode {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "visual" | "pixels" => Ok(Self::Visual),
+            "text" => Ok(Self::Text),
+            _ => Err(anyhow::anyhow!("Unknown compare mode '{}' (expected visual or text)", name)),
+        }
+    }
+}
+
+// How compare-envs compares and where it reports
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    pub mode: CompareMode,
+    // Highest mismatch percentage that still passes
+    pub threshold: f64,
+    // Report directory; a new capture subdirectory when unset
+    pub out: Option<PathBuf>,
+    // Visit paths robots.txt disallows, and don't wait out its crawl-delay
+    pub ignore_robots: bool,
+    // Skip paths an earlier run in `out` (or the latest run) already compared
+    pub resume: bool,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self { mode: CompareMode::Visual, threshold: 0.1, out: None, ignore_robots: false, resume: false }
+    }
+}
+
+// The same path on two hosts: screenshots and their pixel diff, or extracted text and its unified diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathComparison {
+    pub path: String,
+    pub base_url: String,
+    pub target_url: String,
+    pub base_file: PathBuf,
+    pub target_file: PathBuf,
+    pub diff_file: Option<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+    pub different_pixels: u64,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    // Percentage of pixels (visual) or lines (text) that differ
+    pub mismatch: f64,
+    pub passed: bool,
+    // Set when either side couldn't be loaded or captured
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareReport {
+    pub mode: CompareMode,
+    pub base: String,
+    pub target: String,
+    // Highest mismatch percentage that still passes
+    pub threshold: f64,
+    pub dir: PathBuf,
+    pub report: PathBuf,
+    pub paths: Vec<PathComparison>,
+}
+
+impl PathComparison {
+    pub fn new(path: &str, base_url: String, target_url: String, base_file: PathBuf, target_file: PathBuf) -> Self {
+        Self {
+            path: path.to_string(),
+            base_url,
+            target_url,
+            base_file,
+            target_file,
+            diff_file: None,
+            width: 0,
+            height: 0,
+            different_pixels: 0,
+            added_lines: 0,
+            removed_lines: 0,
+            mismatch: 0.0,
+            passed: false,
+            error: None,
+        }
+    }
+
+    fn summary(&self, mode: CompareMode) -> String {
+        match mode {
+            CompareMode::Visual => format!("{:.3}% of {}x{} differ", self.mismatch, self.width, self.height),
+            CompareMode::Text => format!("+{} -{} lines ({:.3}%)", self.added_lines, self.removed_lines, self.mismatch),
+        }
+    }
+}
+
+impl CompareReport {
+    pub fn failures(&self) -> usize {
+        self.paths.iter().filter(|path| !path.passed).count()
+    }
+
+    pub fn print(&self) {
+        status!("{} {} vs {}", "🆚".cyan(), self.base.bold(), self.target.bold());
+        for path in &self.paths {
+            let verdict = match (&path.error, path.passed) {
+                (Some(_), _) => "ERROR".red().bold(),
+                (None, true) => "PASS".green().bold(),
+                (None, false) => "DIFF".yellow().bold(),
+            };
+            match &path.error {
+                Some(error) => status!("  {:<5} {} {}", verdict, path.path, error.dimmed()),
+                None => status!("  {:<5} {} {}", verdict, path.path, path.summary(self.mode).dimmed()),
+            }
+        }
+        let failures = self.failures();
+        let summary = format!("{} of {} paths differ beyond {}% ({})", failures, self.paths.len(), self.threshold,
+            if self.mode == CompareMode::Text { "text" } else { "pixels" });
+        status!("{} {} — report: {}", if failures == 0 { "✓".green() } else { "⚠️".yellow() }, summary, self.report.display());
+    }
+}
+
+// Join a host and a path from the paths file: `/pricing`, `pricing?plan=pro` and `https://…` (used as-is)
+pub fn join_url(host: &str, path: &str) -> String {
+    if path.contains("://") {
+        return path.to_string();
+    }
+    format!("{}/{}", host.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+// Path and query of a full URL (as sitemaps list them), so the same page can be loaded on both hosts
+pub fn url_path(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+// Newest compare-envs directory of any run that has progress to resume
+pub fn latest_run_dir() -> Option<PathBuf> {
+    crate::paths::latest_run_subdir(crate::paths::Artifact::Reports, "compare-envs", PROGRESS_FILE)
+}
+
+// One path per line; blank lines and # comments are skipped
+pub fn read_paths(file: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(file)
+        .map_err(|e| anyhow::anyhow!("Failed to read paths file {}: {}", file.display(), e))?;
+    let paths: Vec<String> = contents.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if paths.is_empty() {
+        return Err(anyhow::anyhow!("{} lists no paths", file.display()));
+    }
+    Ok(paths)
+}
+
+// File-name friendly form of a path, prefixed with its position so files sort in paths-file order
+pub fn file_stem(index: usize, path: &str) -> String {
+    let slug: String = path.trim_matches('/').chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    let slug: String = slug.chars().take(60).collect();
+    format!("{:03}-{}", index + 1, if slug.is_empty() { "root".to_string() } else { slug })
+}
+
+// HTML page of every path: base, target and the highlighted difference side by side, or the text diff
+pub fn write_html_report(report: &CompareReport) -> Result<()> {
+    let relative = |path: &Path| path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let mut rows = String::new();
+    for path in &report.paths {
+        let verdict = match (&path.error, path.passed) {
+            (Some(error), _) => format!("<b class=\"error\">ERROR</b> {}", html_escape(error)),
+            (None, true) => format!("<b class=\"pass\">PASS</b> {}", html_escape(&path.summary(report.mode))),
+            (None, false) => format!("<b class=\"diff\">DIFF</b> {}", html_escape(&path.summary(report.mode))),
+        };
+        if report.mode == CompareMode::Text {
+            let diff = path.diff_file.as_ref()
+                .and_then(|file| std::fs::read_to_string(file).ok())
+                .filter(|diff| !diff.is_empty());
+            let diff = match diff {
+                Some(diff) => format!("<pre>{}</pre>", diff.lines().map(diff_line_html).collect::<Vec<_>>().join("\n")),
+                None if path.error.is_none() => "<span class=\"pass\">Same text</span>".to_string(),
+                None => "<span class=\"missing\">—</span>".to_string(),
+            };
+            rows.push_str(&format!(
+                "<tr><td><a href=\"{}\">{}</a><br>{}<br><a href=\"{}\">base</a> · <a href=\"{}\">target</a></td><td>{}</td></tr>\n",
+                html_escape(&path.target_url), html_escape(&path.path), verdict,
+                html_escape(&relative(&path.base_file)), html_escape(&relative(&path.target_file)), diff,
+            ));
+            continue;
+        }
+        let image = |file: Option<&PathBuf>| match file {
+            Some(file) if file.exists() => format!("<a href=\"{0}\"><img src=\"{0}\"></a>", html_escape(&relative(file))),
+            _ => "<span class=\"missing\">—</span>".to_string(),
+        };
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a><br>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            html_escape(&path.target_url), html_escape(&path.path), verdict,
+            image(Some(&path.base_file)), image(Some(&path.target_file)), image(path.diff_file.as_ref()),
+        ));
+    }
+    let html = format!(
+        r#"<!doctype html>
+<meta charset="utf-8">
+<title>compare-envs: {base} vs {target}</title>
+<style>
+body {{ font: 14px system-ui, sans-serif; margin: 24px; }}
+table {{ border-collapse: collapse; width: 100%; }}
+td, th {{ border: 1px solid #ddd; padding: 8px; vertical-align: top; }}
+img {{ max-width: 400px; border: 1px solid #eee; }}
+pre {{ margin: 0; font-size: 12px; white-space: pre-wrap; }}
+.added {{ background: #e6ffec; }} .removed {{ background: #ffebe9; }} .hunk {{ color: #6f42c1; }}
+.pass {{ color: #2e7d32; }} .diff {{ color: #ef6c00; }} .error, .missing {{ color: #c62828; }}
+</style>
+<h1>{base} vs {target}</h1>
+<p>{failures} of {total} paths differ beyond {threshold}%</p>
+<table>
+{header}
+{rows}</table>
+"#,
+        base = html_escape(&report.base),
+        target = html_escape(&report.target),
+        failures = report.failures(),
+        total = report.paths.len(),
+        threshold = report.threshold,
+        header = match report.mode {
+            CompareMode::Visual => "<tr><th>Path</th><th>Base</th><th>Target</th><th>Difference</th></tr>",
+            CompareMode::Text => "<tr><th>Path</th><th>Text diff</th></tr>",
+        },
+        rows = rows,
+    );
+    std::fs::write(&report.report, html)
+        .map_err(|e| anyhow::anyhow!("Failed to write report {}: {}", report.report.display(), e))?;
+    Ok(())
+}
+
+fn diff_line_html(line: &str) -> String {
+    let class = match line.chars().next() {
+        Some('@') => "hunk",
+        Some('+') if !line.starts_with("+++") => "added",
+        Some('-') if !line.starts_with("---") => "removed",
+        _ => "",
+    };
+    format!("<span class=\"{}\">{}</span>", class, html_escape(line))
+}
+
+// Page text reduced to what a reader sees: whitespace collapsed within lines, blank lines dropped, so
+// markup and indentation changes don't show up as differences
+pub fn normalize_text(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+// Unified diff (as `diff -u` prints it) between two texts' lines, with how many lines were added and removed.
+// Identical texts give an empty diff.
+pub fn unified_diff(base: &[String], target: &[String], base_name: &str, target_name: &str) -> (String, usize, usize) {
+    let edits = edit_script(base, target);
+    // (edit, base index, target index) of each step
+    let mut steps = Vec::with_capacity(edits.len());
+    let (mut i, mut j) = (0, 0);
+    for edit in edits {
+        steps.push((edit, i, j));
+        match edit {
+            Edit::Keep => {
+                i += 1;
+                j += 1;
+            }
+            Edit::Remove => i += 1,
+            Edit::Add => j += 1,
+        }
+    }
+    let added = steps.iter().filter(|(edit, _, _)| *edit == Edit::Add).count();
+    let removed = steps.iter().filter(|(edit, _, _)| *edit == Edit::Remove).count();
+    if added + removed == 0 {
+        return (String::new(), 0, 0);
+    }
+
+    // Ranges of steps around changes, merged when their context overlaps
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, _) in steps.iter().enumerate().filter(|(_, (edit, _, _))| *edit != Edit::Keep) {
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(steps.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", base_name, target_name);
+    for (start, end) in hunks {
+        let steps = &steps[start..end];
+        let base_count = steps.iter().filter(|(edit, _, _)| *edit != Edit::Add).count();
+        let target_count = steps.iter().filter(|(edit, _, _)| *edit != Edit::Remove).count();
+        // An empty side is numbered by the line before it, as diff does
+        let line_number = |index: usize, count: usize| if count == 0 { index } else { index + 1 };
+        let (_, base_start, target_start) = steps[0];
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            line_number(base_start, base_count), base_count, line_number(target_start, target_count), target_count
+        ));
+        for (edit, i, j) in steps {
+            match edit {
+                Edit::Keep => diff.push_str(&format!(" {}\n", base[*i])),
+                Edit::Remove => diff.push_str(&format!("-{}\n", base[*i])),
+                Edit::Add => diff.push_str(&format!("+{}\n", target[*j])),
+            }
+        }
+    }
+    (diff, added, removed)
+}
+
+// Shortest edit script between two line lists. Shared leading and trailing lines are set aside first,
+// which keeps the search (and its trace) to the part of the page that changed.
+fn edit_script(a: &[String], b: &[String]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let mut edits = vec![Edit::Keep; prefix];
+    edits.extend(myers(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]));
+    edits.extend(std::iter::repeat_n(Edit::Keep, suffix));
+    edits
+}
+
+// Myers' O(ND) difference algorithm
+fn myers(a: &[String], b: &[String]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    // Furthest x reached on each diagonal k = x - y, indexed by k + offset
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // v before each round d, for walking the path back
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let down = k == -d || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize]);
+            let mut x = if down { v[(k + 1 + offset) as usize] } else { v[(k - 1 + offset) as usize] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + offset) as usize] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let down = k == -d || (k != d && v[(k - 1 + offset) as usize] < v[(k + 1 + offset) as usize]);
+        let previous_k = if down { k + 1 } else { k - 1 };
+        let previous_x = if d == 0 { 0 } else { v[(previous_k + offset) as usize] };
+        let previous_y = if d == 0 { 0 } else { previous_x - previous_k };
+        while x > previous_x && y > previous_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if down { Edit::Add } else { Edit::Remove });
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    edits.reverse();
+    edits
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::browser::WaitUntil;
+use crate::paths;
+use crate::policy::UrlPolicy;
+
+const CONFIG_FILE_NAME: &str = "browser-cli.toml";
+
+// Settings read from browser-cli.toml (current directory, then the user config dir)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub policy: UrlPolicy,
+    pub navigation: NavigationConfig,
+    pub flags: BTreeMap<String, FlagPreset>,
+}
+
+// Defaults for `navigate` that individual commands can override
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NavigationConfig {
+    // Fail navigation when more JS errors than this happen while the page settles
+    pub max_js_errors: Option<usize>,
+    // When a page counts as loaded: "load", "domcontentloaded", "networkidle" or "selector:<css>"
+    pub wait_until: Option<WaitUntil>,
+}
+
+// How a project forces an experiment variant or feature flag, e.g.
+// `[flags.checkout] cookie = "exp_checkout"`, then `flags set checkout=v2`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FlagPreset {
+    pub cookie: Option<String>,
+    // Cookie domain; defaults to the current page's host
+    pub domain: Option<String>,
+    pub local_storage: Option<String>,
+    pub header: Option<String>,
+    // Template for the stored value, with `{value}` replaced by the requested variant
+    pub format: Option<String>,
+}
+
+impl Config {
+    // An explicit path must exist; the default locations are optional
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        match path {
+            Some(path) => Self::from_file(Path::new(path)),
+            None => match Self::default_paths().into_iter().find(|path| path.is_file()) {
+                Some(path) => Self::from_file(&path),
+                None => Ok(Self::default()),
+            },
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config {}: {}", path.display(), e))?;
+        toml::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid config {}: {}", path.display(), e))
+    }
+
+    fn default_paths() -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from(CONFIG_FILE_NAME)];
+        paths.extend(paths::user_config_files());
+        paths
+    }
+}
@@ -1,27 +1,86 @@
 use anyhow::Result;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
 use colored::*;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::TryRecvError};
 use tokio::sync::Mutex;
 
-use crate::browser::BrowserController;
+use browser_cli::assets::{self, DEFAULT_REPORT_TOP};
+use browser_cli::paths::{self, Artifact};
+use browser_cli::audit::{AuditReport, Severity};
+use browser_cli::checkpoint::Checkpoint;
+use browser_cli::crawl::{normalize_url, CrawlOptions, Visited};
+use browser_cli::browser::{BrowserController, DEFAULT_DRAG_STEPS, DEFAULT_NETWORK_IDLE_MS, DEFAULT_SEQUENCE_FRAMES, DEFAULT_SEQUENCE_INTERVAL_MS, ElementQuery, ElementScope, FieldChange, HardwareProfile, JsTarget, LinkFilter, MediaEmulation, MEDIA_FEATURES, NavigateOptions, SubmitWait, TextFormat, TextOptions, Ticker, TickerCheck, TickerOptions, TypeOptions, WaitCondition, WaitUntil};
+use browser_cli::compare::{CompareMode, CompareOptions};
+use browser_cli::keyboard::KeyboardLayout;
+use browser_cli::events::{BrowserEvent, DialogAction, SessionEvent, EVENT_NAMES};
+use browser_cli::error::Error;
+use browser_cli::network;
+use browser_cli::profiles;
+use browser_cli::progress;
+use browser_cli::scrape::ScrapeSpec;
+use browser_cli::sitemap::SitemapFilter;
+use browser_cli::table::TableFormat;
+use browser_cli::{status, warning};
+
+mod dev;
+mod dispatch;
+mod pipe;
+mod script;
+
+pub use dev::DEFAULT_DEV_ACTION;
+pub use script::Script;
 
 pub struct Console {
     browser: Arc<Mutex<BrowserController>>,
     editor: DefaultEditor,
+    sessions: HashMap<String, Arc<Mutex<BrowserController>>>,
+    session: String,
+    hooks: HashMap<String, Vec<String>>,
+    event_bus: Option<broadcast::Sender<SessionEvent>>,
+    events: Option<broadcast::Receiver<SessionEvent>>,
+    // `set` variables, expanded as ${NAME} in every command
+    variables: HashMap<String, String>,
 }
 
 impl Console {
     pub fn new(browser: Arc<Mutex<BrowserController>>) -> Result<Self> {
         let editor = DefaultEditor::new()?;
-        Ok(Self { browser, editor })
+        Ok(Self {
+            browser,
+            editor,
+            sessions: HashMap::new(),
+            session: String::new(),
+            hooks: HashMap::new(),
+            event_bus: None,
+            events: None,
+            variables: HashMap::new(),
+        })
+    }
+
+    // Register the initial browser as the active session and start listening to its events
+    async fn start_session(&mut self) {
+        let (session, event_bus) = {
+            let browser = self.browser.lock().await;
+            (browser.session_name().to_string(), browser.event_bus())
+        };
+        self.sessions.insert(session.clone(), Arc::clone(&self.browser));
+        Self::spawn_reaper(&session, &self.browser);
+        self.session = session;
+        self.events = Some(event_bus.subscribe());
+        self.event_bus = Some(event_bus);
     }
 
     pub async fn run(&mut self) -> Result<()> {
-        println!("{}", "🚀 Browser CLI Interactive Console".bold().cyan());
-        println!("{}", "Type 'help' for available commands, 'exit' to quit".dimmed());
-        println!();
+        status!("{}", "🚀 Browser CLI Interactive Console".bold().cyan());
+        status!("{}", "Type 'help' for available commands, 'exit' to quit".dimmed());
+        status!();
+
+        self.start_session().await;
 
         loop {
             let readline = self.editor.readline("browser> ");
@@ -35,159 +94,280 @@ impl Console {
                     self.editor.add_history_entry(line).ok();
 
                     if line == "exit" || line == "quit" {
-                        println!("{}", "Goodbye! 👋".green());
+                        status!("{}", "Goodbye! 👋".green());
                         break;
                     }
 
-                    if let Err(e) = self.execute_command(line).await {
-                        println!("{} {}", "Error:".red().bold(), e);
+                    let result = self.execute_command(line).await;
+                    if let Err(e) = &result {
+                        status!("{} {:#}", "Error:".red().bold(), e);
                     }
+                    self.publish_result(line, &result);
+                    
+                    self.run_hooks().await;
                 }
                 Err(ReadlineError::Interrupted) => {
-                    println!("{}", "Use 'exit' to quit".yellow());
+                    status!("{}", "Use 'exit' to quit".yellow());
                 }
                 Err(ReadlineError::Eof) => {
-                    println!("{}", "Goodbye! 👋".green());
+                    status!("{}", "Goodbye! 👋".green());
                     break;
                 }
                 Err(err) => {
-                    println!("{} {}", "Error:".red().bold(), err);
+                    status!("{} {}", "Error:".red().bold(), err);
                     break;
                 }
             }
         }
 
+        self.close_sessions().await;
         Ok(())
     }
 
-    async fn execute_command(&self, input: &str) -> Result<()> {
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        if parts.is_empty() {
-            return Ok(());
-        }
-
-        let command = parts[0];
-        let args = &parts[1..];
-
-        match command {
-            "help" | "h" => self.show_help(),
-            "navigate" | "go" => self.cmd_navigate(args).await,
-            "click" => self.cmd_click(args).await,
-            "clickat" => self.cmd_click_at(args).await,
-            "doubleclickat" => self.cmd_double_click_at(args).await,
-            "rightclickat" => self.cmd_right_click_at(args).await,
-            "type" => self.cmd_type(args).await,
-            "scroll" => self.cmd_scroll(args).await,
-            "search" => self.cmd_search(args).await,
-            "screenshot" | "ss" => self.cmd_screenshot(args).await,
-            "text" => self.cmd_text(args).await,
-            "js" | "eval" => self.cmd_javascript(args).await,
-            "url" => self.cmd_url().await,
-            "title" => self.cmd_title().await,
-            "reload" | "refresh" => self.cmd_reload().await,
-            "back" => self.cmd_back().await,
-            "forward" => self.cmd_forward().await,
-            "waitfor" => self.cmd_wait_for(args).await,
-            "waitfortext" => self.cmd_wait_for_text(args).await,
-            "waitfornav" => self.cmd_wait_for_navigation(args).await,
-            "highlight" => self.cmd_highlight(args).await,
-            "clear" | "cls" => self.cmd_clear(),
-            "status" => self.cmd_status().await,
-            "info" => self.cmd_page_info().await,
-            "elements" => self.cmd_elements().await,
-            "fill" => self.cmd_fill_field(args).await,
-            "submit" => self.cmd_submit_form(args).await,
-            "ticker" => self.cmd_ticker(args).await,
-            "waitenhanced" => self.cmd_wait_enhanced(args).await,
-            _ => {
-                println!("{} Unknown command: '{}'. Type 'help' for available commands.", 
-                    "⚠️".yellow(), command);
-                Ok(())
-            }
-        }
-    }
-
     fn show_help(&self) -> Result<()> {
-        println!("{}", "📖 Available Commands:".bold().blue());
-        println!();
-        
-        println!("{}", "Navigation:".bold());
-        println!("  {} <url>        Navigate to URL", "navigate, go".cyan());
-        println!("  {}              Go back in history", "back".cyan());
-        println!("  {}           Go forward in history", "forward".cyan());
-        println!("  {}, {}     Reload current page", "reload".cyan(), "refresh".cyan());
-        println!();
-        
-        println!("{}", "Interaction:".bold());
-        println!("  {} <selector>     Click an element", "click".cyan());
-        println!("  {} <x> <y>        Click at coordinates", "clickat".cyan());
-        println!("  {} <x> <y>   Double-click at coordinates", "doubleclickat".cyan());
-        println!("  {} <x> <y>    Right-click at coordinates", "rightclickat".cyan());
-        println!("  {} <sel> <text>   Type text into element", "type".cyan());
-        println!("  {} <dir> [amt]    Scroll (up/down/top/bottom)", "scroll".cyan());
-        println!("  {} <query>      Search on current page", "search".cyan());
-        println!();
-        
-        println!("{}", "Information:".bold());
-        println!("  {} [selector]     Get text content", "text".cyan());
-        println!("  {}               Get current URL", "url".cyan());
-        println!("  {}              Get page title", "title".cyan());
-        println!("  {}             Check browser status", "status".cyan());
-        println!();
-        
-        println!("{}", "Capture:".bold());
-        println!("  {}, {} [file]  Take screenshot", "screenshot".cyan(), "ss".cyan());
-        println!();
-        
-        println!("{}", "JavaScript:".bold());
-        println!("  {}, {} <code>    Execute JavaScript", "js".cyan(), "eval".cyan());
-        println!();
-        
-        println!("{}", "Waiting:".bold());
-        println!("  {} <sel> [s]   Wait for element to appear", "waitfor".cyan());
-        println!("  {} <text> [s] Wait for text to appear", "waitfortext".cyan());
-        println!("  {} [s]        Wait for navigation", "waitfornav".cyan());
-        println!();
-        
-        println!("{}", "Debugging:".bold());
-        println!("  {} <selector>    Highlight element temporarily", "highlight".cyan());
-        println!("  {}              Get detailed page information", "info".cyan());
-        println!("  {}           List interactive elements", "elements".cyan());
-        println!();
-        
-        println!("{}", "Form Handling:".bold());
-        println!("  {} <sel> <val>    Robust form field filling", "fill".cyan());
-        println!("  {} [selector]     Submit form", "submit".cyan());
-        println!();
-        
-        println!("{}", "Monitoring:".bold());
-        println!("  {} [sel] [interval] [max] Monitor page changes", "ticker".cyan());
-        println!("  {} <sel> [timeout] Enhanced element waiting", "waitenhanced".cyan());
-        println!();
-        
-        println!("{}", "Utility:".bold());
-        println!("  {}, {}         Clear screen", "clear".cyan(), "cls".cyan());
-        println!("  {}, {}           Show this help", "help".cyan(), "h".cyan());
-        println!("  {}, {}           Exit console", "exit".cyan(), "quit".cyan());
-        println!();
+        status!("{}", "📖 Available Commands:".bold().blue());
+        status!();
+        
+        status!("{}", "Navigation:".bold());
+        status!("  {} <url>        Navigate to URL", "navigate, go".cyan());
+        status!("  {} <url> --max-js-errors N  Fail if the page throws more than N JS errors", "navigate".cyan());
+        status!("  {} <url> --wait-until load|domcontentloaded|networkidle|selector:<css>  When the page counts as loaded", "navigate".cyan());
+        status!("  {}              Go back in history", "back".cyan());
+        status!("  {}           Go forward in history", "forward".cyan());
+        status!("  {}, {}     Reload current page", "reload".cyan(), "refresh".cyan());
+        status!();
+        
+        status!("{}", "Interaction:".bold());
+        status!("  {} <selector>     Click an element", "click".cyan());
+        status!("  {} <x> <y>        Click at coordinates", "clickat".cyan());
+        status!("  {} <x> <y> --from-screenshot [file]  Click at screenshot pixels (HiDPI/zoom corrected)", "clickat".cyan());
+        status!("  {} <sel>          Move the mouse over an element (hover menus, tooltips)", "hover".cyan());
+        status!("  {} <x> <y>        Move the mouse to coordinates", "hoverat".cyan());
+        status!("  {} <from> <to> [--steps N]  Drag one element onto another", "drag".cyan());
+        status!("  {} <x1> <y1> <x2> <y2> [--steps N]  Drag between two points", "dragat".cyan());
+        status!("  {} <x> <y>   Double-click at coordinates", "doubleclickat".cyan());
+        status!("  {} <x> <y>    Right-click at coordinates", "rightclickat".cyan());
+        status!("  {} <sel> <text>   Type text into element", "type".cyan());
+        status!("  {} [--layout de] [--ime] <sel> <text>  Type accented/CJK/emoji text", "type".cyan());
+        status!("  {} <sel|x y> <ms>  Press and hold the mouse button", "press-hold".cyan());
+        status!("  {} <combo>       Press a key or combo (Enter, ctrl+a, shift+Tab)", "press".cyan());
+        status!("  {} / {} <key>   Press / release a key (no key: list held keys)", "keydown".cyan(), "keyup".cyan());
+        status!("  {} <key> -- <command>  Run a command with a key held down", "hold".cyan());
+        status!("  {} <dir> [amt]    Scroll (up/down/top/bottom)", "scroll".cyan());
+        status!("  {} <query>      Search on current page", "search".cyan());
+        status!();
+        
+        status!("{}", "Information:".bold());
+        status!("  {} [selector]     Get text content", "text".cyan());
+        status!("  {} <sel> [--format text|json|md] [--trim] [--max-chars N] [--all]", "text".cyan());
+        status!("  {} <selector> Get text as a user would copy it (visible only)", "copytext".cyan());
+        status!("  {} [selector] [--pretty] Get the page's HTML source or an element's outerHTML", "html".cyan());
+        status!("  {} [selector]  Page or element content as Markdown", "markdown".cyan());
+        status!("  {} [--format md|text|json] Main article with title, byline and date", "article".cyan());
+        status!("  {} --spec <file.json>  JSON document from a map of field names to selectors", "scrape".cyan());
+        status!("  {} <selector> [--format csv|json] [--out file]  Rows of an HTML table", "table".cyan());
+        status!("  {} [--same-origin] [--pattern regex] [--json]  Every link with its text and rel", "links".cyan());
+        status!("  {} <selector>       Whether anything matches the selector", "exists".cyan());
+        status!("  {} <selector>        How many elements match the selector", "count".cyan());
+        status!("  {}               Get current URL", "url".cyan());
+        status!("  {}              Get page title", "title".cyan());
+        status!("  {}             Check browser status", "status".cyan());
+        status!("  {}              CLI/browser/protocol versions and enabled features", "about".cyan());
+        status!();
+        
+        status!("{}", "Capture:".bold());
+        status!("  {}, {} [file]  Take screenshot", "screenshot".cyan(), "ss".cyan());
+        status!("  {}, {} [file] --clipboard  Screenshot and copy to clipboard", "screenshot".cyan(), "ss".cyan());
+        status!("  {} <sel> [--out file]  Save a canvas's pixels (WebGL-safe)", "canvas dump".cyan());
+        status!("  {} <file>        URL, time, viewport and scroll recorded in a screenshot", "ss info".cyan());
+        status!("  {} [--max N]     Screenshot every Tab focus stop", "ss-focus-walk".cyan());
+        status!("  {} [--frames N] [--interval ms] [--out dir]  Numbered burst of screenshots (default {} frames, {}ms apart)", "ss-sequence".cyan(), DEFAULT_SEQUENCE_FRAMES, DEFAULT_SEQUENCE_INTERVAL_MS);
+        status!("  {} [file]          Full-page screenshot with print media (file.pdf: export a PDF)", "ss-print".cyan());
+        status!("  {} <url> [--out file]  Download using the page's cookies/session", "fetch".cyan());
+        status!("  {} [dir]      Download favicons and og:image/twitter:image previews", "assets icons".cyan());
+        status!("  {}           Web fonts: family, weight, style, source URL, bytes", "assets fonts".cyan());
+        status!("  {} [top]    Page load bytes by resource type, heaviest files first", "assets report".cyan());
+        status!();
+        
+        status!("{}", "JavaScript:".bold());
+        status!("  {}, {} <code>    Execute JavaScript", "js".cyan(), "eval".cyan());
+        status!("  {} [--tab n] [--frame sel|n] [--isolated] <code>  Execute in another tab, frame or isolated world", "js".cyan());
+        status!("  {} \"<expr>\" [expected]  Assert an expression equals a JSON value (or is truthy)", "assert-js".cyan());
+        status!();
+        
+        status!("{}", "Determinism:".bold());
+        status!("  {} <iso8601> [--timezone tz]  Freeze Date/performance.now", "freeze-time".cyan());
+        status!("  {} off             Restore the real clock", "freeze-time".cyan());
+        status!("  {} <n> [--crypto]         Seed Math.random (and crypto.getRandomValues)", "seed".cyan());
+        status!("  {} off                    Restore native randomness", "seed".cyan());
+        status!("  {} [--cores n] [--memory gb] [--battery 0-1] [--charging]  Spoof hardware APIs", "spoof hw".cyan());
+        status!("  {}                Restore real hardware values", "spoof hw off".cyan());
+        status!("  {} set <name>=<value>... Force experiment variants via configured presets", "flags".cyan());
+        status!("  {}                       List configured flag presets", "flags".cyan());
+        status!();
+        
+        status!("{}", "Environments:".bold());
+        status!("  {} --base <url> --target <url> --paths <file> [--mode visual|text] [--threshold pct] [--out dir]", "compare-envs".cyan());
+        status!("  {} ... --from-sitemap <url> [--match pattern] [--since YYYY-MM-DD]  Paths from a sitemap instead", "compare-envs".cyan());
+        status!("  {} ... --resume   Continue an interrupted run (--out dir or the latest), skipping compared paths", "compare-envs".cyan());
+        status!("      Screenshot each path on both hosts and pixel-diff them (text: diff their main content); HTML report");
+        status!();
+        
+        status!("{}", "Localization:".bold());
+        status!("  {} check --langs en,de,fr [--selector sel]  Compare strings across locales", "i18n".cyan());
+        status!();
+        
+        status!("{}", "Waiting:".bold());
+        status!("  {} <sel> [s]   Wait for element to appear", "waitfor".cyan());
+        status!("  {} <sel> [s] --visible|--enabled|--clickable|--attr n=v  Wait for element state", "waitfor".cyan());
+        status!("  {} <text> [s] Wait for text to appear", "waitfortext".cyan());
+        status!("  {} [s]        Wait for navigation", "waitfornav".cyan());
+        status!("  {} <pattern> [s]  Wait for an SPA route change to a matching URL (* wildcards)", "waitroute".cyan());
+        status!("  {} [ms] [s]  Wait until no requests for ms (default {})", "waitfornetworkidle".cyan(), DEFAULT_NETWORK_IDLE_MS);
+        status!();
+        
+        status!("{}", "Debugging:".bold());
+        status!("  {} <selector>    Highlight element temporarily", "highlight".cyan());
+        status!("  {}              Get detailed page information", "info".cyan());
+        status!("  {} [--open [devtools]]  Links for a human to take over (page, DevTools for this session)", "handoff".cyan());
+        status!("  {}       Page info as JSON with landmarks and element refs", "info --json".cyan());
+        status!("  {}           List interactive elements (refs, boxes; first 50)", "elements".cyan());
+        status!("  {} [--filter buttons|links|inputs] [--contains text] [--offset N] [--limit N|all]", "elements".cyan());
+        status!("  {} --in-viewport | --all  On screen now / everything incl. hidden (with reason)", "elements".cyan());
+        status!();
+        
+        status!("{}", "Network:".bold());
+        status!("  {} [--all] [--out txt|svg|file]  Request timing waterfall", "network waterfall".cyan());
+        status!("  {} [--operation name]  GraphQL calls by operation", "network graphql".cyan());
+        status!("  {}        Forget captured requests", "network clear".cyan());
+        status!("  {} <file.har> [--unmatched pass|block]  Serve responses from a HAR", "mock from-har".cyan());
+        status!("  {} | {}          Show mock status / stop mocking", "mock".cyan(), "mock off".cyan());
+        status!("  {} [file.har] | {}  Capture all traffic (headers, timings) to a HAR 1.2 file", "har start".cyan(), "har stop".cyan());
+        status!("  {} <url-glob>...       Abort matching requests (ads, analytics, third-party scripts)", "block".cyan());
+        status!("  {} | {} <glob> | {}  Rules with hit counts / drop one / drop all", "block list".cyan(), "block remove".cyan(), "block off".cyan());
+        status!();
+        
+        status!("{}", "Audits:".bold());
+        status!("  {}          HTML structure, alt text, duplicate ids, deprecations", "audit html".cyan());
+        status!("  {} [--max-steps N]  Keyboard focus chain, traps, unreachable controls", "audit tab-order".cyan());
+        status!("  {} [--selector scope]  WCAG AA/AAA text contrast", "audit contrast".cyan());
+        status!("  {} ... --from-sitemap <url> [--match p] [--since date]  Audit every page a sitemap lists", "audit <kind>".cyan());
+        status!("  {} <url> [--depth N] [--same-origin] [--max-pages N] [--delay-ms N] [--out dir]  Save text, HTML and screenshots breadth-first", "crawl".cyan());
+        status!("  {} ... --from-sitemap <url> [--match p] [--since date] [--resume]  Start from a sitemap's pages; continue an interrupted crawl", "crawl".cyan());
+        status!("      Batch visits honour robots.txt (browser-cli or * rules) and its crawl-delay; --ignore-robots skips that");
+        status!();
+        
+        status!("{}", "Form Handling:".bold());
+        status!("  {} <sel> <val>    Robust form field filling", "fill".cyan());
+        status!("  {} [selector]     Submit form (clicks its submit button)", "submit".cyan());
+        status!("  {} [sel] --wait-nav | --wait-selector <sel>  Submit and wait for the result", "submit".cyan());
+        status!();
+        
+        status!("{}", "Monitoring:".bold());
+        status!("  {} [sel] [interval] [max] Monitor page changes", "ticker".cyan());
+        status!("  {} --json ...      One {{ts, field, before, after}} record per change", "ticker".cyan());
+        status!("  {} --min-change-chars N --debounce s --ignore-selectors a,b  Cut noise", "ticker".cyan());
+        status!("  {} ... --on-change <cmd>  Run a command per change (last option; \"!cmd\" for shell, gets $BROWSER_CLI_CHANGES)", "ticker".cyan());
+        status!("  {} <sel> [timeout] Wait in the page, shadow roots and same-origin frames", "waitenhanced".cyan());
+        status!();
+        
+        status!("{}", "Scripts:".bold());
+        status!("  {} <file>            Run console commands from a file, stopping at the first failure", "run".cyan());
+        status!("  {} <file> --screenshots after-each  Capture a numbered screenshot after every step", "run".cyan());
+        status!("  {} <file> --continue-on-error  Run every step despite failures, then report them", "run".cyan());
+        status!("  {} <name> <value>    Define ${{name}} for later commands ({} <name> removes it, ${{env:NAME}} reads the environment)", "set".cyan(), "set".cyan());
+        status!();
+        
+        status!("{}", "Pipes:".bold());
+        status!("  {}  Filter any command's output, e.g. {}", "<command> | <filter> | ...".cyan(), "elements | grep Login".cyan());
+        status!("  {} [-i] [-v] <text>  Keep lines containing text (-i any case, -v lines without it)", "grep".cyan());
+        status!("  {} / {} [N]     First / last N lines (10 by default)", "head".cyan(), "tail".cyan());
+        status!("  {} <path>   Values from JSON output ($.items[0].name, $[?(@.role=='button')].name)", "json-path".cyan());
+        status!("  {} [-a] <file>   Write the output to a file (-a appends), e.g. {}", "save".cyan(), "text .price | save price.txt".cyan());
+        status!();
+        
+        status!("{}", "Tabs:".bold());
+        status!("  {}                List open tabs (* marks the active one)", "tabs".cyan());
+        status!("  {} [url]        Open a URL in a new tab and switch to it", "newtab".cyan());
+        status!("  {} <index|id> Make another tab the active one", "switchtab".cyan());
+        status!("  {} [index|id]  Close a tab, the active one by default", "closetab".cyan());
+        status!();
+        
+        status!("{}", "Profiles:".bold());
+        status!("  {} --from-chrome [profile] --domain a.com,b.com [--local-storage]", "import-profile".cyan());
+        status!("      Copy those domains' cookies (and localStorage) from your Chrome profile to start signed in");
+        status!();
+        
+        status!("{}", "Sessions:".bold());
+        status!("  {} [list]           List browser sessions", "session".cyan());
+        status!("  {} create <name>    Create a session with its own profile and switch to it", "session".cyan());
+        status!("  {} switch <name>    Switch to another session", "session".cyan());
+        status!("  {} destroy <name>   Close a session's browser and remove it", "session".cyan());
+        status!("  {} [--session name|--all]  Close the current, a named, or every browser (other processes' too)", "close".cyan());
+        status!("  {}                 Memory/CPU usage per session", "session stats".cyan());
+        status!("  {} [idle=s] [lifetime=s] [memory=mb]  Resource limits for this session", "session limits".cyan());
+        status!();
+        
+        status!("{}", "Hooks:".bold());
+        status!("  {} <event> <command>  Run command on {}", "on".cyan(), EVENT_NAMES.join("|"));
+        status!("  {} title|audio      Publish title changes (any tab) or sound starting/stopping", "watch".cyan());
+        status!("  {}  Capture notifications and list them", "notifications log".cyan());
+        status!("  {} Forget captured notifications", "notifications clear".cyan());
+        status!("  {} dialog accept|dismiss Answer dialogs automatically", "on".cyan());
+        status!("  {}                    List hooks", "on".cyan());
+        status!("  {} <event>           Remove hooks for event", "off".cyan());
+        status!();
+        
+        status!("{}", "Utility:".bold());
+        status!("  {} [secs] [on|off] CDP call timeout and auto-recovery", "watchdog".cyan());
+        status!("  {} [on|off]      Run helper scripts in an isolated world", "isolation".cyan());
+        status!("  {} [on|off]     Ignore page CSP for injected scripts", "bypass-csp".cyan());
+        status!("  {} [factor|off]  Fix deviceScaleFactor for pixel-consistent screenshots", "device-scale".cyan());
+        status!("  {} [--media print|screen|off] | off  Emulate CSS media on every tab", "emulate-media".cyan());
+        status!("  {} --forced-colors active  High contrast; also --prefers-contrast, --prefers-color-scheme, --prefers-reduced-motion", "emulate-media".cyan());
+        status!("  {} [on|off]     Keep page CSP enforced and report blocked injections", "strict-csp".cyan());
+        status!("  {}               Show the URL allow/deny policy", "policy".cyan());
+        status!("  {} <dir> [--port N] [--open] Serve a local directory over HTTP", "serve-dir".cyan());
+        status!("  {} [list] | {} [--keep N]  Run directories (script logs, step screenshots, reports, traces)", "runs".cyan(), "runs prune".cyan());
+        status!("  {}, {}         Clear screen", "clear".cyan(), "cls".cyan());
+        status!("  {}, {}           Show this help", "help".cyan(), "h".cyan());
+        status!("  {}, {}           Exit console", "exit".cyan(), "quit".cyan());
+        status!();
         
         Ok(())
     }
 
     async fn cmd_navigate(&self, args: &[&str]) -> Result<()> {
-        if args.is_empty() {
-            println!("{} Usage: navigate <url>", "⚠️".yellow());
+        let mut options = NavigateOptions::default();
+        let mut url_parts = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match *arg {
+                "--max-js-errors" => {
+                    let value = iter.next().ok_or_else(|| anyhow::anyhow!("--max-js-errors needs a number"))?;
+                    options.max_js_errors = Some(value.parse().map_err(|_| anyhow::anyhow!("Invalid error budget: {}", value))?);
+                }
+                "--wait-until" => {
+                    let value = iter.next().ok_or_else(|| anyhow::anyhow!("--wait-until needs a strategy"))?;
+                    options.wait_until = Some(WaitUntil::parse(value)?);
+                }
+                part => url_parts.push(part),
+            }
+        }
+        if url_parts.is_empty() {
+            status!("{} Usage: navigate <url> [--max-js-errors N] [--wait-until load|domcontentloaded|networkidle|selector:<css>]", "⚠️".yellow());
             return Ok(());
         }
         
-        let url = args.join(" ");
+        let url = url_parts.join(" ");
         let mut browser = self.browser.lock().await;
-        browser.navigate(&url).await
+        browser.navigate(&url, options).await?;
+        Ok(())
     }
 
     async fn cmd_click(&self, args: &[&str]) -> Result<()> {
         if args.is_empty() {
-            println!("{} Usage: click <selector>", "⚠️".yellow());
+            status!("{} Usage: click <selector>", "⚠️".yellow());
             return Ok(());
         }
         
@@ -198,24 +378,120 @@ impl Console {
     }
 
     async fn cmd_click_at(&self, args: &[&str]) -> Result<()> {
-        if args.len() < 2 {
-            println!("{} Usage: clickat <x> <y>", "⚠️".yellow());
-            return Ok(());
-        }
+        let (coords, screenshot) = match args {
+            [x, y, "--from-screenshot", file] => ([*x, *y], Some(Some(std::path::Path::new(*file)))),
+            [x, y, "--from-screenshot"] => ([*x, *y], Some(None)),
+            [x, y] => ([*x, *y], None),
+            _ => {
+                status!("{} Usage: clickat <x> <y> [--from-screenshot [file]]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
         
-        let x = args[0].parse::<f64>()
+        let x = coords[0].parse::<f64>()
             .map_err(|_| anyhow::anyhow!("Invalid X coordinate"))?;
-        let y = args[1].parse::<f64>()
+        let y = coords[1].parse::<f64>()
             .map_err(|_| anyhow::anyhow!("Invalid Y coordinate"))?;
         
         let mut browser = self.browser.lock().await;
         browser.init().await?;
+        let (x, y) = match screenshot {
+            Some(file) => {
+                let scale = browser.screenshot_scale(file).await?;
+                status!("{} Screenshot ({}, {}) is CSS ({:.1}, {:.1}) at {}x", "🎯".cyan(), x, y, x / scale, y / scale, scale);
+                (x / scale, y / scale)
+            }
+            None => (x, y),
+        };
         browser.click_at_coordinates(x, y).await
     }
 
+    async fn cmd_hover(&self, args: &[&str]) -> Result<()> {
+        let [selector] = args else {
+            status!("{} Usage: hover <selector>", "⚠️".yellow());
+            return Ok(());
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.hover(selector).await
+    }
+
+    async fn cmd_hover_at(&self, args: &[&str]) -> Result<()> {
+        let [x, y] = args else {
+            status!("{} Usage: hoverat <x> <y>", "⚠️".yellow());
+            return Ok(());
+        };
+        let x = x.parse::<f64>().map_err(|_| anyhow::anyhow!("Invalid X coordinate"))?;
+        let y = y.parse::<f64>().map_err(|_| anyhow::anyhow!("Invalid Y coordinate"))?;
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.hover_at(x, y).await
+    }
+
+    async fn cmd_drag(&self, args: &[&str]) -> Result<()> {
+        let (from, to, steps) = match args {
+            [from, to] => (*from, *to, DEFAULT_DRAG_STEPS),
+            [from, to, "--steps", steps] => (*from, *to, steps.parse().map_err(|_| anyhow::anyhow!("Invalid --steps: {}", steps))?),
+            _ => {
+                status!("{} Usage: drag <from-selector> <to-selector> [--steps N]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.drag(from, to, steps).await
+    }
+
+    async fn cmd_drag_at(&self, args: &[&str]) -> Result<()> {
+        let (coords, steps) = match args {
+            [x1, y1, x2, y2] => ([*x1, *y1, *x2, *y2], DEFAULT_DRAG_STEPS),
+            [x1, y1, x2, y2, "--steps", steps] => {
+                ([*x1, *y1, *x2, *y2], steps.parse().map_err(|_| anyhow::anyhow!("Invalid --steps: {}", steps))?)
+            }
+            _ => {
+                status!("{} Usage: dragat <x1> <y1> <x2> <y2> [--steps N]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        let coords = coords.iter()
+            .map(|value| value.parse::<f64>().map_err(|_| anyhow::anyhow!("Invalid coordinate: {}", value)))
+            .collect::<Result<Vec<_>>>()?;
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.drag_at((coords[0], coords[1]), (coords[2], coords[3]), steps).await
+    }
+
+    async fn cmd_press_hold(&self, args: &[&str]) -> Result<()> {
+        let parse_ms = |ms: &str| ms.parse::<u64>()
+            .map(Duration::from_millis)
+            .map_err(|_| anyhow::anyhow!("Invalid duration: {}", ms));
+        
+        let mut browser = self.browser.lock().await;
+        match args {
+            [x, y, ms] if x.parse::<f64>().is_ok() && y.parse::<f64>().is_ok() => {
+                let duration = parse_ms(ms)?;
+                browser.init().await?;
+                browser.press_hold_at(x.parse()?, y.parse()?, duration).await
+            }
+            [selector, ms] => {
+                let duration = parse_ms(ms)?;
+                browser.init().await?;
+                browser.press_hold(selector, duration).await
+            }
+            _ => {
+                status!("{} Usage: press-hold <selector|x y> <duration-ms>", "⚠️".yellow());
+                Ok(())
+            }
+        }
+    }
+
     async fn cmd_double_click_at(&self, args: &[&str]) -> Result<()> {
         if args.len() < 2 {
-            println!("{} Usage: doubleclickat <x> <y>", "⚠️".yellow());
+            status!("{} Usage: doubleclickat <x> <y>", "⚠️".yellow());
             return Ok(());
         }
         
@@ -231,7 +507,7 @@ impl Console {
 
     async fn cmd_right_click_at(&self, args: &[&str]) -> Result<()> {
         if args.len() < 2 {
-            println!("{} Usage: rightclickat <x> <y>", "⚠️".yellow());
+            status!("{} Usage: rightclickat <x> <y>", "⚠️".yellow());
             return Ok(());
         }
         
@@ -245,9 +521,80 @@ impl Console {
         browser.right_click_at_coordinates(x, y).await
     }
 
+    async fn cmd_press(&self, args: &[&str]) -> Result<()> {
+        let [combo] = args else {
+            status!("{} Usage: press <combo>  (e.g. press Enter, press ctrl+a, press shift+Tab)", "⚠️".yellow());
+            return Ok(());
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.press_key(combo).await
+    }
+
+    async fn cmd_key(&self, args: &[&str], down: bool) -> Result<()> {
+        if args.is_empty() {
+            let held = self.browser.lock().await.held_keys();
+            if held.is_empty() {
+                status!("{} No keys held", "⌨️".cyan());
+            } else {
+                status!("{} Held: {}", "⌨️".cyan(), held.join(" + "));
+            }
+            return Ok(());
+        }
+        let [key] = args else {
+            status!("{} Usage: {} <key>", "⚠️".yellow(), if down { "keydown" } else { "keyup" });
+            return Ok(());
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        if down {
+            browser.key_down(key).await
+        } else {
+            browser.key_up(key).await
+        }
+    }
+
+    // hold <key> -- <command>: the key is released even when the command fails
+    async fn cmd_hold(&mut self, args: &[&str]) -> Result<()> {
+        let (key, command) = match args {
+            [key, "--", command @ ..] if !command.is_empty() => (*key, command.join(" ")),
+            _ => {
+                status!("{} Usage: hold <key> -- <command>", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
+        {
+            let mut browser = self.browser.lock().await;
+            browser.init().await?;
+            browser.key_down(key).await?;
+        }
+        let result = self.execute_command(&command).await;
+        self.browser.lock().await.key_up(key).await?;
+        result
+    }
+
     async fn cmd_type(&self, args: &[&str]) -> Result<()> {
+        // Options come before the selector so they can't be confused with the typed text
+        let mut options = TypeOptions::default();
+        let mut args = args;
+        loop {
+            match args {
+                ["--layout", layout, rest @ ..] => {
+                    options.layout = KeyboardLayout::parse(layout)?;
+                    args = rest;
+                }
+                ["--ime", rest @ ..] => {
+                    options.ime = true;
+                    args = rest;
+                }
+                _ => break,
+            }
+        }
         if args.len() < 2 {
-            println!("{} Usage: type <selector> <text>", "⚠️".yellow());
+            status!("{} Usage: type [--layout us|uk|de|fr|es] [--ime] <selector> <text>", "⚠️".yellow());
             return Ok(());
         }
         
@@ -255,12 +602,12 @@ impl Console {
         let text = args[1..].join(" ");
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.type_text(selector, &text).await
+        browser.type_text(selector, &text, &options).await
     }
 
     async fn cmd_scroll(&self, args: &[&str]) -> Result<()> {
         if args.is_empty() {
-            println!("{} Usage: scroll <up|down|top|bottom> [amount]", "⚠️".yellow());
+            status!("{} Usage: scroll <up|down|top|bottom> [amount]", "⚠️".yellow());
             return Ok(());
         }
         
@@ -273,171 +620,1330 @@ impl Console {
 
     async fn cmd_search(&self, args: &[&str]) -> Result<()> {
         if args.is_empty() {
-            println!("{} Usage: search <query>", "⚠️".yellow());
+            status!("{} Usage: search <query>", "⚠️".yellow());
             return Ok(());
         }
         
         let query = args.join(" ");
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.search(&query).await
-    }
-
-    async fn cmd_screenshot(&self, args: &[&str]) -> Result<()> {
-        let filename = args.get(0).copied();
-        let mut browser = self.browser.lock().await;
-        browser.init().await?;
-        browser.screenshot(filename).await?;
+        browser.search(&query).await?;
         Ok(())
     }
 
-    async fn cmd_text(&self, args: &[&str]) -> Result<()> {
-        let selector = args.get(0).copied();
+    async fn cmd_screenshot(&self, args: &[&str]) -> Result<()> {
+        if let ["info", file] = args {
+            let meta = browser_cli::capture::read(std::path::Path::new(file))?;
+            status!("{}", meta);
+            return Ok(());
+        }
+        if args.first() == Some(&"info") {
+            status!("{} Usage: ss info <file>", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let to_clipboard = args.contains(&"--clipboard");
+        let filename = args.iter().find(|a| !a.starts_with("--")).copied();
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        let text = browser.get_text(selector).await?;
-        println!("{}", text.cyan());
+        let path = browser.screenshot(filename).await?;
+        
+        if to_clipboard {
+            browser_cli::clipboard::copy_png(std::path::Path::new(&path))?;
+            status!("{} Copied to clipboard", "📋".cyan());
+        }
+        
         Ok(())
     }
 
-    async fn cmd_javascript(&self, args: &[&str]) -> Result<()> {
-        if args.is_empty() {
-            println!("{} Usage: js <javascript_code>", "⚠️".yellow());
+    async fn cmd_print_capture(&self, args: &[&str]) -> Result<()> {
+        if args.len() > 1 {
+            status!("{} Usage: ss-print [file.png|file.pdf]", "⚠️".yellow());
             return Ok(());
         }
         
-        let code = args.join(" ");
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.execute_javascript(&code).await
+        browser.print_capture(args.first().copied()).await?;
+        Ok(())
     }
 
-    async fn cmd_url(&self) -> Result<()> {
+    async fn cmd_screenshot_sequence(&self, args: &[&str]) -> Result<()> {
+        let (mut frames, mut interval, mut out) = (DEFAULT_SEQUENCE_FRAMES, DEFAULT_SEQUENCE_INTERVAL_MS, None);
+        let mut rest = args.iter();
+        while let Some(arg) = rest.next() {
+            let value = match *arg {
+                "--frames" | "--interval" | "--out" => rest.next().ok_or_else(|| anyhow::anyhow!("{} needs a value", arg))?,
+                _ => {
+                    status!("{} Usage: ss-sequence [--frames N] [--interval ms] [--out dir]", "⚠️".yellow());
+                    return Ok(());
+                }
+            };
+            match *arg {
+                "--frames" => frames = value.parse().map_err(|_| anyhow::anyhow!("Invalid --frames: {}", value))?,
+                "--interval" => interval = value.parse().map_err(|_| anyhow::anyhow!("Invalid --interval: {}", value))?,
+                _ => out = Some(std::path::PathBuf::from(value)),
+            }
+        }
+        
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        let url = browser.get_url().await?;
-        println!("{} {}", "URL:".bold(), url.cyan());
+        browser.screenshot_sequence(frames, std::time::Duration::from_millis(interval), out.as_deref()).await?;
         Ok(())
     }
 
-    async fn cmd_title(&self) -> Result<()> {
+    async fn cmd_focus_walk(&self, args: &[&str]) -> Result<()> {
+        let max_steps = match args {
+            [] => 50,
+            ["--max", value] => value.parse::<usize>().map_err(|_| anyhow::anyhow!("Invalid --max: {}", value))?,
+            _ => {
+                status!("{} Usage: ss-focus-walk [--max N]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        let title = browser.get_title().await?;
-        println!("{} {}", "Title:".bold(), title.cyan());
+        let (dir, stops) = browser.focus_walk_screenshots(max_steps).await?;
+        let hidden = stops.iter().filter(|stop| !stop.focus_visible).count();
+        status!("{} {} focus stops captured in {} ({} without a visible indicator)", "📸".cyan(), stops.len(), dir, hidden);
         Ok(())
     }
 
-    async fn cmd_reload(&self) -> Result<()> {
+    async fn cmd_canvas(&self, args: &[&str]) -> Result<()> {
+        let (selector, out) = match args {
+            ["dump", selector] => (*selector, None),
+            ["dump", selector, "--out", out] => (*selector, Some(*out)),
+            _ => {
+                status!("{} Usage: canvas dump <selector> [--out file.png]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.reload().await
+        browser.canvas_dump(selector, out).await?;
+        Ok(())
     }
 
-    async fn cmd_back(&self) -> Result<()> {
+    async fn cmd_notifications(&self, args: &[&str]) -> Result<()> {
         let mut browser = self.browser.lock().await;
-        browser.init().await?;
-        browser.go_back().await
+        match args {
+            ["log"] => {
+                browser.init().await?;
+                browser.watch_notifications().await?;
+                let records = browser.notifications().await?;
+                if records.is_empty() {
+                    status!("{} No notifications yet", "🔔".cyan());
+                }
+                for record in records {
+                    let tag = record.tag.map(|tag| format!(" #{}", tag)).unwrap_or_default();
+                    status!("  {} [{}] {} {}{}", record.at.format("%H:%M:%S").to_string().dimmed(),
+                        record.source.cyan(), record.title.bold(), record.body, tag.dimmed());
+                }
+                Ok(())
+            }
+            ["clear"] => {
+                browser.clear_notifications();
+                status!("{} Notification log cleared", "✓".green());
+                Ok(())
+            }
+            _ => {
+                status!("{} Usage: notifications log | notifications clear", "⚠️".yellow());
+                Ok(())
+            }
+        }
     }
 
-    async fn cmd_forward(&self) -> Result<()> {
+    async fn cmd_assets(&self, args: &[&str]) -> Result<()> {
         let mut browser = self.browser.lock().await;
-        browser.init().await?;
-        browser.go_forward().await
+        match args {
+            ["icons", rest @ ..] if rest.len() <= 1 => {
+                let dir = match rest.first() {
+                    Some(dir) => paths::capture_path(dir)?,
+                    None => paths::capture_subdir(&format!("icons-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")))?,
+                };
+                browser.init().await?;
+                let mut icons = browser.page_icons().await?;
+                browser.download_assets(&mut icons, &dir).await?;
+                assets::print_icons(&icons);
+            }
+            ["fonts"] => {
+                browser.init().await?;
+                assets::print_fonts(&browser.font_inventory().await?);
+            }
+            ["report", rest @ ..] if rest.len() <= 1 => {
+                let top = match rest.first() {
+                    Some(top) => top.parse().map_err(|_| anyhow::anyhow!("Invalid count '{}'", top))?,
+                    None => DEFAULT_REPORT_TOP,
+                };
+                browser.init().await?;
+                assets::print_report(&assets::group_by_type(&browser.network_requests(false), top));
+            }
+            _ => status!("{} Usage: assets icons [dir] | assets fonts | assets report [top]", "⚠️".yellow()),
+        }
+        Ok(())
     }
 
-    async fn cmd_wait_for(&self, args: &[&str]) -> Result<()> {
-        if args.is_empty() {
-            println!("{} Usage: waitfor <selector> [timeout]", "⚠️".yellow());
+    async fn cmd_fetch(&self, args: &[&str]) -> Result<()> {
+        let Some(url) = args.first() else {
+            status!("{} Usage: fetch <url> [--out file]", "⚠️".yellow());
             return Ok(());
-        }
+        };
+        let out = args.iter().position(|a| *a == "--out").and_then(|i| args.get(i + 1)).copied();
         
-        let selector = args[0];
-        let timeout = args.get(1).and_then(|s| s.parse().ok());
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.wait_for_selector(selector, timeout).await
+        browser.fetch_to_file(url, out).await?;
+        Ok(())
     }
 
-    async fn cmd_wait_for_text(&self, args: &[&str]) -> Result<()> {
-        if args.is_empty() {
-            println!("{} Usage: waitfortext <text> [timeout]", "⚠️".yellow());
-            return Ok(());
-        }
-        
-        // Check if last argument is a number (timeout)
-        let (text, timeout) = if args.len() > 1 {
-            if let Ok(timeout_secs) = args.last().unwrap().parse::<u64>() {
-                let text_parts = &args[..args.len() - 1];
-                (text_parts.join(" "), Some(timeout_secs))
-            } else {
-                (args.join(" "), None)
+    async fn cmd_text(&self, args: &[&str]) -> Result<()> {
+        let mut options = TextOptions::default();
+        let mut selector = Vec::new();
+        let mut args = args;
+        loop {
+            match args {
+                ["--format", format, rest @ ..] => {
+                    options.format = TextFormat::parse(format)?;
+                    args = rest;
+                }
+                ["--max-chars", max, rest @ ..] => {
+                    options.max_chars = Some(max.parse().map_err(|_| anyhow::anyhow!("Invalid --max-chars: {}", max))?);
+                    args = rest;
+                }
+                ["--trim", rest @ ..] => {
+                    options.trim = true;
+                    args = rest;
+                }
+                ["--all", rest @ ..] => {
+                    options.all = true;
+                    args = rest;
+                }
+                [word, rest @ ..] => {
+                    selector.push(*word);
+                    args = rest;
+                }
+                [] => break,
             }
-        } else {
-            (args.join(" "), None)
-        };
+        }
+        let selector = (!selector.is_empty()).then(|| selector.join(" "));
         
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.wait_for_text(&text, timeout).await
+        let text = browser.get_text(selector.as_deref(), &options).await?;
+        match options.format {
+            TextFormat::Text => status!("{}", text.cyan()),
+            _ => status!("{}", text),
+        }
+        Ok(())
     }
 
-    async fn cmd_wait_for_navigation(&self, args: &[&str]) -> Result<()> {
-        let timeout = args.get(0).and_then(|s| s.parse().ok());
+    async fn cmd_html(&self, args: &[&str]) -> Result<()> {
+        let pretty = args.contains(&"--pretty");
+        let selector: Vec<&str> = args.iter().copied().filter(|a| *a != "--pretty").collect();
+        let selector = (!selector.is_empty()).then(|| selector.join(" "));
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.wait_for_navigation(timeout).await
+        let html = browser.get_html(selector.as_deref(), pretty).await?;
+        status!("{}", html);
+        Ok(())
     }
 
-    async fn cmd_highlight(&self, args: &[&str]) -> Result<()> {
-        if args.is_empty() {
-            println!("{} Usage: highlight <selector>", "⚠️".yellow());
-            return Ok(());
-        }
-        
-        let selector = args[0];
+    async fn cmd_markdown(&self, args: &[&str]) -> Result<()> {
+        let selector = (!args.is_empty()).then(|| args.join(" "));
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.highlight_element(selector).await
-    }
-
-    fn cmd_clear(&self) -> Result<()> {
-        print!("\x1B[2J\x1B[1;1H");
-        println!("{}", "🚀 Browser CLI Interactive Console".bold().cyan());
-        println!("{}", "Type 'help' for available commands, 'exit' to quit".dimmed());
-        println!();
+        let markdown = browser.get_markdown(selector.as_deref()).await?;
+        status!("{}", markdown);
         Ok(())
     }
 
-    async fn cmd_status(&self) -> Result<()> {
+    async fn cmd_article(&self, args: &[&str]) -> Result<()> {
+        let format = match args {
+            [] => TextFormat::Markdown,
+            ["--format", format] => TextFormat::parse(format)?,
+            _ => {
+                status!("{} Usage: article [--format md|text|json]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        let status = browser.get_status().await?;
-        println!("{}", status);
+        let article = browser.get_article().await?;
+        match format {
+            TextFormat::Markdown => status!("{}", article.to_markdown()),
+            TextFormat::Text => status!("{}", article.to_text()),
+            TextFormat::Json => status!("{}", serde_json::to_string_pretty(&article)?),
+        }
         Ok(())
     }
 
-    async fn cmd_page_info(&self) -> Result<()> {
+    async fn cmd_scrape(&self, args: &[&str]) -> Result<()> {
+        let file = match args {
+            ["--spec", file] | [file] => *file,
+            _ => {
+                status!("{} Usage: scrape --spec <file.json>", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        let spec = ScrapeSpec::load(std::path::Path::new(file))?;
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        let info = browser.get_concise_page_info().await?;
-        println!("{}", info);
+        let result = browser.scrape(&spec).await?;
+        status!("{}", serde_json::to_string_pretty(&result.data)?);
+        if !result.missing.is_empty() {
+            status!("{} Nothing matched for: {}", "⚠️".yellow(), result.missing.join(", "));
+        }
         Ok(())
     }
 
-    async fn cmd_elements(&self) -> Result<()> {
+    async fn cmd_links(&self, args: &[&str]) -> Result<()> {
+        let mut filter = LinkFilter::default();
+        let mut json = false;
+        let mut args = args;
+        loop {
+            match args {
+                ["--same-origin", rest @ ..] => {
+                    filter.same_origin = true;
+                    args = rest;
+                }
+                ["--pattern", pattern, rest @ ..] => {
+                    filter.pattern = Some(pattern.to_string());
+                    args = rest;
+                }
+                ["--json", rest @ ..] => {
+                    json = true;
+                    args = rest;
+                }
+                [] => break,
+                _ => {
+                    status!("{} Usage: links [--same-origin] [--pattern regex] [--json]", "⚠️".yellow());
+                    return Ok(());
+                }
+            }
+        }
+        
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        
-        let elements_info = browser.get_interactive_elements().await?;
-        println!("{}", elements_info);
-        
+        let links = browser.get_links(&filter).await?;
+        if json {
+            status!("{}", serde_json::to_string_pretty(&links)?);
+            return Ok(());
+        }
+        for link in &links {
+            let rel = if link.rel.is_empty() { String::new() } else { format!(" [rel={}]", link.rel.join(" ")) };
+            status!("  {} {}{}", link.url.cyan(), link.text, rel.dimmed());
+        }
+        status!("{} {} links", "🔗".cyan(), links.len());
         Ok(())
     }
 
-    async fn cmd_fill_field(&self, args: &[&str]) -> Result<()> {
+    async fn cmd_table(&self, args: &[&str]) -> Result<()> {
+        let mut format = TableFormat::default();
+        let mut out = None;
+        let mut selector = Vec::new();
+        let mut args = args;
+        loop {
+            match args {
+                ["--format", name, rest @ ..] => {
+                    format = TableFormat::parse(name)?;
+                    args = rest;
+                }
+                ["--out", file, rest @ ..] => {
+                    out = Some(paths::capture_path(file)?);
+                    args = rest;
+                }
+                [word, rest @ ..] => {
+                    selector.push(*word);
+                    args = rest;
+                }
+                [] => break,
+            }
+        }
+        if selector.is_empty() {
+            status!("{} Usage: table <selector> [--format csv|json] [--out file]", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let table = browser.get_table(&selector.join(" ")).await?;
+        let output = match format {
+            TableFormat::Csv => table.to_csv(),
+            TableFormat::Json => serde_json::to_string_pretty(&table.to_json())?,
+        };
+        match out {
+            Some(path) => {
+                std::fs::write(&path, format!("{}\n", output))
+                    .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+                status!("{} Saved {} rows to {}", "💾".green(), table.rows.len(), path.display());
+            }
+            None => status!("{}", output),
+        }
+        Ok(())
+    }
+
+    async fn cmd_copy_text(&self, args: &[&str]) -> Result<()> {
+        if args.is_empty() {
+            status!("{} Usage: copytext <selector>", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let selector = args.join(" ");
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let text = browser.copy_text(&selector).await?;
+        status!("{}", text.cyan());
+        Ok(())
+    }
+
+    async fn cmd_javascript(&self, args: &[&str]) -> Result<()> {
+        // Options come before the code, so `--` inside the code is left alone
+        let mut target = JsTarget::default();
+        let mut args = args;
+        loop {
+            match args {
+                ["--tab", index, rest @ ..] => {
+                    target.tab = Some(index.parse().map_err(|_| anyhow::anyhow!("Invalid tab index: {}", index))?);
+                    args = rest;
+                }
+                ["--frame", frame, rest @ ..] => {
+                    target.frame = Some(frame.to_string());
+                    args = rest;
+                }
+                ["--isolated", rest @ ..] => {
+                    target.isolated = true;
+                    args = rest;
+                }
+                _ => break,
+            }
+        }
+        if args.is_empty() {
+            status!("{} Usage: js [--tab <index>] [--frame <selector|index>] [--isolated] <javascript_code>", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let code = args.join(" ");
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.execute_javascript(&code, &target).await
+    }
+
+    async fn cmd_assert_js(&self, args: &[&str]) -> Result<()> {
+        let input = args.join(" ");
+        let (expression, expected) = match Self::split_quoted(&input) {
+            Some(parts) => parts,
+            None => {
+                status!("{} Usage: assert-js \"<expression>\" [expected]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.assert_js(expression, expected).await?;
+        Ok(())
+    }
+
+    // Split `"quoted expression" rest` into its parts; an unquoted input is taken whole
+    fn split_quoted(input: &str) -> Option<(&str, Option<&str>)> {
+        let input = input.trim();
+        if input.is_empty() {
+            return None;
+        }
+        let Some(quote) = input.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            return Some((input, None));
+        };
+        
+        let body = &input[1..];
+        let end = body.char_indices()
+            .find(|&(i, c)| c == quote && !body[..i].ends_with('\\'))
+            .map(|(i, _)| i)?;
+        let rest = body[end + 1..].trim();
+        Some((&body[..end], (!rest.is_empty()).then_some(rest)))
+    }
+
+    async fn cmd_freeze_time(&self, args: &[&str]) -> Result<()> {
+        let Some(value) = args.first() else {
+            status!("{} Usage: freeze-time <iso8601> [--timezone <tz>] | freeze-time off", "⚠️".yellow());
+            return Ok(());
+        };
+        let at = match *value {
+            "off" => None,
+            value => Some(Self::parse_instant(value)?),
+        };
+        let timezone = args.iter().position(|a| *a == "--timezone").and_then(|i| args.get(i + 1)).copied();
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.freeze_time(at, timezone).await
+    }
+
+    async fn cmd_seed(&self, args: &[&str]) -> Result<()> {
+        let Some(value) = args.first() else {
+            status!("{} Usage: seed <n> [--crypto] | seed off", "⚠️".yellow());
+            return Ok(());
+        };
+        let seed = match *value {
+            "off" => None,
+            value => Some(value.parse::<u32>().map_err(|_| anyhow::anyhow!("Invalid seed: {} (expected 0-{})", value, u32::MAX))?),
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.seed_random(seed, args.contains(&"--crypto")).await
+    }
+
+    async fn cmd_spoof(&self, args: &[&str]) -> Result<()> {
+        let profile = match args {
+            ["hw", "off"] => None,
+            ["hw", options @ ..] if !options.is_empty() => {
+                let mut profile = HardwareProfile::default();
+                let mut options = options;
+                loop {
+                    match options {
+                        ["--cores", value, rest @ ..] => {
+                            profile.cores = Some(value.parse().map_err(|_| anyhow::anyhow!("Invalid core count: {}", value))?);
+                            options = rest;
+                        }
+                        ["--memory", value, rest @ ..] => {
+                            profile.memory_gb = Some(value.parse().map_err(|_| anyhow::anyhow!("Invalid memory: {}", value))?);
+                            options = rest;
+                        }
+                        ["--battery", value, rest @ ..] => {
+                            profile.battery = Some(value.parse().map_err(|_| anyhow::anyhow!("Invalid battery level: {}", value))?);
+                            options = rest;
+                        }
+                        ["--charging", rest @ ..] => {
+                            profile.charging = true;
+                            options = rest;
+                        }
+                        [] => break,
+                        [other, ..] => return Err(anyhow::anyhow!("Unknown option: {}", other)),
+                    }
+                }
+                Some(profile)
+            }
+            _ => {
+                status!("{} Usage: spoof hw [--cores n] [--memory gb] [--battery 0-1] [--charging] | spoof hw off", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.spoof_hardware(profile).await
+    }
+
+    async fn cmd_flags(&self, args: &[&str]) -> Result<()> {
+        match args.first() {
+            None | Some(&"list") => {
+                let browser = self.browser.lock().await;
+                if browser.flag_presets().is_empty() {
+                    status!("{} No flags configured (add [flags.<name>] sections to browser-cli.toml)", "🚩".cyan());
+                }
+                for (name, preset) in browser.flag_presets() {
+                    let targets = [
+                        preset.cookie.as_ref().map(|c| format!("cookie {}", c)),
+                        preset.local_storage.as_ref().map(|k| format!("localStorage {}", k)),
+                        preset.header.as_ref().map(|h| format!("header {}", h)),
+                    ];
+                    let targets = targets.into_iter().flatten().collect::<Vec<_>>().join(", ");
+                    status!("  {} → {}", name.cyan(), targets);
+                }
+                Ok(())
+            }
+            Some(&"set") if args.len() > 1 => {
+                let assignments = args[1..].iter()
+                    .map(|pair| pair.split_once('=')
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .ok_or_else(|| anyhow::anyhow!("Expected <name>=<value>, got '{}'", pair)))
+                    .collect::<Result<Vec<_>>>()?;
+                
+                let mut browser = self.browser.lock().await;
+                browser.init().await?;
+                browser.apply_flags(&assignments).await
+            }
+            _ => {
+                status!("{} Usage: flags set <name>=<value>... | flags list", "⚠️".yellow());
+                Ok(())
+            }
+        }
+    }
+
+    async fn cmd_i18n(&self, args: &[&str]) -> Result<()> {
+        let flag = |name: &str| args.iter().position(|a| *a == name).and_then(|i| args.get(i + 1)).copied();
+        let langs = flag("--langs")
+            .map(|langs| langs.split(',').map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if args.first() != Some(&"check") || langs.is_empty() {
+            status!("{} Usage: i18n check --langs en,de,fr [--selector <sel>]", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.i18n_check(&langs, flag("--selector").unwrap_or("h1")).await
+    }
+
+    async fn cmd_audit(&self, args: &[&str]) -> Result<()> {
+        let option = |name: &str| args.iter().position(|a| *a == name).and_then(|i| args.get(i + 1)).copied();
+        if !matches!(args.first(), Some(&"html" | &"tab-order" | &"contrast")) {
+            status!("{} Usage: audit html | audit tab-order [--max-steps N] | audit contrast [--selector scope]", "⚠️".yellow());
+            status!("       add --from-sitemap <url> [--match pattern] [--since YYYY-MM-DD] [--ignore-robots]");
+            status!("       [--progress file] [--resume] to audit every page it lists");
+            return Ok(());
+        }
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let Some(sitemap) = option("--from-sitemap") else {
+            Self::run_audit(&mut browser, args).await?.print();
+            return Ok(());
+        };
+        
+        let filter = SitemapFilter {
+            pattern: option("--match").map(str::to_string),
+            since: option("--since").map(SitemapFilter::parse_since).transpose()?,
+        };
+        let urls = browser.sitemap_urls(sitemap, &filter).await?;
+        
+        // (errors, warnings) per audited page, so a resumed run skips them but still counts them
+        let kind = args[0];
+        // By default progress goes to the run's reports; --resume picks up the latest run's
+        let progress_name = format!("audit-{}-progress.jsonl", kind);
+        let resume = args.contains(&"--resume");
+        let progress_file = match (option("--progress"), resume.then(|| paths::latest_run_artifact(Artifact::Reports, &progress_name)).flatten()) {
+            (Some(file), _) => std::path::PathBuf::from(file),
+            (None, Some(latest)) => latest,
+            (None, None) => paths::run_artifacts(Artifact::Reports)?.join(&progress_name),
+        };
+        let job = format!("audit {} {}", kind, sitemap);
+        let mut checkpoint = Checkpoint::<(usize, usize)>::open(&progress_file, &job, resume)?;
+        if checkpoint.completed_count() > 0 {
+            status!("{} Resuming: {} of {} pages already audited, retrying {} that failed", "⏯".cyan(),
+                checkpoint.completed_count(), urls.len(), checkpoint.failed_count());
+        }
+        
+        let (mut errors, mut warnings, mut failed, mut duplicates) = (0, 0, 0, 0);
+        let ignore_robots = args.contains(&"--ignore-robots");
+        let mut visited = Visited::default();
+        for url in &urls {
+            let first_visit = visited.insert(url);
+            if let Some((page_errors, page_warnings)) = checkpoint.completed(url) {
+                errors += page_errors;
+                warnings += page_warnings;
+                continue;
+            }
+            // An earlier page redirected here or named this URL as its rel=canonical
+            if !first_visit {
+                duplicates += 1;
+                status!("{} {} was already audited under another URL; skipped", "↪".dimmed(), url);
+                checkpoint.complete(url, (0, 0))?;
+                continue;
+            }
+            // Ok(Err(other)) when the page turns out to be one already audited as `other`
+            let audited = async {
+                if !ignore_robots {
+                    browser.robots_allow(url).await?;
+                }
+                let landed = browser.navigate_retrying(url).await?.url;
+                // A redirect or rel=canonical can lead to a page that was already audited under another URL
+                let canonical = browser.canonical_url().await?;
+                for same in [Some(landed), canonical].into_iter().flatten() {
+                    if normalize_url(&same) != normalize_url(url) && !visited.insert(&same) {
+                        return Ok(Err(same));
+                    }
+                }
+                Self::run_audit(&mut browser, args).await.map(Ok)
+            }.await;
+            match audited {
+                Ok(Err(same)) => {
+                    duplicates += 1;
+                    status!("{} {} is the same page as {}; skipped", "↪".dimmed(), url, same);
+                    checkpoint.complete(url, (0, 0))?;
+                }
+                Ok(Ok(report)) => {
+                    let counts = (report.count(Severity::Error), report.count(Severity::Warning));
+                    errors += counts.0;
+                    warnings += counts.1;
+                    report.print();
+                    checkpoint.complete(url, counts)?;
+                }
+                Err(e) => {
+                    failed += 1;
+                    status!("{} {}: {}", "✗".red(), url, e);
+                    checkpoint.fail(url, &e.to_string())?;
+                }
+            }
+        }
+        let mut notes = String::new();
+        if failed > 0 {
+            notes.push_str(&format!(", {} failed to load", failed));
+        }
+        if duplicates > 0 {
+            notes.push_str(&format!(", {} duplicates skipped", duplicates));
+        }
+        status!("{} Audited {} pages: {} error(s), {} warning(s){}", "🔎".cyan(), urls.len(), errors, warnings, notes);
+        status!("  {} {}", "Progress:".dimmed(), checkpoint.path().display());
+        Ok(())
+    }
+
+    // The audit of the current page that `audit <kind> [options]` names
+    async fn run_audit(browser: &mut BrowserController, args: &[&str]) -> Result<AuditReport> {
+        let report = match args.first() {
+            Some(&"tab-order") => {
+                let max_steps = match args.iter().position(|a| *a == "--max-steps").and_then(|i| args.get(i + 1)) {
+                    Some(value) => value.parse::<usize>().map_err(|_| anyhow::anyhow!("Invalid --max-steps: {}", value))?,
+                    None => 100,
+                };
+                let (stops, report) = browser.audit_tab_order(max_steps).await?;
+                for (index, stop) in stops.iter().enumerate() {
+                    let indicator = if stop.focus_visible { "◉".green() } else { "○".red() };
+                    status!("  {:>3}. {} {} {} {}", index + 1, indicator, stop.role.cyan(),
+                        format!("\"{}\"", stop.name).bold(), stop.selector.dimmed());
+                }
+                report
+            }
+            Some(&"contrast") => {
+                let scope = args.iter().position(|a| *a == "--selector").and_then(|i| args.get(i + 1)).copied();
+                let (checked, report) = browser.audit_contrast(scope).await?;
+                status!("{} Checked {} text elements", "🔎".cyan(), checked);
+                report
+            }
+            _ => browser.audit_html().await?,
+        };
+        Ok(report)
+    }
+
+    async fn cmd_network(&self, args: &[&str]) -> Result<()> {
+        let browser = self.browser.lock().await;
+        match args.first() {
+            Some(&"waterfall") => {
+                let requests = browser.network_requests(args.contains(&"--all"));
+                if requests.is_empty() {
+                    status!("{} No requests captured yet", "⚠️".yellow());
+                    return Ok(());
+                }
+                
+                let out = args.iter().position(|a| *a == "--out").and_then(|i| args.get(i + 1)).copied().unwrap_or("txt");
+                match out {
+                    "txt" => network::waterfall_text(&requests).lines().for_each(|line| status!("{}", line)),
+                    out => {
+                        let name = if out == "svg" { "network-waterfall.svg" } else { out };
+                        let contents = if name.ends_with(".svg") {
+                            network::waterfall_svg(&requests)
+                        } else {
+                            network::waterfall_text(&requests)
+                        };
+                        let path = paths::capture_path(name)?;
+                        std::fs::write(&path, contents)
+                            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+                        status!("{} Waterfall of {} requests saved to {}", "✓".green(), requests.len(), path.display());
+                    }
+                }
+                Ok(())
+            }
+            Some(&"graphql") => {
+                let operation = args.iter().position(|a| *a == "--operation").and_then(|i| args.get(i + 1)).copied();
+                let report = network::graphql_report(&browser.network_requests(true), operation);
+                if report.is_empty() {
+                    match operation {
+                        Some(name) => status!("{} No GraphQL operation named {} captured", "⚠️".yellow(), name),
+                        None => status!("{} No GraphQL requests captured", "⚠️".yellow()),
+                    }
+                } else {
+                    report.lines().for_each(|line| status!("{}", line));
+                }
+                Ok(())
+            }
+            Some(&"clear") => {
+                browser.clear_network();
+                status!("{} Network capture cleared", "✓".green());
+                Ok(())
+            }
+            _ => {
+                status!("{} Usage: network waterfall [--all] [--out txt|svg|<file>] | network graphql [--operation name] | network clear", "⚠️".yellow());
+                Ok(())
+            }
+        }
+    }
+
+    async fn cmd_mock(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            ["from-har", path, rest @ ..] => {
+                let block_unmatched = match rest {
+                    [] | ["--unmatched", "pass"] => false,
+                    ["--unmatched", "block"] => true,
+                    _ => return Err(anyhow::anyhow!("Expected --unmatched pass|block")),
+                };
+                browser.init().await?;
+                browser.load_har_mocks(path, block_unmatched).await
+            }
+            ["off"] => {
+                browser.clear_mocks();
+                status!("{} Mocking stopped", "✓".green());
+                Ok(())
+            }
+            [] => {
+                match browser.mock_status() {
+                    Some(status) => status!("{} {}", "🎭".cyan(), status),
+                    None => status!("{} Not mocking", "🎭".cyan()),
+                }
+                Ok(())
+            }
+            _ => {
+                status!("{} Usage: mock from-har <file.har> [--unmatched pass|block] | mock off", "⚠️".yellow());
+                Ok(())
+            }
+        }
+    }
+
+    async fn cmd_compare_envs(&self, args: &[&str]) -> Result<()> {
+        let (mut base, mut target, mut paths, mut options) = (None, None, None, CompareOptions::default());
+        let (mut sitemap, mut filter) = (None, SitemapFilter::default());
+        let mut rest = args.iter();
+        while let Some(arg) = rest.next() {
+            // Flags first; every other option takes a value
+            match *arg {
+                "--ignore-robots" => {
+                    options.ignore_robots = true;
+                    continue;
+                }
+                "--resume" => {
+                    options.resume = true;
+                    continue;
+                }
+                _ => {}
+            }
+            let value = rest.next().ok_or_else(|| anyhow::anyhow!("{} needs a value", arg))?;
+            match *arg {
+                "--base" => base = Some(*value),
+                "--target" => target = Some(*value),
+                "--paths" => paths = Some(*value),
+                "--from-sitemap" => sitemap = Some(*value),
+                "--match" => filter.pattern = Some(value.to_string()),
+                "--since" => filter.since = Some(SitemapFilter::parse_since(value)?),
+                "--mode" => options.mode = CompareMode::parse(value)?,
+                "--threshold" => options.threshold = value.parse().map_err(|_| anyhow::anyhow!("Invalid --threshold: {}", value))?,
+                "--out" => options.out = Some(std::path::PathBuf::from(value)),
+                other => return Err(anyhow::anyhow!("Unknown option '{}'", other)),
+            }
+        }
+        let (Some(base), Some(target), true) = (base, target, paths.is_some() != sitemap.is_some()) else {
+            status!("{} Usage: compare-envs --base <url> --target <url> (--paths <file> | --from-sitemap <url> [--match p] [--since date]) [--mode visual|text] [--threshold pct] [--out dir] [--ignore-robots] [--resume]", "⚠️".yellow());
+            return Ok(());
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let paths = match (paths, sitemap) {
+            (Some(paths), _) => browser_cli::compare::read_paths(std::path::Path::new(paths))?,
+            (None, sitemap) => browser.sitemap_urls(sitemap.unwrap_or_default(), &filter).await?
+                .iter()
+                .map(|url| browser_cli::compare::url_path(url))
+                .collect(),
+        };
+        browser.compare_envs(base, target, &paths, &options).await?.print();
+        Ok(())
+    }
+
+    async fn cmd_crawl(&self, args: &[&str]) -> Result<()> {
+        let (mut start, mut options) = (None, CrawlOptions::default());
+        let mut rest = args.iter();
+        while let Some(arg) = rest.next() {
+            match *arg {
+                "--same-origin" => options.same_origin = true,
+                "--ignore-robots" => options.ignore_robots = true,
+                "--resume" => options.resume = true,
+                "--depth" | "--max-pages" | "--delay-ms" | "--out" | "--from-sitemap" | "--match" | "--since" => {
+                    let value = rest.next().ok_or_else(|| anyhow::anyhow!("{} needs a value", arg))?;
+                    let number = || value.parse::<usize>().map_err(|_| anyhow::anyhow!("Invalid {}: {}", arg, value));
+                    match *arg {
+                        "--depth" => options.depth = number()?,
+                        "--max-pages" => options.max_pages = number()?,
+                        "--delay-ms" => options.delay = std::time::Duration::from_millis(number()? as u64),
+                        "--from-sitemap" => options.from_sitemap = Some(value.to_string()),
+                        "--match" => options.sitemap_filter.pattern = Some(value.to_string()),
+                        "--since" => options.sitemap_filter.since = Some(SitemapFilter::parse_since(value)?),
+                        _ => options.out = Some(std::path::PathBuf::from(value)),
+                    }
+                }
+                other if other.starts_with("--") => return Err(anyhow::anyhow!("Unknown option '{}'", other)),
+                url => start = Some(url),
+            }
+        }
+        if start.is_none() && options.from_sitemap.is_none() {
+            status!("{} Usage: crawl (<url> | --from-sitemap <url> [--match p] [--since date]) [--depth N] [--same-origin] [--max-pages N] [--delay-ms N] [--ignore-robots] [--out dir] [--resume]", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.crawl(start, &options).await?.print();
+        Ok(())
+    }
+
+    async fn cmd_import_profile(&self, args: &[&str]) -> Result<()> {
+        let (mut profile, mut domains, mut local_storage, mut from_chrome) = (None, Vec::new(), false, false);
+        let mut rest = args.iter().peekable();
+        while let Some(arg) = rest.next() {
+            match *arg {
+                "--from-chrome" => {
+                    from_chrome = true;
+                    if let Some(name) = rest.next_if(|next| !next.starts_with("--")) {
+                        profile = Some(name.to_string());
+                    }
+                }
+                "--domain" => match rest.next() {
+                    Some(list) => domains.extend(list.split(',').filter(|d| !d.is_empty()).map(str::to_string)),
+                    None => return Err(anyhow::anyhow!("--domain needs a value")),
+                },
+                "--local-storage" => local_storage = true,
+                other => return Err(anyhow::anyhow!("Unknown option '{}'", other)),
+            }
+        }
+        if !from_chrome || domains.is_empty() {
+            status!("{} Usage: import-profile --from-chrome [profile] --domain a.com,b.com [--local-storage]", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.import_chrome_profile(profile.as_deref(), &domains, local_storage).await?;
+        Ok(())
+    }
+
+    async fn cmd_har(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            ["start"] => {
+                browser.init().await?;
+                browser.har_start(paths::run_artifacts(Artifact::Traces)?.join(format!("network-{}.har", chrono::Local::now().format("%H%M%S"))))
+            }
+            ["start", file] => {
+                browser.init().await?;
+                browser.har_start(paths::capture_path(file)?)
+            }
+            ["stop"] => {
+                browser.har_stop()?;
+                Ok(())
+            }
+            _ => {
+                status!("{} Usage: har start [file.har] | har stop", "⚠️".yellow());
+                Ok(())
+            }
+        }
+    }
+
+    async fn cmd_block(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            [] | ["list"] => {
+                let rules = browser.block_rules();
+                if rules.is_empty() {
+                    status!("{} No block rules", "🚫".cyan());
+                }
+                for rule in rules {
+                    status!("  {} {}", rule.pattern.cyan(), format!("({} blocked)", rule.hits).dimmed());
+                }
+                Ok(())
+            }
+            ["remove", pattern] => {
+                browser.unblock_urls(Some(pattern))?;
+                status!("{} No longer blocking {}", "✓".green(), pattern);
+                Ok(())
+            }
+            ["off"] | ["clear"] => {
+                let removed = browser.unblock_urls(None)?;
+                status!("{} Removed {} block rules", "✓".green(), removed);
+                Ok(())
+            }
+            ["remove"] => {
+                status!("{} Usage: block <url-glob>... | block list | block remove <glob> | block off", "⚠️".yellow());
+                Ok(())
+            }
+            patterns => {
+                let patterns: Vec<String> = patterns.iter().map(|pattern| pattern.to_string()).collect();
+                browser.init().await?;
+                browser.block_urls(&patterns).await
+            }
+        }
+    }
+
+    // RFC 3339 timestamps, or a date / date-time without offset taken as UTC
+    fn parse_instant(value: &str) -> Result<DateTime<FixedOffset>> {
+        if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+            return Ok(at);
+        }
+        let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+            .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| date.and_time(NaiveTime::MIN)))
+            .map_err(|_| anyhow::anyhow!("Invalid ISO 8601 time: {}", value))?;
+        Ok(naive.and_utc().fixed_offset())
+    }
+
+    async fn cmd_url(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let url = browser.get_url().await?;
+        status!("{} {}", "URL:".bold(), url.cyan());
+        Ok(())
+    }
+
+    async fn cmd_title(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let title = browser.get_title().await?;
+        status!("{} {}", "Title:".bold(), title.cyan());
+        Ok(())
+    }
+
+    async fn cmd_reload(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.reload().await
+    }
+
+    async fn cmd_back(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.go_back().await
+    }
+
+    async fn cmd_forward(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.go_forward().await
+    }
+
+    async fn cmd_wait_for(&self, args: &[&str]) -> Result<()> {
+        let mut condition = WaitCondition::default();
+        let mut positional = Vec::new();
+        let mut args = args;
+        loop {
+            match args {
+                ["--visible", rest @ ..] => {
+                    condition.visible = true;
+                    args = rest;
+                }
+                ["--enabled", rest @ ..] => {
+                    condition.enabled = true;
+                    args = rest;
+                }
+                ["--clickable", rest @ ..] => {
+                    condition.clickable = true;
+                    args = rest;
+                }
+                ["--attr", spec, rest @ ..] => {
+                    condition.attr = Some(WaitCondition::parse_attr(spec)?);
+                    args = rest;
+                }
+                [arg, rest @ ..] => {
+                    positional.push(*arg);
+                    args = rest;
+                }
+                [] => break,
+            }
+        }
+        
+        if positional.is_empty() {
+            status!("{} Usage: waitfor <selector> [timeout] [--visible] [--enabled] [--clickable] [--attr name=value]", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let selector = positional[0];
+        let timeout = positional.get(1).and_then(|s| s.parse().ok());
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.wait_for_selector(selector, timeout, &condition).await
+    }
+
+    async fn cmd_wait_for_text(&self, args: &[&str]) -> Result<()> {
+        if args.is_empty() {
+            status!("{} Usage: waitfortext <text> [timeout]", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        // Check if last argument is a number (timeout)
+        let (text, timeout) = if args.len() > 1 {
+            if let Ok(timeout_secs) = args.last().unwrap().parse::<u64>() {
+                let text_parts = &args[..args.len() - 1];
+                (text_parts.join(" "), Some(timeout_secs))
+            } else {
+                (args.join(" "), None)
+            }
+        } else {
+            (args.join(" "), None)
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.wait_for_text(&text, timeout).await
+    }
+
+    async fn cmd_wait_for_navigation(&self, args: &[&str]) -> Result<()> {
+        let timeout = args.first().and_then(|s| s.parse().ok());
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.wait_for_navigation(timeout).await
+    }
+
+    async fn cmd_wait_for_route(&self, args: &[&str]) -> Result<()> {
+        let (pattern, timeout) = match args {
+            [pattern] => (*pattern, None),
+            [pattern, timeout] => match timeout.parse() {
+                Ok(timeout) => (*pattern, Some(timeout)),
+                Err(_) => return Err(anyhow::anyhow!("Invalid timeout: {}", timeout)),
+            },
+            _ => {
+                status!("{} Usage: waitroute <pattern> [timeout]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.wait_for_route(pattern, timeout).await?;
+        Ok(())
+    }
+
+    async fn cmd_wait_for_network_idle(&self, args: &[&str]) -> Result<()> {
+        let (idle, timeout) = match args {
+            [] => (None, None),
+            [idle] => (Some(idle), None),
+            [idle, timeout] => (Some(idle), Some(timeout)),
+            _ => {
+                status!("{} Usage: waitfornetworkidle [idle-ms] [timeout]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        let idle_ms = match idle {
+            Some(idle) => idle.parse::<u64>().map_err(|_| anyhow::anyhow!("Invalid idle time '{}' (milliseconds)", idle))?,
+            None => DEFAULT_NETWORK_IDLE_MS,
+        };
+        let timeout = timeout.map(|t| t.parse::<u64>().map_err(|_| anyhow::anyhow!("Invalid timeout '{}' (seconds)", t))).transpose()?;
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.wait_for_network_idle(Duration::from_millis(idle_ms), timeout).await
+    }
+
+    // The server keeps running until the console exits
+    async fn cmd_serve_dir(&self, args: &[&str]) -> Result<()> {
+        let (mut dirs, mut port, mut open) = (Vec::new(), 0, false);
+        let mut rest = args;
+        while let [arg, tail @ ..] = rest {
+            rest = tail;
+            match (*arg, tail) {
+                ("--open", _) => open = true,
+                ("--port", [value, after @ ..]) => {
+                    port = value.parse().map_err(|_| anyhow::anyhow!("Invalid port '{}'", value))?;
+                    rest = after;
+                }
+                (dir, _) => dirs.push(dir),
+            }
+        }
+        let [path] = dirs[..] else {
+            status!("{} Usage: serve-dir <dir> [--port N] [--open]", "⚠️".yellow());
+            return Ok(());
+        };
+        let addr = browser_cli::serve::serve_dir(std::path::Path::new(path), port).await?;
+        if open {
+            let mut browser = self.browser.lock().await;
+            browser.init().await?;
+            browser.navigate(&format!("http://{}/", addr), NavigateOptions::default()).await?;
+        }
+        Ok(())
+    }
+
+    async fn cmd_exists(&self, args: &[&str]) -> Result<()> {
+        let [selector] = args else {
+            status!("{} Usage: exists <selector>", "⚠️".yellow());
+            return Ok(());
+        };
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        // Not matching is a failure, so `run` scripts and --fail-fast can branch on it like the CLI's exit code
+        match browser.count_elements(selector).await? {
+            0 => Err(Error::element_not_found(*selector).into()),
+            count => {
+                status!("{} {} exists ({} {})", "✓".green(), selector, count, if count == 1 { "match" } else { "matches" });
+                Ok(())
+            }
+        }
+    }
+
+    async fn cmd_count(&self, args: &[&str]) -> Result<()> {
+        let [selector] = args else {
+            status!("{} Usage: count <selector>", "⚠️".yellow());
+            return Ok(());
+        };
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        status!("{}", browser.count_elements(selector).await?);
+        Ok(())
+    }
+
+    async fn cmd_highlight(&self, args: &[&str]) -> Result<()> {
+        if args.is_empty() {
+            status!("{} Usage: highlight <selector>", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let selector = args[0];
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.highlight_element(selector).await
+    }
+
+    async fn cmd_tabs(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        for tab in browser.tabs().await? {
+            status!("{}", if tab.active { tab.to_string().green() } else { tab.to_string().normal() });
+        }
+        Ok(())
+    }
+
+    async fn cmd_new_tab(&self, args: &[&str]) -> Result<()> {
+        let url = args.first().copied().unwrap_or("about:blank");
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.new_tab(url).await?;
+        Ok(())
+    }
+
+    async fn cmd_switch_tab(&self, args: &[&str]) -> Result<()> {
+        let [tab] = args else {
+            status!("{} Usage: switchtab <index|id>", "⚠️".yellow());
+            return Ok(());
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.switch_tab(tab).await?;
+        Ok(())
+    }
+
+    async fn cmd_close_tab(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.close_tab(args.first().copied()).await?;
+        Ok(())
+    }
+
+    fn cmd_clear(&self) -> Result<()> {
+        print!("\x1B[2J\x1B[1;1H");
+        status!("{}", "🚀 Browser CLI Interactive Console".bold().cyan());
+        status!("{}", "Type 'help' for available commands, 'exit' to quit".dimmed());
+        status!();
+        Ok(())
+    }
+
+    async fn cmd_status(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let status = browser.get_status().await?;
+        status!("{}", status);
+        Ok(())
+    }
+
+    async fn cmd_about(&self) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        browser.about().await?.print();
+        Ok(())
+    }
+
+    async fn cmd_handoff(&self, args: &[&str]) -> Result<()> {
+        let open = match args {
+            [] => None,
+            ["--open"] => Some(false),
+            ["--open", "devtools"] => Some(true),
+            _ => {
+                status!("{} Usage: handoff [--open [devtools]]", "⚠️".yellow());
+                return Ok(());
+            }
+        };
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        let handoff = browser.handoff().await?;
+        handoff.print();
+        if let Some(devtools) = open {
+            let url = match devtools {
+                true => handoff.devtools.as_deref().ok_or_else(|| anyhow::anyhow!("No DevTools link for {}", handoff.websocket))?,
+                false => handoff.url.as_str(),
+            };
+            browser_cli::handoff::open_url(url)?;
+            status!("{} Opened {}", "🌐".green(), url);
+        }
+        Ok(())
+    }
+
+    async fn cmd_page_info(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        match args {
+            ["--json"] => status!("{}", serde_json::to_string_pretty(&browser.page_details().await?)?),
+            _ => status!("{}", browser.get_concise_page_info().await?),
+        }
+        Ok(())
+    }
+
+    async fn cmd_elements(&self, args: &[&str]) -> Result<()> {
+        let mut query = ElementQuery { limit: Some(50), ..Default::default() };
+        let mut args = args;
+        loop {
+            match args {
+                ["--filter", kind, rest @ ..] => {
+                    query.kind = Some(kind.to_string());
+                    args = rest;
+                }
+                ["--contains", text, rest @ ..] => {
+                    query.contains = Some(text.to_string());
+                    args = rest;
+                }
+                ["--offset", offset, rest @ ..] => {
+                    query.offset = offset.parse().map_err(|_| anyhow::anyhow!("Invalid --offset: {}", offset))?;
+                    args = rest;
+                }
+                ["--in-viewport", rest @ ..] => {
+                    query.scope = ElementScope::InViewport;
+                    args = rest;
+                }
+                ["--all", rest @ ..] => {
+                    query.scope = ElementScope::All;
+                    args = rest;
+                }
+                ["--limit", "all", rest @ ..] => {
+                    query.limit = None;
+                    args = rest;
+                }
+                ["--limit", limit, rest @ ..] => {
+                    query.limit = Some(limit.parse().map_err(|_| anyhow::anyhow!("Invalid --limit: {}", limit))?);
+                    args = rest;
+                }
+                [] => break,
+                _ => {
+                    status!("{} Usage: elements [--in-viewport|--all] [--filter buttons|links|inputs] [--contains text] [--offset N] [--limit N|all]", "⚠️".yellow());
+                    return Ok(());
+                }
+            }
+        }
+        
+        let mut browser = self.browser.lock().await;
+        browser.init().await?;
+        
+        let elements_info = browser.get_interactive_elements(&query).await?;
+        status!("{}", elements_info);
+        
+        Ok(())
+    }
+
+    async fn cmd_fill_field(&self, args: &[&str]) -> Result<()> {
         if args.len() < 2 {
-            println!("{} Usage: fill <selector> <value>", "⚠️".yellow());
+            status!("{} Usage: fill <selector> <value>", "⚠️".yellow());
             return Ok(());
         }
         
@@ -449,37 +1955,179 @@ impl Console {
     }
 
     async fn cmd_submit_form(&self, args: &[&str]) -> Result<()> {
-        let selector = args.get(0).copied();
+        let mut selector = None;
+        let mut wait = SubmitWait::None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match *arg {
+                "--wait-nav" => wait = SubmitWait::Navigation,
+                "--wait-selector" => match iter.next() {
+                    Some(sel) => wait = SubmitWait::Selector(sel.to_string()),
+                    None => {
+                        status!("{} Usage: submit [selector] [--wait-nav | --wait-selector <sel>]", "⚠️".yellow());
+                        return Ok(());
+                    }
+                },
+                sel => selector = Some(sel),
+            }
+        }
+        
         let mut browser = self.browser.lock().await;
         browser.init().await?;
-        browser.submit_form(selector).await
+        browser.submit_form(selector, wait).await
     }
 
-    async fn cmd_ticker(&self, args: &[&str]) -> Result<()> {
-        let selector = args.get(0).copied();
-        let interval = args.get(1).and_then(|s| s.parse::<u64>().ok()).unwrap_or(2);
-        let max_iterations = args.get(2).and_then(|s| s.parse::<u64>().ok());
+    async fn cmd_ticker(&mut self, args: &[&str]) -> Result<()> {
+        let mut options = TickerOptions::default();
+        let mut positional = Vec::new();
+        let mut args = args;
+        loop {
+            match args {
+                ["--json", rest @ ..] => {
+                    options.json = true;
+                    args = rest;
+                }
+                ["--min-change-chars", n, rest @ ..] => {
+                    options.min_change_chars = n.parse()
+                        .map_err(|_| anyhow::anyhow!("--min-change-chars expects a number, got '{}'", n))?;
+                    args = rest;
+                }
+                ["--debounce", secs, rest @ ..] => {
+                    let secs: f64 = secs.parse()
+                        .map_err(|_| anyhow::anyhow!("--debounce expects seconds, got '{}'", secs))?;
+                    options.debounce = std::time::Duration::from_secs_f64(secs.max(0.0));
+                    args = rest;
+                }
+                // Takes the rest of the line, since the command has spaces of its own
+                ["--on-change", command @ ..] if !command.is_empty() => {
+                    options.on_change = Some(command.join(" ").trim_matches('"').to_string());
+                    break;
+                }
+                ["--ignore-selectors", list, rest @ ..] => {
+                    options.ignore_selectors.extend(list.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from));
+                    args = rest;
+                }
+                [arg, rest @ ..] => {
+                    positional.push(*arg);
+                    args = rest;
+                }
+                [] => break,
+            }
+        }
+        
+        let selector = positional.first().copied();
+        let interval = positional.get(1).and_then(|s| s.parse::<u64>().ok()).unwrap_or(2);
+        let max_iterations = positional.get(2).and_then(|s| s.parse::<u64>().ok());
         
         if interval == 0 {
-            println!("{} Interval must be greater than 0 seconds", "⚠️".yellow());
+            status!("{} Interval must be greater than 0 seconds", "⚠️".yellow());
             return Ok(());
         }
         
-        let mut browser = self.browser.lock().await;
-        browser.init().await?;
+        {
+            let mut browser = self.browser.lock().await;
+            browser.init().await?;
+        }
         
-        if let Some(sel) = selector {
-            println!("{} Starting ticker for selector: {}", "⏱️".cyan(), sel);
-        } else {
-            println!("{} Starting page monitoring ticker", "⏱️".cyan());
+        if !options.json {
+            if let Some(sel) = selector {
+                status!("{} Starting ticker for selector: {}", "⏱️".cyan(), sel);
+            } else {
+                status!("{} Starting page monitoring ticker", "⏱️".cyan());
+            }
+            status!("{} Starting ticker ({}s intervals)...", "⏱️".cyan(), interval);
+        }
+        
+        // The browser is only locked for each check, so --on-change commands can drive it in between
+        let mut ticker = Ticker::new(selector, options.clone())?;
+        let watching = progress::spinner("Watching for changes");
+        let mut iteration = 0;
+        loop {
+            if max_iterations.is_some_and(|max| iteration >= max) {
+                watching.finish_and_clear();
+                if !options.json {
+                    status!("{} Ticker completed {} iterations", "✓".green(), iteration);
+                }
+                return Ok(());
+            }
+            
+            let check = self.browser.lock().await.ticker_check(&mut ticker).await;
+            match check {
+                Ok(TickerCheck::Baseline(state)) => {
+                    if !options.json {
+                        watching.suspend(|| {
+                            status!("{} Baseline established", "📊".cyan());
+                            status!("  {}", state.to_string().dimmed());
+                        });
+                    }
+                }
+                Ok(TickerCheck::Unchanged) => {
+                    watching.set_message(format!("Watching for changes ({} checks)", iteration + 1));
+                }
+                Ok(TickerCheck::Changed(changes)) => {
+                    watching.suspend(|| {
+                        for change in &changes {
+                            if options.json {
+                                status!("{}", change.to_json());
+                            } else {
+                                status!("{} {} {} changed: {} → {}",
+                                    "🔄".yellow(),
+                                    change.ts.format("%H:%M:%S"),
+                                    change.field.bold(),
+                                    change.before.to_string().dimmed(),
+                                    change.after
+                                );
+                            }
+                        }
+                    });
+                    if let Some(command) = &options.on_change {
+                        watching.suspend(|| status!("{} on change: {}", "🪝".cyan(), command));
+                        if let Err(e) = self.run_on_change(command, &changes).await {
+                            watching.suspend(|| status!("{} On-change '{}' failed: {}", "Error:".red().bold(), command, e));
+                        }
+                    }
+                }
+                Err(e) => {
+                    watching.suspend(|| warning!("{} Ticker error: {}", "⚠️".yellow(), e));
+                }
+            }
+            
+            iteration += 1;
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
         }
+    }
+
+    // Run a ticker --on-change action: a console command, or with a leading `!` a shell command that gets
+    // the change records as a JSON array in BROWSER_CLI_CHANGES
+    async fn run_on_change(&mut self, command: &str, changes: &[FieldChange]) -> Result<()> {
+        let Some(shell_command) = command.strip_prefix('!') else {
+            return Box::pin(self.execute_command(command)).await;
+        };
         
-        browser.start_ticker(selector, interval, max_iterations).await
+        let records = serde_json::Value::Array(changes.iter().map(FieldChange::to_json).collect());
+        let mut shell = if cfg!(windows) {
+            let mut shell = tokio::process::Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = tokio::process::Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        let status = shell.arg(shell_command.trim())
+            .env("BROWSER_CLI_CHANGES", records.to_string())
+            .status()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start shell: {}", e))?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("exited with {}", status));
+        }
+        Ok(())
     }
 
     async fn cmd_wait_enhanced(&self, args: &[&str]) -> Result<()> {
         if args.is_empty() {
-            println!("{} Usage: waitenhanced <selector> [timeout_seconds]", "⚠️".yellow());
+            status!("{} Usage: waitenhanced <selector> [timeout_seconds]", "⚠️".yellow());
             return Ok(());
         }
         
@@ -489,19 +2137,518 @@ impl Console {
         let mut browser = self.browser.lock().await;
         browser.init().await?;
         
-        match browser.wait_for_element_enhanced(selector, timeout).await {
-            Ok(found) => {
-                if found {
-                    println!("{} Element ready for interaction", "✅".green());
-                } else {
-                    println!("{} Element not found within timeout", "❌".red());
+        match browser.wait_for_element_enhanced(selector, timeout).await? {
+            Some(location) => {
+                status!("{} Element ready for interaction", "✅".green());
+                if location.frame.is_some() || !location.shadow_hosts.is_empty() {
+                    status!("   {} {}", "ℹ️".blue(), "Use js --frame or a shadow-piercing path to act on it".dimmed());
+                }
+                Ok(())
+            }
+            None => Err(Error::Timeout { what: format!("selector: '{}'", selector), secs: timeout, detail: String::new() }.into()),
+        }
+    }
+
+    async fn cmd_policy(&self) -> Result<()> {
+        let browser = self.browser.lock().await;
+        let policy = browser.url_policy();
+        if !policy.is_active() {
+            status!("{} No URL policy configured", "🛡️".cyan());
+        } else {
+            status!("{} {}", "🛡️".cyan(), policy.describe());
+        }
+        Ok(())
+    }
+
+    async fn cmd_watchdog(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        
+        for arg in args {
+            match *arg {
+                "on" => browser.set_auto_recover(true),
+                "off" => browser.set_auto_recover(false),
+                value => {
+                    let secs = value.parse::<u64>()
+                        .map_err(|_| anyhow::anyhow!("Invalid timeout: {}", value))?;
+                    browser.set_cdp_timeout(secs);
+                }
+            }
+        }
+        
+        status!("{} CDP timeout: {}s | auto-recover: {}", "⏱️".cyan(),
+            browser.cdp_timeout_secs(),
+            if browser.auto_recover() { "on" } else { "off" });
+        Ok(())
+    }
+
+    async fn cmd_isolation(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            [] => {}
+            ["on"] => browser.set_isolated_helpers(true),
+            ["off"] => browser.set_isolated_helpers(false),
+            _ => {
+                status!("{} Usage: isolation [on|off]", "⚠️".yellow());
+                return Ok(());
+            }
+        }
+        
+        status!("{} Helper scripts run in {}", "🧪".cyan(),
+            if browser.isolated_helpers() { "an isolated world" } else { "the page's world" });
+        Ok(())
+    }
+
+    async fn cmd_bypass_csp(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            [] => {}
+            ["on"] => browser.set_bypass_csp(true).await?,
+            ["off"] => browser.set_bypass_csp(false).await?,
+            _ => {
+                status!("{} Usage: bypass-csp [on|off]", "⚠️".yellow());
+                return Ok(());
+            }
+        }
+        
+        if browser.bypass_csp() {
+            status!("{} CSP bypass on (takes effect on the next navigation)", "🔓".cyan());
+        } else {
+            status!("{} CSP bypass off", "🔒".cyan());
+        }
+        Ok(())
+    }
+
+    async fn cmd_device_scale(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            [] => {}
+            ["off"] => browser.set_device_scale(None).await?,
+            [scale] => {
+                let scale = scale.parse().map_err(|_| anyhow::anyhow!("Invalid device scale: {}", scale))?;
+                browser.set_device_scale(Some(scale)).await?;
+            }
+            _ => {
+                status!("{} Usage: device-scale [factor|off]", "⚠️".yellow());
+                return Ok(());
+            }
+        }
+        
+        match browser.device_scale() {
+            Some(scale) => status!("{} Device scale {} (screenshots are {}x the CSS size)", "🖼️".cyan(), scale, scale),
+            None => status!("{} Device scale not forced", "🖼️".cyan()),
+        }
+        Ok(())
+    }
+
+    async fn cmd_emulate_media(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        let mut emulation = browser.media_emulation().clone();
+        match args {
+            [] => {}
+            ["off"] => emulation = MediaEmulation::default(),
+            _ => {
+                for pair in args.chunks(2) {
+                    match pair {
+                        ["--media", "off"] => emulation.media = None,
+                        ["--media", media] => emulation.media = Some(media.to_string()),
+                        [flag, value] if flag.starts_with("--") => {
+                            let value = Some(*value).filter(|value| *value != "off");
+                            emulation.set_feature(&flag[2..], value)?;
+                        }
+                        _ => {
+                            status!("{} Usage: emulate-media [--media print|screen|off] [--<feature> <value>|off ...] | off", "⚠️".yellow());
+                            let features: Vec<String> = MEDIA_FEATURES.iter()
+                                .map(|(name, values)| format!("--{} {}", name, values.join("|")))
+                                .collect();
+                            status!("  {}", features.join("  ").dimmed());
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+        if !args.is_empty() {
+            browser.set_media_emulation(emulation).await?;
+        }
+        
+        match browser.media_emulation() {
+            emulation if emulation.is_active() => status!("{} Emulating {}", "🖨️".cyan(), emulation),
+            _ => status!("{} Media emulation off", "🖨️".cyan()),
+        }
+        Ok(())
+    }
+
+    async fn cmd_runs(&self, args: &[&str]) -> Result<()> {
+        match args {
+            [] | ["list"] => {
+                let runs = paths::list_runs();
+                for run in &runs {
+                    status!("  {}", run.display());
+                }
+                status!("{} {} runs in {}", "🗂️".cyan(), runs.len(), paths::runs_dir().display());
+            }
+            ["prune", rest @ ..] => {
+                let keep = match rest {
+                    [] => paths::DEFAULT_RUNS_KEPT,
+                    ["--keep", keep] => keep.parse().map_err(|_| anyhow::anyhow!("Invalid --keep: {}", keep))?,
+                    _ => {
+                        status!("{} Usage: runs prune [--keep N]", "⚠️".yellow());
+                        return Ok(());
+                    }
+                };
+                let removed = paths::prune_runs(keep)?;
+                status!("{} Removed {} runs, kept the newest {}", "🧹".cyan(), removed.len(), keep.min(paths::list_runs().len()));
+            }
+            _ => status!("{} Usage: runs [list] | runs prune [--keep N]", "⚠️".yellow()),
+        }
+        Ok(())
+    }
+
+    async fn cmd_strict_csp(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            [] => {}
+            ["on"] => browser.set_strict_csp(true)?,
+            ["off"] => browser.set_strict_csp(false)?,
+            _ => {
+                status!("{} Usage: strict-csp [on|off]", "⚠️".yellow());
+                return Ok(());
+            }
+        }
+        
+        status!("{} Strict CSP mode {}", "🔒".cyan(), if browser.strict_csp() { "on" } else { "off" });
+        Ok(())
+    }
+
+    // Run hook commands for events raised by the last command; events raised by the hooks
+    // themselves are dropped so a hook can never retrigger itself
+    async fn run_hooks(&mut self) {
+        if self.hooks.is_empty() {
+            self.drain_events();
+            return;
+        }
+        
+        let session = self.session.clone();
+        for SessionEvent { event, .. } in self.drain_events().into_iter().filter(|e| e.session == session) {
+            let Some(commands) = self.hooks.get(event.name()).cloned() else {
+                continue;
+            };
+            
+            status!("{} on {}: {}", "🪝".cyan(), event.name(), event.describe().dimmed());
+            for command in commands {
+                if matches!(event, BrowserEvent::Dialog { .. }) && Self::dialog_action(&command).is_some() {
+                    // Already answered by the browser's dialog listener
+                    continue;
+                }
+                if let Err(e) = self.execute_command(&command).await {
+                    status!("{} Hook '{}' failed: {:#}", "Error:".red().bold(), command, e);
+                }
+            }
+        }
+        
+        self.drain_events();
+    }
+
+    fn publish_result(&self, command: &str, result: &Result<()>) {
+        if let Some(bus) = &self.event_bus {
+            bus.send(SessionEvent {
+                session: self.session.clone(),
+                event: BrowserEvent::CommandResult {
+                    command: command.to_string(),
+                    success: result.is_ok(),
+                    error: result.as_ref().err().map(|e| format!("{:#}", e)),
+                },
+            }).ok();
+        }
+    }
+
+    fn drain_events(&mut self) -> Vec<SessionEvent> {
+        let mut drained = Vec::new();
+        if let Some(events) = self.events.as_mut() {
+            loop {
+                match events.try_recv() {
+                    Ok(event) => drained.push(event),
+                    Err(TryRecvError::Lagged(_)) => continue,
+                    Err(_) => break,
+                }
+            }
+        }
+        drained
+    }
+
+    fn dialog_action(command: &str) -> Option<DialogAction> {
+        let mut parts = command.splitn(2, ' ');
+        match parts.next() {
+            Some("accept") => Some(DialogAction::Accept(parts.next().map(|text| text.to_string()))),
+            Some("dismiss") => Some(DialogAction::Dismiss),
+            _ => None,
+        }
+    }
+
+    async fn cmd_on(&mut self, args: &[&str]) -> Result<()> {
+        if args.is_empty() {
+            if self.hooks.is_empty() {
+                status!("{} No hooks registered", "🪝".cyan());
+            }
+            for event in EVENT_NAMES {
+                for command in self.hooks.get(event).into_iter().flatten() {
+                    status!("  {} {} {}", "on".cyan(), event, command);
+                }
+            }
+            return Ok(());
+        }
+        
+        if args.len() < 2 {
+            status!("{} Usage: on <event> <command>", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let event = args[0];
+        if !EVENT_NAMES.contains(&event) {
+            return Err(anyhow::anyhow!("Unknown event '{}'. Expected one of: {}", event, EVENT_NAMES.join(", ")));
+        }
+        
+        let command = args[1..].join(" ");
+        if event == "dialog" {
+            if let Some(action) = Self::dialog_action(&command) {
+                self.browser.lock().await.set_dialog_action(Some(action));
+            }
+        }
+        // These events are only published once something watches for them
+        if event == "title" || event == "audio" {
+            self.cmd_watch(&[event]).await?;
+        }
+        
+        status!("{} on {}: {}", "🪝".cyan(), event, command);
+        self.hooks.entry(event.to_string()).or_default().push(command);
+        Ok(())
+    }
+
+    async fn cmd_watch(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        match args {
+            ["title"] => {
+                browser.init().await?;
+                browser.watch_titles().await
+            }
+            ["audio"] => {
+                browser.init().await?;
+                browser.watch_audio().await
+            }
+            _ => {
+                status!("{} Usage: watch title | watch audio", "⚠️".yellow());
+                Ok(())
+            }
+        }
+    }
+
+    async fn cmd_off(&mut self, args: &[&str]) -> Result<()> {
+        if args.is_empty() {
+            status!("{} Usage: off <event>", "⚠️".yellow());
+            return Ok(());
+        }
+        
+        let event = args[0];
+        if self.hooks.remove(event).is_none() {
+            status!("{} No hooks for '{}'", "⚠️".yellow(), event);
+            return Ok(());
+        }
+        
+        if event == "dialog" {
+            self.browser.lock().await.set_dialog_action(None);
+        }
+        
+        status!("{} Removed hooks for {}", "✓".green(), event);
+        Ok(())
+    }
+
+    async fn cmd_session(&mut self, args: &[&str]) -> Result<()> {
+        match args.first().copied().unwrap_or("list") {
+            "list" | "ls" => {
+                let mut names: Vec<&String> = self.sessions.keys().collect();
+                names.sort();
+                for name in names {
+                    let browser = self.sessions[name].lock().await;
+                    let state = if browser.is_initialized() {
+                        browser.get_url().await.unwrap_or_default()
+                    } else {
+                        "not started".to_string()
+                    };
+                    let marker = if *name == self.session { "*" } else { " " };
+                    status!("  {} {} {}", marker.green(), name.cyan(), state.dimmed());
                 }
+                Ok(())
+            }
+            "create" | "new" => {
+                let Some(name) = args.get(1) else {
+                    status!("{} Usage: session create <name>", "⚠️".yellow());
+                    return Ok(());
+                };
+                profiles::check_session_name(name)?;
+                if self.sessions.contains_key(*name) {
+                    return Err(anyhow::anyhow!("Session '{}' already exists", name));
+                }
+                
+                let browser = self.browser.lock().await.new_session(name);
+                let browser = Arc::new(Mutex::new(browser));
+                Self::spawn_reaper(name, &browser);
+                self.sessions.insert(name.to_string(), browser);
+                status!("{} Created session: {}", "✓".green(), name);
+                self.switch_session(name)
+            }
+            "switch" | "use" => {
+                let Some(name) = args.get(1) else {
+                    status!("{} Usage: session switch <name>", "⚠️".yellow());
+                    return Ok(());
+                };
+                self.switch_session(name)
+            }
+            "destroy" | "rm" => {
+                let Some(name) = args.get(1) else {
+                    status!("{} Usage: session destroy <name>", "⚠️".yellow());
+                    return Ok(());
+                };
+                if *name == self.session {
+                    return Err(anyhow::anyhow!("Cannot destroy the active session; switch to another one first"));
+                }
+                let browser = self.sessions.remove(*name)
+                    .ok_or_else(|| anyhow::anyhow!("No session named '{}'", name))?;
+                browser.lock().await.close().await?;
+                status!("{} Destroyed session: {}", "✓".green(), name);
+                Ok(())
+            }
+            "stats" => self.cmd_session_stats().await,
+            "limits" => self.cmd_session_limits(&args[1..]).await,
+            other => {
+                status!("{} Unknown session command '{}'. Use list|create|switch|destroy|stats|limits", "⚠️".yellow(), other);
+                Ok(())
+            }
+        }
+    }
+
+    // close: the current session's browser; --session: that session here, or in another process if it isn't one of ours;
+    // --all: every session here and every browser-cli browser elsewhere. Sessions stay and relaunch on next use.
+    async fn cmd_close(&mut self, args: &[&str]) -> Result<()> {
+        let (local, external): (Vec<String>, Vec<profiles::ProfileDir>) = match args {
+            [] => (vec![self.session.clone()], Vec::new()),
+            ["--session", name] if self.sessions.contains_key(*name) => (vec![name.to_string()], Vec::new()),
+            ["--session", name] => {
+                let found: Vec<_> = profiles::list().into_iter().filter(|p| p.session == *name).collect();
+                if found.is_empty() {
+                    return Err(anyhow::anyhow!("No session named '{}' here or in other processes", name));
+                }
+                (Vec::new(), found)
+            }
+            ["--all"] => (self.sessions.keys().cloned().collect(), profiles::list()),
+            _ => {
+                status!("{} Usage: close [--session name|--all]", "⚠️".yellow());
+                return Ok(());
             }
-            Err(e) => {
-                println!("{} Wait error: {}", "⚠️".yellow(), e);
+        };
+        
+        for name in local {
+            let mut browser = self.sessions[&name].lock().await;
+            if browser.is_initialized() {
+                browser.close().await?;
+                status!("{} Closed session '{}'", "✓".green(), name);
+            }
+        }
+        profiles::shutdown_all(&external).await;
+        Ok(())
+    }
+
+    async fn cmd_session_stats(&self) -> Result<()> {
+        let mut names: Vec<&String> = self.sessions.keys().collect();
+        names.sort();
+        
+        for name in names {
+            let browser = self.sessions[name].lock().await;
+            if !browser.is_initialized() {
+                status!("  {} {}", name.cyan(), "not started".dimmed());
+                continue;
+            }
+            match browser.resource_stats().await {
+                Ok(stats) => status!(
+                    "  {} up:{}s idle:{}s heap:{:.1}/{:.1}MB nodes:{} cpu:{:.1}s",
+                    name.cyan(),
+                    stats.uptime.as_secs(),
+                    stats.idle.as_secs(),
+                    stats.js_heap_used_mb,
+                    stats.js_heap_total_mb,
+                    stats.dom_nodes,
+                    stats.cpu_seconds
+                ),
+                Err(e) => status!("  {} {}", name.cyan(), format!("stats unavailable: {}", e).red()),
+            }
+        }
+        
+        Ok(())
+    }
+
+    async fn cmd_session_limits(&self, args: &[&str]) -> Result<()> {
+        let mut browser = self.browser.lock().await;
+        let mut limits = browser.limits().clone();
+        
+        for arg in args {
+            let (key, value) = arg.split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Expected key=value, got '{}'", arg))?;
+            // 0 or "off" removes the limit
+            let value = match value {
+                "off" | "0" => None,
+                v => Some(v.parse::<u64>().map_err(|_| anyhow::anyhow!("Invalid value for {}: {}", key, v))?),
+            };
+            match key {
+                "idle" => limits.idle_timeout = value.map(Duration::from_secs),
+                "lifetime" => limits.max_lifetime = value.map(Duration::from_secs),
+                "memory" => limits.max_memory_mb = value,
+                _ => return Err(anyhow::anyhow!("Unknown limit '{}'. Use idle, lifetime, or memory", key)),
             }
         }
         
+        if !args.is_empty() {
+            browser.set_limits(limits.clone());
+        }
+        
+        let show = |value: Option<u64>, unit: &str| value.map(|v| format!("{}{}", v, unit)).unwrap_or("none".to_string());
+        status!("{} {} limits | idle: {} | lifetime: {} | memory: {}", "📏".cyan(), self.session,
+            show(limits.idle_timeout.map(|d| d.as_secs()), "s"),
+            show(limits.max_lifetime.map(|d| d.as_secs()), "s"),
+            show(limits.max_memory_mb, "MB"));
+        Ok(())
+    }
+
+    // Background reaper closing a session's browser once it breaks its limits; stops when the session is destroyed
+    fn spawn_reaper(name: &str, browser: &Arc<Mutex<BrowserController>>) {
+        let name = name.to_string();
+        let browser: Weak<Mutex<BrowserController>> = Arc::downgrade(browser);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                let Some(session) = browser.upgrade() else {
+                    break;
+                };
+                let reaped = session.lock().await.enforce_limits().await;
+                if let Some(reason) = reaped {
+                    status!("\n{} Session '{}' reaped: {}", "♻️".yellow(), name, reason);
+                }
+            }
+        });
+    }
+
+    fn switch_session(&mut self, name: &str) -> Result<()> {
+        let browser = self.sessions.get(name)
+            .ok_or_else(|| anyhow::anyhow!("No session named '{}'", name))?;
+        self.browser = Arc::clone(browser);
+        self.session = name.to_string();
+        status!("{} Active session: {}", "🗂️".cyan(), name);
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn close_sessions(&mut self) {
+        for (name, browser) in self.sessions.drain() {
+            if let Err(e) = browser.lock().await.close().await {
+                status!("{} Failed to close session {}: {}", "⚠️".yellow(), name, e);
+            }
+        }
+    }
+}
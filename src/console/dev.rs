@@ -0,0 +1,51 @@
+use anyhow::Result;
+use colored::*;
+use std::path::Path;
+use std::time::Duration;
+
+use browser_cli::browser::NavigateOptions;
+use browser_cli::fswatch::DirWatcher;
+use browser_cli::{serve, status};
+
+use super::Console;
+
+// What `dev` does on a change when no --on-change is given
+pub const DEFAULT_DEV_ACTION: &str = "reload";
+
+impl Console {
+    // `browser-cli dev`: open the page (the directory itself, served locally, unless a URL is given), then
+    // run the actions — console command lines such as `reload`, `screenshot` or `run checks.txt` — each time
+    // files under the directory change. A failing action is reported and the loop carries on; it ends with
+    // the process.
+    pub async fn dev(&mut self, dir: &Path, url: Option<&str>, actions: &[String], poll: Duration) -> Result<()> {
+        let mut watcher = DirWatcher::new(dir)?;
+        self.start_session().await;
+        let url = match url {
+            Some(url) => url.to_string(),
+            None => format!("http://{}/", serve::serve_dir(dir, 0).await?),
+        };
+        {
+            let mut browser = self.browser.lock().await;
+            browser.init().await?;
+            browser.navigate(&url, NavigateOptions::default()).await?;
+        }
+        status!("{} Watching {} ({} files); on change: {}", "👀".cyan(), watcher.root().display(), watcher.file_count(), actions.join(", "));
+        status!("{}", "Press Ctrl-C to stop".dimmed());
+
+        loop {
+            let changes = watcher.changed(poll).await;
+            let first = changes[0].strip_prefix(watcher.root()).unwrap_or(&changes[0]).display().to_string();
+            let changed = match changes.len() {
+                1 => first,
+                n => format!("{} and {} more", first, n - 1),
+            };
+            status!("{} {} changed", "🔄".cyan(), changed);
+            for action in actions {
+                if let Err(e) = Box::pin(self.execute_command(action)).await {
+                    status!("{} {}: {:#}", "⚠️".yellow(), action, e);
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use colored::*;
+
+use browser_cli::status;
+
+use super::pipe::{self, Filter};
+use super::Console;
+
+// The console's command table. The interactive prompt, `run` scripts (console and `browser-cli run`),
+// event hooks and ticker --on-change all come through here, so they accept exactly the same commands.
+impl Console {
+    pub(super) async fn execute_command(&mut self, input: &str) -> Result<()> {
+        let input = self.expand_variables(input)?;
+        let parts: Vec<&str> = input.split_whitespace().collect();
+
+        // `command | filter | …` pipes the command's output through console filters
+        let pipes: Vec<usize> = (0..parts.len()).filter(|&i| pipe::is_pipe(&parts, i)).collect();
+        let Some(&first) = pipes.first() else {
+            return self.dispatch(&parts).await;
+        };
+        let filters = pipes.iter()
+            .enumerate()
+            .map(|(n, &at)| Filter::parse(&parts[at + 1..pipes.get(n + 1).copied().unwrap_or(parts.len())]))
+            .collect::<Result<Vec<_>>>()?;
+        self.execute_pipeline(&parts[..first], &filters).await
+    }
+
+    pub(super) async fn dispatch(&mut self, parts: &[&str]) -> Result<()> {
+        if parts.is_empty() {
+            return Ok(());
+        }
+
+        let command = parts[0];
+        let args = &parts[1..];
+
+        match command {
+            "help" | "h" => self.show_help(),
+            "navigate" | "go" => self.cmd_navigate(args).await,
+            "click" => self.cmd_click(args).await,
+            "clickat" => self.cmd_click_at(args).await,
+            "hover" => self.cmd_hover(args).await,
+            "hoverat" => self.cmd_hover_at(args).await,
+            "drag" => self.cmd_drag(args).await,
+            "dragat" => self.cmd_drag_at(args).await,
+            "doubleclickat" => self.cmd_double_click_at(args).await,
+            "rightclickat" => self.cmd_right_click_at(args).await,
+            "type" => self.cmd_type(args).await,
+            "scroll" => self.cmd_scroll(args).await,
+            "search" => self.cmd_search(args).await,
+            "screenshot" | "ss" => self.cmd_screenshot(args).await,
+            "fetch" => self.cmd_fetch(args).await,
+            "assets" => self.cmd_assets(args).await,
+            "copytext" => self.cmd_copy_text(args).await,
+            "html" => self.cmd_html(args).await,
+            "markdown" | "md" => self.cmd_markdown(args).await,
+            "article" => self.cmd_article(args).await,
+            "scrape" => self.cmd_scrape(args).await,
+            "table" => self.cmd_table(args).await,
+            "links" => self.cmd_links(args).await,
+            "exists" => self.cmd_exists(args).await,
+            "count" => self.cmd_count(args).await,
+            "assert-js" => self.cmd_assert_js(args).await,
+            "freeze-time" => self.cmd_freeze_time(args).await,
+            "seed" => self.cmd_seed(args).await,
+            "flags" => self.cmd_flags(args).await,
+            "i18n" => self.cmd_i18n(args).await,
+            "audit" => self.cmd_audit(args).await,
+            "network" => self.cmd_network(args).await,
+            "mock" => self.cmd_mock(args).await,
+            "block" => self.cmd_block(args).await,
+            "har" => self.cmd_har(args).await,
+            "import-profile" => self.cmd_import_profile(args).await,
+            "compare-envs" => self.cmd_compare_envs(args).await,
+            "text" => self.cmd_text(args).await,
+            "js" | "eval" => self.cmd_javascript(args).await,
+            "url" => self.cmd_url().await,
+            "title" => self.cmd_title().await,
+            "reload" | "refresh" => self.cmd_reload().await,
+            "back" => self.cmd_back().await,
+            "forward" => self.cmd_forward().await,
+            "waitfor" => self.cmd_wait_for(args).await,
+            "waitfortext" => self.cmd_wait_for_text(args).await,
+            "waitfornav" => self.cmd_wait_for_navigation(args).await,
+            "waitfornetworkidle" => self.cmd_wait_for_network_idle(args).await,
+            "waitroute" => self.cmd_wait_for_route(args).await,
+            "highlight" => self.cmd_highlight(args).await,
+            "tabs" => self.cmd_tabs().await,
+            "newtab" => self.cmd_new_tab(args).await,
+            "switchtab" => self.cmd_switch_tab(args).await,
+            "closetab" => self.cmd_close_tab(args).await,
+            "clear" | "cls" => self.cmd_clear(),
+            "status" => self.cmd_status().await,
+            "about" => self.cmd_about().await,
+            "info" => self.cmd_page_info(args).await,
+            "handoff" => self.cmd_handoff(args).await,
+            "elements" => self.cmd_elements(args).await,
+            "fill" => self.cmd_fill_field(args).await,
+            "submit" => self.cmd_submit_form(args).await,
+            "ticker" => Box::pin(self.cmd_ticker(args)).await,
+            "waitenhanced" => self.cmd_wait_enhanced(args).await,
+            "watchdog" => self.cmd_watchdog(args).await,
+            "isolation" => self.cmd_isolation(args).await,
+            "bypass-csp" => self.cmd_bypass_csp(args).await,
+            "device-scale" => self.cmd_device_scale(args).await,
+            "emulate-media" => self.cmd_emulate_media(args).await,
+            "runs" => self.cmd_runs(args).await,
+            "crawl" => self.cmd_crawl(args).await,
+            "strict-csp" => self.cmd_strict_csp(args).await,
+            "policy" => self.cmd_policy().await,
+            "serve-dir" => self.cmd_serve_dir(args).await,
+            "session" | "sessions" => self.cmd_session(args).await,
+            "close" => self.cmd_close(args).await,
+            "on" => self.cmd_on(args).await,
+            "watch" => self.cmd_watch(args).await,
+            "run" => Box::pin(self.cmd_run(args)).await,
+            "set" => self.cmd_set(args),
+            "press-hold" => self.cmd_press_hold(args).await,
+            "canvas" => self.cmd_canvas(args).await,
+            "ss-focus-walk" => self.cmd_focus_walk(args).await,
+            "ss-print" => self.cmd_print_capture(args).await,
+            "ss-sequence" => self.cmd_screenshot_sequence(args).await,
+            "notifications" => self.cmd_notifications(args).await,
+            "spoof" => self.cmd_spoof(args).await,
+            "press" => self.cmd_press(args).await,
+            "keydown" => self.cmd_key(args, true).await,
+            "keyup" => self.cmd_key(args, false).await,
+            "hold" => Box::pin(self.cmd_hold(args)).await,
+            "off" => self.cmd_off(args).await,
+            // An error rather than a warning so scripts stop at a typo instead of skipping the step
+            _ => Err(anyhow::anyhow!("Unknown command: '{}'. Type 'help' for available commands.", command)),
+        }
+    }
+
+    // `set NAME value…` defines ${NAME} for later commands, `set NAME` removes it, `set` lists them
+    fn cmd_set(&mut self, args: &[&str]) -> Result<()> {
+        match args {
+            [] if self.variables.is_empty() => status!("{}", "No variables set".dimmed()),
+            [] => {
+                let mut names: Vec<_> = self.variables.keys().collect();
+                names.sort();
+                for name in names {
+                    status!("  {} = {}", name.cyan(), self.variables[name]);
+                }
+            }
+            [name] => {
+                self.variables.remove(*name);
+            }
+            [name, value @ ..] => {
+                if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(anyhow::anyhow!("Invalid variable name '{}' (letters, digits and _ only)", name));
+                }
+                self.variables.insert(name.to_string(), value.join(" "));
+            }
+        }
+        Ok(())
+    }
+
+    // Replace ${NAME} with a `set` variable and ${env:NAME} with an environment variable. Other ${…}
+    // are left alone, so JS template literals in `js` commands pass through untouched.
+    fn expand_variables(&self, input: &str) -> Result<String> {
+        let mut expanded = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            expanded.push_str(&rest[..start]);
+            let Some(len) = rest[start + 2..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let name = &rest[start + 2..start + 2 + len];
+            let whole = &rest[start..start + 3 + len];
+            match name.strip_prefix("env:") {
+                Some(var) => expanded.push_str(&std::env::var(var)
+                    .map_err(|_| anyhow::anyhow!("Environment variable {} is not set", var))?),
+                None => expanded.push_str(self.variables.get(name).map(String::as_str).unwrap_or(whole)),
+            }
+            rest = &rest[start + 3 + len..];
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+}
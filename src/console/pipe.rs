@@ -0,0 +1,207 @@
+use anyhow::Result;
+use colored::*;
+use serde_json::Value;
+use std::io::Write;
+
+use browser_cli::{jsonpath, output, paths, status};
+
+use super::Console;
+
+// Filters a command's output can be piped through. A lone | only pipes when one of these follows it, so
+// `js a | b` still reaches the page as written.
+pub(super) const FILTERS: [&str; 5] = ["grep", "head", "tail", "json-path", "save"];
+
+// Lines head and tail keep when no count is given
+const DEFAULT_LINES: usize = 10;
+
+// One stage after a | in `command | filter | …`
+pub(super) enum Filter {
+    // Lines containing the text (-i ignores case, -v keeps the lines that don't)
+    Grep { pattern: String, ignore_case: bool, invert: bool },
+    Head(usize),
+    Tail(usize),
+    // Values a JSONPath selects from the JSON in the output, one per line
+    JsonPath(String),
+    // Write the lines to a file (under browser-ss/ for a bare name, like other captures) instead of showing them; -a adds them
+    // to the end of the file instead of replacing it
+    Save { file: String, append: bool },
+}
+
+impl Filter {
+    pub(super) fn parse(stage: &[&str]) -> Result<Self> {
+        let lines = |name: &str, args: &[&str]| match args {
+            [] => Ok(DEFAULT_LINES),
+            [count] => count.parse().map_err(|_| anyhow::anyhow!("{}: '{}' is not a line count", name, count)),
+            _ => Err(anyhow::anyhow!("Usage: ... | {} [lines]", name)),
+        };
+        match stage {
+            ["grep", args @ ..] => {
+                let (mut args, mut ignore_case, mut invert) = (args, false, false);
+                while let [flag @ ("-i" | "-v" | "-iv" | "-vi"), rest @ ..] = args {
+                    ignore_case |= flag.contains('i');
+                    invert |= flag.contains('v');
+                    args = rest;
+                }
+                if args.is_empty() {
+                    return Err(anyhow::anyhow!("Usage: ... | grep [-i] [-v] <text>"));
+                }
+                Ok(Self::Grep { pattern: args.join(" "), ignore_case, invert })
+            }
+            ["head", args @ ..] => Ok(Self::Head(lines("head", args)?)),
+            ["tail", args @ ..] => Ok(Self::Tail(lines("tail", args)?)),
+            ["json-path", path] => Ok(Self::JsonPath(path.to_string())),
+            ["json-path", ..] => Err(anyhow::anyhow!("Usage: ... | json-path <path> (e.g. $.items[0].name)")),
+            ["save", file] => Ok(Self::Save { file: file.to_string(), append: false }),
+            ["save", "-a" | "--append", file] => Ok(Self::Save { file: file.to_string(), append: true }),
+            ["save", ..] => Err(anyhow::anyhow!("Usage: ... | save [-a] <file>")),
+            _ => Err(anyhow::anyhow!("Unknown filter '{}' (filters: {})", stage.join(" "), FILTERS.join(", "))),
+        }
+    }
+
+    fn apply(&self, lines: Vec<String>) -> Result<Vec<String>> {
+        Ok(match self {
+            Self::Grep { pattern, ignore_case, invert } => {
+                let pattern = if *ignore_case { pattern.to_lowercase() } else { pattern.clone() };
+                lines.into_iter()
+                    .filter(|line| {
+                        let found = if *ignore_case { line.to_lowercase().contains(&pattern) } else { line.contains(&pattern) };
+                        found != *invert
+                    })
+                    .collect()
+            }
+            Self::Head(count) => lines.into_iter().take(*count).collect(),
+            Self::Tail(count) => {
+                let skip = lines.len().saturating_sub(*count);
+                lines.into_iter().skip(skip).collect()
+            }
+            Self::JsonPath(path) => {
+                let json = find_json(&lines.join("\n"))?;
+                jsonpath::select(&json, path)?.into_iter()
+                    .flat_map(|value| jsonpath::render(value).lines().map(str::to_string).collect::<Vec<_>>())
+                    .collect()
+            }
+            Self::Save { file, append } => {
+                let path = paths::capture_path(file)?;
+                let mut contents = lines.join("\n");
+                if !contents.is_empty() {
+                    contents.push('\n');
+                }
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(*append)
+                    .write(true)
+                    .truncate(!*append)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(contents.as_bytes()))
+                    .map_err(|e| anyhow::anyhow!("Failed to save {}: {}", path.display(), e))?;
+                let verb = if *append { "Appended" } else { "Saved" };
+                status!("{} {} {} lines to {}", "💾".green(), verb, lines.len(), path.display());
+                Vec::new()
+            }
+        })
+    }
+}
+
+impl Console {
+    // `command | filter | …`: run the command with its output captured, then show what's left of it after
+    // the filters. Filters see the plain text, without colours or hyperlinks, and without the progress
+    // lines ("Getting text from …") the command printed on the way.
+    pub(super) async fn execute_pipeline(&mut self, parts: &[&str], filters: &[Filter]) -> Result<()> {
+        if parts.is_empty() {
+            return Err(anyhow::anyhow!("Nothing to pipe: a command has to come before |"));
+        }
+
+        output::start_capture();
+        let result = self.dispatch(parts).await;
+        let captured = output::take_capture();
+        result?;
+
+        for line in filter_lines(&captured, filters)? {
+            status!("{}", line);
+        }
+        Ok(())
+    }
+}
+
+fn filter_lines(captured: &str, filters: &[Filter]) -> Result<Vec<String>> {
+    let mut lines: Vec<String> = strip_ansi(captured).lines().map(str::to_string).collect();
+    for filter in filters {
+        lines = filter.apply(lines)?;
+    }
+    Ok(lines)
+}
+
+// Where a lone | starts a filter stage, so `js a | b` isn't split
+pub(super) fn is_pipe(parts: &[&str], index: usize) -> bool {
+    parts[index] == "|" && parts.get(index + 1).is_some_and(|next| FILTERS.contains(next))
+}
+
+// The first JSON object or array in the output; status lines before it and after it are skipped
+fn find_json(text: &str) -> Result<Value> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = line.trim_start();
+        if start.starts_with('{') || start.starts_with('[') {
+            let from = offset + line.len() - start.len();
+            if let Some(Ok(json)) = serde_json::Deserializer::from_str(&text[from..]).into_iter::<Value>().next() {
+                return Ok(json);
+            }
+        }
+        offset += line.len();
+    }
+    Err(anyhow::anyhow!("json-path: no JSON in the output (try the command's --format json or --json variant)"))
+}
+
+// Drop terminal escape sequences: colours (ESC [ … letter) and hyperlinks (ESC ] … BEL or ESC \)
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use browser_cli::progress;
+
+    // One test, as captures are process-wide
+    #[test]
+    fn filters_see_only_the_value() {
+        // What `text .price` prints: a progress line, then the value
+        output::start_capture();
+        progress!("{}", "Getting text from: .price".blue());
+        status!("{}", "$42.00".cyan());
+        let captured = output::take_capture();
+        assert_eq!(filter_lines(&captured, &[Filter::Head(1)]).unwrap(), ["$42.00"]);
+
+        let path = std::env::temp_dir().join(format!("browser-cli-pipe-test-{}.txt", std::process::id()));
+        let save = Filter::Save { file: path.display().to_string(), append: false };
+        assert!(filter_lines(&captured, &[save]).unwrap().is_empty());
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(saved, "$42.00\n");
+    }
+}
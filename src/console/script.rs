@@ -0,0 +1,161 @@
+use anyhow::Result;
+use colored::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use browser_cli::paths::{self, Artifact};
+use browser_cli::status;
+
+use super::Console;
+
+// A file of console commands: one per line, blank lines and lines starting with # ignored
+pub struct Script {
+    path: String,
+    // File line number and command of each step
+    steps: Vec<(usize, String)>,
+}
+
+impl Script {
+    pub fn load(path: &str) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read script {}: {}", path, e))?;
+        let steps = source.lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| (number, line.to_string()))
+            .collect();
+        Ok(Self { path: path.to_string(), steps })
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    // File name without extension, naming the script's artifacts in the run directory
+    fn name(&self) -> String {
+        Path::new(&self.path).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "script".to_string())
+    }
+}
+
+// `--screenshots after-each` gallery directory for a run (screenshots/<script> in the run directory), None
+// without the option
+fn gallery(mode: Option<&str>, script: &Script) -> Result<Option<PathBuf>> {
+    match mode {
+        None => Ok(None),
+        Some("after-each") => Ok(Some(paths::run_subdir(Artifact::Screenshots, &script.name())?)),
+        Some(mode) => Err(anyhow::anyhow!("Unknown screenshot mode '{}'. Use after-each", mode)),
+    }
+}
+
+// "5 passed, 1 failed, 2 not run", leaving out the zero counts after passed
+fn summary(script: &Script, ran: usize, failed: usize) -> String {
+    let mut parts = vec![format!("{} passed", ran - failed)];
+    if failed > 0 {
+        parts.push(format!("{} failed", failed));
+    }
+    let not_run = script.step_count() - ran;
+    if not_run > 0 {
+        parts.push(format!("{} not run", not_run));
+    }
+    parts.join(", ")
+}
+
+impl Console {
+    pub(super) async fn cmd_run(&mut self, args: &[&str]) -> Result<()> {
+        let mode = args.iter().position(|a| *a == "--screenshots").map(|i| args.get(i + 1).copied().unwrap_or_default());
+        let Some(path) = args.iter().find(|a| !a.starts_with("--") && Some(**a) != mode).copied() else {
+            status!("{} Usage: run <file> [--screenshots after-each] [--fail-fast|--continue-on-error]", "⚠️".yellow());
+            return Ok(());
+        };
+        // --fail-fast is the default; the last of the two given wins
+        let continue_on_error = args.iter().rev()
+            .find_map(|a| match *a {
+                "--continue-on-error" => Some(true),
+                "--fail-fast" => Some(false),
+                _ => None,
+            })
+            .unwrap_or(false);
+        
+        let script = Script::load(path)?;
+        let gallery = gallery(mode, &script)?;
+        self.execute_script(&script, gallery, continue_on_error).await
+    }
+
+    // `browser-cli run`: a script against the initial session with the given variables preset, closing
+    // every session it opened afterwards. Fails with the first failing step.
+    pub async fn run_script(&mut self, script: &Script, variables: &[(String, String)], screenshots: Option<&str>, continue_on_error: bool) -> Result<()> {
+        let gallery = gallery(screenshots, script)?;
+        self.start_session().await;
+        self.variables.extend(variables.iter().cloned());
+        let result = self.execute_script(script, gallery, continue_on_error).await;
+        self.close_sessions().await;
+        result
+    }
+
+    // Run the steps in order. A failing step stops the script unless `continue_on_error`, in which case the
+    // rest still run and the script fails at the end. Either way it ends with a passed/failed summary. Each
+    // step's outcome is also logged to logs/<script>.log in the run directory.
+    async fn execute_script(&mut self, script: &Script, gallery: Option<PathBuf>, continue_on_error: bool) -> Result<()> {
+        let log_path = paths::run_artifacts(Artifact::Logs)?.join(format!("{}.log", script.name()));
+        let mut log = std::fs::OpenOptions::new().create(true).append(true).open(&log_path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", log_path.display(), e))?;
+        writeln!(log, "{} run {}", chrono::Local::now().to_rfc3339(), script.path)?;
+        let mut failures = Vec::new();
+        let mut ran = 0;
+        for (index, (number, line)) in script.steps.iter().enumerate() {
+            let step = index + 1;
+            status!("{} [{}] {}", "▶".cyan(), step, line);
+            
+            let started = std::time::Instant::now();
+            let mut result = self.execute_command(line).await;
+            ran += 1;
+            let outcome = match &result {
+                Ok(()) => "ok".to_string(),
+                Err(e) => format!("FAILED: {:#}", e),
+            };
+            writeln!(log, "[{}] {}:{} {} ({} ms) {}", step, script.path, number, line, started.elapsed().as_millis(), outcome)?;
+            self.publish_result(line, &result);
+            self.run_hooks().await;
+            
+            // A screenshot that can't be taken fails its step, which --continue-on-error then carries on past
+            if let Some(dir) = &gallery {
+                let browser = self.browser.lock().await;
+                if browser.is_initialized() {
+                    let name = line.split_whitespace().next().unwrap_or("step");
+                    let path = dir.join(format!("{:03}-{}.png", step, name)).display().to_string();
+                    if let Err(e) = browser.screenshot(Some(&path)).await {
+                        writeln!(log, "[{}] {}:{} step screenshot FAILED: {:#}", step, script.path, number, e)?;
+                        if result.is_ok() {
+                            result = Err(e.context("Step screenshot failed"));
+                        }
+                    }
+                }
+            }
+            
+            // Context rather than a new error, so the exit code still reflects what went wrong
+            if let Err(e) = result {
+                let e = e.context(format!("Step {} ({}:{}: {}) failed", step, script.path, number, line));
+                if !continue_on_error {
+                    status!("{} Script stopped: {}", "✗".red(), summary(script, ran, 1));
+                    status!("  {} {}", "Log:".dimmed(), log_path.display());
+                    return Err(e);
+                }
+                status!("  {} {:#}", "✗".red(), e);
+                failures.push(e);
+            }
+        }
+        
+        if let Some(dir) = &gallery {
+            status!("{} Step screenshots in {}", "📸".cyan(), dir.display());
+        }
+        status!("  {} {}", "Log:".dimmed(), log_path.display());
+        let failed = failures.len();
+        let Some(first) = failures.into_iter().next() else {
+            status!("{} Script finished: {}", "✓".green(), summary(script, ran, 0));
+            return Ok(());
+        };
+        status!("{} Script finished: {}", "✗".red(), summary(script, ran, failed));
+        Err(first.context(format!("{} of {} steps failed; the first was", failed, script.step_count())))
+    }
+}
@@ -0,0 +1,230 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::paths::{self, Artifact};
+use crate::sitemap::SitemapFilter;
+
+// Pages finished so far, in the crawl's directory; see Checkpoint
+pub const PROGRESS_FILE: &str = "progress.jsonl";
+
+// The form batch visits compare URLs in, so the same page isn't visited twice under trivially different
+// URLs: the fragment is dropped, query parameters are sorted by name (repeats of a name keep their order,
+// which can matter to the server) and an empty query is removed. Scheme and host case and default ports
+// are already normalized by parsing. Anything that doesn't parse is kept as given.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    parsed.set_fragment(None);
+    let mut pairs: Vec<(String, String)> = parsed.query_pairs().into_owned().collect();
+    if pairs.is_empty() {
+        parsed.set_query(None);
+    } else {
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    parsed.to_string()
+}
+
+// Pages a batch job has visited, by normalized URL. A page's rel=canonical URL is recorded alongside it,
+// so another URL for the same content is recognised as already visited.
+#[derive(Debug, Clone, Default)]
+pub struct Visited {
+    seen: HashSet<String>,
+}
+
+impl Visited {
+    // Record a URL; false when it (or a URL normalizing to it) was already visited
+    pub fn insert(&mut self, url: &str) -> bool {
+        self.seen.insert(normalize_url(url))
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.seen.contains(&normalize_url(url))
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+// Defaults of `crawl`: how many links deep it follows from the start page, how many pages it visits at
+// most, and the pause between visits when robots.txt doesn't ask for a longer one
+pub const DEFAULT_CRAWL_DEPTH: usize = 2;
+pub const DEFAULT_CRAWL_MAX_PAGES: usize = 100;
+pub const DEFAULT_CRAWL_DELAY_MS: u64 = 500;
+
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    // Links followed from the start page; 0 visits only the start page
+    pub depth: usize,
+    // Only follow links to the start page's origin
+    pub same_origin: bool,
+    pub max_pages: usize,
+    pub delay: Duration,
+    pub ignore_robots: bool,
+    // Where page files and the report go (default: reports/crawl in the run directory)
+    pub out: Option<PathBuf>,
+    // Sitemap whose pages are crawled from too, as depth-0 starting points, and which of them
+    pub from_sitemap: Option<String>,
+    pub sitemap_filter: SitemapFilter,
+    // Continue the crawl in `out` (or the latest run's) instead of starting over
+    pub resume: bool,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_CRAWL_DEPTH,
+            same_origin: false,
+            max_pages: DEFAULT_CRAWL_MAX_PAGES,
+            delay: Duration::from_millis(DEFAULT_CRAWL_DELAY_MS),
+            ignore_robots: false,
+            out: None,
+            from_sitemap: None,
+            sitemap_filter: SitemapFilter::default(),
+            resume: false,
+        }
+    }
+}
+
+// One URL the crawl reached: the files saved for it, or why it has none
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlPage {
+    pub url: String,
+    pub depth: usize,
+    // Where the page landed after redirects
+    pub final_url: Option<String>,
+    pub title: Option<String>,
+    // Links on the page the crawl hadn't seen yet and queued (within --depth)
+    pub new_links: usize,
+    // Those links, so a resumed crawl queues them again without revisiting the page
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub queued: Vec<String>,
+    pub text_file: Option<PathBuf>,
+    pub html_file: Option<PathBuf>,
+    pub screenshot: Option<PathBuf>,
+    // The earlier page this one turned out to be (via redirect or rel=canonical)
+    pub duplicate_of: Option<String>,
+    pub error: Option<String>,
+}
+
+impl CrawlPage {
+    pub fn new(url: &str, depth: usize) -> Self {
+        Self {
+            url: url.to_string(),
+            depth,
+            final_url: None,
+            title: None,
+            new_links: 0,
+            queued: Vec::new(),
+            text_file: None,
+            html_file: None,
+            screenshot: None,
+            duplicate_of: None,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlReport {
+    pub start: String,
+    pub depth: usize,
+    pub same_origin: bool,
+    pub dir: PathBuf,
+    pub report: PathBuf,
+    // In visiting order, which is breadth-first
+    pub pages: Vec<CrawlPage>,
+    // Links left in the queue when --max-pages was reached
+    pub unvisited: usize,
+}
+
+// Newest crawl directory of any run that has progress to resume
+pub fn latest_run_dir() -> Option<PathBuf> {
+    paths::latest_run_subdir(Artifact::Reports, "crawl", PROGRESS_FILE)
+}
+
+impl CrawlReport {
+    pub fn failures(&self) -> usize {
+        self.pages.iter().filter(|page| page.error.is_some()).count()
+    }
+
+    pub fn print(&self) {
+        status!("{} Crawl of {} (depth {}{})", "🕸️".cyan(), self.start.bold(), self.depth, if self.same_origin { ", same origin" } else { "" });
+        for page in &self.pages {
+            let depth = format!("[{}]", page.depth).dimmed();
+            match (&page.error, &page.duplicate_of) {
+                (Some(error), _) => status!("  {} {} {} {}", "✗".red(), depth, page.url, error.dimmed()),
+                (None, Some(same)) => status!("  {} {} {} {}", "↪".dimmed(), depth, page.url, format!("same page as {}", same).dimmed()),
+                (None, None) => status!("  {} {} {} {}", "✓".green(), depth, page.url, page.title.as_deref().unwrap_or_default().dimmed()),
+            }
+        }
+        let mut notes = String::new();
+        if self.failures() > 0 {
+            notes.push_str(&format!(", {} failed", self.failures()));
+        }
+        if self.unvisited > 0 {
+            notes.push_str(&format!(", {} links left unvisited (--max-pages)", self.unvisited));
+        }
+        status!("{} Crawled {} pages{} — report: {}", "🕸️".cyan(), self.pages.len(), notes, self.report.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_drops_the_fragment() {
+        assert_eq!(normalize_url("https://example.com/page#section"), "https://example.com/page");
+    }
+
+    #[test]
+    fn normalize_sorts_query_parameters() {
+        assert_eq!(normalize_url("https://example.com/search?b=2&a=1"), "https://example.com/search?a=1&b=2");
+    }
+
+    #[test]
+    fn normalize_keeps_the_order_of_repeated_parameters() {
+        assert_eq!(normalize_url("https://example.com/search?a=2&a=1"), "https://example.com/search?a=2&a=1");
+        assert_eq!(normalize_url("https://example.com/search?z=0&a=2&b=1&a=1"), "https://example.com/search?a=2&a=1&b=1&z=0");
+        assert_ne!(normalize_url("https://example.com/search?a=2&a=1"), normalize_url("https://example.com/search?a=1&a=2"));
+    }
+
+    #[test]
+    fn normalize_removes_an_empty_query() {
+        assert_eq!(normalize_url("https://example.com/page?"), "https://example.com/page");
+        assert_eq!(normalize_url("https://example.com/page?#top"), "https://example.com/page");
+    }
+
+    #[test]
+    fn normalize_leaves_case_of_scheme_and_host_and_default_ports_to_parsing() {
+        assert_eq!(normalize_url("HTTPS://Example.COM:443/Path"), "https://example.com/Path");
+    }
+
+    #[test]
+    fn normalize_keeps_unparseable_urls() {
+        assert_eq!(normalize_url("not a url"), "not a url");
+    }
+
+    #[test]
+    fn visited_recognises_equivalent_urls() {
+        let mut visited = Visited::default();
+        assert!(visited.is_empty());
+        assert!(visited.insert("https://example.com/list?page=2&sort=name#results"));
+        assert!(!visited.insert("https://example.com/list?sort=name&page=2"));
+        assert!(visited.contains("https://example.com/list?sort=name&page=2#top"));
+        assert!(!visited.contains("https://example.com/list?page=3&sort=name"));
+        assert!(visited.insert("https://example.com/other?"));
+        assert!(!visited.insert("https://example.com/other"));
+        assert_eq!(visited.len(), 2);
+    }
+}
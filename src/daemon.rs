@@ -0,0 +1,301 @@
+use anyhow::Result;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+
+use crate::auth::{constant_time_eq, AccessPolicy};
+use crate::browser::BrowserController;
+use crate::{event_stream, paths};
+
+// How long a client waits on the control endpoint before deciding the daemon is gone
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(2);
+// How often the daemon checks that its browser is still there and within its session limits
+const LIVENESS_INTERVAL: Duration = Duration::from_secs(2);
+
+// What a daemon publishes about itself in the temp dir so commands for its session can find it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    pub pid: u32,
+    pub session: String,
+    // Loopback address of the control endpoint
+    pub control: String,
+    pub token: String,
+    pub started: i64,
+}
+
+// One line of JSON per request and per response on the control endpoint
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+enum Request {
+    Status { token: String },
+    Attach { token: String },
+    Activate { token: String, target_id: String },
+    HarStart { token: String, path: PathBuf },
+    HarStop { token: String },
+    Stop { token: String },
+}
+
+// Where a client attaches: the browser's DevTools socket and the daemon's current page
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AttachTarget {
+    pub websocket: String,
+    pub target_id: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<AttachTarget>,
+    // File and request count of a finished HAR capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    har: Option<(PathBuf, usize)>,
+}
+
+const STATE_PREFIX: &str = "browser-cli-daemon-";
+
+fn state_path(session: &str) -> PathBuf {
+    paths::temp_root().join(format!("{}{}.json", STATE_PREFIX, session))
+}
+
+// Sessions with a daemon state file, running or stale; `find` tells which
+pub fn sessions() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(paths::temp_root()) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<String> = entries.flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            Some(name.strip_prefix(STATE_PREFIX)?.strip_suffix(".json")?.to_string())
+        })
+        .collect();
+    sessions.sort();
+    sessions
+}
+
+// Unguessable enough to keep other local users off the control endpoint, without pulling in an RNG crate
+fn new_token() -> String {
+    let state = std::collections::hash_map::RandomState::new();
+    let mut token = String::new();
+    for salt in 0..2u64 {
+        let mut hasher = state.build_hasher();
+        hasher.write_u64(salt);
+        hasher.write_u32(std::process::id());
+        hasher.write_i64(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default());
+        token.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    token
+}
+
+fn write_state(info: &DaemonInfo) -> Result<()> {
+    let path = state_path(&info.session);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to write daemon state {}: {}", path.display(), e))?;
+    std::io::Write::write_all(&mut file, serde_json::to_string_pretty(info)?.as_bytes())?;
+    Ok(())
+}
+
+fn read_state(session: &str) -> Option<DaemonInfo> {
+    let contents = std::fs::read_to_string(state_path(session)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+// Drop the state file of this process's daemon, if it runs one
+pub fn remove_owned_state(session: &str) {
+    if read_state(session).is_some_and(|info| info.pid == std::process::id()) {
+        std::fs::remove_file(state_path(session)).ok();
+    }
+}
+
+async fn request(info: &DaemonInfo, request: &Request) -> Result<Response> {
+    let exchange = async {
+        let mut stream = TcpStream::connect(&info.control).await?;
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).await?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply).await?;
+        Ok::<Response, anyhow::Error>(serde_json::from_str(&reply)?)
+    };
+    let response = tokio::time::timeout(CONTROL_TIMEOUT, exchange).await
+        .map_err(|_| anyhow::anyhow!("Daemon at {} did not answer", info.control))??;
+    match response.ok {
+        true => Ok(response),
+        false => Err(anyhow::anyhow!(response.error.unwrap_or_else(|| "Daemon refused the request".to_string()))),
+    }
+}
+
+// The running daemon for a session; a state file nobody answers for is stale and removed
+pub async fn find(session: &str) -> Option<DaemonInfo> {
+    let info = read_state(session)?;
+    if info.pid == std::process::id() {
+        return None;
+    }
+    match request(&info, &Request::Status { token: info.token.clone() }).await {
+        Ok(_) => Some(info),
+        Err(_) => {
+            std::fs::remove_file(state_path(session)).ok();
+            None
+        }
+    }
+}
+
+// Where to attach for a session with a running daemon, None to launch a browser of our own
+pub async fn attach_target(session: &str) -> Option<(DaemonInfo, AttachTarget)> {
+    let info = find(session).await?;
+    let response = request(&info, &Request::Attach { token: info.token.clone() }).await.ok()?;
+    Some((info, response.target?))
+}
+
+// Make one of the daemon browser's tabs the page later clients attach to
+pub async fn activate(session: &str, target_id: &str) -> Result<()> {
+    let info = find(session).await
+        .ok_or_else(|| anyhow::anyhow!("The daemon for session '{}' is no longer running", session))?;
+    request(&info, &Request::Activate { token: info.token.clone(), target_id: target_id.to_string() }).await?;
+    Ok(())
+}
+
+// Start a HAR capture in the daemon's browser, which outlives the CLI invocation asking for it
+pub async fn har_start(session: &str, path: &Path) -> Result<()> {
+    let info = find(session).await
+        .ok_or_else(|| anyhow::anyhow!("The daemon for session '{}' is no longer running", session))?;
+    request(&info, &Request::HarStart { token: info.token.clone(), path: path.to_path_buf() }).await?;
+    Ok(())
+}
+
+// Finish the daemon's HAR capture; returns the file and how many requests it holds
+pub async fn har_stop(session: &str) -> Result<(PathBuf, usize)> {
+    let info = find(session).await
+        .ok_or_else(|| anyhow::anyhow!("The daemon for session '{}' is no longer running", session))?;
+    let response = request(&info, &Request::HarStop { token: info.token.clone() }).await?;
+    response.har.ok_or_else(|| anyhow::anyhow!("Daemon did not report the saved HAR"))
+}
+
+// Ask a session's daemon to close its browser and exit. Returns the daemon that was stopped, if any.
+pub async fn stop(session: &str) -> Result<Option<DaemonInfo>> {
+    let Some(info) = find(session).await else {
+        return Ok(None);
+    };
+    request(&info, &Request::Stop { token: info.token.clone() }).await?;
+    Ok(Some(info))
+}
+
+// Keep this process's browser alive for other browser-cli invocations of the same session to attach to,
+// until a `stop` request arrives, the browser goes away or it breaks its session limits. With `events`,
+// its browser events are also published as a Server-Sent Events stream on that address.
+pub async fn serve(browser: Arc<Mutex<BrowserController>>, bind: &str, events: Option<(String, AccessPolicy)>) -> Result<()> {
+    let session = browser.lock().await.session_name().to_string();
+    if let Some(info) = find(&session).await {
+        return Err(anyhow::anyhow!("A daemon for session '{}' is already running (pid {})", session, info.pid));
+    }
+
+    let listener = TcpListener::bind(bind).await
+        .map_err(|e| anyhow::anyhow!("Failed to bind daemon control endpoint {}: {}", bind, e))?;
+    let addr = listener.local_addr()?;
+    if !addr.ip().is_loopback() {
+        return Err(anyhow::anyhow!("The daemon control endpoint must be a loopback address, got {}", addr));
+    }
+    browser.lock().await.init().await?;
+    if let Some((addr, policy)) = events {
+        event_stream::serve(&addr, Arc::clone(&browser), policy).await?;
+    }
+
+    let info = DaemonInfo {
+        pid: std::process::id(),
+        session: session.clone(),
+        control: addr.to_string(),
+        token: new_token(),
+        started: chrono::Utc::now().timestamp(),
+    };
+    write_state(&info)?;
+    status!("{} Daemon for session '{}' listening on {} (pid {})", "🛰️".cyan(), session, addr, info.pid);
+    status!("{}", "Other browser-cli commands for this session now reuse this browser; stop with `browser-cli close`".dimmed());
+
+    let reason = loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else {
+                    continue;
+                };
+                match handle(stream, &browser, &info.token).await {
+                    Ok(true) => break "stop requested".to_string(),
+                    Ok(false) => {}
+                    Err(e) => warning!("{} Control request failed: {}", "⚠️".yellow(), e),
+                }
+            }
+            _ = tokio::time::sleep(LIVENESS_INTERVAL) => {
+                let mut browser = browser.lock().await;
+                if let Some(reason) = browser.enforce_limits().await {
+                    break reason;
+                }
+                if !browser.is_responsive().await {
+                    break "browser exited".to_string();
+                }
+            }
+        }
+    };
+
+    status!("{} Daemon stopping: {}", "🛰️".cyan(), reason);
+    std::fs::remove_file(state_path(&session)).ok();
+    browser.lock().await.close().await.ok();
+    Ok(())
+}
+
+// Answer one control request; true when the daemon should stop
+async fn handle(stream: TcpStream, browser: &Arc<Mutex<BrowserController>>, token: &str) -> Result<bool> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    tokio::time::timeout(CONTROL_TIMEOUT, BufReader::new(reader).read_line(&mut line)).await
+        .map_err(|_| anyhow::anyhow!("client sent no request"))??;
+
+    let request: Request = serde_json::from_str(&line)?;
+    let given = match &request {
+        Request::Status { token } | Request::Attach { token } | Request::Activate { token, .. }
+            | Request::HarStart { token, .. } | Request::HarStop { token } | Request::Stop { token } => token,
+    };
+    let (response, stop) = if !constant_time_eq(given, token) {
+        (Response { ok: false, error: Some("Invalid daemon token".to_string()), target: None, har: None }, false)
+    } else {
+        match request {
+            Request::Status { .. } => (Response { ok: true, error: None, target: None, har: None }, false),
+            Request::Attach { .. } => match browser.lock().await.attach_target().await {
+                Ok(target) => (Response { ok: true, error: None, target: Some(target), har: None }, false),
+                Err(e) => (Response { ok: false, error: Some(e.to_string()), target: None, har: None }, false),
+            },
+            Request::Activate { target_id, .. } => match browser.lock().await.switch_tab(&target_id).await {
+                Ok(_) => (Response { ok: true, error: None, target: None, har: None }, false),
+                Err(e) => (Response { ok: false, error: Some(e.to_string()), target: None, har: None }, false),
+            },
+            Request::HarStart { path, .. } => match browser.lock().await.har_start(path) {
+                Ok(()) => (Response { ok: true, error: None, target: None, har: None }, false),
+                Err(e) => (Response { ok: false, error: Some(e.to_string()), target: None, har: None }, false),
+            },
+            Request::HarStop { .. } => match browser.lock().await.har_stop() {
+                Ok(saved) => (Response { ok: true, error: None, target: None, har: Some(saved) }, false),
+                Err(e) => (Response { ok: false, error: Some(e.to_string()), target: None, har: None }, false),
+            },
+            Request::Stop { .. } => (Response { ok: true, error: None, target: None, har: None }, true),
+        }
+    };
+
+    let mut reply = serde_json::to_string(&response)?;
+    reply.push('\n');
+    writer.write_all(reply.as_bytes()).await?;
+    Ok(stop)
+}
@@ -0,0 +1,71 @@
+use thiserror::Error;
+
+// Failures library callers may want to tell apart. They travel inside anyhow::Error like every other
+// error here; match on them with `err.downcast_ref::<browser_cli::Error>()`.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Browser not initialized")]
+    NotInitialized,
+    #[error("Element not found: {selector}{detail}")]
+    ElementNotFound { selector: String, detail: String },
+    #[error("Timeout waiting for {what} after {secs} seconds{detail}")]
+    Timeout { what: String, secs: u64, detail: String },
+    #[error("CDP call '{operation}' timed out after {secs}s{}", if *.crashed { " (renderer crashed)" } else { "" })]
+    CdpTimeout { operation: String, secs: u64, crashed: bool },
+    #[error("{0}")]
+    BrowserNotFound(String),
+    #[error("Failed to launch {}: {reason}", .path.display())]
+    Launch { path: std::path::PathBuf, reason: String },
+    #[error("Blocked by policy: {0}")]
+    Blocked(String),
+    #[error("Navigation to {url} failed: {reason}")]
+    Navigation { url: String, reason: String },
+    #[error("Invalid session name '{0}' (use only letters, digits and _)")]
+    InvalidSessionName(String),
+}
+
+// Process exit codes, stable across releases so scripts and CI can tell failures apart. Signals exit with
+// 128 + the signal number as usual.
+pub const EXIT_SUCCESS: i32 = 0;
+// Anything without a more specific code
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_ELEMENT_NOT_FOUND: i32 = 2;
+// A wait or a DevTools call ran out of time
+pub const EXIT_TIMEOUT: i32 = 3;
+pub const EXIT_NAVIGATION: i32 = 4;
+// No browser found, or it failed to start
+pub const EXIT_LAUNCH: i32 = 5;
+// The URL policy refused a URL
+pub const EXIT_BLOCKED: i32 = 6;
+// Bad command line (sysexits EX_USAGE)
+pub const EXIT_USAGE: i32 = 64;
+
+impl Error {
+    pub(crate) fn timeout(what: impl Into<String>, secs: u64) -> Self {
+        Self::Timeout { what: what.into(), secs, detail: String::new() }
+    }
+
+    pub fn element_not_found(selector: impl Into<String>) -> Self {
+        Self::ElementNotFound { selector: selector.into(), detail: String::new() }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ElementNotFound { .. } => EXIT_ELEMENT_NOT_FOUND,
+            Self::Timeout { .. } | Self::CdpTimeout { .. } => EXIT_TIMEOUT,
+            Self::Navigation { .. } => EXIT_NAVIGATION,
+            Self::BrowserNotFound(_) | Self::Launch { .. } => EXIT_LAUNCH,
+            Self::Blocked(_) => EXIT_BLOCKED,
+            Self::InvalidSessionName(_) => EXIT_USAGE,
+            Self::NotInitialized => EXIT_FAILURE,
+        }
+    }
+}
+
+// Exit code for a failed command: that of the first Error in its chain of causes, so context added on the
+// way up (a failing script step) keeps the code of what actually went wrong
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    error.chain()
+        .find_map(|cause| cause.downcast_ref::<Error>())
+        .map_or(EXIT_FAILURE, Error::exit_code)
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use colored::*;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::Mutex;
+use tokio::time::{interval, Duration};
+
+use crate::auth::{AccessPolicy, Scope};
+use crate::browser::{BrowserController, NavigateOptions};
+use crate::events::{BrowserEvent, EventPublisher, SessionEvent};
+
+// How long a client gets to send its request head, and how big it may be
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+// A POST /navigate body is just the URL
+const MAX_NAVIGATE_BODY: usize = 8 * 1024;
+
+// Serve the browser event bus as a Server-Sent Events stream so external orchestrators
+// can react to navigations, console output, network traffic, and command results. Tokens with the
+// interactive scope may also drive the browser: `POST /navigate` with the URL as the body.
+pub async fn serve(addr: &str, browser: Arc<Mutex<BrowserController>>, policy: AccessPolicy) -> Result<()> {
+    let socket_addr = tokio::net::lookup_host(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Invalid event stream address {}: {}", addr, e))?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Invalid event stream address: {}", addr))?;
+    policy.check_bind(&socket_addr)?;
+    
+    let listener = TcpListener::bind(socket_addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind event stream on {}: {}", addr, e))?;
+    
+    let scheme = if policy.tls().is_some() { "https" } else { "http" };
+    status!("{} Event stream: {}://{}/events", "📡".cyan(), scheme, listener.local_addr()?);
+    
+    let bus = browser.lock().await.event_bus();
+    let policy = Arc::new(policy);
+    tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let browser = Arc::clone(&browser);
+            let events = bus.subscribe();
+            let policy = Arc::clone(&policy);
+            tokio::spawn(async move {
+                match policy.tls() {
+                    Some(acceptor) => {
+                        // A client that never finishes the handshake is dropped like one that never sends a request
+                        if let Ok(Ok(stream)) = tokio::time::timeout(REQUEST_HEAD_TIMEOUT, acceptor.accept(stream)).await {
+                            answer(stream, &browser, events, &policy).await.ok();
+                        }
+                    }
+                    None => {
+                        answer(stream, &browser, events, &policy).await.ok();
+                    }
+                }
+            });
+        }
+    });
+    
+    Ok(())
+}
+
+async fn answer<S>(mut stream: S, browser: &Mutex<BrowserController>, events: broadcast::Receiver<SessionEvent>, policy: &AccessPolicy) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // POST /navigate drives the browser; any other path gets the full stream
+    let request = read_request_head(&mut stream).await?;
+    let path = request.split_whitespace().nth(1).unwrap_or("/").split('?').next().unwrap_or("/");
+    let navigate = path == "/navigate";
+    let required = if navigate { Scope::Interactive } else { Scope::ReadOnly };
+    if let Err(denied) = policy.authorize(&request, required) {
+        return respond(&mut stream, denied.status_line(), "").await;
+    }
+    if navigate {
+        return navigate_to(stream, &request, browser).await;
+    }
+    stream_events(stream, events).await
+}
+
+async fn stream_events<S>(mut stream: S, mut events: broadcast::Receiver<SessionEvent>) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // No Access-Control-Allow-Origin: without a token the stream is open to anything that can reach the
+    // port, and CORS is what keeps web pages the user happens to have open from reading it
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\n\
+          Content-Type: text/event-stream\r\n\
+          Cache-Control: no-cache\r\n\
+          Connection: keep-alive\r\n\r\n",
+    ).await?;
+    stream.flush().await?;
+    
+    let mut keepalive = interval(Duration::from_secs(15));
+    loop {
+        let chunk = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => format!("event: {}\ndata: {}\n\n", event.event.name(), event.to_json()),
+                Err(RecvError::Lagged(skipped)) => format!(": {} events dropped\n\n", skipped),
+                Err(RecvError::Closed) => break,
+            },
+            _ = keepalive.tick() => ": keepalive\n\n".to_string(),
+        };
+        stream.write_all(chunk.as_bytes()).await?;
+        stream.flush().await?;
+    }
+    
+    Ok(())
+}
+
+// Navigate to the URL in the body and answer with the page summary, publishing the outcome like a console
+// command's
+async fn navigate_to<S>(mut stream: S, request: &str, browser: &Mutex<BrowserController>) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    if !request.starts_with("POST ") {
+        return respond(&mut stream, "405 Method Not Allowed", "").await;
+    }
+    let body = read_request_body(&mut stream, request, MAX_NAVIGATE_BODY).await?;
+    let url = body.trim();
+    if url.is_empty() {
+        return respond(&mut stream, "400 Bad Request", &serde_json::json!({ "error": "POST the URL to navigate to as the body" }).to_string()).await;
+    }
+    let mut browser = browser.lock().await;
+    let result = browser.navigate(url, NavigateOptions::default()).await;
+    EventPublisher::new(browser.session_name(), browser.event_bus()).publish(BrowserEvent::CommandResult {
+        command: format!("navigate {}", url),
+        success: result.is_ok(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    });
+    drop(browser);
+    match result {
+        Ok(summary) => respond(&mut stream, "200 OK", &serde_json::to_string(&summary)?).await,
+        Err(e) => respond(&mut stream, "502 Bad Gateway", &serde_json::json!({ "error": format!("{:#}", e) }).to_string()).await,
+    }
+}
+
+async fn respond<S: AsyncWrite + Unpin>(stream: &mut S, status: &str, json: &str) -> Result<()> {
+    let content_type = if json.is_empty() { "" } else { "Content-Type: application/json\r\n" };
+    let response = format!("HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}", status, content_type, json.len(), json);
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+// Everything up to and including the blank line ending the head, plus whatever of the body came with it.
+// A client that stays silent or sends an endless head is cut off rather than holding its task forever.
+pub(crate) async fn read_request_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    
+    let read_head = async {
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            if head.len() > MAX_REQUEST_HEAD {
+                return Err(anyhow::anyhow!("Request head larger than {} bytes", MAX_REQUEST_HEAD));
+            }
+            let read = stream.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..read]);
+        }
+        Ok(())
+    };
+    tokio::time::timeout(REQUEST_HEAD_TIMEOUT, read_head).await
+        .map_err(|_| anyhow::anyhow!("No request head within {}s", REQUEST_HEAD_TIMEOUT.as_secs()))??;
+    
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+// The body after a head read by read_request_head, as long as its Content-Length says, up to `limit` bytes
+async fn read_request_body<S: AsyncRead + Unpin>(stream: &mut S, request: &str, limit: usize) -> Result<String> {
+    let (head, received) = request.split_once("\r\n\r\n").unwrap_or((request, ""));
+    let length = head.lines().skip(1)
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+        })
+        .unwrap_or(received.len());
+    if length > limit {
+        return Err(anyhow::anyhow!("Request body larger than {} bytes", limit));
+    }
+    let mut body = received.as_bytes().to_vec();
+    if body.len() < length {
+        let mut rest = vec![0u8; length - body.len()];
+        tokio::time::timeout(REQUEST_HEAD_TIMEOUT, stream.read_exact(&mut rest)).await
+            .map_err(|_| anyhow::anyhow!("Request body incomplete after {}s", REQUEST_HEAD_TIMEOUT.as_secs()))??;
+        body.extend_from_slice(&rest);
+    }
+    body.truncate(length);
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn request_head_is_capped() {
+        let (mut client, mut server) = tokio::io::duplex(64 * 1024);
+        let endless = format!("GET /events HTTP/1.1\r\nX-Padding: {}", "a".repeat(MAX_REQUEST_HEAD * 2));
+        client.write_all(endless.as_bytes()).await.unwrap();
+        assert!(read_request_head(&mut server).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn body_follows_content_length() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client.write_all(b"POST /navigate HTTP/1.1\r\nContent-Length: 20\r\n\r\nhttps://exa").await.unwrap();
+        let head = read_request_head(&mut server).await.unwrap();
+        client.write_all(b"mple.com/").await.unwrap();
+        assert_eq!(read_request_body(&mut server, &head, MAX_NAVIGATE_BODY).await.unwrap(), "https://example.com/");
+
+        let oversized = "POST /navigate HTTP/1.1\r\nContent-Length: 100000\r\n\r\n";
+        assert!(read_request_body(&mut server, oversized, MAX_NAVIGATE_BODY).await.is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use serde_json::json;
+use tokio::sync::broadcast;
+
+// Event names accepted by hooks (`on <event> <command>`)
+pub const EVENT_NAMES: [&str; 7] = ["navigate", "dialog", "download", "console-error", "notification", "title", "audio"];
+
+// Page lifecycle events published by the browser on its event bus
+#[derive(Debug, Clone)]
+pub enum BrowserEvent {
+    Navigated { url: String },
+    Dialog { kind: String, message: String },
+    Download { url: String, filename: String },
+    ConsoleError { message: String },
+    ConsoleLog { level: String, message: String },
+    Notification { source: String, title: String, body: String },
+    // A tab's title changed (e.g. an unread count in "(3) Inbox"); any tab, not just the active one
+    TitleChanged { url: String, title: String },
+    // The active tab started or stopped playing sound
+    Audio { url: String, audible: bool },
+    Network { url: String, status: i64, resource_type: String },
+    CommandResult { command: String, success: bool, error: Option<String> },
+}
+
+impl BrowserEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrowserEvent::Navigated { .. } => "navigate",
+            BrowserEvent::Dialog { .. } => "dialog",
+            BrowserEvent::Download { .. } => "download",
+            BrowserEvent::ConsoleError { .. } => "console-error",
+            BrowserEvent::ConsoleLog { .. } => "console",
+            BrowserEvent::Notification { .. } => "notification",
+            BrowserEvent::TitleChanged { .. } => "title",
+            BrowserEvent::Audio { .. } => "audio",
+            BrowserEvent::Network { .. } => "network",
+            BrowserEvent::CommandResult { .. } => "command",
+        }
+    }
+
+    fn data_json(&self) -> serde_json::Value {
+        match self {
+            BrowserEvent::Navigated { url } => json!({ "url": url }),
+            BrowserEvent::Dialog { kind, message } => json!({ "type": kind, "message": message }),
+            BrowserEvent::Download { url, filename } => json!({ "url": url, "filename": filename }),
+            BrowserEvent::ConsoleError { message } => json!({ "message": message }),
+            BrowserEvent::ConsoleLog { level, message } => json!({ "level": level, "message": message }),
+            BrowserEvent::Notification { source, title, body } => json!({ "source": source, "title": title, "body": body }),
+            BrowserEvent::TitleChanged { url, title } => json!({ "url": url, "title": title }),
+            BrowserEvent::Audio { url, audible } => json!({ "url": url, "audible": audible }),
+            BrowserEvent::Network { url, status, resource_type } => {
+                json!({ "url": url, "status": status, "type": resource_type })
+            }
+            BrowserEvent::CommandResult { command, success, error } => {
+                json!({ "command": command, "success": success, "error": error })
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            BrowserEvent::Navigated { url } => url.clone(),
+            BrowserEvent::Dialog { kind, message } => format!("{}: {}", kind, message),
+            BrowserEvent::Download { url, filename } => format!("{} ({})", filename, url),
+            BrowserEvent::ConsoleError { message } => message.clone(),
+            BrowserEvent::ConsoleLog { level, message } => format!("[{}] {}", level, message),
+            BrowserEvent::Notification { source, title, body } => format!("[{}] {} {}", source, title, body),
+            BrowserEvent::TitleChanged { url, title } => format!("{} ({})", title, url),
+            BrowserEvent::Audio { url, audible } => format!("{} {}", if *audible { "playing sound" } else { "silent" }, url),
+            BrowserEvent::Network { url, status, resource_type } => format!("{} {} ({})", status, url, resource_type),
+            BrowserEvent::CommandResult { command, success, error } => match error {
+                Some(error) if !success => format!("{} failed: {}", command, error),
+                _ => format!("{} ok", command),
+            },
+        }
+    }
+}
+
+// How dialogs are answered automatically when a dialog hook asks for it
+#[derive(Debug, Clone, PartialEq)]
+pub enum DialogAction {
+    Accept(Option<String>),
+    Dismiss,
+}
+
+// Bus item: every event is tagged with the browser session that produced it
+#[derive(Debug, Clone)]
+pub struct SessionEvent {
+    pub session: String,
+    pub event: BrowserEvent,
+}
+
+impl SessionEvent {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "event": self.event.name(),
+            "session": self.session,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "data": self.event.data_json(),
+        })
+    }
+}
+
+// Publishes a session's events onto a (possibly shared) event bus
+#[derive(Debug, Clone)]
+pub struct EventPublisher {
+    session: String,
+    bus: broadcast::Sender<SessionEvent>,
+}
+
+impl EventPublisher {
+    pub fn new(session: &str, bus: broadcast::Sender<SessionEvent>) -> Self {
+        Self {
+            session: session.to_string(),
+            bus,
+        }
+    }
+
+    pub fn publish(&self, event: BrowserEvent) {
+        self.bus.send(SessionEvent { session: self.session.clone(), event }).ok();
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    pub fn bus(&self) -> broadcast::Sender<SessionEvent> {
+        self.bus.clone()
+    }
+}
@@ -0,0 +1,663 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// A DOM subtree as the page hands it over (see EXTRACT_TREE_JS): text, or an element with the few
+// attributes Markdown needs. Scripts, styles, form controls and hidden elements are already left out.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Node {
+    Text(String),
+    Element {
+        tag: String,
+        #[serde(default)]
+        attrs: HashMap<String, String>,
+        #[serde(default)]
+        children: Vec<Node>,
+    },
+}
+
+// JS function turning an element into a Node tree, leaving out the elements in the optional skip set.
+// Links and images carry resolved URLs; class is kept for code block languages only.
+pub const EXTRACT_TREE_JS: &str = r#"(root, skip = new Set()) => {
+    const SKIP = new Set(['script', 'style', 'noscript', 'template', 'svg', 'canvas', 'iframe', 'object', 'embed',
+        'head', 'input', 'select', 'textarea', 'button', 'dialog']);
+    const walk = node => {
+        if (node.nodeType === Node.TEXT_NODE) return node.textContent;
+        if (node.nodeType !== Node.ELEMENT_NODE) return null;
+        const tag = node.localName;
+        if (SKIP.has(tag) || skip.has(node) || node.hidden || node.getAttribute('aria-hidden') === 'true') return null;
+        if (node.checkVisibility && !node.checkVisibility() && tag !== 'br') return null;
+        const attrs = {};
+        if (tag === 'a' && node.hasAttribute('href')) attrs.href = node.href;
+        if (tag === 'img') {
+            attrs.src = node.currentSrc || node.src;
+            attrs.alt = node.getAttribute('alt') || '';
+        }
+        if (tag === 'ol' && node.hasAttribute('start')) attrs.start = node.getAttribute('start');
+        if ((tag === 'pre' || tag === 'code') && node.className) attrs.class = String(node.className);
+        const children = Array.from(node.childNodes).map(walk).filter(child => child !== null);
+        return { tag, attrs, children };
+    };
+    return walk(root);
+}"#;
+
+// Elements that start their own block; everything else flows inline
+const BLOCK_TAGS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "body", "dd", "details", "div", "dl", "dt", "fieldset",
+    "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header", "hr", "li",
+    "main", "nav", "ol", "p", "pre", "section", "summary", "table", "tbody", "td", "tfoot", "th", "thead",
+    "tr", "ul",
+];
+
+// Markdown for a Node tree: ATX headings, paragraphs, nested - and 1. lists, > quotes, fenced code blocks
+// (with the language from a language-* class), GFM tables, links, images and emphasis. Layout-only
+// wrappers (div, section, span, ...) contribute their content but no markup.
+pub fn to_markdown(node: &Node) -> String {
+    let markdown = blocks(std::slice::from_ref(node)).join("\n\n");
+    let mut out = String::with_capacity(markdown.len());
+    let mut blank_lines = 0;
+    // Lines of a fenced code block are code: kept as they are, blank runs and trailing spaces included
+    let mut fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let marker = line.trim();
+        if let Some(open) = fence {
+            out.push_str(line);
+            out.push('\n');
+            if marker == open {
+                fence = None;
+            }
+            continue;
+        }
+        if marker.starts_with("```") {
+            fence = Some(&marker[..marker.len() - marker.trim_start_matches('`').len()]);
+        }
+        let line = line.trim_end();
+        blank_lines = if line.is_empty() { blank_lines + 1 } else { 0 };
+        if blank_lines < 2 {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.trim().to_string()
+}
+
+// Plain text for a Node tree: one line per block, whitespace collapsed as rendered
+pub fn to_text(node: &Node) -> String {
+    let mut text = String::new();
+    push_plain(node, &mut text);
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n")
+}
+
+fn push_plain(node: &Node, out: &mut String) {
+    match node {
+        Node::Text(text) => push_text(out, text),
+        Node::Element { tag, .. } if tag == "br" => out.push('\n'),
+        Node::Element { children, .. } => {
+            let block = is_block(node);
+            if block {
+                out.push('\n');
+            }
+            for child in children {
+                push_plain(child, out);
+            }
+            if block {
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn is_block(node: &Node) -> bool {
+    matches!(node, Node::Element { tag, .. } if BLOCK_TAGS.contains(&tag.as_str()))
+}
+
+// The blocks a run of sibling nodes makes: consecutive inline nodes become one paragraph
+fn blocks(nodes: &[Node]) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut run: Vec<&Node> = Vec::new();
+    let flush = |run: &mut Vec<&Node>, blocks: &mut Vec<String>| {
+        let paragraph = inline_of(run.drain(..)).trim().to_string();
+        if !paragraph.is_empty() {
+            blocks.push(paragraph);
+        }
+    };
+    for node in nodes {
+        if is_block(node) {
+            flush(&mut run, &mut blocks);
+            blocks.extend(block(node));
+        } else {
+            run.push(node);
+        }
+    }
+    flush(&mut run, &mut blocks);
+    blocks
+}
+
+fn block(node: &Node) -> Vec<String> {
+    let Node::Element { tag, attrs, children } = node else {
+        return Vec::new();
+    };
+    let single = |text: String| if text.is_empty() { Vec::new() } else { vec![text] };
+    match tag.as_str() {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = tag[1..].parse().unwrap_or(1);
+            let text = inline(children).trim().replace('\n', " ");
+            single(if text.is_empty() { text } else { format!("{} {}", "#".repeat(level), text) })
+        }
+        "p" | "dd" | "figcaption" | "summary" => single(inline(children).trim().to_string()),
+        "dt" => {
+            let text = inline(children).trim().to_string();
+            single(if text.is_empty() { text } else { format!("**{}**", text) })
+        }
+        "hr" => vec!["---".to_string()],
+        "ul" | "ol" => single(list(children, tag == "ol", attrs.get("start").and_then(|s| s.parse().ok()).unwrap_or(1))),
+        "blockquote" => {
+            let quoted = blocks(children).join("\n\n");
+            single(quoted.lines()
+                .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+        "pre" => single(code_block(node)),
+        "table" => single(table(node)),
+        _ => blocks(children),
+    }
+}
+
+fn list(items: &[Node], ordered: bool, start: usize) -> String {
+    let mut lines = Vec::new();
+    let mut number = start;
+    for item in items {
+        let Node::Element { tag, children, .. } = item else {
+            continue;
+        };
+        if tag != "li" {
+            // Stray content between items (some sites wrap items in divs)
+            lines.extend(blocks(std::slice::from_ref(item)));
+            continue;
+        }
+        let marker = if ordered { format!("{}.", number) } else { "-".to_string() };
+        number += 1;
+        let content = blocks(children).join("\n");
+        let indent = " ".repeat(marker.len() + 1);
+        let mut item_lines = content.lines();
+        lines.push(format!("{} {}", marker, item_lines.next().unwrap_or_default()));
+        lines.extend(item_lines.map(|line| if line.is_empty() { String::new() } else { format!("{}{}", indent, line) }));
+    }
+    lines.join("\n")
+}
+
+fn code_block(pre: &Node) -> String {
+    let code = raw_text(pre);
+    let code = code.trim_end_matches('\n').trim_start_matches('\n');
+    if code.trim().is_empty() {
+        return String::new();
+    }
+    let language = language(pre).unwrap_or_default();
+    // The fence has to be longer than any run of backticks in the code
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, language, code, fence)
+}
+
+// The language-* (or lang-*) class on the pre or the code inside it
+fn language(pre: &Node) -> Option<String> {
+    let Node::Element { attrs, children, .. } = pre else {
+        return None;
+    };
+    let classes = attrs.get("class").into_iter().chain(children.iter().filter_map(|child| match child {
+        Node::Element { tag, attrs, .. } if tag == "code" => attrs.get("class"),
+        _ => None,
+    }));
+    classes.flat_map(|class| class.split_whitespace())
+        .find_map(|class| class.strip_prefix("language-").or_else(|| class.strip_prefix("lang-")))
+        .map(str::to_string)
+}
+
+fn table(node: &Node) -> String {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    collect_rows(node, &mut rows);
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let line = |cells: &[String]| {
+        let padded: Vec<&str> = (0..columns).map(|i| cells.get(i).map_or("", String::as_str)).collect();
+        format!("| {} |", padded.join(" | "))
+    };
+    // The first row is the header, whether or not it used th
+    let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+// Rows of a table in document order, across thead/tbody/tfoot; nested tables are flattened into their cell
+fn collect_rows(node: &Node, rows: &mut Vec<Vec<String>>) {
+    let Node::Element { tag, children, .. } = node else {
+        return;
+    };
+    if tag == "tr" {
+        let cells = children.iter()
+            .filter_map(|cell| match cell {
+                Node::Element { tag, children, .. } if tag == "td" || tag == "th" => {
+                    Some(blocks(children).join(" ").replace('\n', " ").replace('|', "\\|"))
+                }
+                _ => None,
+            })
+            .collect();
+        rows.push(cells);
+        return;
+    }
+    for child in children {
+        collect_rows(child, rows);
+    }
+}
+
+fn inline(nodes: &[Node]) -> String {
+    inline_of(nodes.iter())
+}
+
+fn inline_of<'a>(nodes: impl Iterator<Item = &'a Node>) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            Node::Text(text) => push_text(&mut out, text),
+            Node::Element { .. } => {
+                let markdown = inline_element(node);
+                // Collapse the space between "word " and " **bold**"
+                if out.ends_with([' ', '\n']) {
+                    out.push_str(markdown.trim_start_matches(' '));
+                } else {
+                    out.push_str(&markdown);
+                }
+            }
+        }
+    }
+    out
+}
+
+// Text with its whitespace collapsed the way the browser renders it
+fn push_text(out: &mut String, text: &str) {
+    for (i, word) in text.split(|c: char| c.is_ascii_whitespace()).enumerate() {
+        if i > 0 && !out.is_empty() && !out.ends_with([' ', '\n']) {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+}
+
+fn inline_element(node: &Node) -> String {
+    let Node::Element { tag, attrs, children } = node else {
+        return String::new();
+    };
+    // Emphasis markers have to hug the text, so surrounding spaces move outside them
+    let wrap = |marker: &str| {
+        let text = inline(children);
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return text;
+        }
+        let (before, after) = (if text.starts_with(' ') { " " } else { "" }, if text.ends_with(' ') { " " } else { "" });
+        format!("{}{}{}{}{}", before, marker, trimmed, marker, after)
+    };
+    match tag.as_str() {
+        "br" => "\n".to_string(),
+        "strong" | "b" => wrap("**"),
+        "em" | "i" => wrap("*"),
+        "del" | "s" | "strike" => wrap("~~"),
+        "code" | "kbd" | "samp" | "tt" => {
+            let code = raw_text(node).replace('\n', " ");
+            if code.is_empty() {
+                return code;
+            }
+            let fence = if code.contains('`') { "``" } else { "`" };
+            let pad = if code.starts_with('`') || code.ends_with('`') { " " } else { "" };
+            format!("{}{}{}{}{}", fence, pad, code, pad, fence)
+        }
+        "a" => {
+            let text = inline(children).trim().replace('\n', " ");
+            match attrs.get("href").filter(|href| !href.is_empty() && !href.starts_with("javascript:")) {
+                Some(href) if text.is_empty() => format!("<{}>", href),
+                Some(href) => format!("[{}]({})", text, markdown_url(href)),
+                None => text,
+            }
+        }
+        "img" => match attrs.get("src").filter(|src| !src.is_empty() && !src.starts_with("data:")) {
+            Some(src) => format!("![{}]({})", attrs.get("alt").map_or("", String::as_str).trim(), markdown_url(src)),
+            None => String::new(),
+        },
+        // A block inside inline content (a div in a link): keep its text on the line
+        _ if is_block(node) => format!(" {} ", blocks(children).join(" ")),
+        _ => inline(children),
+    }
+}
+
+// Spaces and parentheses would end a Markdown link destination early
+fn markdown_url(url: &str) -> String {
+    url.replace(' ', "%20").replace('(', "%28").replace(')', "%29")
+}
+
+// All text under a node as written, for code
+fn raw_text(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text.clone(),
+        Node::Element { tag, .. } if tag == "br" => "\n".to_string(),
+        Node::Element { children, .. } => children.iter().map(raw_text).collect(),
+    }
+}
+
+// What the page's article script (ARTICLE_JS) finds: metadata, and the main content with boilerplate
+// inside it already left out
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArticleSource {
+    pub url: String,
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub published: Option<String>,
+    pub content: Option<Node>,
+}
+
+// The main content of a page with its metadata, as `article` returns it
+#[derive(Debug, Clone, Serialize)]
+pub struct Article {
+    pub url: String,
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    // As the page states it (usually ISO 8601)
+    pub published: Option<String>,
+    pub word_count: usize,
+    pub text: String,
+    pub markdown: String,
+}
+
+impl Article {
+    pub fn from_source(source: ArticleSource) -> Self {
+        let (text, markdown) = match &source.content {
+            Some(content) => (to_text(content), to_markdown(content)),
+            None => (String::new(), String::new()),
+        };
+        Self {
+            url: source.url,
+            title: source.title,
+            byline: source.byline,
+            published: source.published,
+            word_count: text.split_whitespace().count(),
+            text,
+            markdown,
+        }
+    }
+
+    // Title, byline and date lines, then the content's text
+    pub fn to_text(&self) -> String {
+        let mut lines: Vec<&str> = [self.title.as_deref(), self.byline.as_deref(), self.published.as_deref()].into_iter().flatten().collect();
+        if !lines.is_empty() {
+            lines.push("");
+        }
+        lines.push(&self.text);
+        lines.join("\n")
+    }
+
+    // Title heading, a byline/date line, then the content
+    pub fn to_markdown(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(title) = &self.title {
+            parts.push(format!("# {}", title));
+        }
+        let meta: Vec<&str> = [self.byline.as_deref(), self.published.as_deref()].into_iter().flatten().collect();
+        if !meta.is_empty() {
+            parts.push(format!("*{}*", meta.join(" · ")));
+        }
+        parts.push(self.markdown.clone());
+        parts.join("\n\n")
+    }
+}
+
+// Readability-style main content detection, in the page. Paragraph-like elements score their parent
+// (and half that for the grandparent) by length and commas; a container's class and id add or take away
+// points, and its score shrinks with its link density. The best container wins, unless the page marks its
+// article body with itemprop=articleBody. Inside it, navigation, forms, share/related/ad blocks and other
+// link-heavy boilerplate are skipped.
+pub const ARTICLE_JS: &str = r#"(extractTree) => {
+    const POSITIVE = /article|body|content|entry|hentry|h-entry|main|page|post|text|blog|story/i;
+    const NEGATIVE = /hidden|banner|breadcrumb|combx|comment|com-|contact|cookie|foot|masthead|media|meta|modal|newsletter|outbrain|promo|related|scroll|share|shoutbox|sidebar|skyscraper|sponsor|shopping|social|subscribe|tags|tool|widget|\bad-|advert|nav|menu|popup/i;
+    const text = el => (el.innerText || el.textContent || '').trim();
+    const classWeight = el => {
+        const names = `${el.className && typeof el.className === 'string' ? el.className : ''} ${el.id || ''}`;
+        return (POSITIVE.test(names) ? 25 : 0) - (NEGATIVE.test(names) ? 25 : 0);
+    };
+    const linkDensity = el => {
+        const length = text(el).length;
+        if (!length) return 0;
+        const linked = Array.from(el.querySelectorAll('a')).reduce((sum, a) => sum + text(a).length, 0);
+        return linked / length;
+    };
+    const meta = (...names) => {
+        for (const name of names) {
+            const el = document.querySelector(`meta[property="${name}"], meta[name="${name}"], meta[itemprop="${name}"]`);
+            if (el && el.content && el.content.trim()) return el.content.trim();
+        }
+        return null;
+    };
+    const jsonLd = key => {
+        for (const script of document.querySelectorAll('script[type="application/ld+json"]')) {
+            try {
+                const queue = [JSON.parse(script.textContent)];
+                while (queue.length) {
+                    const item = queue.shift();
+                    if (Array.isArray(item)) { queue.push(...item); continue; }
+                    if (!item || typeof item !== 'object') continue;
+                    if (item[key]) return item[key];
+                    if (item['@graph']) queue.push(item['@graph']);
+                }
+            } catch (e) {}
+        }
+        return null;
+    };
+
+    let content = document.querySelector('[itemprop="articleBody"]');
+    if (!content || text(content).length < 200) {
+        const scores = new Map();
+        const base = el => {
+            if (!scores.has(el)) {
+                const tag = el.localName;
+                const tagScore = tag === 'article' ? 10 : tag === 'div' || tag === 'section' || tag === 'main' ? 5
+                    : ['pre', 'td', 'blockquote'].includes(tag) ? 3
+                    : ['address', 'ol', 'ul', 'dl', 'dd', 'dt', 'li', 'form'].includes(tag) ? -3
+                    : /^h[1-6]$|^th$/.test(tag) ? -5 : 0;
+                scores.set(el, tagScore + classWeight(el));
+            }
+            return scores.get(el);
+        };
+        for (const paragraph of document.body.querySelectorAll('p, pre, td, blockquote')) {
+            const length = text(paragraph).length;
+            if (length < 25) continue;
+            const points = 1 + text(paragraph).split(/[,，]/).length - 1 + Math.min(Math.floor(length / 100), 3);
+            const parent = paragraph.parentElement, grandparent = parent && parent.parentElement;
+            if (parent && parent !== document.documentElement) scores.set(parent, base(parent) + points);
+            if (grandparent && grandparent !== document.documentElement) scores.set(grandparent, base(grandparent) + points / 2);
+        }
+        let best = null, bestScore = -Infinity;
+        for (const [el, score] of scores) {
+            const final = score * (1 - linkDensity(el));
+            if (final > bestScore) { best = el; bestScore = final; }
+        }
+        content = best || document.querySelector('article, main, [role="main"]') || document.body;
+    }
+
+    const skip = new Set();
+    for (const el of content.querySelectorAll('*')) {
+        const tag = el.localName;
+        if (['nav', 'aside', 'footer', 'form', 'header'].includes(tag) || el.getAttribute('role') === 'navigation') {
+            skip.add(el);
+        } else if (['div', 'section', 'ul', 'ol', 'table', 'figure'].includes(tag)) {
+            const density = linkDensity(el);
+            if (classWeight(el) < 0 && (density > 0.2 || text(el).length < 200) || density > 0.5 && text(el).length < 1000) skip.add(el);
+        }
+    }
+
+    const heading = content.querySelector('h1') || document.querySelector('h1');
+    let title = meta('og:title', 'twitter:title') || (heading && text(heading)) || document.title.trim() || null;
+    if (heading && title && text(heading) && title.includes(text(heading))) title = text(heading);
+    // The title is returned on its own, so it isn't repeated at the top of the content
+    if (heading && content.contains(heading) && text(heading) === title) skip.add(heading);
+
+    let byline = meta('author', 'article:author', 'parsely-author', 'sailthru.author');
+    if (!byline) {
+        const author = jsonLd('author');
+        byline = typeof author === 'string' ? author : Array.isArray(author) ? author.map(a => a && a.name).filter(Boolean).join(', ') || null : author && author.name || null;
+    }
+    if (!byline) {
+        const el = document.querySelector('[rel="author"], [itemprop="author"], .byline, .author, .article-author');
+        const candidate = el && text(el);
+        byline = candidate && candidate.length < 100 ? candidate.replace(/\s+/g, ' ') : null;
+    }
+    const time = content.querySelector('time[datetime]') || document.querySelector('time[datetime]');
+    const published = meta('article:published_time', 'datePublished', 'date', 'pubdate', 'publishdate', 'dc.date')
+        || jsonLd('datePublished') || (time && time.getAttribute('datetime')) || null;
+
+    return { url: location.href, title, byline, published, content: extractTree(content, skip) };
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // A fixture page as EXTRACT_TREE_JS hands it over, converted
+    fn markdown(tree: serde_json::Value) -> String {
+        to_markdown(&serde_json::from_value(tree).unwrap())
+    }
+
+    fn el(tag: &str, children: serde_json::Value) -> serde_json::Value {
+        json!({ "tag": tag, "children": children })
+    }
+
+    #[test]
+    fn headings() {
+        let page = el("body", json!([
+            el("h1", json!(["Release notes"])),
+            el("h2", json!(["\n    What's   ", el("em", json!(["new"])), "\n  "])),
+            el("h3", json!([])),
+            el("p", json!(["Intro  text\n  over two lines."])),
+            el("h6", json!(["Fine print"])),
+        ]));
+        assert_eq!(markdown(page), "# Release notes\n\n## What's *new*\n\nIntro text over two lines.\n\n###### Fine print");
+    }
+
+    #[test]
+    fn nested_lists() {
+        let page = el("div", json!([
+            el("ul", json!([
+                el("li", json!(["One"])),
+                el("li", json!([
+                    "Two",
+                    el("ul", json!([
+                        el("li", json!(["Two a"])),
+                        el("li", json!(["Two b", el("ol", json!([el("li", json!(["deep"])), el("li", json!(["deeper"]))]))])),
+                    ])),
+                ])),
+                el("li", json!([el("p", json!(["Three"]))])),
+            ])),
+            { "tag": "ol", "attrs": { "start": "9" }, "children": [
+                el("li", json!(["Nine"])),
+                el("li", json!(["Ten", el("ul", json!([el("li", json!(["under ten"]))]))])),
+            ] },
+        ]));
+        assert_eq!(markdown(page), [
+            "- One",
+            "- Two",
+            "  - Two a",
+            "  - Two b",
+            "    1. deep",
+            "    2. deeper",
+            "- Three",
+            "",
+            "9. Nine",
+            "10. Ten",
+            "    - under ten",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn tables() {
+        let page = el("table", json!([
+            el("thead", json!([el("tr", json!([el("th", json!(["Name"])), el("th", json!(["Price"]))]))])),
+            el("tbody", json!([
+                el("tr", json!([el("td", json!(["Tea"])), el("td", json!(["3 | 4"]))])),
+                el("tr", json!([el("td", json!([{ "tag": "a", "attrs": { "href": "https://shop.test/coffee" }, "children": ["Coffee"] }]))])),
+                el("tr", json!([el("td", json!([el("p", json!(["Two"])), el("p", json!(["paragraphs"]))])), el("td", json!(["x"]))])),
+            ])),
+        ]));
+        assert_eq!(markdown(page), [
+            "| Name | Price |",
+            "| --- | --- |",
+            "| Tea | 3 \\| 4 |",
+            "| [Coffee](https://shop.test/coffee) |  |",
+            "| Two paragraphs | x |",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn code_blocks() {
+        let page = el("div", json!([
+            el("pre", json!([{ "tag": "code", "attrs": { "class": "hljs language-rust" }, "children": [
+                "fn main() {\n    println!(\"hi\");\n}\n"
+            ] }])),
+            { "tag": "pre", "attrs": { "class": "lang-md" }, "children": ["Use ```fences``` for code"] },
+            el("p", json!(["Run ", el("code", json!(["cargo test"])), " or ", el("kbd", json!(["`"])), "."])),
+        ]));
+        assert_eq!(markdown(page), [
+            "```rust",
+            "fn main() {",
+            "    println!(\"hi\");",
+            "}",
+            "```",
+            "",
+            "````md",
+            "Use ```fences``` for code",
+            "````",
+            "",
+            "Run `cargo test` or `` ` ``.",
+        ].join("\n"));
+    }
+
+    #[test]
+    fn code_blocks_keep_their_blank_lines_and_indentation() {
+        let page = el("pre", json!([el("code", json!(["first  \n\n\n\n    indented\n"]))]));
+        assert_eq!(markdown(page), "```\nfirst  \n\n\n\n    indented\n```");
+    }
+
+    #[test]
+    fn links_and_images() {
+        let link = |href: &str, children: serde_json::Value| json!({ "tag": "a", "attrs": { "href": href }, "children": children });
+        let page = el("p", json!([
+            "See ",
+            link("https://docs.test/guide (v2)", json!(["the ", el("strong", json!(["docs"]))])),
+            ", ",
+            link("https://docs.test/", json!([])),
+            ", ",
+            link("javascript:void(0)", json!(["a button"])),
+            " and ",
+            { "tag": "img", "attrs": { "src": "https://cdn.test/logo.png", "alt": " Logo " } },
+            { "tag": "img", "attrs": { "src": "data:image/png;base64,AAAA", "alt": "inline" } },
+            el("a", json!(["no href"])),
+            ".",
+        ]));
+        assert_eq!(
+            markdown(page),
+            "See [the **docs**](https://docs.test/guide%20%28v2%29), <https://docs.test/>, a button and ![Logo](https://cdn.test/logo.png)no href."
+        );
+    }
+
+    #[test]
+    fn quotes_rules_and_plain_text() {
+        let tree: Node = serde_json::from_value(el("article", json!([
+            el("blockquote", json!([el("p", json!(["Quoted"])), el("p", json!(["twice"]))])),
+            el("hr", json!([])),
+            el("p", json!(["Line", el("br", json!([])), "break"])),
+        ])))
+        .unwrap();
+        assert_eq!(to_markdown(&tree), "> Quoted\n>\n> twice\n\n---\n\nLine\nbreak");
+        assert_eq!(to_text(&tree), "Quoted\ntwice\nLine\nbreak");
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// Directories never watched: version control, dependencies and build output churn without the sources changing
+const IGNORED_DIRS: &[&str] = &[".git", ".hg", ".svn", "node_modules", "target", ".cache"];
+
+// How long the tree has to stay unchanged before a change is reported, so a build writing many files
+// triggers one action rather than one per file
+const SETTLE: Duration = Duration::from_millis(200);
+
+// Polls a directory tree for added, removed and modified files. Polling rather than OS notifications keeps
+// it working the same on every platform and on network or container mounts, at the cost of a short delay.
+pub struct DirWatcher {
+    root: PathBuf,
+    // Modification time and size of every file under the root
+    snapshot: HashMap<PathBuf, (Option<SystemTime>, u64)>,
+}
+
+impl DirWatcher {
+    pub fn new(root: &Path) -> Result<Self> {
+        if !root.is_dir() {
+            return Err(anyhow::anyhow!("Can't watch {}: not a directory", root.display()));
+        }
+        let root = root.to_path_buf();
+        let snapshot = scan(&root);
+        Ok(Self { root, snapshot })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.snapshot.len()
+    }
+
+    // Wait for the next change and return the files it touched, sorted; checks every `poll`
+    pub async fn changed(&mut self, poll: Duration) -> Vec<PathBuf> {
+        loop {
+            tokio::time::sleep(poll).await;
+            let mut changes = self.rescan();
+            if changes.is_empty() {
+                continue;
+            }
+            loop {
+                tokio::time::sleep(SETTLE).await;
+                let more = self.rescan();
+                if more.is_empty() {
+                    break;
+                }
+                changes.extend(more);
+            }
+            changes.sort();
+            changes.dedup();
+            return changes;
+        }
+    }
+
+    // Files that differ from the last snapshot, which is replaced
+    fn rescan(&mut self) -> Vec<PathBuf> {
+        let current = scan(&self.root);
+        let mut changes: Vec<PathBuf> = current.iter()
+            .filter(|(path, stamp)| self.snapshot.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changes.extend(self.snapshot.keys().filter(|path| !current.contains_key(*path)).cloned());
+        self.snapshot = current;
+        changes
+    }
+}
+
+fn scan(root: &Path) -> HashMap<PathBuf, (Option<SystemTime>, u64)> {
+    let mut files = HashMap::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        // A directory removed mid-scan just has nothing in it
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            if metadata.is_dir() {
+                if !IGNORED_DIRS.iter().any(|ignored| entry.file_name() == *ignored) {
+                    pending.push(path);
+                }
+            } else {
+                files.insert(path, (metadata.modified().ok(), metadata.len()));
+            }
+        }
+    }
+    files
+}
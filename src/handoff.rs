@@ -0,0 +1,72 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use std::process::{Command, Stdio};
+
+// Everything a human needs to take over the session: the page itself and a DevTools link that drives the
+// automated browser (its cookies, storage and in-flight state) rather than a fresh one
+#[derive(Debug, Clone, Serialize)]
+pub struct Handoff {
+    pub url: String,
+    pub title: String,
+    pub devtools: Option<String>,
+    pub websocket: String,
+}
+
+impl Handoff {
+    // DevTools front end the browser serves itself, pointed at one page target
+    pub fn new(url: String, title: String, websocket: String, target_id: &str) -> Self {
+        let devtools = url::Url::parse(&websocket).ok().and_then(|ws| {
+            let host = format!("{}:{}", ws.host_str()?, ws.port()?);
+            Some(format!("http://{}/devtools/inspector.html?ws={}/devtools/page/{}", host, host, target_id))
+        });
+        Self { url, title, devtools, websocket }
+    }
+
+    pub fn print(&self) {
+        status!("{} Handing off: {}", "🤝".cyan(), self.title.bold());
+        status!("  {:<9} {}", "Page:", hyperlink(&self.url, &self.url));
+        match &self.devtools {
+            Some(devtools) => status!("  {:<9} {}", "DevTools:", hyperlink(devtools, devtools)),
+            None => status!("  {:<9} {}", "DevTools:", self.websocket),
+        }
+        status!("  {}", "The page link starts fresh in your browser; DevTools drives this very session (cookies, state).".dimmed());
+    }
+}
+
+// Terminal hyperlink (OSC 8); terminals without support show the label as plain text
+pub fn hyperlink(url: &str, label: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, label)
+}
+
+// Open a URL with the desktop's default handler
+pub fn open_url(url: &str) -> Result<()> {
+    let (program, args) = opener(url);
+    let status = Command::new(program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run '{}' to open {}: {}", program, url, e))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("'{}' failed to open {} ({})", program, url, status));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn opener(url: &str) -> (&'static str, Vec<String>) {
+    ("open", vec![url.to_string()])
+}
+
+#[cfg(target_os = "windows")]
+fn opener(url: &str) -> (&'static str, Vec<String>) {
+    // Unlike `cmd /C start`, this doesn't need & and friends in the URL escaped
+    ("rundll32", vec!["url.dll,FileProtocolHandler".to_string(), url.to_string()])
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn opener(url: &str) -> (&'static str, Vec<String>) {
+    ("xdg-open", vec![url.to_string()])
+}
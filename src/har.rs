@@ -0,0 +1,237 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use crate::network::RequestRecord;
+
+// Write captured requests as a HAR 1.2 file. Bodies aren't kept by the recorder, so responses carry
+// metadata, headers and timings only. Returns the number of entries written.
+pub fn write(path: &Path, records: &[RequestRecord]) -> Result<usize> {
+    let entries: Vec<Value> = records.iter().filter(|record| !record.url.is_empty()).map(entry).collect();
+    let har = json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "browser-cli", "version": crate::VERSION },
+            "pages": [],
+            "entries": entries,
+        }
+    });
+    std::fs::write(path, serde_json::to_string_pretty(&har)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write HAR {}: {}", path.display(), e))?;
+    Ok(entries.len())
+}
+
+fn entry(record: &RequestRecord) -> Value {
+    let started_at = record.wall_time
+        .and_then(|secs| DateTime::<Utc>::from_timestamp_millis((secs * 1000.0) as i64))
+        .unwrap_or_else(Utc::now);
+    let timings = timings(record);
+    let time: f64 = ["blocked", "dns", "connect", "send", "wait", "receive"].iter()
+        .filter_map(|phase| timings[phase].as_f64())
+        .filter(|ms| *ms > 0.0)
+        .sum();
+    let http_version = http_version(record.protocol.as_deref());
+    let transfer_size = record.encoded_size.unwrap_or(0.0).round() as i64;
+    let content_size = record.decoded_size.unwrap_or(0.0).round() as i64;
+
+    let mut request = json!({
+        "method": record.method,
+        "url": record.url,
+        "httpVersion": http_version,
+        "cookies": [],
+        "headers": headers(&record.request_headers),
+        "queryString": query_string(&record.url),
+        "headersSize": -1,
+        "bodySize": record.post_data.as_ref().map(|body| body.len() as i64).unwrap_or(0),
+    });
+    if let Some(body) = &record.post_data {
+        let mime_type = record.request_headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+        request["postData"] = json!({ "mimeType": mime_type, "text": body });
+    }
+
+    let redirect_url = record.response_headers.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("location"))
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default();
+    let mut entry = json!({
+        "startedDateTime": started_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "time": time,
+        "request": request,
+        "response": {
+            // Requests that never got a response are recorded with status 0, as browsers do
+            "status": record.status.unwrap_or(0),
+            "statusText": record.status_text,
+            "httpVersion": http_version,
+            "cookies": [],
+            "headers": headers(&record.response_headers),
+            "content": { "size": content_size, "mimeType": record.mime_type.clone().unwrap_or_default() },
+            "redirectURL": redirect_url,
+            "headersSize": -1,
+            "bodySize": if record.status.is_some() { transfer_size } else { -1 },
+            "_transferSize": transfer_size,
+        },
+        "cache": {},
+        "timings": timings,
+        "_resourceType": record.resource_type.to_lowercase(),
+    });
+    if let Some(address) = &record.remote_address {
+        entry["serverIPAddress"] = json!(address.rsplit_once(':').map(|(ip, _)| ip).unwrap_or(address));
+    }
+    if let Some(error) = &record.error {
+        entry["response"]["_error"] = json!(error);
+    }
+    entry
+}
+
+// HAR's spelling of the ALPN protocol Chrome reports ("h2", "http/1.1", "h3-29")
+fn http_version(protocol: Option<&str>) -> String {
+    match protocol.map(str::to_ascii_lowercase).as_deref() {
+        None | Some("") => "HTTP/1.1".to_string(),
+        Some("h2" | "h2c") => "HTTP/2".to_string(),
+        Some(h3) if h3 == "h3" || h3.starts_with("h3-") || h3 == "quic" => "HTTP/3".to_string(),
+        Some(other) => other.to_uppercase(),
+    }
+}
+
+// HAR phases in milliseconds from the response's ResourceTiming; -1 marks a phase that didn't happen
+fn timings(record: &RequestRecord) -> Value {
+    let total = record.finished.map(|finished| ((finished - record.started) * 1000.0).max(0.0)).unwrap_or(0.0);
+    let Some(timing) = &record.timing else {
+        return json!({ "blocked": -1, "dns": -1, "connect": -1, "send": 0, "wait": total, "receive": 0, "ssl": -1 });
+    };
+
+    let span = |start: f64, end: f64| if start >= 0.0 && end >= start { end - start } else { -1.0 };
+    // Time between the request being issued and the request_time ResourceTiming is relative to
+    let queued = ((timing.request_time - record.started) * 1000.0).max(0.0);
+    let first = [timing.dns_start, timing.connect_start, timing.send_start].into_iter()
+        .find(|t| *t >= 0.0)
+        .unwrap_or(0.0);
+    let receive = record.finished
+        .map(|finished| ((finished - timing.request_time) * 1000.0 - timing.receive_headers_end).max(0.0))
+        .unwrap_or(0.0);
+    json!({
+        "blocked": queued + first,
+        "dns": span(timing.dns_start, timing.dns_end),
+        // HAR's connect includes the TLS handshake, which ssl repeats
+        "connect": span(timing.connect_start, timing.connect_end),
+        "send": span(timing.send_start, timing.send_end).max(0.0),
+        "wait": span(timing.send_end, timing.receive_headers_end).max(0.0),
+        "receive": receive,
+        "ssl": span(timing.ssl_start, timing.ssl_end),
+    })
+}
+
+fn headers(pairs: &[(String, String)]) -> Vec<Value> {
+    pairs.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect()
+}
+
+fn query_string(url: &str) -> Vec<Value> {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return Vec::new();
+    };
+    parsed.query_pairs().map(|(name, value)| json!({ "name": name, "value": value })).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chromiumoxide::cdp::browser_protocol::network::ResourceTiming;
+
+    // Offsets in ms from request_time, -1 for the phases a test leaves out
+    fn resource_timing(request_time: f64, dns: (f64, f64), connect: (f64, f64), ssl: (f64, f64), send: (f64, f64), headers_end: f64) -> ResourceTiming {
+        ResourceTiming {
+            request_time,
+            proxy_start: -1.0,
+            proxy_end: -1.0,
+            dns_start: dns.0,
+            dns_end: dns.1,
+            connect_start: connect.0,
+            connect_end: connect.1,
+            ssl_start: ssl.0,
+            ssl_end: ssl.1,
+            worker_start: -1.0,
+            worker_ready: -1.0,
+            worker_fetch_start: -1.0,
+            worker_respond_with_settled: -1.0,
+            send_start: send.0,
+            send_end: send.1,
+            push_start: 0.0,
+            push_end: 0.0,
+            receive_headers_end: headers_end,
+        }
+    }
+
+    fn phases(timings: &Value) -> Vec<(&'static str, i64)> {
+        ["blocked", "dns", "connect", "ssl", "send", "wait", "receive"].iter()
+            .map(|phase| (*phase, timings[phase].as_f64().unwrap().round() as i64))
+            .collect()
+    }
+
+    #[test]
+    fn timings_split_a_fresh_connection_into_phases() {
+        let record = RequestRecord {
+            started: 100.0,
+            finished: Some(100.2),
+            timing: Some(resource_timing(100.01, (1.0, 6.0), (6.0, 30.0), (15.0, 30.0), (31.0, 32.0), 80.0)),
+            ..Default::default()
+        };
+        assert_eq!(phases(&timings(&record)), [
+            ("blocked", 11), ("dns", 5), ("connect", 24), ("ssl", 15), ("send", 1), ("wait", 48), ("receive", 110),
+        ]);
+    }
+
+    #[test]
+    fn timings_of_a_reused_connection_skip_dns_and_connect() {
+        let record = RequestRecord {
+            started: 50.0,
+            finished: Some(50.1),
+            timing: Some(resource_timing(50.0, (-1.0, -1.0), (-1.0, -1.0), (-1.0, -1.0), (2.0, 3.0), 40.0)),
+            ..Default::default()
+        };
+        assert_eq!(phases(&timings(&record)), [
+            ("blocked", 2), ("dns", -1), ("connect", -1), ("ssl", -1), ("send", 1), ("wait", 37), ("receive", 60),
+        ]);
+    }
+
+    #[test]
+    fn timings_without_resource_timing_count_as_waiting() {
+        let record = RequestRecord { started: 10.0, finished: Some(10.25), ..Default::default() };
+        assert_eq!(phases(&timings(&record)), [
+            ("blocked", -1), ("dns", -1), ("connect", -1), ("ssl", -1), ("send", 0), ("wait", 250), ("receive", 0),
+        ]);
+        let unfinished = RequestRecord { started: 10.0, ..Default::default() };
+        assert_eq!(timings(&unfinished)["wait"], json!(0.0));
+    }
+
+    #[test]
+    fn http_versions_use_har_spelling() {
+        assert_eq!(http_version(Some("h2")), "HTTP/2");
+        assert_eq!(http_version(Some("h3")), "HTTP/3");
+        assert_eq!(http_version(Some("h3-29")), "HTTP/3");
+        assert_eq!(http_version(Some("http/1.1")), "HTTP/1.1");
+        assert_eq!(http_version(Some("http/1.0")), "HTTP/1.0");
+        assert_eq!(http_version(None), "HTTP/1.1");
+    }
+
+    #[test]
+    fn content_size_is_the_decoded_body() {
+        let record = RequestRecord {
+            url: "https://example.com/app.js".to_string(),
+            method: "GET".to_string(),
+            status: Some(200),
+            protocol: Some("h2".to_string()),
+            encoded_size: Some(5120.0),
+            decoded_size: Some(20480.0),
+            ..Default::default()
+        };
+        let response = &entry(&record)["response"];
+        assert_eq!(response["httpVersion"], "HTTP/2");
+        assert_eq!(response["content"]["size"], 20480);
+        assert_eq!(response["bodySize"], 5120);
+        assert_eq!(response["_transferSize"], 5120);
+    }
+}
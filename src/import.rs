@@ -0,0 +1,194 @@
+use anyhow::Result;
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    DisableParams as FetchDisableParams, EnableParams as FetchEnableParams, EventRequestPaused, FulfillRequestParams,
+    HeaderEntry, RequestPattern,
+};
+use chromiumoxide::cdp::browser_protocol::network::{Cookie, CookieParam, TimeSinceEpoch};
+use chromiumoxide::cdp::browser_protocol::storage::GetCookiesParams;
+use chromiumoxide::{Browser, BrowserConfig, Page};
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::browser::watchdog;
+use crate::chrome;
+use crate::policy;
+use crate::profiles;
+use crate::progress;
+
+// What `import-profile` copies out of a real Chrome profile
+#[derive(Debug, Clone, Default)]
+pub struct ImportedState {
+    pub cookies: Vec<Cookie>,
+    // localStorage items per origin
+    pub local_storage: BTreeMap<String, Vec<(String, String)>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub profile: String,
+    pub cookies: usize,
+    pub local_storage_items: usize,
+}
+
+// Read cookies (and localStorage of https://<domain>) for the given domains from one of the user's Chrome
+// profiles. The profile is copied and opened by a headless Chrome, which decrypts its own cookies, so the
+// real profile is never touched and Chrome may keep running. Each DevTools call gets `timeout`.
+pub async fn read_chrome_profile(profile: Option<&str>, domains: &[String], local_storage: bool, timeout: Duration) -> Result<(String, ImportedState)> {
+    if domains.is_empty() {
+        return Err(anyhow::anyhow!("Name the domains to import with --domain (cookies of every site are never copied)"));
+    }
+    let user_data = chrome::user_data_dir()
+        .ok_or_else(|| anyhow::anyhow!("No Chrome user data directory found on this machine"))?;
+    let profile_dir = resolve_profile(&user_data, profile)?;
+
+    let copy = profiles::new_profile_dir("import");
+    let result = async {
+        copy_profile(&user_data, &profile_dir, &copy)?;
+        read_copy(&copy, &profile_dir, domains, local_storage, timeout).await
+    }.await;
+    std::fs::remove_dir_all(&copy).ok();
+    Ok((profile_dir, result?))
+}
+
+// A profile directory ("Default", "Profile 1") or the name Chrome shows for it ("Work")
+fn resolve_profile(user_data: &Path, wanted: Option<&str>) -> Result<String> {
+    let wanted = wanted.unwrap_or("Default");
+    if user_data.join(wanted).is_dir() {
+        return Ok(wanted.to_string());
+    }
+    let local_state: serde_json::Value = std::fs::read_to_string(user_data.join("Local State")).ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    let profiles = local_state["profile"]["info_cache"].as_object().cloned().unwrap_or_default();
+    profiles.iter()
+        .find(|(_, info)| info["name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(wanted)))
+        .map(|(dir, _)| dir.clone())
+        .ok_or_else(|| {
+            let known: Vec<String> = profiles.iter()
+                .map(|(dir, info)| format!("{} ({})", dir, info["name"].as_str().unwrap_or_default()))
+                .collect();
+            anyhow::anyhow!("No Chrome profile '{}' in {}. Profiles: {}", wanted, user_data.display(),
+                if known.is_empty() { "none".to_string() } else { known.join(", ") })
+        })
+}
+
+// Only what holds cookies, storage and the key to decrypt them
+fn copy_profile(user_data: &Path, profile_dir: &str, copy: &Path) -> Result<()> {
+    std::fs::create_dir_all(copy.join(profile_dir))?;
+    if user_data.join("Local State").is_file() {
+        std::fs::copy(user_data.join("Local State"), copy.join("Local State"))?;
+    }
+    for item in ["Cookies", "Cookies-journal", "Network", "Local Storage", "Preferences"] {
+        let source = user_data.join(profile_dir).join(item);
+        if source.exists() {
+            copy_recursive(&source, &copy.join(profile_dir).join(item))
+                .map_err(|e| anyhow::anyhow!("Failed to copy {}: {}", source.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_recursive(source: &Path, target: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        std::fs::create_dir_all(target)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(source, target).map(|_| ())
+    }
+}
+
+async fn read_copy(copy: &Path, profile_dir: &str, domains: &[String], local_storage: bool, timeout: Duration) -> Result<ImportedState> {
+    // Chromium keeps its cookie key under a different keychain/keyring entry, so Chrome's own binary is needed
+    let executable = chrome::google_chrome_executable()
+        .ok_or_else(|| anyhow::anyhow!("Google Chrome itself is needed to decrypt its profile's cookies, but it wasn't found"))?;
+    let config = BrowserConfig::builder()
+        .chrome_executable(&executable)
+        .user_data_dir(copy)
+        .arg(format!("--profile-directory={}", profile_dir))
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to build browser config: {}", e))?;
+
+    let reading = progress::spinner(format!("Reading Chrome profile '{}'", profile_dir));
+    let (mut browser, mut handler) = Browser::launch(config).await
+        .map_err(|e| anyhow::anyhow!("Failed to launch {}: {}", executable.display(), e))?;
+    tokio::spawn(async move { while handler.next().await.is_some() {} });
+
+    let result = async {
+        let cookies = watchdog(timeout, "get_cookies", browser.execute(GetCookiesParams::default())).await?.result.cookies;
+        let cookies: Vec<Cookie> = cookies.into_iter()
+            .filter(|cookie| domains.iter().any(|domain| policy::domain_matches(cookie.domain.trim_start_matches('.'), domain)))
+            .collect();
+        // Chrome returns empty values when it couldn't decrypt them (keyring locked, app-bound encryption)
+        if !cookies.is_empty() && cookies.iter().all(|cookie| cookie.value.is_empty()) {
+            return Err(anyhow::anyhow!("Chrome could not decrypt the profile's cookies (is the OS keyring unlocked?)"));
+        }
+
+        let mut state = ImportedState { cookies, ..Default::default() };
+        if local_storage {
+            let page = watchdog(timeout, "new_page", browser.new_page("about:blank")).await?;
+            for domain in domains {
+                let origin = format!("https://{}", domain.trim_start_matches("*.").trim_start_matches('.'));
+                open_blank_origin(&page, &origin, timeout).await?;
+                let items: Vec<(String, String)> = watchdog(timeout, "evaluate", page.evaluate("Object.entries(localStorage)")).await?
+                    .into_value().unwrap_or_default();
+                if !items.is_empty() {
+                    state.local_storage.insert(origin, items);
+                }
+            }
+        }
+        Ok(state)
+    }.await;
+    reading.finish_and_clear();
+    browser.close().await.ok();
+    browser.wait().await.ok();
+    result
+}
+
+// Cookie as it can be set again; session cookies stay session cookies
+pub fn cookie_param(cookie: &Cookie) -> CookieParam {
+    let mut param = CookieParam::new(cookie.name.clone(), cookie.value.clone());
+    param.domain = Some(cookie.domain.clone());
+    param.path = Some(cookie.path.clone());
+    param.secure = Some(cookie.secure);
+    param.http_only = Some(cookie.http_only);
+    param.same_site = cookie.same_site.clone();
+    if !cookie.session {
+        param.expires = Some(TimeSinceEpoch::new(cookie.expires));
+    }
+    param
+}
+
+// Load an origin without touching the network, answering every request with an empty page, so its
+// storage can be read or written. Each DevTools call gets `timeout`.
+pub async fn open_blank_origin(page: &Page, origin: &str, timeout: Duration) -> Result<()> {
+    let mut paused = page.event_listener::<EventRequestPaused>().await?;
+    watchdog(timeout, "fetch_enable", page.execute(FetchEnableParams {
+        patterns: Some(vec![RequestPattern { url_pattern: Some("*".to_string()), resource_type: None, request_stage: None }]),
+        handle_auth_requests: None,
+    })).await?;
+    let responder = page.clone();
+    let answering = tokio::spawn(async move {
+        while let Some(event) = paused.next().await {
+            let params = FulfillRequestParams {
+                response_headers: Some(vec![HeaderEntry { name: "Content-Type".to_string(), value: "text/html".to_string() }]),
+                body: Some(base64::engine::general_purpose::STANDARD.encode("<!doctype html><title></title>").into()),
+                ..FulfillRequestParams::new(event.request_id.clone(), 200)
+            };
+            watchdog(timeout, "fulfill_request", responder.execute(params)).await.ok();
+        }
+    });
+
+    let loaded = watchdog(timeout, "goto", page.goto(format!("{}/", origin))).await;
+    answering.abort();
+    watchdog(timeout, "fetch_disable", page.execute(FetchDisableParams::default())).await?;
+    loaded.map_err(|e| anyhow::anyhow!("Failed to open {}: {}", origin, e))?;
+    Ok(())
+}
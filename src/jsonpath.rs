@@ -0,0 +1,292 @@
+use anyhow::Result;
+use serde_json::Value;
+
+// One step of a path: `.name` / `['name']`, `[n]` (negative counts from the end), `*` / `[*]`, or a
+// `[?(…)]` filter over the elements
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Filter(Predicate),
+}
+
+// `@.path`, optionally compared with a literal: `@.name == 'session'`, `@.status >= 400`
+#[derive(Debug, Clone, PartialEq)]
+struct Predicate {
+    path: Vec<Step>,
+    comparison: Option<(Comparison, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+// Longest first, so `<=` isn't read as `<`
+const COMPARISONS: [(&str, Comparison); 6] = [
+    ("==", Comparison::Equal),
+    ("!=", Comparison::NotEqual),
+    ("<=", Comparison::LessOrEqual),
+    (">=", Comparison::GreaterOrEqual),
+    ("<", Comparison::Less),
+    (">", Comparison::Greater),
+];
+
+// The JSONPath subset browser-cli understands: an optional leading `$`, then `.key`, `['key']`,
+// `["key"]`, `[n]`, `[*]`, `.*` and `[?(@.key op literal)]` steps (`items[0].name`, `$.links[*].href`,
+// `$[?(@.name=='session')].value`). A filter without a comparison keeps elements that have the path.
+// Every value the path reaches is returned in document order; a step that doesn't apply (a key on an
+// array, an index past the end) reaches nothing rather than failing.
+pub fn select<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a Value>> {
+    Ok(walk(value, &steps(path)?))
+}
+
+// Check a path's syntax before there is anything to select from
+pub fn validate(path: &str) -> Result<()> {
+    steps(path).map(|_| ())
+}
+
+// A selected value as a line of text: strings without their quotes, objects and arrays pretty-printed
+pub fn render(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(_) | Value::Object(_) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+fn walk<'a>(value: &'a Value, steps: &[Step]) -> Vec<&'a Value> {
+    let mut current = vec![value];
+    for step in steps {
+        current = current.into_iter().flat_map(|value| apply(step, value)).collect();
+    }
+    current
+}
+
+fn steps(path: &str) -> Result<Vec<Step>> {
+    let trimmed = path.trim();
+    parse(trimmed.strip_prefix('$').unwrap_or(trimmed), path)
+}
+
+// Steps of `rest` (a path after its `$` or `@`); `path` is the whole path, for errors
+fn parse(mut rest: &str, path: &str) -> Result<Vec<Step>> {
+    let invalid = |reason: &str| anyhow::anyhow!("Invalid JSON path '{}': {}", path, reason);
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(filter) = rest.strip_prefix("[?(") {
+            let end = filter_end(filter).ok_or_else(|| invalid("unclosed [?("))?;
+            steps.push(Step::Filter(parse_predicate(&filter[..end], path)?));
+            rest = &filter[end + 2..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            // A quoted key may itself hold a `]`
+            let end = outside_quotes(after, "]").ok_or_else(|| invalid("unclosed ["))?;
+            let inner = after[..end].trim();
+            steps.push(match (inner, unquote(inner)) {
+                ("*", _) => Step::Wildcard,
+                (_, Some(key)) => Step::Key(key.to_string()),
+                (index, None) => Step::Index(index.parse().map_err(|_| invalid(&format!("'{}' is not an index", index)))?),
+            });
+            rest = &after[end + 1..];
+        } else {
+            // The first key may leave out its dot (`items[0]`)
+            let after = match rest.strip_prefix('.') {
+                Some(after) => after,
+                None if steps.is_empty() => rest,
+                None => return Err(invalid(&format!("expected . or [ before '{}'", rest))),
+            };
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            steps.push(match &after[..end] {
+                "" if after.starts_with('.') => return Err(invalid("recursive descent (..) isn't supported")),
+                "" => return Err(invalid("empty key")),
+                "*" => Step::Wildcard,
+                key => Step::Key(key.to_string()),
+            });
+            rest = &after[end..];
+        }
+    }
+    Ok(steps)
+}
+
+// Position of the `)]` closing a filter, skipping quoted literals
+fn filter_end(filter: &str) -> Option<usize> {
+    let mut quote = None;
+    for (at, c) in filter.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ')') if filter[at + 1..].starts_with(']') => return Some(at),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_predicate(expression: &str, path: &str) -> Result<Predicate> {
+    let invalid = |reason: &str| anyhow::anyhow!("Invalid JSON path '{}': {}", path, reason);
+    let expression = expression.trim();
+    let relative = expression.strip_prefix('@')
+        .ok_or_else(|| invalid("a filter has to start with @"))?;
+    // The operator is the first one outside the @ path (keys in brackets are quoted, so skip those)
+    let operator = COMPARISONS.iter()
+        .filter_map(|(symbol, comparison)| outside_quotes(relative, symbol).map(|at| (at, *symbol, *comparison)))
+        .min_by_key(|(at, symbol, _)| (*at, std::cmp::Reverse(symbol.len())));
+    let Some((at, symbol, comparison)) = operator else {
+        return Ok(Predicate { path: parse(relative.trim(), path)?, comparison: None });
+    };
+    let literal = relative[at + symbol.len()..].trim();
+    let value = match unquote(literal) {
+        Some(text) => Value::String(text.to_string()),
+        None => serde_json::from_str(literal).map_err(|_| invalid(&format!("'{}' is not a string, number, true, false or null", literal)))?,
+    };
+    Ok(Predicate { path: parse(relative[..at].trim(), path)?, comparison: Some((comparison, value)) })
+}
+
+fn outside_quotes(text: &str, needle: &str) -> Option<usize> {
+    let mut quote = None;
+    for (at, c) in text.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if text[at..].starts_with(needle) => return Some(at),
+            None => {}
+        }
+    }
+    None
+}
+
+// The text of a 'single' or "double" quoted literal
+fn unquote(text: &str) -> Option<&str> {
+    let quoted = text.len() >= 2
+        && ((text.starts_with('\'') && text.ends_with('\'')) || (text.starts_with('"') && text.ends_with('"')));
+    quoted.then(|| &text[1..text.len() - 1])
+}
+
+impl Predicate {
+    fn matches(&self, value: &Value) -> bool {
+        let found = walk(value, &self.path);
+        match &self.comparison {
+            None => !found.is_empty(),
+            Some((comparison, literal)) => found.into_iter().any(|value| compare(value, *comparison, literal)),
+        }
+    }
+}
+
+fn compare(value: &Value, comparison: Comparison, literal: &Value) -> bool {
+    let ordering = match (value, literal) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) => (a == b).then_some(std::cmp::Ordering::Equal),
+    };
+    match comparison {
+        Comparison::Equal => ordering == Some(std::cmp::Ordering::Equal),
+        Comparison::NotEqual => ordering != Some(std::cmp::Ordering::Equal),
+        Comparison::Less => ordering == Some(std::cmp::Ordering::Less),
+        Comparison::LessOrEqual => matches!(ordering, Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)),
+        Comparison::Greater => ordering == Some(std::cmp::Ordering::Greater),
+        Comparison::GreaterOrEqual => matches!(ordering, Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)),
+    }
+}
+
+fn apply<'a>(step: &Step, value: &'a Value) -> Vec<&'a Value> {
+    match (step, value) {
+        (Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+        (Step::Index(index), Value::Array(items)) => {
+            let at = if *index < 0 { items.len() as i64 + index } else { *index };
+            usize::try_from(at).ok().and_then(|at| items.get(at)).into_iter().collect()
+        }
+        (Step::Wildcard, Value::Array(items)) => items.iter().collect(),
+        (Step::Wildcard, Value::Object(map)) => map.values().collect(),
+        (Step::Filter(predicate), Value::Array(items)) => items.iter().filter(|item| predicate.matches(item)).collect(),
+        (Step::Filter(predicate), Value::Object(map)) => map.values().filter(|item| predicate.matches(item)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Value {
+        json!({
+            "items": [
+                { "name": "session", "value": "abc", "status": 200 },
+                { "name": "theme", "value": "dark", "status": 404 },
+                { "name": "lang", "status": 500 }
+            ],
+            "a]b": 1,
+            "]": 2,
+            "it's": 3,
+            "dotted.key": 4
+        })
+    }
+
+    fn values(path: &str) -> Vec<Value> {
+        select(&sample(), path).unwrap().into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn dotted_and_bracketed_keys() {
+        assert_eq!(values("items[0].name"), [json!("session")]);
+        assert_eq!(values("$.items[1]['value']"), [json!("dark")]);
+        assert_eq!(values(r#"$["dotted.key"]"#), [json!(4)]);
+        assert_eq!(values("$.missing.deeper"), Vec::<Value>::new());
+    }
+
+    #[test]
+    fn quoted_keys_may_hold_brackets_and_quotes() {
+        assert_eq!(values("$[']']"), [json!(2)]);
+        assert_eq!(values("$['a]b']"), [json!(1)]);
+        assert_eq!(values(r#"$["it's"]"#), [json!(3)]);
+    }
+
+    #[test]
+    fn indexes_count_from_either_end() {
+        assert_eq!(values("$.items[-1].name"), [json!("lang")]);
+        assert_eq!(values("$.items[ 1 ].name"), [json!("theme")]);
+        assert_eq!(values("$.items[3]"), Vec::<Value>::new());
+        assert_eq!(values("$.items[-4]"), Vec::<Value>::new());
+    }
+
+    #[test]
+    fn wildcards_reach_every_element() {
+        assert_eq!(values("$.items[*].name"), [json!("session"), json!("theme"), json!("lang")]);
+        assert_eq!(values("$.items.*.status"), [json!(200), json!(404), json!(500)]);
+    }
+
+    #[test]
+    fn filters_compare_or_test_for_presence() {
+        assert_eq!(values("$.items[?(@.name=='theme')].value"), [json!("dark")]);
+        assert_eq!(values("$.items[?(@.status >= 404)].name"), [json!("theme"), json!("lang")]);
+        assert_eq!(values("$.items[?(@.status != 200)].name"), [json!("theme"), json!("lang")]);
+        assert_eq!(values("$.items[?(@.value)].name"), [json!("session"), json!("theme")]);
+        assert_eq!(values("$.items[?(@['name'] == 'a)]b')].name"), Vec::<Value>::new());
+    }
+
+    #[test]
+    fn malformed_paths_are_rejected() {
+        let malformed = [
+            "$.items[0", "$['a]", "$.items[x]", "$..name", "$.items.",
+            "$.items[?(@.name == 'x'", "$.items[?(name)]", "$.items[?(@.a == bare)]",
+        ];
+        for path in malformed {
+            assert!(validate(path).is_err(), "{} should not parse", path);
+        }
+    }
+
+    #[test]
+    fn rendering_drops_string_quotes() {
+        assert_eq!(render(&json!("text")), "text");
+        assert_eq!(render(&json!(42)), "42");
+        assert_eq!(render(&json!(null)), "null");
+        assert_eq!(render(&json!([1])), "[\n  1\n]");
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use chromiumoxide::keys::{get_key_definition, KeyDefinition};
+
+// Keyboard layout hint for typing: characters on the layout are sent as real key presses,
+// anything else is inserted as text the way an IME would commit it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeyboardLayout {
+    #[default]
+    Us,
+    Uk,
+    De,
+    Fr,
+    Es,
+}
+
+impl KeyboardLayout {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "us" | "en-us" => Ok(Self::Us),
+            "uk" | "gb" | "en-gb" => Ok(Self::Uk),
+            "de" | "de-de" => Ok(Self::De),
+            "fr" | "fr-fr" => Ok(Self::Fr),
+            "es" | "es-es" => Ok(Self::Es),
+            _ => Err(anyhow::anyhow!("Unknown keyboard layout '{}' (expected us, uk, de, fr or es)", name)),
+        }
+    }
+
+    // Non-ASCII characters with a key of their own on the layout, beyond the US keys every layout shares here
+    fn extra_keys(&self) -> &'static str {
+        match self {
+            Self::Us => "",
+            Self::Uk => "£€¬",
+            Self::De => "äöüÄÖÜß§°€",
+            Self::Fr => "éèàçùâêîôûëïü€²µ°§",
+            Self::Es => "ñÑáéíóúüÁÉÍÓÚÜ¿¡ºª€",
+        }
+    }
+}
+
+// One run of text and how it should be delivered
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    // Characters the US key table knows, pressed one by one
+    Keys(String),
+    // A character on the chosen layout without a US key definition, pressed as a key carrying that text
+    LayoutKey(char),
+    // Everything else (CJK, emoji, ...), inserted or composed as a whole
+    Text(String),
+}
+
+pub fn segments(text: &str, layout: KeyboardLayout) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for c in text.chars() {
+        if get_key_definition(c.encode_utf8(&mut [0; 4])).is_some() {
+            match segments.last_mut() {
+                Some(Segment::Keys(keys)) => keys.push(c),
+                _ => segments.push(Segment::Keys(c.to_string())),
+            }
+        } else if layout.extra_keys().contains(c) {
+            segments.push(Segment::LayoutKey(c));
+        } else {
+            match segments.last_mut() {
+                Some(Segment::Text(text)) => text.push(c),
+                _ => segments.push(Segment::Text(c.to_string())),
+            }
+        }
+    }
+    segments
+}
+
+// Resolve a key name as written on the command line (`shift`, `ctrl`, `Enter`, `a`) to its US key definition
+pub fn key_definition(name: &str) -> Result<&'static KeyDefinition> {
+    let canonical = match name.to_ascii_lowercase().as_str() {
+        "shift" => "Shift",
+        "ctrl" | "control" => "Control",
+        "alt" | "option" => "Alt",
+        "meta" | "cmd" | "command" | "super" | "win" => "Meta",
+        "enter" | "return" => "Enter",
+        "esc" | "escape" => "Escape",
+        "tab" => "Tab",
+        "space" => " ",
+        "backspace" => "Backspace",
+        "delete" | "del" => "Delete",
+        "up" => "ArrowUp",
+        "down" => "ArrowDown",
+        "left" => "ArrowLeft",
+        "right" => "ArrowRight",
+        _ => name,
+    };
+    get_key_definition(canonical).ok_or_else(|| anyhow::anyhow!("Unknown key: {}", name))
+}
+
+// Split a combo like `ctrl+a`, `shift+Tab` or `ctrl+shift+K` into its modifier keys and the key pressed with
+// them; `ctrl++` presses the plus key. Shifted letters resolve to their capital, as a keyboard would send them.
+pub fn parse_combo(combo: &str) -> Result<(Vec<&'static KeyDefinition>, &'static KeyDefinition)> {
+    let (modifiers, key) = match combo.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => combo.rsplit_once('+').unwrap_or(("", combo)),
+    };
+    if key.is_empty() {
+        return Err(anyhow::anyhow!("Missing key in '{}'", combo));
+    }
+    let modifiers = modifiers.split('+')
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let definition = key_definition(name)?;
+            match modifier_bit(definition.key) {
+                0 => Err(anyhow::anyhow!("'{}' in '{}' is not a modifier (use shift, ctrl, alt or meta)", name, combo)),
+                _ => Ok(definition),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let shifted = modifiers.iter().any(|modifier| modifier.key == "Shift");
+    let key = match key_definition(key)? {
+        definition if shifted && definition.key.len() == 1 && definition.key.chars().all(|c| c.is_ascii_lowercase()) => {
+            key_definition(&definition.key.to_ascii_uppercase())?
+        }
+        definition => definition,
+    };
+    Ok((modifiers, key))
+}
+
+// CDP modifier bit for a modifier key (Alt=1, Ctrl=2, Meta=4, Shift=8), 0 for other keys
+pub fn modifier_bit(key: &str) -> i64 {
+    match key {
+        "Alt" => 1,
+        "Control" => 2,
+        "Meta" => 4,
+        "Shift" => 8,
+        _ => 0,
+    }
+}
@@ -0,0 +1,56 @@
+// Browser automation over the Chrome DevTools Protocol. The browser-cli binary is a thin front end
+// over this library; other programs can drive a browser the same way:
+//
+//     let mut browser = browser_cli::BrowserController::builder()
+//         .session("scraper")
+//         .cdp_timeout(30)
+//         .build()?;
+//     browser_cli::output::set_status_output(browser_cli::output::StatusOutput::Silent);
+//     browser.init().await?;
+//     browser.navigate("https://example.com", Default::default()).await?;
+//     let heading = browser.get_text(Some("h1"), &Default::default()).await?;
+//
+// Errors are anyhow::Error; the failures worth handling programmatically carry a browser_cli::Error.
+
+#[macro_use]
+pub mod output;
+
+pub mod assets;
+pub mod audit;
+pub mod auth;
+pub mod browser;
+pub mod capture;
+pub mod checkpoint;
+pub mod chrome;
+pub mod clipboard;
+pub mod compare;
+pub mod config;
+pub mod crawl;
+pub mod daemon;
+pub mod error;
+pub mod event_stream;
+pub mod events;
+pub mod extract;
+pub mod fswatch;
+pub mod handoff;
+pub mod har;
+pub mod import;
+pub mod jsonpath;
+pub mod keyboard;
+pub mod network;
+pub mod paths;
+pub mod policy;
+pub mod profiles;
+pub mod progress;
+pub mod robots;
+pub mod scrape;
+pub mod selftest;
+pub mod serve;
+pub mod sitemap;
+pub mod table;
+
+// The CLI's own version, as reported by `--version` and `version --full`
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub use browser::{BrowserController, BrowserControllerBuilder};
+pub use error::Error;
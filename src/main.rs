@@ -1,19 +1,85 @@
-mod browser;
 mod console;
 
 use anyhow::Result;
-use browser::BrowserController;
-use clap::{Parser, Subcommand};
+use browser_cli::assets::DEFAULT_REPORT_TOP;
+use browser_cli::auth::AccessPolicy;
+use browser_cli::browser::{BrowserController, DEFAULT_DRAG_STEPS, DEFAULT_NETWORK_IDLE_MS, DEFAULT_SEQUENCE_FRAMES, DEFAULT_SEQUENCE_INTERVAL_MS, FakeMedia, LinkFilter, MediaEmulation, NavigateOptions, SessionLimits, TextFormat, TextOptions, TypeOptions, WaitCondition, WaitUntil};
+use browser_cli::compare::{CompareMode, CompareOptions};
+use browser_cli::config::Config;
+use browser_cli::crawl::{CrawlOptions, DEFAULT_CRAWL_DELAY_MS, DEFAULT_CRAWL_DEPTH, DEFAULT_CRAWL_MAX_PAGES};
+use browser_cli::keyboard::KeyboardLayout;
+use browser_cli::output::{self, StatusOutput};
+use browser_cli::paths::{Artifact, DEFAULT_RUNS_KEPT};
+use browser_cli::scrape::ScrapeSpec;
+use browser_cli::sitemap::SitemapFilter;
+use browser_cli::table::TableFormat;
+use browser_cli::{assets, capture, chrome, clipboard, daemon, error, handoff, jsonpath, paths, event_stream, profiles, selftest, serve, status, warning};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::*;
-use console::Console;
+use console::{Console, Script, DEFAULT_DEV_ACTION};
+use serde_json::json;
+use std::io::Write;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::Mutex;
 
 #[derive(Parser)]
 #[command(name = "browser-cli")]
 #[command(about = "Command line browser automation tool")]
-#[command(version = "1.0.0")]
+#[command(version)]
+#[command(after_help = "Exit codes: 0 success, 1 other failure, 2 element not found, 3 timeout, 4 navigation error, \
+5 browser launch failure, 6 blocked by URL policy, 64 bad command line, 128+N killed by signal N")]
 struct Cli {
+    #[arg(long, global = true, help = "Print one JSON result (status, data, error, timing) on stdout; human-readable output goes to stderr")]
+    json: bool,
+    #[arg(long, global = true, value_name = "JSONPATH", help = "Print only the values this JSONPath selects from the command's result (e.g. '$.title', '$[?(@.name==\'session\')].value'), one per line; with --json they become the data")]
+    query: Option<String>,
+    #[arg(long, global = true, value_name = "FILE", help = "Write the command's output (text, page info, --query values, the --json result) to FILE instead of stdout; status lines stay on the terminal. For fetch, where the download goes; for assets icons, crawl, compare-envs and ss-sequence, the directory their files go to")]
+    out: Option<String>,
+    #[arg(long, global = true, requires = "out", help = "Add to the --out file instead of replacing it")]
+    append: bool,
+    #[arg(long, global = true, value_name = "DIR", help = "Where run directories (script logs and step screenshots, reports, traces) go instead of ./browser-cli-runs")]
+    artifacts_dir: Option<std::path::PathBuf>,
+    #[arg(long, global = true, help = "Path to a config file (default: ./browser-cli.toml, then config.toml in the user config dir)")]
+    config: Option<String>,
+    #[arg(long, global = true, help = "Timeout in seconds for each DevTools protocol call (default 30)")]
+    cdp_timeout: Option<u64>,
+    #[arg(long, global = true, help = "Reload the page automatically when a DevTools call times out")]
+    auto_recover: bool,
+    #[arg(long, global = true, help = "Run helper scripts (fill, highlight, elements, ticker) in an isolated world so page overrides can't break them")]
+    isolated_helpers: bool,
+    #[arg(long, global = true, help = "Bypass the page's Content-Security-Policy so injected scripts always run")]
+    bypass_csp: bool,
+    #[arg(long, global = true, conflicts_with = "bypass_csp", help = "Keep the page's CSP enforced and fail clearly when it blocks an injected script")]
+    strict_csp: bool,
+    #[arg(long, global = true, help = "Use fake camera and microphone devices and auto-grant their permissions")]
+    fake_media: bool,
+    #[arg(long, global = true, requires = "fake_media", help = "Video file (.y4m or .mjpeg) for the fake camera")]
+    video_file: Option<std::path::PathBuf>,
+    #[arg(long, global = true, requires = "fake_media", help = "Audio file (.wav) for the fake microphone")]
+    audio_file: Option<std::path::PathBuf>,
+    #[arg(long, global = true, value_name = "URL", help = "Attach to a running browser's DevTools endpoint (ws://…/devtools/browser/… or http://host:port) instead of launching one")]
+    connect: Option<String>,
+    #[arg(long, global = true, conflicts_with = "connect", value_name = "PORT", help = "Attach to a browser started with --remote-debugging-port=PORT on this machine")]
+    connect_port: Option<u16>,
+    #[arg(long, global = true, value_name = "FACTOR", help = "Render at this deviceScaleFactor so screenshots match across machines (default 1)")]
+    device_scale: Option<f64>,
+    #[arg(long, global = true, value_name = "TYPE", value_parser = ["print", "screen"], help = "Emulate this CSS media type on every tab, e.g. print to render with the page's print stylesheets")]
+    emulate_media: Option<String>,
+    #[arg(long, global = true, value_name = "MODE", value_parser = ["active", "none"], help = "Emulate forced-colors on every tab; active renders like Windows High Contrast")]
+    forced_colors: Option<String>,
+    #[arg(long, global = true, value_name = "NAME=VALUE", help = "Emulate a media feature on every tab (repeatable): prefers-contrast, prefers-color-scheme, prefers-reduced-motion or forced-colors")]
+    media_feature: Vec<String>,
+    #[arg(long, global = true, value_delimiter = ',', value_name = "GLOBS", help = "Abort requests whose URL matches any of these comma-separated globs (e.g. '*google-analytics.com*,*/ads/*')")]
+    block_urls: Vec<String>,
+    #[arg(long, global = true, value_parser = parse_session_name, help = "Name of the browser session (letters, digits and _; each session has its own profile)")]
+    session: Option<String>,
+    #[arg(long, global = true, help = "Close a session's browser after this many idle seconds (console, run, dev and daemon)")]
+    idle_timeout: Option<u64>,
+    #[arg(long, global = true, help = "Close a session's browser after it has run this many seconds (console, run, dev and daemon)")]
+    max_lifetime: Option<u64>,
+    #[arg(long, global = true, help = "Close a session's browser when its JS heap exceeds this many MB (console, run, dev and daemon)")]
+    max_memory: Option<u64>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -24,6 +90,10 @@ enum Commands {
     Navigate {
         #[arg(help = "URL to navigate to")]
         url: String,
+        #[arg(long, help = "Fail if more than this many JS errors occur while the page settles")]
+        max_js_errors: Option<usize>,
+        #[arg(long, value_name = "STRATEGY", help = "When the page counts as loaded: load, domcontentloaded, networkidle or selector:<css> (default: the config's navigation.wait_until, else load)")]
+        wait_until: Option<String>,
     },
     #[command(about = "Click an element by CSS selector")]
     Click {
@@ -36,6 +106,42 @@ enum Commands {
         x: f64,
         #[arg(help = "Y coordinate")]
         y: f64,
+        #[arg(long, num_args = 0..=1, value_name = "FILE", help = "Coordinates are screenshot pixels (of FILE if given); convert them to CSS pixels")]
+        from_screenshot: Option<Option<std::path::PathBuf>>,
+    },
+    #[command(about = "Move the mouse over an element, opening hover menus and tooltips")]
+    Hover {
+        #[arg(help = "CSS selector of element to hover")]
+        selector: String,
+    },
+    #[command(about = "Move the mouse to specific coordinates")]
+    HoverAt {
+        #[arg(help = "X coordinate")]
+        x: f64,
+        #[arg(help = "Y coordinate")]
+        y: f64,
+    },
+    #[command(about = "Drag one element onto another (sortable lists, drop zones)")]
+    Drag {
+        #[arg(help = "CSS selector of the element to drag")]
+        from: String,
+        #[arg(help = "CSS selector of the element to drop it on")]
+        to: String,
+        #[arg(long, default_value_t = DEFAULT_DRAG_STEPS, help = "Mouse moves between press and release")]
+        steps: u32,
+    },
+    #[command(about = "Drag between two points (sliders, canvases)")]
+    DragAt {
+        #[arg(help = "Start X coordinate")]
+        x1: f64,
+        #[arg(help = "Start Y coordinate")]
+        y1: f64,
+        #[arg(help = "End X coordinate")]
+        x2: f64,
+        #[arg(help = "End Y coordinate")]
+        y2: f64,
+        #[arg(long, default_value_t = DEFAULT_DRAG_STEPS, help = "Mouse moves between press and release")]
+        steps: u32,
     },
     #[command(about = "Double-click at specific coordinates")]
     DoubleClickAt {
@@ -57,6 +163,15 @@ enum Commands {
         selector: String,
         #[arg(help = "Text to type")]
         text: String,
+        #[arg(long, help = "Keyboard layout whose keys are pressed directly: us, uk, de, fr, es")]
+        layout: Option<String>,
+        #[arg(long, help = "Send text without a key (CJK, emoji) as IME composition instead of a plain insert")]
+        ime: bool,
+    },
+    #[command(about = "Press a key or key combo with real key events")]
+    Press {
+        #[arg(help = "Key or combo, e.g. Enter, ctrl+a, shift+Tab")]
+        combo: String,
     },
     #[command(about = "Scroll the page")]
     Scroll {
@@ -74,11 +189,108 @@ enum Commands {
     Screenshot {
         #[arg(help = "Optional filename for screenshot")]
         filename: Option<String>,
+        #[arg(long, help = "Also copy the captured image to the system clipboard")]
+        clipboard: bool,
+    },
+    #[command(about = "Show the URL, time, viewport and scroll position recorded in a screenshot", visible_alias = "ss-info")]
+    ScreenshotInfo {
+        #[arg(help = "Screenshot file")]
+        file: std::path::PathBuf,
+    },
+    #[command(about = "Capture what the print preview shows: a full-page PNG with print media, or a PDF when the file ends in .pdf")]
+    SsPrint {
+        #[arg(help = "Optional filename (.png, or .pdf to export a PDF)")]
+        filename: Option<String>,
+    },
+    #[command(about = "Capture a numbered burst of viewport screenshots into --out DIR (default: screenshots/sequence in the run directory), to examine an animation or transition frame by frame")]
+    SsSequence {
+        #[arg(long, default_value_t = DEFAULT_SEQUENCE_FRAMES, help = "How many screenshots to take")]
+        frames: usize,
+        #[arg(long, value_name = "MS", default_value_t = DEFAULT_SEQUENCE_INTERVAL_MS, help = "Milliseconds between screenshots")]
+        interval: u64,
+    },
+    #[command(about = "Download a URL using the page's cookies and session")]
+    Fetch {
+        #[arg(help = "URL to download (relative URLs resolve against the current page)")]
+        url: String,
+    },
+    #[command(about = "Inventory the page's assets: download its icons (into --out, default a new capture subdirectory), list its fonts, or report transfer sizes")]
+    Assets {
+        #[command(subcommand)]
+        kind: AssetsKind,
     },
     #[command(about = "Get text content from an element or page info")]
     Text {
         #[arg(help = "CSS selector (optional - gets page info if omitted)")]
         selector: Option<String>,
+        #[arg(long, default_value = "text", help = "Output format: text, json or md")]
+        format: String,
+        #[arg(long, help = "Collapse whitespace and blank lines")]
+        trim: bool,
+        #[arg(long, help = "Truncate each match to this many characters")]
+        max_chars: Option<usize>,
+        #[arg(long, help = "Return every match instead of the first")]
+        all: bool,
+    },
+    #[command(about = "Extract the main article: title, byline, date and content without navigation, ads and other boilerplate")]
+    Article {
+        #[arg(long, default_value = "md", help = "Output format: md, text or json")]
+        format: String,
+    },
+    #[command(about = "Scrape the page into a JSON document, following a spec that maps field names to CSS selectors")]
+    Scrape {
+        #[arg(long, value_name = "FILE", help = "JSON spec: {\"field\": \"selector\"} or {\"field\": {\"selector\": .., \"attr\": .., \"list\": true, \"fields\": {..}}}")]
+        spec: std::path::PathBuf,
+    },
+    #[command(about = "Check whether an element matches; exits 0 when one does, 2 when none does")]
+    Exists {
+        #[arg(help = "CSS selector")]
+        selector: String,
+    },
+    #[command(about = "Print how many elements match a selector")]
+    Count {
+        #[arg(help = "CSS selector")]
+        selector: String,
+    },
+    #[command(about = "List every link on the page with its text and rel attribute")]
+    Links {
+        #[arg(long, help = "Only links to the page's own origin")]
+        same_origin: bool,
+        #[arg(long, value_name = "REGEX", help = "Only links whose URL matches this (JavaScript) regular expression")]
+        pattern: Option<String>,
+    },
+    #[command(about = "Extract an HTML table's rows as CSV or JSON, header rows and colspan/rowspan cells resolved")]
+    Table {
+        #[arg(help = "CSS selector of the table (or of an element containing it)")]
+        selector: String,
+        #[arg(long, default_value = "csv", help = "Output format: csv or json (an object per row, keyed by header)")]
+        format: String,
+    },
+    #[command(about = "Convert the page (or an element) to Markdown: headings, lists, links, tables, code blocks")]
+    Markdown {
+        #[arg(help = "CSS selector (optional - the whole body if omitted)")]
+        selector: Option<String>,
+    },
+    #[command(about = "Get the page's HTML source, or an element's outerHTML (write it to a file with --out)")]
+    Html {
+        #[arg(help = "CSS selector (optional - the whole document if omitted)")]
+        selector: Option<String>,
+        #[arg(long, help = "Put each element on its own indented line")]
+        pretty: bool,
+    },
+    #[command(about = "One-line page summary; with --json also landmarks and interactive elements with their refs")]
+    Info,
+    #[command(about = "Get an element's text as a user would copy it (selection-based, visible text only)")]
+    CopyText {
+        #[arg(help = "CSS selector of the element")]
+        selector: String,
+    },
+    #[command(about = "Assert a JavaScript expression equals an expected JSON value (or is truthy)")]
+    AssertJs {
+        #[arg(help = "JavaScript expression to evaluate")]
+        expression: String,
+        #[arg(help = "Expected value as JSON (bare words compare as strings)")]
+        expected: Option<String>,
     },
     #[command(about = "Wait for an element to appear")]
     WaitFor {
@@ -86,6 +298,14 @@ enum Commands {
         selector: String,
         #[arg(help = "Timeout in seconds", default_value = "10")]
         timeout: Option<u64>,
+        #[arg(long, help = "Also wait until the element is rendered and not hidden")]
+        visible: bool,
+        #[arg(long, help = "Also wait until the element is not disabled")]
+        enabled: bool,
+        #[arg(long, help = "Also wait until the element is visible, enabled and not covered")]
+        clickable: bool,
+        #[arg(long, value_name = "NAME=VALUE", help = "Also wait until the attribute has this value")]
+        attr: Option<String>,
     },
     #[command(about = "Wait for text to appear on page")]
     WaitForText {
@@ -99,112 +319,1142 @@ enum Commands {
         #[arg(help = "Timeout in seconds", default_value = "30")]
         timeout: Option<u64>,
     },
+    #[command(about = "Wait for a client-side (SPA) route change to a matching URL")]
+    WaitRoute {
+        #[arg(help = "URL substring, or a pattern with * wildcards over the whole URL")]
+        pattern: String,
+        #[arg(help = "Timeout in seconds", default_value = "30")]
+        timeout: Option<u64>,
+    },
+    #[command(about = "Wait until the page has made no network requests for a while")]
+    WaitForNetworkIdle {
+        #[arg(help = "Milliseconds without requests that count as idle", default_value_t = DEFAULT_NETWORK_IDLE_MS)]
+        idle_ms: u64,
+        #[arg(help = "Timeout in seconds", default_value = "30")]
+        timeout: Option<u64>,
+    },
     #[command(about = "Highlight an element for debugging")]
     Highlight {
         #[arg(help = "CSS selector to highlight")]
         selector: String,
     },
-    #[command(about = "Close the browser")]
-    Close,
+    #[command(about = "Print links for a human to take over the session: the page, and DevTools for this very browser")]
+    Handoff {
+        #[arg(long, help = "Also open the page in the default browser")]
+        open: bool,
+        #[arg(long, help = "Also open the DevTools link in the default browser")]
+        open_devtools: bool,
+    },
+    #[command(about = "Copy cookies (and localStorage) for some domains from your own Chrome profile, to start signed in")]
+    ImportProfile {
+        #[arg(long, value_name = "PROFILE", required = true, num_args = 0..=1, default_missing_value = "Default", help = "Chrome profile directory or name (default: Default)")]
+        from_chrome: Option<String>,
+        #[arg(long = "domain", required = true, value_delimiter = ',', value_name = "DOMAINS", help = "Domains whose cookies to copy, subdomains included (repeatable or comma-separated)")]
+        domains: Vec<String>,
+        #[arg(long, help = "Also copy localStorage of https://<domain>")]
+        local_storage: bool,
+    },
+    #[command(about = "List open tabs; the active one is marked with *")]
+    Tabs,
+    #[command(about = "Open a URL in a new tab and make it the active one")]
+    NewTab {
+        #[arg(help = "URL to open", default_value = "about:blank")]
+        url: String,
+    },
+    #[command(about = "Make another tab the active one")]
+    SwitchTab {
+        #[arg(help = "Tab index (as listed by tabs) or id")]
+        tab: String,
+    },
+    #[command(about = "Close a tab, the active one by default")]
+    CloseTab {
+        #[arg(help = "Tab index (as listed by tabs) or id")]
+        tab: Option<String>,
+    },
+    #[command(about = "Close the browser of a session (--session, default otherwise) or of every session, and remove its profile")]
+    Close {
+        #[arg(long, help = "Close every browser-cli browser on this machine")]
+        all: bool,
+    },
+    #[command(about = "Show the CLI version, and with --full the browser and protocol versions and enabled features")]
+    Version {
+        #[arg(long, help = "Start or attach to the session's browser and report its versions too")]
+        full: bool,
+    },
+    #[command(about = "Download a pinned Chromium build for browser-cli to use when no browser is installed")]
+    InstallBrowser,
+    #[command(about = "Check the install: serve built-in test pages locally and exercise navigation, input, frames, shadow DOM, dialogs, downloads and screenshots against them")]
+    SelfTest,
+    #[command(about = "Serve a local directory over HTTP until interrupted, e.g. to screenshot or test a front-end build")]
+    ServeDir {
+        #[arg(help = "Directory to serve (its index.html answers /)")]
+        path: std::path::PathBuf,
+        #[arg(long, default_value_t = 0, help = "Port on 127.0.0.1 (0 picks a free one)")]
+        port: u16,
+        #[arg(long, help = "Navigate the browser to the served directory once it's up")]
+        open: bool,
+    },
+    #[command(about = "Keep a browser running so other commands for the same --session reuse it instead of launching their own")]
+    Daemon {
+        #[arg(long, default_value = "127.0.0.1:0", help = "Loopback address for the control endpoint")]
+        bind: String,
+        #[command(flatten)]
+        events: EventStreamArgs,
+    },
+    #[command(about = "Compare every listed path on two hosts by screenshot pixels or main-content text, with an HTML report in --out DIR (default: reports/compare-envs in the run directory)")]
+    CompareEnvs {
+        #[arg(long, value_name = "URL", help = "Reference host, e.g. https://prod.example")]
+        base: String,
+        #[arg(long, value_name = "URL", help = "Host to check against it, e.g. https://staging.example")]
+        target: String,
+        #[arg(long, value_name = "FILE", required_unless_present = "from_sitemap", conflicts_with = "from_sitemap", help = "File with one path per line (# comments allowed)")]
+        paths: Option<std::path::PathBuf>,
+        #[arg(long, value_name = "URL", help = "Take the paths from a sitemap.xml (indexes and .gz followed) instead of a file")]
+        from_sitemap: Option<String>,
+        #[arg(long = "match", value_name = "PATTERN", requires = "from_sitemap", help = "Only sitemap URLs containing this (or matching this * glob)")]
+        pattern: Option<String>,
+        #[arg(long, value_name = "YYYY-MM-DD", requires = "from_sitemap", help = "Only sitemap URLs with a lastmod on or after this day")]
+        since: Option<String>,
+        #[arg(long, default_value = "visual", help = "What to compare: visual (screenshots) or text (main content, unified diffs)")]
+        mode: String,
+        #[arg(long, value_name = "PERCENT", default_value = "0.1", help = "Highest share of differing pixels (or text lines) that still passes")]
+        threshold: f64,
+        #[arg(long, help = "Visit paths robots.txt disallows for browser-cli, and don't wait out its crawl-delay")]
+        ignore_robots: bool,
+        #[arg(long, help = "Continue the run in --out (or the latest run), skipping paths it already compared")]
+        resume: bool,
+    },
+    #[command(about = "Crawl breadth-first from a page, saving each page's text, HTML and screenshot plus a report.json into --out DIR (default: reports/crawl in the run directory)")]
+    Crawl {
+        #[arg(help = "Page to start from", required_unless_present = "from_sitemap")]
+        start: Option<String>,
+        #[arg(long, value_name = "URL", help = "Also start from every page this sitemap lists (sitemap indexes are followed)")]
+        from_sitemap: Option<String>,
+        #[arg(long = "match", value_name = "PATTERN", requires = "from_sitemap", help = "Only sitemap URLs containing this (or matching this * glob)")]
+        pattern: Option<String>,
+        #[arg(long, value_name = "YYYY-MM-DD", requires = "from_sitemap", help = "Only sitemap URLs with a lastmod on or after this day")]
+        since: Option<String>,
+        #[arg(long, default_value_t = DEFAULT_CRAWL_DEPTH, help = "How many links away from the start page to follow (0: the start page only)")]
+        depth: usize,
+        #[arg(long, help = "Only follow links to the start page's origin")]
+        same_origin: bool,
+        #[arg(long, default_value_t = DEFAULT_CRAWL_MAX_PAGES, help = "Stop after visiting this many pages")]
+        max_pages: usize,
+        #[arg(long, default_value_t = DEFAULT_CRAWL_DELAY_MS, help = "Pause between page visits; a longer robots.txt crawl-delay wins")]
+        delay_ms: u64,
+        #[arg(long, help = "Visit pages robots.txt disallows for browser-cli, and don't wait out its crawl-delay")]
+        ignore_robots: bool,
+        #[arg(long, help = "Continue an interrupted crawl in --out (or the latest run's crawl) instead of starting over")]
+        resume: bool,
+    },
+    #[command(about = "Record network traffic to a HAR 1.2 file (across invocations when a daemon runs)")]
+    Har {
+        #[command(subcommand)]
+        action: HarAction,
+    },
+    #[command(about = "List or prune run directories (./browser-cli-runs/<timestamp>, or --artifacts-dir)")]
+    Runs {
+        #[command(subcommand)]
+        action: Option<RunsAction>,
+    },
+    #[command(about = "Run a file of console commands against one browser, stopping at the first failing step")]
+    Run {
+        #[arg(help = "Script file: one console command per line, # comments, ${NAME} variables")]
+        file: String,
+        #[arg(long = "var", value_name = "NAME=VALUE", help = "Preset a ${NAME} variable (repeatable)")]
+        vars: Vec<String>,
+        #[arg(long, value_name = "MODE", help = "Capture screenshots during the run: after-each")]
+        screenshots: Option<String>,
+        #[arg(long, help = "Stop at the first failing step (the default)")]
+        fail_fast: bool,
+        #[arg(long, conflicts_with = "fail_fast", help = "Run every step even after failures; the run still fails if any step did")]
+        continue_on_error: bool,
+    },
+    #[command(about = "Live preview: open a page and rerun actions in the same browser whenever files under a directory change")]
+    Dev {
+        #[arg(long, help = "Directory to watch (.git, node_modules and target are skipped)")]
+        dir: std::path::PathBuf,
+        #[arg(long, help = "Page to open, e.g. a dev server's http://localhost:3000 (default: serve --dir itself)")]
+        url: Option<String>,
+        #[arg(long = "on-change", value_name = "COMMAND", default_value = DEFAULT_DEV_ACTION, help = "Console command to run on each change: reload, screenshot [file], run <script>, ... (repeatable, run in order)")]
+        on_change: Vec<String>,
+        #[arg(long, default_value_t = 500, value_name = "MS", help = "How often to check the directory for changes")]
+        poll_ms: u64,
+    },
     #[command(about = "Enter interactive console mode")]
-    Console,
+    Console {
+        #[command(flatten)]
+        events: EventStreamArgs,
+    },
+}
+
+// The Server-Sent Events stream `console` and `daemon` can publish
+#[derive(clap::Args)]
+struct EventStreamArgs {
+    #[arg(long, help = "Publish a Server-Sent Events stream of browser events on this address (e.g. 127.0.0.1:9300)")]
+    events: Option<String>,
+    #[arg(long = "token", help = "API token the event stream requires, as a Bearer header or ?token=; TOKEN or TOKEN:read|interactive, where only interactive tokens may POST /navigate (repeatable)")]
+    tokens: Vec<String>,
+    #[arg(long = "allow-bind", help = "Non-loopback address the event stream may bind to (repeatable)")]
+    allow_bind: Vec<String>,
+    #[arg(long, requires = "tls_key", help = "PEM certificate to serve the event stream over TLS")]
+    tls_cert: Option<String>,
+    #[arg(long, requires = "tls_cert", help = "PEM private key for --tls-cert")]
+    tls_key: Option<String>,
+}
+
+impl EventStreamArgs {
+    // Where to serve the stream and who may use it; None without --events
+    fn endpoint(self) -> Result<Option<(String, AccessPolicy)>> {
+        let Some(addr) = self.events else {
+            return Ok(None);
+        };
+        let mut policy = AccessPolicy::new(&self.tokens, &self.allow_bind)?;
+        if let (Some(cert), Some(key)) = (self.tls_cert, self.tls_key) {
+            policy = policy.with_tls(&cert, &key)?;
+        }
+        Ok(Some((addr, policy)))
+    }
+}
+
+#[derive(Subcommand)]
+enum HarAction {
+    #[command(about = "Start capturing every request and response")]
+    Start {
+        #[arg(help = "HAR file to write on stop (bare names go to the capture directory; default traces/network-<time>.har in the run directory)")]
+        file: Option<String>,
+    },
+    #[command(about = "Stop capturing and write the HAR file")]
+    Stop,
+}
+
+#[derive(Subcommand)]
+enum RunsAction {
+    #[command(about = "List run directories, oldest first")]
+    List,
+    #[command(about = "Delete all but the newest run directories")]
+    Prune {
+        #[arg(long, default_value_t = DEFAULT_RUNS_KEPT, help = "How many of the newest runs to keep")]
+        keep: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum AssetsKind {
+    #[command(about = "Favicons (link icons, apple-touch-icon, mask-icon, manifest icons) and og:image / twitter:image previews")]
+    Icons,
+    #[command(about = "List the web fonts the page declares and downloads: family, weight, style, source URL, bytes")]
+    Fonts,
+    #[command(about = "Bytes transferred by the last page load, grouped by resource type with the heaviest files")]
+    Report {
+        #[arg(long, default_value_t = DEFAULT_REPORT_TOP, help = "Heaviest files to list per type")]
+        top: usize,
+    },
+}
+
+// Where a command's output goes: stdout, or the --out file, replaced by the first write of the run or
+// added to with --append. Status lines never go here.
+struct DataOut {
+    // Taken as given, relative to the working directory like shell redirection; unlike screenshots, output
+    // files don't go to the capture directory
+    path: std::cell::RefCell<Option<std::path::PathBuf>>,
+    append: bool,
+    written: std::cell::Cell<bool>,
+}
+
+impl DataOut {
+    fn new(out: Option<&str>, append: bool) -> Self {
+        let path = out.map(std::path::PathBuf::from);
+        Self { path: std::cell::RefCell::new(path), append, written: std::cell::Cell::new(false) }
+    }
+
+    fn print(&self, text: &str) -> Result<()> {
+        let path = self.path.borrow();
+        let Some(path) = path.as_ref() else {
+            println!("{}", text);
+            return Ok(());
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(self.append || self.written.get())
+            .write(true)
+            .truncate(!self.append && !self.written.get())
+            .open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+        writeln!(file, "{}", text).map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        self.written.set(true);
+        Ok(())
+    }
+
+    // Text that is cyan on the terminal, plain in a file
+    fn print_highlighted(&self, text: &str) -> Result<()> {
+        match self.path.borrow().as_ref() {
+            Some(_) => self.print(text),
+            None => {
+                println!("{}", text.cyan());
+                Ok(())
+            }
+        }
+    }
+
+    // fetch downloads to --out itself; its result then goes to stdout
+    fn take_path(&self) -> Option<std::path::PathBuf> {
+        self.path.borrow_mut().take()
+    }
+
+    // Commands that write a directory of files take --out as that directory
+    fn take_dir(&self, command: &str) -> Result<Option<std::path::PathBuf>> {
+        if self.append {
+            return Err(anyhow::anyhow!("{} writes files into its --out directory; --append doesn't apply", command));
+        }
+        Ok(self.take_path())
+    }
+
+    fn report(&self) {
+        if let (Some(path), true) = (self.path.borrow().as_ref(), self.written.get()) {
+            let verb = if self.append { "Appended" } else { "Saved" };
+            status!("{} {} output to {}", "💾".green(), verb, path.display());
+        }
+    }
+}
+
+fn exit_usage(e: clap::Error) -> ! {
+    e.print().ok();
+    std::process::exit(if e.use_stderr() { error::EXIT_USAGE } else { error::EXIT_SUCCESS });
+}
+
+fn parse_session_name(name: &str) -> Result<String, error::Error> {
+    profiles::check_session_name(name)?;
+    Ok(name.to_string())
+}
+
+// Commands that keep a browser running and reap it once it breaks --idle-timeout, --max-lifetime or
+// --max-memory; elsewhere the limits would silently do nothing
+const LIMITED_COMMANDS: [&str; 4] = ["console", "run", "dev", "daemon"];
+
+fn check_session_limits(cli: &Cli, command: &str) -> Result<(), clap::Error> {
+    let limited = cli.idle_timeout.is_some() || cli.max_lifetime.is_some() || cli.max_memory.is_some();
+    if !limited || LIMITED_COMMANDS.contains(&command) {
+        return Ok(());
+    }
+    Err(Cli::command().error(clap::error::ErrorKind::ArgumentConflict, format!(
+        "--idle-timeout, --max-lifetime and --max-memory only apply to {}, not {}", LIMITED_COMMANDS.join(", "), command)))
+}
+
+// How long shutdown waits for the command in flight before closing the browser from under it
+const SHUTDOWN_DRAIN: std::time::Duration = std::time::Duration::from_secs(10);
+
+// Resolves on the first termination request, with its name and the conventional 128+signal exit code
+#[cfg(unix)]
+async fn shutdown_signal() -> (&'static str, i32) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let (Ok(mut term), Ok(mut hup)) = (signal(SignalKind::terminate()), signal(SignalKind::hangup())) else {
+        tokio::signal::ctrl_c().await.ok();
+        return ("interrupt signal", 130);
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => ("interrupt signal", 130),
+        _ = term.recv() => ("SIGTERM", 143),
+        _ = hup.recv() => ("SIGHUP", 129),
+    }
+}
+
+#[cfg(windows)]
+async fn shutdown_signal() -> (&'static str, i32) {
+    use tokio::signal::windows::{ctrl_break, ctrl_close, ctrl_logoff, ctrl_shutdown};
+    let (Ok(mut brk), Ok(mut close), Ok(mut logoff), Ok(mut shutdown)) = (ctrl_break(), ctrl_close(), ctrl_logoff(), ctrl_shutdown()) else {
+        tokio::signal::ctrl_c().await.ok();
+        return ("interrupt signal", 130);
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => ("interrupt signal", 130),
+        _ = brk.recv() => ("Ctrl-Break", 130),
+        _ = close.recv() => ("console close", 1),
+        _ = logoff.recv() => ("logoff", 1),
+        _ = shutdown.recv() => ("system shutdown", 1),
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let browser = Arc::new(Mutex::new(BrowserController::new()));
+    // Usage errors get their own code; clap's default (2) is element-not-found here
+    let matches = Cli::command().try_get_matches().unwrap_or_else(|e| exit_usage(e));
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
+    let cli = Cli::from_arg_matches(&matches)?;
+    if let Err(e) = check_session_limits(&cli, &command_name) {
+        exit_usage(e);
+    }
+    if let Some(query) = &cli.query {
+        jsonpath::validate(query)?;
+    }
+    if let Some(dir) = cli.artifacts_dir.clone() {
+        paths::set_artifacts_dir(dir);
+    }
+    let out = DataOut::new(cli.out.as_deref(), cli.append);
+    // Status lines would get mixed into the selected values
+    if cli.json || cli.query.is_some() {
+        output::set_status_output(StatusOutput::Stderr);
+    }
+    let config = Config::load(cli.config.as_deref())?;
+    let mut builder = BrowserController::builder()
+        .auto_recover(cli.auto_recover)
+        .isolated_helpers(cli.isolated_helpers)
+        .bypass_csp(cli.bypass_csp)
+        .strict_csp(cli.strict_csp)
+        .limits(SessionLimits {
+            idle_timeout: cli.idle_timeout.map(std::time::Duration::from_secs),
+            max_lifetime: cli.max_lifetime.map(std::time::Duration::from_secs),
+            max_memory_mb: cli.max_memory,
+        })
+        .url_policy(config.policy)
+        .block_urls(cli.block_urls.clone())
+        .flag_presets(config.flags)
+        .navigation(config.navigation);
+    if let Some(name) = cli.session.as_deref() {
+        builder = builder.session(name);
+    }
+    if let Some(secs) = cli.cdp_timeout {
+        builder = builder.cdp_timeout(secs);
+    }
+    if let Some(endpoint) = cli.connect.clone().or(cli.connect_port.map(|port| format!("http://127.0.0.1:{}", port))) {
+        builder = builder.connect(endpoint);
+    }
+    if let Some(scale) = cli.device_scale {
+        builder = builder.device_scale(scale);
+    }
+    let mut media_emulation = MediaEmulation { media: cli.emulate_media.clone(), ..Default::default() };
+    if let Some(mode) = cli.forced_colors.as_deref() {
+        media_emulation.set_feature("forced-colors", Some(mode))?;
+    }
+    for feature in &cli.media_feature {
+        let (name, value) = feature.split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Invalid --media-feature '{}' (expected NAME=VALUE)", feature))?;
+        media_emulation.set_feature(name, Some(value))?;
+    }
+    builder = builder.media_emulation(media_emulation);
+    if cli.fake_media {
+        builder = builder.fake_media(Some(FakeMedia { video_file: cli.video_file, audio_file: cli.audio_file }));
+    }
+    let controller = builder.build()?;
+    let browser = Arc::new(Mutex::new(controller));
     
     // Set up signal handling for graceful shutdown
     let browser_clone = Arc::clone(&browser);
+    let (json_output, interrupted_command) = (cli.json, command_name.clone());
     tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.ok();
-        println!("{}", "\nReceived interrupt signal, closing browser...".yellow());
-        let mut browser = browser_clone.lock().await;
-        browser.close().await.ok();
-        std::process::exit(0);
+        let (signal, code) = shutdown_signal().await;
+        status!("{}", format!("\nReceived {}, closing browser...", signal).yellow());
+        // The command in flight holds the lock; let it finish, but don't hang on one that's stuck
+        match tokio::time::timeout(SHUTDOWN_DRAIN, browser_clone.lock()).await {
+            Ok(mut browser) => {
+                daemon::remove_owned_state(browser.session_name());
+                // A running HAR capture only exists in memory; script logs and reports are written as they go
+                if browser.har_capturing() {
+                    if let Err(e) = browser.har_stop() {
+                        warning!("Failed to save the HAR capture: {:#}", e);
+                    }
+                }
+                browser.close().await.ok();
+            }
+            Err(_) => eprintln!("{} Command still running after {}s, shutting down anyway", "⚠️".yellow(), SHUTDOWN_DRAIN.as_secs()),
+        }
+        // Console sessions and anything the drain skipped still have profiles on disk
+        profiles::shutdown_all(&profiles::owned()).await;
+        if json_output {
+            let error = format!("Interrupted by {}", signal);
+            println!("{}", json!({ "command": interrupted_command, "status": "error", "data": null, "error": error, "timing": null }));
+        }
+        std::io::Write::flush(&mut std::io::stdout()).ok();
+        std::io::Write::flush(&mut std::io::stderr()).ok();
+        std::process::exit(code);
     });
 
-    match cli.command {
-        Commands::Navigate { url } => {
+    let session = cli.session.clone().unwrap_or_else(|| "default".to_string());
+    let started_at = chrono::Utc::now();
+    let started = Instant::now();
+    // --query needs the data even without --json, so commands return it instead of printing
+    let mut result = run_command(cli.command, &browser, &session, cli.json || cli.query.is_some(), &out).await;
+    if let (Some(query), Ok(data)) = (&cli.query, &result) {
+        result = jsonpath::select(data, query)
+            .map(|values| serde_json::Value::Array(values.into_iter().cloned().collect()));
+    }
+    if !cli.json {
+        if let (Some(_), Ok(serde_json::Value::Array(values))) = (&cli.query, &result) {
+            for value in values {
+                out.print(&jsonpath::render(value))?;
+            }
+        }
+        out.report();
+        if let Err(e) = &result {
+            eprintln!("Error: {:?}", e);
+            std::process::exit(error::exit_code(e));
+        }
+        return Ok(());
+    }
+
+    let timing = json!({ "started_at": started_at.to_rfc3339(), "elapsed_ms": started.elapsed().as_millis() as u64 });
+    let (status, data, error) = match &result {
+        Ok(data) => ("ok", data.clone(), serde_json::Value::Null),
+        Err(e) => ("error", serde_json::Value::Null, json!(format!("{:#}", e))),
+    };
+    out.print(&json!({ "command": command_name, "status": status, "data": data, "error": error, "timing": timing }).to_string())?;
+    out.report();
+    if let Err(e) = &result {
+        std::process::exit(error::exit_code(e));
+    }
+    Ok(())
+}
+
+// Run one subcommand; the returned data is what --json reports, human output is printed along the way
+// (command output through `out`, status lines directly)
+async fn run_command(command: Commands, browser: &Arc<Mutex<BrowserController>>, session: &str, json_output: bool, out: &DataOut) -> Result<serde_json::Value> {
+    let data = match command {
+        Commands::Navigate { url, max_js_errors, wait_until } => {
+            let wait_until = wait_until.as_deref().map(WaitUntil::parse).transpose()?;
             let mut browser = browser.lock().await;
-            browser.navigate(&url).await?;
+            let summary = browser.navigate(&url, NavigateOptions { max_js_errors, wait_until }).await?;
+            serde_json::to_value(summary)?
         }
         Commands::Click { selector } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.click(&selector).await?;
+            json!({ "selector": selector })
+        }
+        Commands::Hover { selector } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            browser.hover(&selector).await?;
+            json!({ "selector": selector })
+        }
+        Commands::HoverAt { x, y } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            browser.hover_at(x, y).await?;
+            json!({ "x": x, "y": y })
+        }
+        Commands::Drag { from, to, steps } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            browser.drag(&from, &to, steps).await?;
+            json!({ "from": from, "to": to, "steps": steps })
         }
-        Commands::ClickAt { x, y } => {
+        Commands::DragAt { x1, y1, x2, y2, steps } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
-            browser.click_at_coordinates(x, y).await?;
+            browser.drag_at((x1, y1), (x2, y2), steps).await?;
+            json!({ "from": [x1, y1], "to": [x2, y2], "steps": steps })
+        }
+        Commands::ClickAt { x, y, from_screenshot } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let scale = match &from_screenshot {
+                Some(file) => browser.screenshot_scale(file.as_deref()).await?,
+                None => 1.0,
+            };
+            if from_screenshot.is_some() {
+                status!("{} Screenshot ({}, {}) is CSS ({:.1}, {:.1}) at {}x", "🎯".cyan(), x, y, x / scale, y / scale, scale);
+            }
+            browser.click_at_coordinates(x / scale, y / scale).await?;
+            json!({ "x": x / scale, "y": y / scale, "scale": scale })
         }
         Commands::DoubleClickAt { x, y } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.double_click_at_coordinates(x, y).await?;
+            json!({ "x": x, "y": y })
         }
         Commands::RightClickAt { x, y } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.right_click_at_coordinates(x, y).await?;
+            json!({ "x": x, "y": y })
+        }
+        Commands::Type { selector, text, layout, ime } => {
+            let options = TypeOptions {
+                layout: layout.as_deref().map(KeyboardLayout::parse).transpose()?.unwrap_or_default(),
+                ime,
+            };
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            browser.type_text(&selector, &text, &options).await?;
+            json!({ "selector": selector, "chars": text.chars().count() })
         }
-        Commands::Type { selector, text } => {
+        Commands::Press { combo } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
-            browser.type_text(&selector, &text).await?;
+            browser.press_key(&combo).await?;
+            json!({ "key": combo })
         }
         Commands::Scroll { direction, amount } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.scroll(&direction, amount).await?;
+            json!({ "direction": direction, "amount": amount })
         }
         Commands::Search { query } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
-            browser.search(&query).await?;
+            let input = browser.search(&query).await?;
+            json!({ "query": query, "input": input })
+        }
+        Commands::Screenshot { filename, clipboard } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let path = browser.screenshot(filename.as_deref()).await?;
+            if clipboard {
+                clipboard::copy_png(std::path::Path::new(&path))?;
+                status!("{} Copied to clipboard", "📋".cyan());
+            }
+            json!({ "path": path, "clipboard": clipboard })
+        }
+        Commands::ScreenshotInfo { file } => {
+            let meta = capture::read(&file)?;
+            if !json_output {
+                out.print(&meta.to_string())?;
+            }
+            serde_json::to_value(meta)?
+        }
+        Commands::SsPrint { filename } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let path = browser.print_capture(filename.as_deref()).await?;
+            json!({ "path": path })
+        }
+        Commands::SsSequence { frames, interval } => {
+            let out = out.take_dir("ss-sequence")?;
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let (dir, frames) = browser.screenshot_sequence(frames, std::time::Duration::from_millis(interval), out.as_deref()).await?;
+            json!({ "dir": dir, "frames": frames })
+        }
+        Commands::Fetch { url } => {
+            if out.append {
+                return Err(anyhow::anyhow!("fetch replaces its --out file; --append doesn't apply"));
+            }
+            let target = out.take_path().map(|path| path.display().to_string());
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let path = browser.fetch_to_file(&url, target.as_deref()).await?;
+            json!({ "url": url, "path": path })
+        }
+        Commands::Assets { kind: AssetsKind::Fonts } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let fonts = browser.font_inventory().await?;
+            assets::print_fonts(&fonts);
+            json!({ "fonts": fonts })
+        }
+        Commands::Assets { kind: AssetsKind::Report { top } } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let groups = assets::group_by_type(&browser.network_requests(false), top);
+            assets::print_report(&groups);
+            json!({ "groups": groups })
+        }
+        Commands::Assets { kind: AssetsKind::Icons } => {
+            let dir = match out.take_dir("assets")? {
+                Some(dir) => dir,
+                None => paths::capture_subdir(&format!("icons-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")))?,
+            };
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let mut icons = browser.page_icons().await?;
+            browser.download_assets(&mut icons, &dir).await?;
+            assets::print_icons(&icons);
+            json!({ "dir": dir, "assets": icons })
+        }
+        Commands::Text { selector, format, trim, max_chars, all } => {
+            let options = TextOptions { format: TextFormat::parse(&format)?, trim, max_chars, all };
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let text = browser.get_text(selector.as_deref(), &options).await?;
+            if json_output {
+                // --format json already produces JSON; embed it rather than a string of it
+                match options.format {
+                    TextFormat::Json => serde_json::from_str(&text).unwrap_or(json!(text)),
+                    _ => json!({ "text": text }),
+                }
+            } else {
+                match options.format {
+                    TextFormat::Text => out.print_highlighted(&text)?,
+                    _ => out.print(&text)?,
+                }
+                serde_json::Value::Null
+            }
+        }
+        Commands::Info => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let details = browser.page_details().await?;
+            if !json_output {
+                out.print(&details.summary.to_string())?;
+            }
+            serde_json::to_value(details)?
+        }
+        Commands::Article { format } => {
+            let format = TextFormat::parse(&format)?;
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let article = browser.get_article().await?;
+            if !json_output {
+                match format {
+                    TextFormat::Markdown => out.print(&article.to_markdown())?,
+                    TextFormat::Text => out.print(&article.to_text())?,
+                    TextFormat::Json => out.print(&serde_json::to_string_pretty(&article)?)?,
+                }
+            }
+            serde_json::to_value(article)?
+        }
+        Commands::Scrape { spec } => {
+            let spec = ScrapeSpec::load(&spec)?;
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let result = browser.scrape(&spec).await?;
+            if !result.missing.is_empty() {
+                warning!("Nothing matched for: {}", result.missing.join(", "));
+            }
+            if !json_output {
+                out.print(&serde_json::to_string_pretty(&result.data)?)?;
+            }
+            serde_json::to_value(result)?
+        }
+        Commands::Exists { selector } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let count = browser.count_elements(&selector).await?;
+            if count == 0 {
+                return Err(error::Error::element_not_found(selector).into());
+            }
+            json!({ "selector": selector, "exists": true, "count": count })
+        }
+        Commands::Count { selector } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let count = browser.count_elements(&selector).await?;
+            if !json_output {
+                out.print(&count.to_string())?;
+            }
+            json!({ "selector": selector, "count": count })
+        }
+        Commands::Links { same_origin, pattern } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let links = browser.get_links(&LinkFilter { same_origin, pattern }).await?;
+            if !json_output {
+                for link in &links {
+                    out.print(&link.to_string())?;
+                }
+            }
+            serde_json::to_value(links)?
+        }
+        Commands::Table { selector, format } => {
+            let format = TableFormat::parse(&format)?;
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let table = browser.get_table(&selector).await?;
+            if !json_output {
+                match format {
+                    TableFormat::Csv => out.print(&table.to_csv())?,
+                    TableFormat::Json => out.print(&serde_json::to_string_pretty(&table.to_json())?)?,
+                }
+            }
+            json!({ "selector": selector, "headers": table.headers, "rows": table.to_json() })
         }
-        Commands::Screenshot { filename } => {
+        Commands::Markdown { selector } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
-            browser.screenshot(filename.as_deref()).await?;
+            let markdown = browser.get_markdown(selector.as_deref()).await?;
+            if !json_output {
+                out.print(&markdown)?;
+            }
+            json!({ "selector": selector, "markdown": markdown })
         }
-        Commands::Text { selector } => {
+        Commands::Html { selector, pretty } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
-            let text = browser.get_text(selector.as_deref()).await?;
-            println!("{}", text.cyan());
+            let html = browser.get_html(selector.as_deref(), pretty).await?;
+            if !json_output {
+                out.print(&html)?;
+            }
+            json!({ "selector": selector, "html": html })
         }
-        Commands::WaitFor { selector, timeout } => {
+        Commands::CopyText { selector } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
-            browser.wait_for_selector(&selector, timeout).await?;
+            let text = browser.copy_text(&selector).await?;
+            if !json_output {
+                out.print_highlighted(&text)?;
+            }
+            json!({ "selector": selector, "text": text })
+        }
+        Commands::AssertJs { expression, expected } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let actual = browser.assert_js(&expression, expected.as_deref()).await?;
+            json!({ "expression": expression, "value": actual })
+        }
+        Commands::WaitFor { selector, timeout, visible, enabled, clickable, attr } => {
+            let condition = WaitCondition {
+                visible,
+                enabled,
+                clickable,
+                attr: attr.as_deref().map(WaitCondition::parse_attr).transpose()?,
+            };
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            browser.wait_for_selector(&selector, timeout, &condition).await?;
+            json!({ "selector": selector })
         }
         Commands::WaitForText { text, timeout } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.wait_for_text(&text, timeout).await?;
+            json!({ "text": text })
         }
         Commands::WaitForNavigation { timeout } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.wait_for_navigation(timeout).await?;
+            json!({ "url": browser.get_url().await? })
+        }
+        Commands::WaitRoute { pattern, timeout } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let url = browser.wait_for_route(&pattern, timeout).await?;
+            json!({ "pattern": pattern, "url": url })
+        }
+        Commands::WaitForNetworkIdle { idle_ms, timeout } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            browser.wait_for_network_idle(std::time::Duration::from_millis(idle_ms), timeout).await?;
+            json!({ "url": browser.get_url().await?, "idle_ms": idle_ms })
         }
         Commands::Highlight { selector } => {
             let mut browser = browser.lock().await;
             browser.init().await?;
             browser.highlight_element(&selector).await?;
+            json!({ "selector": selector })
+        }
+        Commands::Handoff { open, open_devtools } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let details = browser.handoff().await?;
+            if !json_output {
+                details.print();
+            }
+            if browser.attachment().is_none() {
+                status!("{} This browser closes when the command exits; start 'browser-cli daemon' first to keep it for the handoff", "⚠️".yellow());
+            }
+            if open {
+                handoff::open_url(&details.url)?;
+            }
+            if open_devtools {
+                handoff::open_url(details.devtools.as_deref().ok_or_else(|| anyhow::anyhow!("No DevTools link for {}", details.websocket))?)?;
+            }
+            serde_json::to_value(details)?
+        }
+        Commands::ImportProfile { from_chrome, domains, local_storage } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let summary = browser.import_chrome_profile(from_chrome.as_deref(), &domains, local_storage).await?;
+            if browser.attachment().is_none() {
+                status!("{} This browser closes when the command exits; start 'browser-cli daemon' first to keep the imported session", "⚠️".yellow());
+            }
+            serde_json::to_value(summary)?
+        }
+        Commands::Tabs => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let tabs = browser.tabs().await?;
+            if !json_output {
+                for tab in &tabs {
+                    out.print(&tab.to_string())?;
+                }
+            }
+            serde_json::to_value(tabs)?
+        }
+        Commands::NewTab { url } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            serde_json::to_value(browser.new_tab(&url).await?)?
+        }
+        Commands::SwitchTab { tab } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            serde_json::to_value(browser.switch_tab(&tab).await?)?
+        }
+        Commands::CloseTab { tab } => {
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            serde_json::to_value(browser.close_tab(tab.as_deref()).await?)?
+        }
+        Commands::Close { all } => {
+            let mut stopped_daemons = Vec::new();
+            if all {
+                for daemon_session in daemon::sessions() {
+                    match daemon::stop(&daemon_session).await {
+                        Ok(Some(info)) => {
+                            status!("{} Stopped daemon for session '{}' (pid {})", "✓".green(), info.session, info.pid);
+                            stopped_daemons.push(info);
+                        }
+                        Ok(None) => {}
+                        Err(e) => status!("{} Daemon for session '{}': {}", "⚠️".yellow(), daemon_session, e),
+                    }
+                }
+            } else if let Some(info) = daemon::stop(session).await? {
+                status!("{} Stopped daemon for session '{}' (pid {})", "✓".green(), info.session, info.pid);
+                stopped_daemons.push(info);
+            }
+            // Browsers live in the processes that launched them; find them through their profile directories
+            // (a stopped daemon removes its own, anything left over is cleaned up here)
+            let targets: Vec<_> = profiles::list().into_iter()
+                .filter(|profile| all || profile.session == session)
+                .collect();
+            if targets.is_empty() {
+                match all {
+                    true => status!("{} No browser-cli browsers running", "ℹ️".blue()),
+                    false => status!("{} No browser running for session '{}'", "ℹ️".blue(), session),
+                }
+            }
+            let closed: Vec<_> = profiles::shutdown_all(&targets).await.into_iter()
+                .map(|(profile, was_running)| json!({
+                    "session": profile.session,
+                    "pid": profile.pid,
+                    "profile": profile.path,
+                    "was_running": was_running,
+                }))
+                .collect();
+            let daemons: Vec<_> = stopped_daemons.iter().map(|info| json!({ "session": info.session, "pid": info.pid })).collect();
+            json!({
+                "daemon_pid": stopped_daemons.iter().find(|info| info.session == session).map(|info| info.pid),
+                "daemons": daemons,
+                "closed": closed,
+            })
+        }
+        Commands::Version { full } => {
+            if !full {
+                status!("browser-cli {}", browser_cli::VERSION);
+                return Ok(json!({ "cli_version": browser_cli::VERSION }));
+            }
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let about = browser.about().await?;
+            about.print();
+            serde_json::to_value(about)?
+        }
+        Commands::InstallBrowser => {
+            let executable = chrome::install().await?;
+            status!("{} Chromium installed: {}", "✓".green(), executable.display());
+            json!({ "executable": executable })
+        }
+        Commands::SelfTest => {
+            let mut browser = browser.lock().await;
+            let report = selftest::run(&mut browser).await?;
+            let report_path = paths::run_artifacts(Artifact::Reports)?.join("self-test.json");
+            std::fs::write(&report_path, serde_json::to_string_pretty(&report)?)?;
+            status!("  {} {}", "Report:".dimmed(), report_path.display());
+            if report.failed() > 0 {
+                return Err(anyhow::anyhow!("{} of {} self-test checks failed", report.failed(), report.checks.len()));
+            }
+            serde_json::to_value(report)?
+        }
+        Commands::ServeDir { path, port, open } => {
+            let addr = serve::serve_dir(&path, port).await?;
+            let url = format!("http://{}/", addr);
+            if open {
+                let mut browser = browser.lock().await;
+                browser.init().await?;
+                browser.navigate(&url, NavigateOptions::default()).await?;
+            }
+            status!("{}", "Press Ctrl-C to stop serving".dimmed());
+            std::future::pending::<()>().await;
+            json!({ "url": url })
+        }
+        Commands::Daemon { bind, events } => {
+            daemon::serve(Arc::clone(browser), &bind, events.endpoint()?).await?;
+            serde_json::Value::Null
+        }
+        Commands::CompareEnvs { base, target, paths, from_sitemap, pattern, since, mode, threshold, ignore_robots, resume } => {
+            let out = out.take_dir("compare-envs")?;
+            let options = CompareOptions { mode: CompareMode::parse(&mode)?, threshold, out, ignore_robots, resume };
+            let mut browser = browser.lock().await;
+            browser.init().await?;
+            let paths = match (paths, from_sitemap) {
+                (Some(paths), _) => browser_cli::compare::read_paths(&paths)?,
+                (None, Some(sitemap)) => {
+                    let filter = SitemapFilter { pattern, since: since.as_deref().map(SitemapFilter::parse_since).transpose()? };
+                    let urls = browser.sitemap_urls(&sitemap, &filter).await?;
+                    urls.iter().map(|url| browser_cli::compare::url_path(url)).collect()
+                }
+                (None, None) => return Err(anyhow::anyhow!("compare-envs needs --paths or --from-sitemap")),
+            };
+            let report = browser.compare_envs(&base, &target, &paths, &options).await?;
+            report.print();
+            serde_json::to_value(report)?
         }
-        Commands::Close => {
+        Commands::Crawl { start, from_sitemap, pattern, since, depth, same_origin, max_pages, delay_ms, ignore_robots, resume } => {
+            let out = out.take_dir("crawl")?;
+            let options = CrawlOptions {
+                depth,
+                same_origin,
+                max_pages,
+                delay: std::time::Duration::from_millis(delay_ms),
+                ignore_robots,
+                out,
+                from_sitemap,
+                sitemap_filter: SitemapFilter { pattern, since: since.as_deref().map(SitemapFilter::parse_since).transpose()? },
+                resume,
+            };
             let mut browser = browser.lock().await;
-            browser.close().await?;
+            browser.init().await?;
+            let report = browser.crawl(start.as_deref(), &options).await?;
+            report.print();
+            serde_json::to_value(report)?
+        }
+        Commands::Runs { action } => match action.unwrap_or(RunsAction::List) {
+            RunsAction::List => {
+                let runs = paths::list_runs();
+                if !json_output {
+                    for run in &runs {
+                        out.print(&run.display().to_string())?;
+                    }
+                }
+                status!("{} {} runs in {}", "🗂️".cyan(), runs.len(), paths::runs_dir().display());
+                json!({ "runs": runs })
+            }
+            RunsAction::Prune { keep } => {
+                let removed = paths::prune_runs(keep)?;
+                status!("{} Removed {} runs, kept the newest {}", "🧹".cyan(), removed.len(), keep.min(paths::list_runs().len()));
+                json!({ "removed": removed, "kept": paths::list_runs() })
+            }
+        },
+        Commands::Har { action } => {
+            // A capture has to outlive this process, so it lives in the session's daemon
+            if daemon::find(session).await.is_none() {
+                return Err(anyhow::anyhow!(
+                    "har start/stop needs a running daemon for session '{}' (start 'browser-cli daemon'), or use the console's har command",
+                    session
+                ));
+            }
+            match action {
+                HarAction::Start { file } => {
+                    let path = match file {
+                        Some(file) => paths::capture_path(&file)?,
+                        None => paths::run_artifacts(Artifact::Traces)?.join(format!("network-{}.har", chrono::Local::now().format("%H%M%S"))),
+                    };
+                    let path = std::path::absolute(path)?;
+                    daemon::har_start(session, &path).await?;
+                    status!("{} Capturing network traffic for {}", "🔴".red(), path.display());
+                    json!({ "path": path, "capturing": true })
+                }
+                HarAction::Stop => {
+                    let (path, entries) = daemon::har_stop(session).await?;
+                    status!("{} Saved {} requests to {}", "💾".green(), entries, path.display());
+                    json!({ "path": path, "entries": entries })
+                }
+            }
         }
-        Commands::Console => {
-            let mut console = Console::new(Arc::clone(&browser))?;
+        Commands::Run { file, vars, screenshots, fail_fast: _, continue_on_error } => {
+            let vars = vars.iter()
+                .map(|var| var.split_once('=')
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .ok_or_else(|| anyhow::anyhow!("Invalid --var '{}' (expected NAME=VALUE)", var)))
+                .collect::<Result<Vec<_>>>()?;
+            let script = Script::load(&file)?;
+            let mut console = Console::new(Arc::clone(browser))?;
+            console.run_script(&script, &vars, screenshots.as_deref(), continue_on_error).await?;
+            json!({ "file": file, "steps": script.step_count() })
+        }
+        Commands::Dev { dir, url, on_change, poll_ms } => {
+            let mut console = Console::new(Arc::clone(browser))?;
+            console.dev(&dir, url.as_deref(), &on_change, std::time::Duration::from_millis(poll_ms)).await?;
+            serde_json::Value::Null
+        }
+        Commands::Console { events } => {
+            if json_output {
+                return Err(anyhow::anyhow!("The interactive console has no --json mode"));
+            }
+            if let Some((addr, policy)) = events.endpoint()? {
+                event_stream::serve(&addr, Arc::clone(browser), policy).await?;
+            }
+            let mut console = Console::new(Arc::clone(browser))?;
             console.run().await?;
+            serde_json::Value::Null
+        }
+    };
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("browser-cli").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn directory_commands_take_global_out() {
+        let cli = parse(&["crawl", "--out", "d", "http://example.com/"]);
+        assert!(matches!(cli.command, Commands::Crawl { start: Some(_), .. }));
+        assert_eq!(cli.out.as_deref(), Some("d"));
+
+        let cli = parse(&["compare-envs", "--base", "https://a.example", "--target", "https://b.example", "--paths", "p.txt", "--out", "d"]);
+        assert!(matches!(cli.command, Commands::CompareEnvs { .. }));
+        assert_eq!(cli.out.as_deref(), Some("d"));
+
+        let cli = parse(&["ss-sequence", "--out", "d"]);
+        assert!(matches!(cli.command, Commands::SsSequence { .. }));
+        assert_eq!(cli.out.as_deref(), Some("d"));
+    }
+
+    #[test]
+    fn directory_out_rejects_append() {
+        let out = DataOut::new(Some("d"), true);
+        assert!(out.take_dir("crawl").is_err());
+        let out = DataOut::new(Some("d"), false);
+        assert_eq!(out.take_dir("crawl").unwrap(), Some(std::path::PathBuf::from("d")));
+        // Taken as the directory, so the command's result goes to stdout rather than into it
+        assert!(out.take_path().is_none());
+    }
+
+    #[test]
+    fn daemon_publishes_events_like_console() {
+        let cli = parse(&["daemon", "--events", "127.0.0.1:9300", "--token", "watcher:read"]);
+        let Commands::Daemon { events, .. } = cli.command else {
+            panic!("not parsed as daemon");
+        };
+        let (addr, _) = events.endpoint().unwrap().unwrap();
+        assert_eq!(addr, "127.0.0.1:9300");
+
+        let cli = parse(&["console"]);
+        let Commands::Console { events } = cli.command else {
+            panic!("not parsed as console");
+        };
+        assert!(events.endpoint().unwrap().is_none());
+    }
+
+    #[test]
+    fn session_names_are_checked_as_usage_errors() {
+        assert_eq!(parse(&["--session", "ci_2", "console"]).session.as_deref(), Some("ci_2"));
+        for name in ["../x", "a/b", "my-session"] {
+            let e = Cli::try_parse_from(["browser-cli", "--session", name, "console"]).err().unwrap();
+            assert_eq!(e.kind(), clap::error::ErrorKind::ValueValidation, "{}", name);
+            assert!(e.use_stderr());
         }
     }
 
-    Ok(())
+    #[test]
+    fn session_limits_need_a_command_that_enforces_them() {
+        let cli = parse(&["navigate", "--idle-timeout", "60", "http://example.com/"]);
+        assert!(check_session_limits(&cli, "navigate").is_err());
+        let cli = parse(&["console", "--idle-timeout", "60"]);
+        assert!(check_session_limits(&cli, "console").is_ok());
+        let cli = parse(&["daemon", "--max-lifetime", "3600"]);
+        assert!(check_session_limits(&cli, "daemon").is_ok());
+        let cli = parse(&["navigate", "http://example.com/"]);
+        assert!(check_session_limits(&cli, "navigate").is_ok());
+    }
 }
@@ -0,0 +1,781 @@
+use anyhow::Result;
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    ContinueRequestParams, EnableParams as FetchEnableParams, EventRequestPaused, FailRequestParams, FulfillRequestParams,
+    HeaderEntry, RequestPattern,
+};
+use chromiumoxide::cdp::browser_protocol::network::{
+    ErrorReason, EventDataReceived, EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent,
+    EventResponseReceived, GetResponseBodyParams, Headers, ResourceTiming,
+};
+use chromiumoxide::Page;
+use colored::*;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::browser::watchdog;
+use crate::policy::UrlPolicy;
+
+// Oldest requests are dropped once a session has captured this many
+const MAX_ENTRIES: usize = 2000;
+
+// One request as seen through the Network domain, from request to finished/failed
+#[derive(Debug, Clone, Default)]
+pub struct RequestRecord {
+    pub request_id: String,
+    pub url: String,
+    pub method: String,
+    pub resource_type: String,
+    pub status: Option<i64>,
+    pub status_text: String,
+    pub protocol: Option<String>,
+    pub mime_type: Option<String>,
+    pub remote_address: Option<String>,
+    pub request_headers: Vec<(String, String)>,
+    pub response_headers: Vec<(String, String)>,
+    // Network.MonotonicTime seconds
+    pub started: f64,
+    // Seconds since the epoch at `started`
+    pub wall_time: Option<f64>,
+    pub finished: Option<f64>,
+    pub timing: Option<ResourceTiming>,
+    // Bytes on the wire, headers included
+    pub encoded_size: Option<f64>,
+    // Body bytes after content decoding
+    pub decoded_size: Option<f64>,
+    pub error: Option<String>,
+    pub post_data: Option<String>,
+    // Only fetched for requests worth inspecting (GraphQL), bodies are not kept in general
+    pub response_body: Option<String>,
+}
+
+// Requests captured by a session, shared between the recorder task and commands
+#[derive(Debug, Default)]
+pub struct NetworkLog {
+    entries: VecDeque<RequestRecord>,
+    // Position of the first entry of a running HAR capture; nothing is dropped while one runs
+    capture_start: Option<usize>,
+}
+
+impl NetworkLog {
+    fn entry(&mut self, request_id: &str) -> &mut RequestRecord {
+        let position = match self.entries.iter().rposition(|e| e.request_id == request_id) {
+            Some(position) => position,
+            None => {
+                if self.entries.len() >= MAX_ENTRIES && self.capture_start.is_none() {
+                    self.entries.pop_front();
+                }
+                self.entries.push_back(RequestRecord { request_id: request_id.to_string(), ..Default::default() });
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[position]
+    }
+
+    pub fn entries(&self) -> Vec<RequestRecord> {
+        self.entries.iter().cloned().collect()
+    }
+
+    // Requests belonging to the most recent page load (from its document request on)
+    pub fn last_page_load(&self) -> Vec<RequestRecord> {
+        let start = self.entries.iter().rposition(|e| e.resource_type == "Document").unwrap_or(0);
+        self.entries.iter().skip(start).cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        if self.capture_start.is_some() {
+            self.capture_start = Some(0);
+        }
+    }
+
+    pub fn start_capture(&mut self) {
+        self.capture_start = Some(self.entries.len());
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture_start.is_some()
+    }
+
+    // Requests since start_capture, ending the capture
+    pub fn take_capture(&mut self) -> Option<Vec<RequestRecord>> {
+        let start = self.capture_start.take()?;
+        Some(self.entries.iter().skip(start).cloned().collect())
+    }
+}
+
+// Headers as name/value pairs; repeated headers arrive joined by newlines
+fn header_pairs(headers: &Headers) -> Vec<(String, String)> {
+    let Some(object) = headers.inner().as_object() else {
+        return Vec::new();
+    };
+    object.iter()
+        .flat_map(|(name, value)| {
+            let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+            value.split('\n').map(|line| (name.clone(), line.to_string())).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// Record every request on the page into the log for the lifetime of the page
+pub async fn record(page: &Page, log: Arc<Mutex<NetworkLog>>) -> Result<()> {
+    let mut requests = page.event_listener::<EventRequestWillBeSent>().await?;
+    let mut responses = page.event_listener::<EventResponseReceived>().await?;
+    let mut data = page.event_listener::<EventDataReceived>().await?;
+    let mut finished = page.event_listener::<EventLoadingFinished>().await?;
+    let mut failed = page.event_listener::<EventLoadingFailed>().await?;
+
+    let page = page.clone();
+    tokio::spawn(async move {
+        // Events for one request can arrive on different streams in any order, so each handler creates the entry on demand
+        loop {
+            tokio::select! {
+                Some(event) = requests.next() => {
+                    let mut log = log.lock().unwrap();
+                    let entry = log.entry(event.request_id.inner());
+                    // A redirect reuses the request id; the record follows the final hop
+                    entry.url = event.request.url.clone();
+                    entry.method = event.request.method.clone();
+                    entry.resource_type = event.r#type.as_ref().map(|t| t.as_ref().to_string()).unwrap_or_default();
+                    entry.started = *event.timestamp.inner();
+                    entry.wall_time = Some(*event.wall_time.inner());
+                    entry.request_headers = header_pairs(&event.request.headers);
+                    entry.status = None;
+                    entry.timing = None;
+                    entry.decoded_size = None;
+                    entry.post_data = event.request.post_data.clone();
+                }
+                Some(event) = responses.next() => {
+                    let mut log = log.lock().unwrap();
+                    let entry = log.entry(event.request_id.inner());
+                    entry.status = Some(event.response.status);
+                    entry.status_text = event.response.status_text.clone();
+                    entry.protocol = event.response.protocol.clone();
+                    entry.mime_type = Some(event.response.mime_type.clone());
+                    entry.remote_address = event.response.remote_ip_address.as_ref()
+                        .map(|ip| format!("{}:{}", ip, event.response.remote_port.unwrap_or_default()));
+                    entry.response_headers = header_pairs(&event.response.headers);
+                    entry.timing = event.response.timing.clone();
+                    entry.resource_type = event.r#type.as_ref().to_string();
+                }
+                Some(event) = data.next() => {
+                    let mut log = log.lock().unwrap();
+                    let entry = log.entry(event.request_id.inner());
+                    entry.decoded_size = Some(entry.decoded_size.unwrap_or(0.0) + event.data_length as f64);
+                }
+                Some(event) = finished.next() => {
+                    let wants_body = {
+                        let mut log = log.lock().unwrap();
+                        let entry = log.entry(event.request_id.inner());
+                        entry.finished = Some(*event.timestamp.inner());
+                        entry.encoded_size = Some(event.encoded_data_length);
+                        graphql_operations(entry).is_some()
+                    };
+                    // The body is only available until the page navigates away, so grab it now
+                    if wants_body {
+                        let (page, log, request_id) = (page.clone(), Arc::clone(&log), event.request_id.clone());
+                        tokio::spawn(async move {
+                            let Ok(response) = page.execute(GetResponseBodyParams::new(request_id.clone())).await else {
+                                return;
+                            };
+                            let body = if response.base64_encoded {
+                                base64::engine::general_purpose::STANDARD.decode(&response.body)
+                                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                                    .unwrap_or_default()
+                            } else {
+                                response.body.clone()
+                            };
+                            log.lock().unwrap().entry(request_id.inner()).response_body = Some(body);
+                        });
+                    }
+                }
+                Some(event) = failed.next() => {
+                    let mut log = log.lock().unwrap();
+                    let entry = log.entry(event.request_id.inner());
+                    entry.finished = Some(*event.timestamp.inner());
+                    entry.error = Some(event.error_text.clone());
+                }
+                else => break,
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// One operation in a GraphQL request body; batched requests carry several
+#[derive(Debug, Clone)]
+pub struct GraphqlOperation {
+    pub name: String,
+    pub kind: String,
+    pub variables: serde_json::Value,
+}
+
+// GraphQL operations in a captured POST, or None when the body isn't a GraphQL payload
+pub fn graphql_operations(entry: &RequestRecord) -> Option<Vec<GraphqlOperation>> {
+    if entry.method != "POST" {
+        return None;
+    }
+    let body: serde_json::Value = serde_json::from_str(entry.post_data.as_deref()?).ok()?;
+    let payloads = match &body {
+        serde_json::Value::Array(batch) => batch.iter().collect::<Vec<_>>(),
+        single => vec![single],
+    };
+
+    let operations = payloads.into_iter()
+        .map(|payload| {
+            let query = payload["query"].as_str();
+            // Persisted queries send only a hash, so the operation name is all there is to go on
+            let persisted = payload["extensions"]["persistedQuery"].is_object();
+            if query.is_none() && !persisted {
+                return None;
+            }
+            let (kind, parsed_name) = query.map(parse_operation_header).unwrap_or(("query", None));
+            let name = payload["operationName"].as_str().map(str::to_string)
+                .or(parsed_name)
+                .unwrap_or_else(|| "(anonymous)".to_string());
+            Some(GraphqlOperation { name, kind: kind.to_string(), variables: payload["variables"].clone() })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!operations.is_empty()).then_some(operations)
+}
+
+// Operation type and name from the first definition, e.g. `mutation AddToCart($id: ID!)`
+fn parse_operation_header(query: &str) -> (&'static str, Option<String>) {
+    let mut words = query.split(|c: char| c.is_whitespace() || c == '(' || c == '{' || c == '@').filter(|w| !w.is_empty());
+    let kind = match words.next() {
+        Some("mutation") => "mutation",
+        Some("subscription") => "subscription",
+        Some("query") => "query",
+        // `{ field }` shorthand is an anonymous query
+        _ => return ("query", None),
+    };
+    (kind, words.next().map(str::to_string))
+}
+
+// GraphQL traffic grouped by operation name, with variables and responses pretty-printed
+pub fn graphql_report(entries: &[RequestRecord], operation: Option<&str>) -> String {
+    let mut groups: Vec<(String, Vec<(&RequestRecord, GraphqlOperation)>)> = Vec::new();
+    for entry in entries {
+        for op in graphql_operations(entry).into_iter().flatten() {
+            if operation.is_some_and(|wanted| !wanted.eq_ignore_ascii_case(&op.name)) {
+                continue;
+            }
+            match groups.iter_mut().find(|(name, _)| *name == op.name) {
+                Some((_, calls)) => calls.push((entry, op)),
+                None => groups.push((op.name.clone(), vec![(entry, op)])),
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for (name, calls) in &groups {
+        out.push_str(&format!("{} {} {} ({} call{})\n", "◆".magenta(), calls[0].1.kind.dimmed(), name.bold(),
+            calls.len(), if calls.len() == 1 { "" } else { "s" }));
+        for (index, (entry, op)) in calls.iter().enumerate() {
+            out.push_str(&format!("  #{} {} {}\n", index + 1, status_label(entry), short_url(&entry.url, 60).dimmed()));
+            if !op.variables.is_null() {
+                out.push_str(&format!("    {}\n{}\n", "variables:".cyan(), indent(&pretty_json(&op.variables.to_string()), 6)));
+            }
+            match &entry.response_body {
+                Some(body) => out.push_str(&format!("    {}\n{}\n", "response:".cyan(), indent(&pretty_json(body), 6))),
+                None if entry.finished.is_none() => out.push_str(&format!("    {}\n", "response: (pending)".dimmed())),
+                None => out.push_str(&format!("    {}\n", "response: (not captured)".dimmed())),
+            }
+        }
+    }
+    out
+}
+
+fn pretty_json(text: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| text.to_string())
+}
+
+fn indent(text: &str, width: usize) -> String {
+    text.lines().map(|line| format!("{:width$}{}", "", line, width = width)).collect::<Vec<_>>().join("\n")
+}
+
+// Rules applied to every request paused by the Fetch domain: the URL policy first, then block rules,
+// then HAR mocks
+#[derive(Debug, Default)]
+pub struct Interception {
+    pub policy: Arc<UrlPolicy>,
+    pub blocks: Vec<BlockRule>,
+    pub mocks: Option<HarMocks>,
+}
+
+impl Interception {
+    pub fn is_active(&self) -> bool {
+        self.policy.is_active() || !self.blocks.is_empty() || self.mocks.is_some()
+    }
+
+    // Count the hit on the first matching rule, if any
+    fn block(&mut self, url: &str) -> bool {
+        match self.blocks.iter_mut().find(|rule| rule.matches(url)) {
+            Some(rule) => {
+                rule.hits += 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// A URL glob whose requests are aborted, e.g. `*google-analytics.com*` or `https://ads.example.com/*`
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockRule {
+    pub pattern: String,
+    pub hits: usize,
+}
+
+impl BlockRule {
+    pub fn new(pattern: &str) -> Self {
+        Self { pattern: pattern.to_string(), hits: 0 }
+    }
+
+    pub fn matches(&self, url: &str) -> bool {
+        pattern_matches(&self.pattern, url)
+    }
+}
+
+// `*` matches any run of characters over the whole URL; a pattern without one matches anywhere in it
+pub(crate) fn pattern_matches(pattern: &str, url: &str) -> bool {
+    if !pattern.contains('*') {
+        return url.contains(pattern);
+    }
+    glob_matches(pattern, url)
+}
+
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+// JS expression matching the URL expression `url` the way pattern_matches does, for waits that run in the page.
+// A glob's pieces each have to appear in order, with the first and last anchored, which is what an anchored
+// regular expression with `[\s\S]*` for every `*` checks.
+pub(crate) fn pattern_matches_js(pattern: &str, url: &str) -> String {
+    let literal = |value: &str| serde_json::to_string(value).unwrap_or_default();
+    if !pattern.contains('*') {
+        return format!("{}.includes({})", url, literal(pattern));
+    }
+    let escaped: Vec<String> = pattern.split('*')
+        .map(|part| part.chars().fold(String::new(), |mut out, c| {
+            if "\\^$.|?*+()[]{}/".contains(c) {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        }))
+        .collect();
+    format!("new RegExp({}).test({})", literal(&format!("^{}$", escaped.join("[\\s\\S]*"))), url)
+}
+
+// Pause every request on the page and answer it according to the (changeable) interception rules. Each
+// answer gets `timeout`, so a hung renderer can't wedge the task answering the rest.
+pub async fn intercept(page: &Page, rules: Arc<Mutex<Interception>>, timeout: Duration) -> Result<()> {
+    let mut paused = page.event_listener::<EventRequestPaused>().await?;
+    watchdog(timeout, "fetch_enable", page.execute(FetchEnableParams {
+        patterns: Some(vec![RequestPattern { url_pattern: Some("*".to_string()), resource_type: None, request_stage: None }]),
+        handle_auth_requests: None,
+    })).await?;
+
+    let page = page.clone();
+    tokio::spawn(async move {
+        while let Some(event) = paused.next().await {
+            let request_id = event.request_id.clone();
+            let policy = Arc::clone(&rules.lock().unwrap().policy);
+            if let Err(e) = policy.check(&event.request.url).await {
+                status!("{} {}", "🛡️".yellow(), e);
+                watchdog(timeout, "fail_request", page.execute(FailRequestParams::new(request_id, ErrorReason::BlockedByClient))).await.ok();
+                continue;
+            }
+            if rules.lock().unwrap().block(&event.request.url) {
+                watchdog(timeout, "fail_request", page.execute(FailRequestParams::new(request_id, ErrorReason::BlockedByClient))).await.ok();
+                continue;
+            }
+
+            let mocked = rules.lock().unwrap().mocks.as_mut()
+                .map(|mocks| (mocks.respond(&event.request.method, &event.request.url), mocks.block_unmatched));
+            match mocked {
+                Some((Some(response), _)) => {
+                    let params = FulfillRequestParams {
+                        response_headers: Some(response.headers),
+                        body: Some(response.body.into()),
+                        response_phrase: (!response.status_text.is_empty()).then_some(response.status_text),
+                        ..FulfillRequestParams::new(request_id, response.status)
+                    };
+                    watchdog(timeout, "fulfill_request", page.execute(params)).await.ok();
+                }
+                Some((None, true)) => {
+                    watchdog(timeout, "fail_request", page.execute(FailRequestParams::new(request_id, ErrorReason::BlockedByClient))).await.ok();
+                }
+                _ => {
+                    watchdog(timeout, "continue_request", page.execute(ContinueRequestParams::new(request_id))).await.ok();
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+struct MockResponse {
+    status: i64,
+    status_text: String,
+    headers: Vec<HeaderEntry>,
+    // Base64, as Fetch.fulfillRequest expects
+    body: String,
+}
+
+// Responses recorded in a HAR file, served back for requests with the same method and URL
+#[derive(Debug)]
+pub struct HarMocks {
+    responses: HashMap<String, Vec<MockResponse>>,
+    served: HashMap<String, usize>,
+    pub block_unmatched: bool,
+    hits: usize,
+    misses: usize,
+}
+
+impl HarMocks {
+    pub fn from_har(path: &Path, block_unmatched: bool) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read HAR {}: {}", path.display(), e))?;
+        let har: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid HAR {}: {}", path.display(), e))?;
+        let entries = har["log"]["entries"].as_array()
+            .ok_or_else(|| anyhow::anyhow!("{} has no log.entries; is it a HAR file?", path.display()))?;
+
+        let mut responses: HashMap<String, Vec<MockResponse>> = HashMap::new();
+        for entry in entries {
+            let (request, response) = (&entry["request"], &entry["response"]);
+            let (Some(method), Some(url)) = (request["method"].as_str(), request["url"].as_str()) else {
+                continue;
+            };
+            // Requests that never got a response are recorded with status 0
+            let status = response["status"].as_i64().unwrap_or(0);
+            if status == 0 {
+                continue;
+            }
+
+            let content = &response["content"];
+            let text = content["text"].as_str().unwrap_or_default();
+            let body = match content["encoding"].as_str() {
+                Some("base64") => text.to_string(),
+                _ => base64::engine::general_purpose::STANDARD.encode(text),
+            };
+            // The body is served decoded, so length and encoding headers from the original response no longer apply
+            let headers = response["headers"].as_array().into_iter().flatten()
+                .filter_map(|h| Some(HeaderEntry { name: h["name"].as_str()?.to_string(), value: h["value"].as_str()?.to_string() }))
+                .filter(|h| !matches!(h.name.to_ascii_lowercase().as_str(), "content-length" | "content-encoding" | "transfer-encoding"))
+                .collect();
+
+            responses.entry(Self::key(method, url)).or_default().push(MockResponse {
+                status,
+                status_text: response["statusText"].as_str().unwrap_or_default().to_string(),
+                headers,
+                body,
+            });
+        }
+
+        if responses.is_empty() {
+            return Err(anyhow::anyhow!("{} contains no recorded responses", path.display()));
+        }
+        Ok(Self { responses, served: HashMap::new(), block_unmatched, hits: 0, misses: 0 })
+    }
+
+    fn key(method: &str, url: &str) -> String {
+        let url = url.split('#').next().unwrap_or(url);
+        format!("{} {}", method.to_ascii_uppercase(), url)
+    }
+
+    // Repeated requests get the recorded responses in order, then the last one again
+    fn respond(&mut self, method: &str, url: &str) -> Option<MockResponse> {
+        let key = Self::key(method, url);
+        let Some(recorded) = self.responses.get(&key) else {
+            self.misses += 1;
+            return None;
+        };
+        let served = self.served.entry(key).or_insert(0);
+        let response = recorded[(*served).min(recorded.len() - 1)].clone();
+        *served += 1;
+        self.hits += 1;
+        Some(response)
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.values().all(Vec::is_empty)
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} recorded responses | served {} | unmatched {} ({})",
+            self.len(),
+            self.hits,
+            self.misses,
+            if self.block_unmatched { "blocked" } else { "passed through" }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Blocked,
+    Dns,
+    Connect,
+    Ttfb,
+    Download,
+}
+
+impl Phase {
+    fn symbol(&self) -> char {
+        match self {
+            Phase::Blocked => '·',
+            Phase::Dns => 'd',
+            Phase::Connect => 'c',
+            Phase::Ttfb => 'w',
+            Phase::Download => '█',
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Phase::Blocked => "blocked",
+            Phase::Dns => "DNS",
+            Phase::Connect => "connect",
+            Phase::Ttfb => "TTFB",
+            Phase::Download => "download",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Phase::Blocked => "#c8c8c8",
+            Phase::Dns => "#1f9e89",
+            Phase::Connect => "#f28e2b",
+            Phase::Ttfb => "#4e79a7",
+            Phase::Download => "#59a14f",
+        }
+    }
+}
+
+const PHASES: [Phase; 5] = [Phase::Blocked, Phase::Dns, Phase::Connect, Phase::Ttfb, Phase::Download];
+
+// Phases of a request in milliseconds since `origin` (seconds), from the response's ResourceTiming
+fn phases(entry: &RequestRecord, origin: f64) -> Vec<(Phase, f64, f64)> {
+    let start = (entry.started - origin) * 1000.0;
+    let end = entry.finished.map(|f| (f - origin) * 1000.0).unwrap_or(start);
+
+    let Some(timing) = &entry.timing else {
+        return vec![(Phase::Download, start, end.max(start))];
+    };
+
+    let base = (timing.request_time - origin) * 1000.0;
+    let at = |offset: f64| base + offset;
+    let mut phases = Vec::new();
+
+    let first = [timing.dns_start, timing.connect_start, timing.send_start]
+        .into_iter()
+        .find(|t| *t >= 0.0)
+        .unwrap_or(0.0);
+    if at(first) > start {
+        phases.push((Phase::Blocked, start, at(first)));
+    }
+    if timing.dns_start >= 0.0 && timing.dns_end > timing.dns_start {
+        phases.push((Phase::Dns, at(timing.dns_start), at(timing.dns_end)));
+    }
+    if timing.connect_start >= 0.0 && timing.connect_end > timing.connect_start {
+        phases.push((Phase::Connect, at(timing.connect_start), at(timing.connect_end)));
+    }
+    if timing.send_start >= 0.0 {
+        phases.push((Phase::Ttfb, at(timing.send_start), at(timing.receive_headers_end)));
+    }
+    let headers_end = at(timing.receive_headers_end);
+    if end > headers_end {
+        phases.push((Phase::Download, headers_end, end));
+    }
+    phases
+}
+
+fn short_url(url: &str, max: usize) -> String {
+    let short = url::Url::parse(url)
+        .map(|parsed| match (parsed.path(), parsed.query()) {
+            ("/", None) => parsed.host_str().unwrap_or(url).to_string(),
+            (path, Some(query)) => format!("{}?{}", path, query),
+            (path, None) => path.to_string(),
+        })
+        .unwrap_or_else(|_| url.to_string());
+    if short.chars().count() > max {
+        format!("{}…", short.chars().take(max - 1).collect::<String>())
+    } else {
+        short
+    }
+}
+
+fn status_label(entry: &RequestRecord) -> String {
+    match (&entry.error, entry.status) {
+        (Some(_), _) => "ERR".to_string(),
+        (None, Some(status)) => status.to_string(),
+        (None, None) => "…".to_string(),
+    }
+}
+
+fn size_label(entry: &RequestRecord) -> String {
+    entry.encoded_size.map_or_else(|| "-".to_string(), format_size)
+}
+
+// 512B, 12.3KB, 1.5MB
+pub fn format_size(bytes: f64) -> String {
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1}MB", bytes / 1024.0 / 1024.0)
+    } else if bytes >= 1024.0 {
+        format!("{:.1}KB", bytes / 1024.0)
+    } else {
+        format!("{}B", bytes as u64)
+    }
+}
+
+// Span of the whole capture: earliest start and total length in milliseconds
+fn extent(entries: &[RequestRecord]) -> (f64, f64) {
+    let origin = entries.iter().map(|e| e.started).fold(f64::INFINITY, f64::min);
+    let total = entries.iter()
+        .flat_map(|e| phases(e, origin))
+        .map(|(_, _, end)| end)
+        .fold(1.0, f64::max);
+    (origin, total)
+}
+
+pub fn waterfall_text(entries: &[RequestRecord]) -> String {
+    const WIDTH: usize = 60;
+    let (origin, total) = extent(entries);
+    let scale = WIDTH as f64 / total;
+
+    let mut out = format!("{:>4} {:<40} {:>8} {:>8}  0{:>width$}\n", "", "url", "size", "time", format!("{:.0}ms", total), width = WIDTH - 1);
+    for entry in entries {
+        let phases = phases(entry, origin);
+        let mut bar = vec![' '; WIDTH];
+        for (phase, start, end) in &phases {
+            let from = ((start * scale) as usize).min(WIDTH - 1);
+            let to = ((end * scale).ceil() as usize).clamp(from + 1, WIDTH);
+            bar[from..to].iter_mut().for_each(|c| *c = phase.symbol());
+        }
+        let duration = phases.last().map(|(_, _, end)| end).unwrap_or(&0.0) - phases.first().map(|(_, start, _)| *start).unwrap_or(0.0);
+        out.push_str(&format!(
+            "{:>4} {:<40} {:>8} {:>6.0}ms  {}\n",
+            status_label(entry),
+            short_url(&entry.url, 40),
+            size_label(entry),
+            duration,
+            bar.into_iter().collect::<String>().trim_end()
+        ));
+    }
+    let legend = PHASES.iter().map(|p| format!("{} {}", p.symbol(), p.label())).collect::<Vec<_>>().join("  ");
+    out.push_str(&format!("\n{}\n", legend));
+    out
+}
+
+pub fn waterfall_svg(entries: &[RequestRecord]) -> String {
+    const LABEL_WIDTH: f64 = 420.0;
+    const CHART_WIDTH: f64 = 640.0;
+    const ROW: f64 = 18.0;
+    let (origin, total) = extent(entries);
+    let scale = CHART_WIDTH / total;
+    let height = ROW * (entries.len() as f64 + 3.0);
+    let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="11">"#,
+        LABEL_WIDTH + CHART_WIDTH + 20.0, height
+    );
+    svg.push_str(&format!(
+        r#"<text x="{}" y="12">0ms</text><text x="{}" y="12" text-anchor="end">{:.0}ms</text>"#,
+        LABEL_WIDTH, LABEL_WIDTH + CHART_WIDTH, total
+    ));
+    for (row, entry) in entries.iter().enumerate() {
+        let y = ROW * (row as f64 + 1.0);
+        svg.push_str(&format!(
+            r#"<text x="4" y="{}">{} {} {}</text>"#,
+            y + 12.0,
+            escape(&status_label(entry)),
+            escape(&short_url(&entry.url, 48)),
+            escape(&size_label(entry))
+        ));
+        for (phase, start, end) in phases(entry, origin) {
+            svg.push_str(&format!(
+                r#"<rect x="{:.1}" y="{}" width="{:.1}" height="{}" fill="{}"><title>{} {:.1}ms</title></rect>"#,
+                LABEL_WIDTH + start * scale,
+                y + 3.0,
+                ((end - start) * scale).max(1.0),
+                ROW - 6.0,
+                phase.color(),
+                phase.label(),
+                end - start
+            ));
+        }
+    }
+    let legend_y = ROW * (entries.len() as f64 + 2.0);
+    for (index, phase) in PHASES.iter().enumerate() {
+        let x = 4.0 + index as f64 * 110.0;
+        svg.push_str(&format!(
+            r#"<rect x="{}" y="{}" width="10" height="10" fill="{}"/><text x="{}" y="{}">{}</text>"#,
+            x, legend_y, phase.color(), x + 14.0, legend_y + 9.0, phase.label()
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_without_a_star_match_substrings() {
+        assert!(pattern_matches("/checkout", "https://shop.test/checkout?step=2"));
+        assert!(!pattern_matches("/cart", "https://shop.test/checkout"));
+        assert_eq!(pattern_matches_js("/a'b", "url"), r#"url.includes("/a'b")"#);
+    }
+
+    #[test]
+    fn globs_match_the_whole_url() {
+        assert!(pattern_matches("https://shop.test/*/done", "https://shop.test/orders/7/done"));
+        assert!(!pattern_matches("https://shop.test/*/done", "https://shop.test/orders/7/done?x"));
+        assert!(!pattern_matches("*/done", "/done/again"));
+        assert!(pattern_matches("*", ""));
+    }
+
+    #[test]
+    fn glob_regexes_escape_everything_but_the_stars() {
+        assert_eq!(
+            pattern_matches_js("https://a.test/*?q=(1)", "location.href"),
+            r#"new RegExp("^https:\\/\\/a\\.test\\/[\\s\\S]*\\?q=\\(1\\)$").test(location.href)"#
+        );
+    }
+}
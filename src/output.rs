@@ -0,0 +1,98 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+// Where the library's status lines ("✓ Navigated to …", spinners, warnings) go. The CLI shows them on
+// stdout; a service embedding the library will usually silence them and rely on return values instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusOutput {
+    #[default]
+    Stdout,
+    Stderr,
+    Silent,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_status_output(mode: StatusOutput) {
+    MODE.store(mode as u8, Ordering::SeqCst);
+}
+
+pub fn status_output() -> StatusOutput {
+    match MODE.load(Ordering::SeqCst) {
+        0 => StatusOutput::Stdout,
+        1 => StatusOutput::Stderr,
+        _ => StatusOutput::Silent,
+    }
+}
+
+// Status lines collected instead of shown, innermost capture last (the console's `command | filter`)
+static CAPTURES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// Collect status lines until the matching take_capture instead of showing them. Warnings still go to
+// stderr and progress lines are dropped. Captures nest; lines go to the innermost one.
+pub fn start_capture() {
+    CAPTURES.lock().unwrap_or_else(|e| e.into_inner()).push(String::new());
+}
+
+// End the innermost capture and return what it collected
+pub fn take_capture() -> String {
+    CAPTURES.lock().unwrap_or_else(|e| e.into_inner()).pop().unwrap_or_default()
+}
+
+#[doc(hidden)]
+pub fn emit(args: fmt::Arguments) {
+    if let Some(capture) = CAPTURES.lock().unwrap_or_else(|e| e.into_inner()).last_mut() {
+        use fmt::Write;
+        let _ = writeln!(capture, "{}", args);
+        return;
+    }
+    match status_output() {
+        StatusOutput::Stdout => println!("{}", args),
+        StatusOutput::Stderr => eprintln!("{}", args),
+        StatusOutput::Silent => {}
+    }
+}
+
+#[doc(hidden)]
+pub fn emit_progress(args: fmt::Arguments) {
+    // Not part of what a command outputs, so a capture drops it rather than collecting it
+    if !CAPTURES.lock().unwrap_or_else(|e| e.into_inner()).is_empty() {
+        return;
+    }
+    emit(args);
+}
+
+#[doc(hidden)]
+pub fn warn(args: fmt::Arguments) {
+    if status_output() != StatusOutput::Silent {
+        eprintln!("{}", args);
+    }
+}
+
+// println! for library status lines, honouring set_status_output
+#[macro_export]
+macro_rules! status {
+    () => {
+        $crate::output::emit(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::emit(format_args!($($arg)*))
+    };
+}
+
+// status! for lines announcing work ("Getting text from …"), which captures leave out
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        $crate::output::emit_progress(format_args!($($arg)*))
+    };
+}
+
+// eprintln! for library warnings; only silenced, never redirected
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::output::warn(format_args!($($arg)*))
+    };
+}
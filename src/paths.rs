@@ -0,0 +1,262 @@
+use anyhow::Result;
+use directories::{BaseDirs, ProjectDirs};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+// Where captures go when no path is given, relative to the working directory
+const CAPTURE_DIR: &str = "browser-ss";
+// Where each run's artifacts go (script logs and step screenshots, reports, traces), unless --artifacts-dir
+const RUNS_DIR: &str = "browser-cli-runs";
+// Runs `runs prune` keeps when not told otherwise
+pub const DEFAULT_RUNS_KEPT: usize = 10;
+
+static ARTIFACTS_DIR: OnceLock<PathBuf> = OnceLock::new();
+// This process's run directory, once something has been written to it
+static RUN_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// The kinds of files a run directory holds, one subdirectory each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Artifact {
+    Screenshots,
+    Logs,
+    Reports,
+    Traces,
+}
+
+impl Artifact {
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Self::Screenshots => "screenshots",
+            Self::Logs => "logs",
+            Self::Reports => "reports",
+            Self::Traces => "traces",
+        }
+    }
+}
+
+// Platform temp dir (honours TMPDIR / TEMP) for throwaway profiles and daemon state
+pub fn temp_root() -> PathBuf {
+    std::env::temp_dir()
+}
+
+pub fn capture_dir() -> PathBuf {
+    PathBuf::from(CAPTURE_DIR)
+}
+
+// A bare file name goes into the capture dir (created on demand); anything with a directory part,
+// relative or absolute, is used as given. Directory parts are split on the platform's separators: `\`
+// counts on Windows, while on Unix it is just a character of the file name.
+pub fn capture_path(name: &str) -> Result<PathBuf> {
+    capture_path_in(&capture_dir(), name)
+}
+
+fn capture_path_in(dir: &Path, name: &str) -> Result<PathBuf> {
+    let path = Path::new(name);
+    if path.is_absolute() || path.parent().is_some_and(|parent| !parent.as_os_str().is_empty()) {
+        return Ok(path.to_path_buf());
+    }
+    std::fs::create_dir_all(dir)?;
+    Ok(dir.join(name))
+}
+
+// A fresh subdirectory of the capture dir for multi-file captures (focus walks, run galleries)
+pub fn capture_subdir(name: &str) -> Result<PathBuf> {
+    let dir = capture_dir().join(name);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// --artifacts-dir: keep run directories here instead of ./browser-cli-runs. Only the first call counts, and
+// it has to come before anything is written to the run.
+pub fn set_artifacts_dir(dir: PathBuf) {
+    ARTIFACTS_DIR.set(dir).ok();
+}
+
+pub fn runs_dir() -> PathBuf {
+    ARTIFACTS_DIR.get().cloned().unwrap_or_else(|| PathBuf::from(RUNS_DIR))
+}
+
+// <runs>/<timestamp>: one per process, created on first use and shared by everything the process writes.
+// Two processes starting in the same second get "-2", "-3", ... suffixes.
+pub fn run_dir() -> Result<PathBuf> {
+    let mut current = RUN_DIR.lock().unwrap();
+    if let Some(dir) = current.as_ref() {
+        return Ok(dir.clone());
+    }
+    let runs = runs_dir();
+    std::fs::create_dir_all(&runs)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut dir = runs.join(&stamp);
+    let mut n = 2;
+    loop {
+        match std::fs::create_dir(&dir) {
+            Ok(()) => break,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                dir = runs.join(format!("{}-{}", stamp, n));
+                n += 1;
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to create run directory {}: {}", dir.display(), e)),
+        }
+    }
+    *current = Some(dir.clone());
+    Ok(dir)
+}
+
+// The run directory's subdirectory for one kind of artifact, created on demand
+pub fn run_artifacts(kind: Artifact) -> Result<PathBuf> {
+    let dir = run_dir()?.join(kind.dir_name());
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// A fresh directory for a multi-file artifact (a script's step screenshots, a focus walk), numbered
+// when the run already has one by that name
+pub fn run_subdir(kind: Artifact, name: &str) -> Result<PathBuf> {
+    let parent = run_artifacts(kind)?;
+    let mut dir = parent.join(name);
+    let mut n = 2;
+    while dir.exists() {
+        dir = parent.join(format!("{}-{}", name, n));
+        n += 1;
+    }
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Run directories, oldest first; their timestamp names sort by age. Anything else in the runs directory
+// is left alone.
+pub fn list_runs() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(runs_dir()) else {
+        return Vec::new();
+    };
+    let mut runs: Vec<PathBuf> = entries.filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .filter(|entry| is_run_name(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    runs.sort();
+    runs
+}
+
+// "20240131-154502", optionally with a "-2" style suffix
+fn is_run_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    bytes.len() >= 15
+        && bytes[..8].iter().all(u8::is_ascii_digit)
+        && bytes[8] == b'-'
+        && bytes[9..15].iter().all(u8::is_ascii_digit)
+        && (bytes.len() == 15 || (bytes[15] == b'-' && bytes.len() > 16 && bytes[16..].iter().all(u8::is_ascii_digit)))
+}
+
+// Newest run that has this artifact (a path relative to the run directory), for resuming interrupted work
+pub fn latest_run_artifact(kind: Artifact, name: &str) -> Option<PathBuf> {
+    list_runs().into_iter().rev()
+        .map(|run| run.join(kind.dir_name()).join(name))
+        .find(|path| path.exists())
+}
+
+// Newest run_subdir(kind, name) (or a numbered "name-N" sibling) of any run that holds `file`, for
+// resuming a batch job from its progress file
+pub fn latest_run_subdir(kind: Artifact, name: &str, file: &str) -> Option<PathBuf> {
+    list_runs().into_iter().rev()
+        .find_map(|run| {
+            let mut dirs: Vec<PathBuf> = std::fs::read_dir(run.join(kind.dir_name())).ok()?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with(name))
+                .map(|entry| entry.path())
+                .filter(|dir| dir.join(file).is_file())
+                .collect();
+            dirs.sort();
+            dirs.pop()
+        })
+}
+
+// Delete all but the newest `keep` runs, never this process's own; returns the deleted directories
+pub fn prune_runs(keep: usize) -> Result<Vec<PathBuf>> {
+    let current = RUN_DIR.lock().unwrap().clone();
+    let runs = list_runs();
+    let mut removed = Vec::new();
+    for run in runs.iter().take(runs.len().saturating_sub(keep)) {
+        if Some(run) == current.as_ref() {
+            continue;
+        }
+        std::fs::remove_dir_all(run).map_err(|e| anyhow::anyhow!("Failed to remove {}: {}", run.display(), e))?;
+        removed.push(run.clone());
+    }
+    Ok(removed)
+}
+
+// Per-user data (managed browser installs), e.g. ~/.local/share/browser-cli or %APPDATA%\browser-cli\data
+pub fn data_dir() -> PathBuf {
+    match ProjectDirs::from("", "", "browser-cli") {
+        Some(project) => project.data_dir().to_path_buf(),
+        None => temp_root().join("browser-cli-data"),
+    }
+}
+
+// User-level config files in lookup order: ~/.config/browser-cli (XDG; long-standing on macOS too),
+// then the platform config dir (%APPDATA%\browser-cli\config, ~/Library/Application Support/browser-cli)
+pub fn user_config_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(base) = BaseDirs::new() {
+        files.push(base.home_dir().join(".config").join("browser-cli").join("config.toml"));
+    }
+    if let Some(project) = ProjectDirs::from("", "", "browser-cli") {
+        let file = project.config_dir().join("config.toml");
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A capture dir of the test's own, so `cargo test` doesn't leave browser-ss/ behind
+    fn scratch_dir(test: &str) -> PathBuf {
+        temp_root().join(format!("browser-cli-test-{}-{}", test, std::process::id()))
+    }
+
+    #[test]
+    fn bare_names_go_into_the_capture_dir() {
+        let dir = scratch_dir("bare");
+        assert_eq!(capture_path_in(&dir, "shot.png").unwrap(), dir.join("shot.png"));
+        assert!(dir.is_dir());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn relative_paths_are_used_as_given() {
+        let dir = scratch_dir("relative");
+        let name = Path::new("out").join("shot.png");
+        assert_eq!(capture_path_in(&dir, name.to_str().unwrap()).unwrap(), name);
+        assert_eq!(capture_path_in(&dir, "./shot.png").unwrap(), PathBuf::from("./shot.png"));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn absolute_paths_are_used_as_given() {
+        let dir = scratch_dir("absolute");
+        let name = temp_root().join("shot.png");
+        assert_eq!(capture_path_in(&dir, name.to_str().unwrap()).unwrap(), name);
+        assert!(!dir.exists());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn backslashes_separate_directories_on_windows() {
+        let dir = scratch_dir("backslash");
+        assert_eq!(capture_path_in(&dir, r"out\shot.png").unwrap(), PathBuf::from(r"out\shot.png"));
+        assert_eq!(capture_path_in(&dir, r"C:\captures\shot.png").unwrap(), PathBuf::from(r"C:\captures\shot.png"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn backslashes_are_part_of_the_name_on_unix() {
+        let dir = scratch_dir("backslash");
+        assert_eq!(capture_path_in(&dir, r"out\shot.png").unwrap(), dir.join(r"out\shot.png"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
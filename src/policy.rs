@@ -0,0 +1,117 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use url::Url;
+
+use crate::error::Error;
+
+// Which URLs a session may load, enforced on navigation and on every intercepted request
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UrlPolicy {
+    // When non-empty, only these domains (and their subdomains) may be loaded
+    pub allowed_domains: Vec<String>,
+    pub blocked_domains: Vec<String>,
+    // Refuse loopback, private, link-local (cloud metadata) and other internal addresses
+    pub block_private_ips: bool,
+}
+
+impl UrlPolicy {
+    pub fn is_active(&self) -> bool {
+        !self.allowed_domains.is_empty() || !self.blocked_domains.is_empty() || self.block_private_ips
+    }
+
+    // Only network URLs are checked; about:, data:, blob: and friends never leave the browser
+    pub async fn check(&self, url: &str) -> Result<()> {
+        let Ok(parsed) = Url::parse(url) else {
+            return Ok(());
+        };
+        if !matches!(parsed.scheme(), "http" | "https" | "ws" | "wss") {
+            return Ok(());
+        }
+        let Some(host) = parsed.host_str() else {
+            return Ok(());
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+
+        if let Some(rule) = self.blocked_domains.iter().find(|rule| domain_matches(&host, rule)) {
+            return Err(Error::Blocked(format!("{} matches blocked domain '{}'", host, rule)).into());
+        }
+        if !self.allowed_domains.is_empty() && !self.allowed_domains.iter().any(|rule| domain_matches(&host, rule)) {
+            return Err(Error::Blocked(format!("{} is not in allowed_domains", host)).into());
+        }
+        if self.block_private_ips {
+            let port = parsed.port_or_known_default().unwrap_or(80);
+            if let Some(ip) = resolve_private(&host, port).await {
+                return Err(Error::Blocked(format!("{} resolves to private address {}", host, ip)).into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn describe(&self) -> String {
+        let list = |rules: &[String], empty: &str| if rules.is_empty() { empty.to_string() } else { rules.join(", ") };
+        format!(
+            "allowed: {} | blocked: {} | private IPs: {}",
+            list(&self.allowed_domains, "any"),
+            list(&self.blocked_domains, "none"),
+            if self.block_private_ips { "blocked" } else { "allowed" }
+        )
+    }
+}
+
+// `example.com` and `*.example.com` both cover the domain's subdomains; only the former covers the apex
+pub(crate) fn domain_matches(host: &str, rule: &str) -> bool {
+    let rule = rule.trim().to_lowercase();
+    match rule.strip_prefix("*.") {
+        Some(parent) => host.ends_with(&format!(".{}", parent)),
+        None => {
+            let rule = rule.trim_start_matches('.');
+            host == rule || host.ends_with(&format!(".{}", rule))
+        }
+    }
+}
+
+// Hostnames are resolved so a public name pointing at an internal address is caught too
+async fn resolve_private(host: &str, port: u16) -> Option<IpAddr> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return is_private_ip(&ip).then_some(ip);
+    }
+    if host == "localhost" || host.ends_with(".localhost") {
+        return Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    }
+
+    // Unresolvable hosts are left for the browser to fail on
+    let addrs = tokio::net::lookup_host((host, port)).await.ok()?;
+    addrs.map(|addr| addr.ip()).find(is_private_ip)
+}
+
+fn is_private_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_private_ipv4(ip),
+        IpAddr::V6(ip) => is_private_ipv6(ip),
+    }
+}
+
+fn is_private_ipv4(ip: &Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // Carrier-grade NAT range 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+}
+
+fn is_private_ipv6(ip: &Ipv6Addr) -> bool {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return is_private_ipv4(&mapped);
+    }
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        // Unique local fc00::/7 and link-local fe80::/10
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+}
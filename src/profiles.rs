@@ -0,0 +1,189 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use chromiumoxide::Browser;
+use colored::*;
+use futures_util::StreamExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::paths;
+
+// Every launched browser gets a throwaway profile in the temp dir named browser-cli-[<session>-]<pid>-<timestamp>
+const PROFILE_PREFIX: &str = "browser-cli-";
+
+// Session names end up in profile and daemon state file names, so they're kept to [A-Za-z0-9_]+
+pub fn check_session_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(Error::InvalidSessionName(name.to_string()));
+    }
+    Ok(())
+}
+
+// Profile directory for a browser this process is about to launch
+pub fn new_profile_dir(session: &str) -> PathBuf {
+    let (pid, now) = (std::process::id(), Utc::now().timestamp());
+    let name = match session {
+        "default" => format!("{}{}-{}", PROFILE_PREFIX, pid, now),
+        session => format!("{}{}-{}-{}", PROFILE_PREFIX, session, pid, now),
+    };
+    paths::temp_root().join(name)
+}
+
+// A browser profile some browser-cli process left in the temp dir; its browser may or may not still run
+#[derive(Debug, Clone)]
+pub struct ProfileDir {
+    pub path: PathBuf,
+    pub session: String,
+    pub pid: u32,
+    pub started: Option<DateTime<Utc>>,
+}
+
+impl ProfileDir {
+    fn parse(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.strip_prefix(PROFILE_PREFIX)?.to_string();
+        let mut parts = name.rsplitn(3, '-');
+        let started = parts.next()?.parse::<i64>().ok()?;
+        let pid = parts.next()?.parse::<u32>().ok()?;
+        let session = parts.next().unwrap_or("default").to_string();
+        Some(Self { path, session, pid, started: DateTime::from_timestamp(started, 0) })
+    }
+
+    // DevTools port and browser endpoint path Chrome writes into the profile once it's listening
+    fn devtools_endpoint(&self) -> Option<(u16, String)> {
+        let contents = std::fs::read_to_string(self.path.join("DevToolsActivePort")).ok()?;
+        let mut lines = contents.lines();
+        let port = lines.next()?.trim().parse().ok()?;
+        let path = lines.next()?.trim().to_string();
+        Some((port, path))
+    }
+
+    // Close the profile's browser if it's still running, then delete the profile.
+    // Returns whether a live browser was shut down (false for leftovers of crashed or killed runs).
+    pub async fn shutdown(&self) -> Result<bool> {
+        let mut was_running = false;
+        if let Some((port, path)) = self.devtools_endpoint() {
+            let url = format!("ws://127.0.0.1:{}{}", port, path);
+            if let Ok(Ok((mut browser, mut handler))) = tokio::time::timeout(Duration::from_secs(5), Browser::connect(url)).await {
+                let handler = tokio::spawn(async move { while handler.next().await.is_some() {} });
+                was_running = browser.close().await.is_ok();
+                handler.abort();
+            }
+            // A browser too busy to take the connection still holds its DevTools port; deleting the profile
+            // from under it would break it, so it's left for a later try
+            if !was_running && port_answers(port).await {
+                return Err(anyhow::anyhow!(
+                    "could not close its browser (DevTools still listening on port {}); left {} in place",
+                    port, self.path.display()
+                ));
+            }
+        }
+
+        // Chrome can still be flushing the profile for a moment after it acknowledges the close
+        let mut attempts = 0;
+        while let Err(e) = std::fs::remove_dir_all(&self.path) {
+            attempts += 1;
+            if e.kind() == std::io::ErrorKind::NotFound {
+                break;
+            }
+            if attempts >= 10 {
+                return Err(anyhow::anyhow!("Failed to remove {}: {}", self.path.display(), e));
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        Ok(was_running)
+    }
+}
+
+async fn port_answers(port: u16) -> bool {
+    matches!(
+        tokio::time::timeout(Duration::from_secs(1), tokio::net::TcpStream::connect(("127.0.0.1", port))).await,
+        Ok(Ok(_))
+    )
+}
+
+// Profiles of browsers launched by other browser-cli processes, oldest first
+pub fn list() -> Vec<ProfileDir> {
+    scan().into_iter().filter(|profile| profile.pid != std::process::id()).collect()
+}
+
+// Profiles of browsers this process launched and hasn't cleaned up yet
+pub fn owned() -> Vec<ProfileDir> {
+    scan().into_iter().filter(|profile| profile.pid == std::process::id()).collect()
+}
+
+fn scan() -> Vec<ProfileDir> {
+    let Ok(entries) = std::fs::read_dir(paths::temp_root()) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<ProfileDir> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|entry| ProfileDir::parse(entry.path()))
+        .collect();
+    profiles.sort_by_key(|profile| profile.started);
+    profiles
+}
+
+// Shut down the given profiles and print what happened to each. Returns the profiles that were removed,
+// with whether their browser was still running.
+pub async fn shutdown_all(profiles: &[ProfileDir]) -> Vec<(ProfileDir, bool)> {
+    let mut removed = Vec::new();
+    for profile in profiles {
+        let started = profile.started
+            .map(|at| at.with_timezone(&chrono::Local).format(" since %Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let was_running = match profile.shutdown().await {
+            Ok(true) => {
+                status!("{} Closed session '{}' (pid {}{}), removed {}",
+                    "✓".green(), profile.session, profile.pid, started, profile.path.display());
+                true
+            }
+            Ok(false) => {
+                status!("{} Removed stale profile of session '{}' (pid {}{}): {}",
+                    "🧹".cyan(), profile.session, profile.pid, started, profile.path.display());
+                false
+            }
+            Err(e) => {
+                status!("{} Session '{}' (pid {}): {}", "⚠️".yellow(), profile.session, profile.pid, e);
+                continue;
+            }
+        };
+        removed.push((profile.clone(), was_running));
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_profile_dirs_live_under_the_temp_root() {
+        let dir = new_profile_dir("default");
+        assert_eq!(dir.parent(), Some(paths::temp_root().as_path()));
+        let profile = ProfileDir::parse(dir).unwrap();
+        assert_eq!(profile.session, "default");
+        assert_eq!(profile.pid, std::process::id());
+        assert!(profile.started.is_some());
+    }
+
+    #[test]
+    fn named_sessions_round_trip_through_the_dir_name() {
+        let dir = new_profile_dir("my_session");
+        assert_eq!(dir.parent(), Some(paths::temp_root().as_path()));
+        let profile = ProfileDir::parse(dir).unwrap();
+        assert_eq!(profile.session, "my_session");
+        assert_eq!(profile.pid, std::process::id());
+    }
+
+    #[test]
+    fn session_names_stay_inside_their_file_names() {
+        for name in ["default", "work", "ci_2"] {
+            assert!(check_session_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "../x", "a/b", "my-session", "a b", "é"] {
+            assert!(matches!(check_session_name(name), Err(Error::InvalidSessionName(_))), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+use crate::output::{self, StatusOutput};
+
+// Progress indicators for long operations. They draw on stderr and hide themselves when it isn't a
+// terminal, so piped and scripted output stays exactly as before.
+
+// Spinner for open-ended waits (launches, page loads, selector waits, monitoring)
+pub fn spinner(message: impl Into<String>) -> ProgressBar {
+    if output::status_output() == StatusOutput::Silent {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new_spinner();
+    bar.set_style(
+        ProgressStyle::with_template("{spinner:.cyan} {msg} {elapsed:.dim}")
+            .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+    );
+    bar.set_message(message.into());
+    bar.enable_steady_tick(Duration::from_millis(120));
+    bar
+}
+
+// Bar over a known number of items (batch jobs, crawls, per-element checks), with an ETA
+pub fn items(len: u64, message: impl Into<String>) -> ProgressBar {
+    if output::status_output() == StatusOutput::Silent {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new(len);
+    bar.set_style(
+        ProgressStyle::with_template("{spinner:.cyan} {msg} [{bar:30.cyan/blue}] {pos}/{len} ETA {eta}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+    );
+    bar.set_message(message.into());
+    bar.enable_steady_tick(Duration::from_millis(120));
+    bar
+}
@@ -0,0 +1,171 @@
+// Product token batch visits go by in robots.txt; groups for it win over the `*` group
+pub const USER_AGENT_TOKEN: &str = "browser-cli";
+
+// The robots.txt rules (RFC 9309) that apply to browser-cli on one origin
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    // (allow, path pattern)
+    rules: Vec<(bool, String)>,
+    // Seconds to leave between visits
+    pub crawl_delay: Option<f64>,
+}
+
+impl RobotsRules {
+    // A site that couldn't be asked (server errors) is treated as off limits, as the RFC requires
+    pub fn disallow_all() -> Self {
+        Self { rules: vec![(false, "/".to_string())], crawl_delay: None }
+    }
+
+    // Rules of the groups naming browser-cli, or of the `*` groups when none does. Groups naming the same
+    // agent are merged.
+    pub fn parse(text: &str) -> Self {
+        let mut specific = Self::default();
+        let mut wildcard = Self::default();
+        let (mut found_specific, mut agents, mut in_rules) = (false, Vec::new(), false);
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            if key == "user-agent" {
+                // A user-agent line after rules starts the next group
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                agents.push(value.split('/').next().unwrap_or_default().trim().to_ascii_lowercase());
+                continue;
+            }
+            in_rules = true;
+            let targets: Vec<&mut Self> = match (agents.iter().any(|a| a == USER_AGENT_TOKEN), agents.iter().any(|a| a == "*")) {
+                (true, true) => vec![&mut specific, &mut wildcard],
+                (true, false) => vec![&mut specific],
+                (false, true) => vec![&mut wildcard],
+                (false, false) => continue,
+            };
+            found_specific |= agents.iter().any(|a| a == USER_AGENT_TOKEN);
+            for rules in targets {
+                match key.as_str() {
+                    // An empty Disallow allows everything, which is what having no rule does too
+                    "allow" | "disallow" if !value.is_empty() => rules.rules.push((key == "allow", value.to_string())),
+                    "crawl-delay" => rules.crawl_delay = value.parse().ok().filter(|delay: &f64| *delay >= 0.0),
+                    _ => {}
+                }
+            }
+        }
+        if found_specific { specific } else { wildcard }
+    }
+
+    // The longest matching pattern decides; on a tie Allow wins. /robots.txt itself is always allowed.
+    pub fn allowed(&self, url: &str) -> bool {
+        let path = match url::Url::parse(url) {
+            Ok(parsed) => match parsed.query() {
+                Some(query) => format!("{}?{}", parsed.path(), query),
+                None => parsed.path().to_string(),
+            },
+            Err(_) => url.to_string(),
+        };
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules.iter()
+            .filter(|(_, pattern)| path_matches(pattern, &path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+// robots.txt path pattern: a prefix match where * stands for any run of characters and a trailing $
+// anchors the end
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specific_groups_win_over_wildcard() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow: /\n\nUser-agent: browser-cli\nDisallow: /private\n");
+        assert!(rules.allowed("https://example.com/page"));
+        assert!(!rules.allowed("https://example.com/private/page"));
+    }
+
+    #[test]
+    fn groups_for_the_same_agent_merge() {
+        let rules = RobotsRules::parse(
+            "User-agent: browser-cli\nDisallow: /a\n\nUser-agent: other\nDisallow: /b\n\nUser-agent: Browser-CLI/2.0\nDisallow: /c\nCrawl-delay: 1.5\n",
+        );
+        assert!(!rules.allowed("https://example.com/a"));
+        assert!(rules.allowed("https://example.com/b"));
+        assert!(!rules.allowed("https://example.com/c"));
+        assert_eq!(rules.crawl_delay, Some(1.5));
+    }
+
+    #[test]
+    fn consecutive_user_agents_share_a_group() {
+        let rules = RobotsRules::parse("User-agent: other\nUser-agent: *\nDisallow: /tmp # scratch\n");
+        assert!(!rules.allowed("https://example.com/tmp/x"));
+        assert!(rules.allowed("https://example.com/other"));
+    }
+
+    #[test]
+    fn empty_disallow_and_missing_file_allow_everything() {
+        assert!(RobotsRules::parse("User-agent: *\nDisallow:\n").allowed("https://example.com/anything"));
+        assert!(RobotsRules::default().allowed("https://example.com/anything"));
+        assert!(!RobotsRules::disallow_all().allowed("https://example.com/anything"));
+        assert!(RobotsRules::disallow_all().allowed("https://example.com/robots.txt"));
+    }
+
+    #[test]
+    fn longest_match_decides_and_allow_wins_ties() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow: /docs\nAllow: /docs/public\nAllow: /page\nDisallow: /page\n");
+        assert!(!rules.allowed("https://example.com/docs/secret"));
+        assert!(rules.allowed("https://example.com/docs/public/intro"));
+        assert!(rules.allowed("https://example.com/page"));
+    }
+
+    #[test]
+    fn queries_count_as_part_of_the_path() {
+        let rules = RobotsRules::parse("User-agent: *\nDisallow: /search?q=\n");
+        assert!(!rules.allowed("https://example.com/search?q=cats"));
+        assert!(rules.allowed("https://example.com/search"));
+    }
+
+    #[test]
+    fn path_patterns() {
+        assert!(path_matches("/fish", "/fish.html"));
+        assert!(path_matches("/fish", "/fish/salmon"));
+        assert!(!path_matches("/fish", "/Fish"));
+        assert!(path_matches("/*.php", "/index.php"));
+        assert!(path_matches("/*.php", "/folder/page.php?x=1"));
+        assert!(path_matches("/*.php$", "/index.php"));
+        assert!(!path_matches("/*.php$", "/index.php?x=1"));
+        assert!(path_matches("/fish*", "/fish"));
+        assert!(path_matches("/a/*/c", "/a/b/c/d"));
+        assert!(!path_matches("/a/*/c", "/a/b/d"));
+        assert!(path_matches("/exact$", "/exact"));
+        assert!(!path_matches("/exact$", "/exactly"));
+    }
+}
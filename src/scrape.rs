@@ -0,0 +1,145 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// What `scrape --spec` extracts: output field names mapped to rules for finding their values, e.g.
+//
+//   {
+//     "title": "h1",
+//     "image": { "selector": "img.hero", "attr": "src" },
+//     "tags": { "selector": ".tag", "list": true },
+//     "products": { "selector": ".product", "list": true, "fields": { "name": "h2", "price": ".price" } }
+//   }
+//
+// Keys come out in alphabetical order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct ScrapeSpec(BTreeMap<String, ScrapeRule>);
+
+// A field of the spec; a bare string in the file is shorthand for a rule with only a selector
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrapeRule {
+    pub selector: String,
+    // Attribute to read instead of the text; href, src and the other URL attributes come back absolute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attr: Option<String>,
+    // Every match as an array instead of the first one
+    pub list: bool,
+    // Nested spec read inside each match, making the value an object
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<ScrapeSpec>,
+}
+
+impl ScrapeSpec {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read scrape spec {}: {}", path.display(), e))?;
+        let value: Value = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid scrape spec {}: {}", path.display(), e))?;
+        Self::parse(&value, "").map_err(|e| anyhow::anyhow!("Invalid scrape spec {}: {}", path.display(), e))
+    }
+
+    // `at` is the dotted path of the field holding this spec, empty at the top
+    fn parse(value: &Value, at: &str) -> Result<Self> {
+        let Some(object) = value.as_object() else {
+            return Err(anyhow::anyhow!("{} must be an object mapping field names to selectors", describe(at)));
+        };
+        if object.is_empty() {
+            return Err(anyhow::anyhow!("{} is empty", describe(at)));
+        }
+        let mut fields = BTreeMap::new();
+        for (name, field) in object {
+            let path = if at.is_empty() { name.clone() } else { format!("{}.{}", at, name) };
+            fields.insert(name.clone(), ScrapeRule::parse(field, &path)?);
+        }
+        Ok(Self(fields))
+    }
+}
+
+impl ScrapeRule {
+    fn parse(value: &Value, at: &str) -> Result<Self> {
+        let object = match value {
+            Value::String(selector) if !selector.trim().is_empty() => {
+                return Ok(Self { selector: selector.clone(), attr: None, list: false, fields: None });
+            }
+            Value::Object(object) => object,
+            _ => return Err(anyhow::anyhow!("field '{}' must be a selector or an object with a selector", at)),
+        };
+        if let Some(key) = object.keys().find(|key| !["selector", "attr", "list", "fields"].contains(&key.as_str())) {
+            return Err(anyhow::anyhow!("field '{}' has unknown key '{}' (expected selector, attr, list or fields)", at, key));
+        }
+        let selector = match object.get("selector") {
+            Some(Value::String(selector)) if !selector.trim().is_empty() => selector.clone(),
+            _ => return Err(anyhow::anyhow!("field '{}' needs a selector", at)),
+        };
+        let attr = match object.get("attr") {
+            None => None,
+            Some(Value::String(attr)) if !attr.is_empty() => Some(attr.clone()),
+            Some(_) => return Err(anyhow::anyhow!("field '{}': attr must be an attribute name", at)),
+        };
+        let list = match object.get("list") {
+            None => false,
+            Some(Value::Bool(list)) => *list,
+            Some(_) => return Err(anyhow::anyhow!("field '{}': list must be true or false", at)),
+        };
+        let fields = object.get("fields").map(|fields| ScrapeSpec::parse(fields, at)).transpose()?;
+        if attr.is_some() && fields.is_some() {
+            return Err(anyhow::anyhow!("field '{}' can't have both attr and fields", at));
+        }
+        Ok(Self { selector, attr, list, fields })
+    }
+}
+
+fn describe(at: &str) -> String {
+    if at.is_empty() { "spec".to_string() } else { format!("'{}.fields'", at) }
+}
+
+// The scraped document, plus the fields that matched nothing (null, or an empty list). Fields inside lists
+// are named like `products[].price`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapeResult {
+    pub data: Value,
+    #[serde(default)]
+    pub missing: Vec<String>,
+}
+
+// JS function applying a serialized ScrapeSpec to the document; returns { data, missing } or { error }
+// naming the field whose selector the browser rejected
+pub const SCRAPE_JS: &str = r#"(spec) => {
+    const URL_ATTRS = new Set(['href', 'src', 'action', 'poster', 'cite', 'data', 'formaction']);
+    const missing = new Set();
+    const text = el => (el.innerText ?? el.textContent ?? '').replace(/\s+/g, ' ').trim();
+    const read = (el, rule, at) => {
+        if (rule.fields) return scope(el, rule.fields, at);
+        if (!rule.attr) return text(el);
+        const value = el.getAttribute(rule.attr);
+        if (value === null || !URL_ATTRS.has(rule.attr)) return value;
+        try {
+            return new URL(value, document.baseURI).href;
+        } catch (e) {
+            return value;
+        }
+    };
+    const scope = (root, fields, path) => {
+        const out = {};
+        for (const [name, rule] of Object.entries(fields)) {
+            const at = path ? `${path}.${name}` : name;
+            let matches;
+            try {
+                matches = rule.list ? Array.from(root.querySelectorAll(rule.selector)) : [root.querySelector(rule.selector)].filter(Boolean);
+            } catch (e) {
+                throw new Error(`field '${at.replace(/\[\]/g, '')}': invalid selector '${rule.selector}'`);
+            }
+            if (!matches.length) missing.add(at);
+            out[name] = rule.list ? matches.map(el => read(el, rule, `${at}[]`)) : (matches.length ? read(matches[0], rule, at) : null);
+        }
+        return out;
+    };
+    try {
+        return { data: scope(document, spec, ''), missing: Array.from(missing) };
+    } catch (e) {
+        return { error: e.message };
+    }
+}"#;
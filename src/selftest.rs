@@ -0,0 +1,291 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+use crate::browser::{BrowserController, NavigateOptions, TextOptions, TypeOptions};
+use crate::event_stream::read_request_head;
+use crate::events::DialogAction;
+use crate::output;
+use crate::serve::respond;
+
+// Checks in the order they run; later ones reuse pages loaded by earlier ones
+pub const CHECKS: &[&str] = &[
+    "navigate",
+    "type",
+    "click",
+    "keyboard",
+    "text",
+    "hover",
+    "markdown",
+    "frames",
+    "shadow-dom",
+    "dialogs",
+    "downloads",
+    "network-idle",
+    "screenshot",
+];
+
+// Seconds a check waits for something the fixture page does
+const CHECK_WAIT_SECS: u64 = 5;
+
+const DOWNLOAD_BODY: &str = "browser-cli self-test download\n";
+
+// What the markdown check expects /article.html to convert to
+const ARTICLE_MARKDOWN: &str = "# Release notes
+
+Version **2.0** adds [links](http://HOST/form.html) and `code`.
+
+- First
+- Second
+  1. Nested
+
+```sh
+cargo install
+```
+
+| Name | Size |
+| --- | --- |
+| a | 1 |";
+
+// Path, content type and body of each page the fixture server knows
+const PAGES: &[(&str, &str, &str)] = &[
+    ("/form.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Form</title></head><body>
+<form id="form"><input id="name" name="name"> <button id="submit" type="submit">Greet</button></form>
+<p id="output"></p>
+<div id="hover-target" style="width: 200px; height: 40px">Hover me</div>
+<script>
+let greetings = 0;
+document.getElementById('form').addEventListener('submit', event => {
+    event.preventDefault();
+    greetings += 1;
+    document.getElementById('output').textContent = `Hello ${document.getElementById('name').value} (${greetings})`;
+});
+document.getElementById('hover-target').addEventListener('mouseover', event => { event.target.textContent = 'Hovered'; });
+</script>
+</body></html>"#),
+    ("/frames.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Frames</title></head><body>
+<iframe src="/frame-child.html" title="child"></iframe>
+</body></html>"#),
+    ("/frame-child.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Child</title></head><body><button id="in-frame">In a frame</button></body></html>"#),
+    ("/shadow.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Shadow DOM</title></head><body>
+<shadow-host></shadow-host>
+<script>
+customElements.define('shadow-host', class extends HTMLElement {
+    constructor() {
+        super();
+        this.attachShadow({ mode: 'open' }).innerHTML = '<button id="in-shadow">In a shadow root</button>';
+    }
+});
+</script>
+</body></html>"#),
+    ("/dialog.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Dialogs</title></head><body>
+<button id="confirm" onclick="window.answer = confirm('Continue?')">Confirm</button>
+</body></html>"#),
+    ("/network.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Network</title></head><body>
+<p id="data">loading</p>
+<script>
+setTimeout(() => fetch('/data.json')
+    .then(response => response.json())
+    .then(data => { document.getElementById('data').textContent = data.message; }), 200);
+</script>
+</body></html>"#),
+    ("/article.html", "text/html", r#"<!DOCTYPE html>
+<html lang="en"><head><title>Article</title><style>p { color: black }</style></head><body>
+<article>
+  <h1>Release   notes</h1>
+  <p>Version <strong>2.0</strong> adds <a href="/form.html">links</a> and <code>code</code>.</p>
+  <p hidden>Hidden</p>
+  <ul><li>First</li><li>Second<ol><li>Nested</li></ol></li></ul>
+  <pre><code class="language-sh">cargo install
+</code></pre>
+  <table><tr><th>Name</th><th>Size</th></tr><tr><td>a</td><td>1</td></tr></table>
+  <script>document.title = 'Article';</script>
+</article>
+</body></html>"#),
+    ("/data.json", "application/json", r#"{"message": "loaded"}"#),
+    ("/download.txt", "text/plain", DOWNLOAD_BODY),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub passed: bool,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub base_url: String,
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    pub fn failed(&self) -> usize {
+        self.checks.iter().filter(|check| !check.passed).count()
+    }
+}
+
+// Serve the fixture pages on a free loopback port for as long as the process runs; returns the base URL
+pub async fn serve_fixtures() -> Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0").await
+        .map_err(|e| anyhow::anyhow!("Failed to start the self-test server: {}", e))?;
+    let base_url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+            tokio::spawn(async move {
+                let Ok(head) = read_request_head(&mut stream).await else {
+                    return;
+                };
+                // "GET /path?query HTTP/1.1"
+                let target = head.split_whitespace().nth(1).unwrap_or("/");
+                let path = target.split('?').next().unwrap_or(target);
+                match PAGES.iter().find(|(page, _, _)| *page == path) {
+                    Some((page, content_type, body)) => {
+                        let content_type = format!("{}; charset=utf-8", content_type);
+                        let disposition: &[(&str, &str)] = if page.ends_with(".txt") { &[("Content-Disposition", "attachment; filename=\"self-test.txt\"")] } else { &[] };
+                        respond(&mut stream, "200 OK", &content_type, disposition, body.as_bytes(), true).await.ok();
+                    }
+                    None => {
+                        respond(&mut stream, "404 Not Found", "text/plain; charset=utf-8", &[], b"", true).await.ok();
+                    }
+                }
+                stream.shutdown().await.ok();
+            });
+        }
+    });
+    Ok(base_url)
+}
+
+// Exercise each capability against the fixture server and report per check. The controller's own status
+// lines are swallowed so only the results show.
+pub async fn run(browser: &mut BrowserController) -> Result<SelfTestReport> {
+    let base_url = serve_fixtures().await?;
+    status!("{} Self-test against {}", "🧪".cyan(), base_url);
+    browser.init().await?;
+
+    let scratch = std::env::temp_dir().join(format!("browser-cli-self-test-{}", std::process::id()));
+    std::fs::create_dir_all(&scratch)?;
+    let mut checks = Vec::new();
+    for name in CHECKS {
+        let started = Instant::now();
+        output::start_capture();
+        let result = run_check(browser, &base_url, &scratch, name).await;
+        output::take_capture();
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(()) => status!("  {} {} {}", "✓".green(), name, format!("({}ms)", elapsed_ms).dimmed()),
+            Err(e) => status!("  {} {}: {}", "✗".red(), name, e),
+        }
+        checks.push(CheckResult { name: name.to_string(), passed: result.is_ok(), error: result.err().map(|e| e.to_string()), elapsed_ms });
+    }
+    browser.set_dialog_action(None);
+    std::fs::remove_dir_all(&scratch).ok();
+
+    let report = SelfTestReport { base_url, checks };
+    match report.failed() {
+        0 => status!("{} All {} checks passed", "✓".green(), report.checks.len()),
+        failed => status!("{} {} of {} checks failed", "✗".red(), failed, report.checks.len()),
+    }
+    Ok(report)
+}
+
+async fn run_check(browser: &mut BrowserController, base_url: &str, scratch: &Path, name: &str) -> Result<()> {
+    let page = |path: &str| format!("{}{}", base_url, path);
+    match name {
+        "navigate" => {
+            browser.navigate(&page("/form.html"), NavigateOptions::default()).await?;
+            expect("title", browser.get_title().await?, "Form")
+        }
+        "type" => {
+            browser.type_text("#name", "Ada", &TypeOptions::default()).await?;
+            browser.assert_js("document.getElementById('name').value", Some("Ada")).await.map(|_| ())
+        }
+        "click" => {
+            browser.click("#submit").await?;
+            browser.wait_for_text("Hello Ada (1)", Some(CHECK_WAIT_SECS)).await
+        }
+        "keyboard" => {
+            browser.click("#name").await?;
+            browser.press_key("Enter").await?;
+            browser.wait_for_text("Hello Ada (2)", Some(CHECK_WAIT_SECS)).await
+        }
+        "text" => {
+            let text = browser.get_text(Some("#output"), &TextOptions::default()).await?;
+            expect("text", text.trim().to_string(), "Hello Ada (2)")
+        }
+        "hover" => {
+            browser.hover("#hover-target").await?;
+            browser.assert_js("document.getElementById('hover-target').textContent", Some("Hovered")).await.map(|_| ())
+        }
+        "markdown" => {
+            browser.navigate(&page("/article.html"), NavigateOptions::default()).await?;
+            let host = base_url.trim_start_matches("http://");
+            expect("markdown", browser.get_markdown(Some("article")).await?, &ARTICLE_MARKDOWN.replace("HOST", host))
+        }
+        "frames" => {
+            browser.navigate(&page("/frames.html"), NavigateOptions::default()).await?;
+            match browser.wait_for_element_enhanced("#in-frame", CHECK_WAIT_SECS).await? {
+                Some(location) if location.frame.is_some() => Ok(()),
+                Some(location) => Err(anyhow::anyhow!("found #in-frame {} instead of in the iframe", location.describe())),
+                None => Err(anyhow::anyhow!("#in-frame not found in the iframe")),
+            }
+        }
+        "shadow-dom" => {
+            browser.navigate(&page("/shadow.html"), NavigateOptions::default()).await?;
+            match browser.wait_for_element_enhanced("#in-shadow", CHECK_WAIT_SECS).await? {
+                Some(location) if !location.shadow_hosts.is_empty() => Ok(()),
+                Some(location) => Err(anyhow::anyhow!("found #in-shadow {} instead of in a shadow root", location.describe())),
+                None => Err(anyhow::anyhow!("#in-shadow not found in the shadow root")),
+            }
+        }
+        "dialogs" => {
+            browser.navigate(&page("/dialog.html"), NavigateOptions::default()).await?;
+            browser.set_dialog_action(Some(DialogAction::Accept(None)));
+            browser.click("#confirm").await?;
+            browser.assert_js("window.answer", Some("true")).await.map(|_| ())
+        }
+        "downloads" => {
+            let target: PathBuf = scratch.join("download.txt");
+            browser.fetch_to_file(&page("/download.txt"), Some(&target.display().to_string())).await?;
+            expect("download", std::fs::read_to_string(&target)?, DOWNLOAD_BODY)
+        }
+        "network-idle" => {
+            browser.navigate(&page("/network.html"), NavigateOptions::default()).await?;
+            browser.wait_for_network_idle(Duration::from_millis(500), Some(CHECK_WAIT_SECS * 2)).await?;
+            browser.assert_js("document.getElementById('data').textContent", Some("loaded")).await.map(|_| ())
+        }
+        "screenshot" => {
+            let target = scratch.join("screenshot.png");
+            browser.screenshot(Some(&target.display().to_string())).await?;
+            let bytes = std::fs::read(&target)?;
+            if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("{} is not a PNG", target.display()))
+            }
+        }
+        other => Err(anyhow::anyhow!("Unknown check '{}'", other)),
+    }
+}
+
+fn expect(what: &str, actual: String, expected: &str) -> Result<()> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{} was {:?}, expected {:?}", what, actual, expected))
+    }
+}
@@ -0,0 +1,272 @@
+use anyhow::Result;
+use colored::*;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use crate::event_stream::read_request_head;
+
+// Content type by file extension; anything else is served as application/octet-stream
+const CONTENT_TYPES: &[(&str, &str)] = &[
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("xml", "application/xml"),
+    ("svg", "image/svg+xml"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("wasm", "application/wasm"),
+    ("pdf", "application/pdf"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("mp3", "audio/mpeg"),
+];
+
+// Serve a directory over HTTP on 127.0.0.1 (port 0 picks a free one) until the process exits, for
+// testing local builds without a separate web server. Directories serve their index.html, or a listing.
+// Nothing is cached, so a reload always sees the files on disk.
+pub async fn serve_dir(root: &Path, port: u16) -> Result<SocketAddr> {
+    let root = root.canonicalize()
+        .map_err(|e| anyhow::anyhow!("Can't serve {}: {}", root.display(), e))?;
+    if !root.is_dir() {
+        return Err(anyhow::anyhow!("Can't serve {}: not a directory", root.display()));
+    }
+    let listener = TcpListener::bind(("127.0.0.1", port)).await
+        .map_err(|e| anyhow::anyhow!("Failed to listen on port {}: {}", port, e))?;
+    let addr = listener.local_addr()?;
+    status!("{} Serving {} at http://{}/", "📂".cyan(), root.display(), addr);
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                continue;
+            };
+            let root = root.clone();
+            tokio::spawn(async move {
+                let Ok(head) = read_request_head(&mut stream).await else {
+                    return;
+                };
+                answer(&mut stream, &root, &head).await.ok();
+                stream.shutdown().await.ok();
+            });
+        }
+    });
+    Ok(addr)
+}
+
+async fn answer<S: AsyncWrite + Unpin>(stream: &mut S, root: &Path, head: &str) -> Result<()> {
+    // "GET /path?query HTTP/1.1"
+    let mut request_line = head.split_whitespace();
+    let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or("/"));
+    if method != "GET" && method != "HEAD" {
+        return respond(stream, "405 Method Not Allowed", "text/plain; charset=utf-8", &[("Allow", "GET, HEAD")], b"Method not allowed\n", true).await;
+    }
+    let with_body = method == "GET";
+    let path = target.split(['?', '#']).next().unwrap_or("/");
+    let Some(file) = resolve(root, path) else {
+        return respond(stream, "404 Not Found", "text/plain; charset=utf-8", &[], b"Not found\n", with_body).await;
+    };
+
+    if file.is_dir() {
+        // Relative links in the page resolve against the directory only with the trailing slash
+        if !path.ends_with('/') {
+            let location = format!("{}/", path);
+            return respond(stream, "301 Moved Permanently", "text/plain; charset=utf-8", &[("Location", &location)], b"", with_body).await;
+        }
+        let index = file.join("index.html");
+        if !index.is_file() {
+            let listing = listing(&file, path)?;
+            return respond(stream, "200 OK", "text/html; charset=utf-8", &[], listing.as_bytes(), with_body).await;
+        }
+        return send_file(stream, &index, with_body).await;
+    }
+    send_file(stream, &file, with_body).await
+}
+
+async fn send_file<S: AsyncWrite + Unpin>(stream: &mut S, file: &Path, with_body: bool) -> Result<()> {
+    let body = tokio::fs::read(file).await?;
+    let extension = file.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    let content_type = CONTENT_TYPES.iter()
+        .find(|(known, _)| *known == extension)
+        .map_or("application/octet-stream", |(_, content_type)| content_type);
+    respond(stream, "200 OK", content_type, &[], &body, with_body).await
+}
+
+// The file a request path names inside the root, or None when it doesn't exist or would leave the root
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(path)?;
+    let mut file = root.to_path_buf();
+    for component in Path::new(decoded.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => file.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    // A symlink may still point outside
+    let file = file.canonicalize().ok()?;
+    file.starts_with(root).then_some(file)
+}
+
+fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+// HTML list of a directory's entries, directories first
+fn listing(dir: &Path, path: &str) -> Result<String> {
+    let mut entries: Vec<(bool, String)> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| (!entry.path().is_dir(), entry.file_name().to_string_lossy().into_owned()))
+        .collect();
+    entries.sort();
+    let escape = |text: &str| text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+    let items: String = entries.iter()
+        .map(|(file, name)| {
+            let name = if *file { name.clone() } else { format!("{}/", name) };
+            format!("<li><a href=\"{}\">{}</a></li>\n", escape(&name), escape(&name))
+        })
+        .collect();
+    Ok(format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Index of {0}</title></head>\n<body><h1>Index of {0}</h1>\n<ul>\n{1}</ul></body></html>\n",
+        escape(path), items
+    ))
+}
+
+// Write a complete HTTP/1.1 response and leave the connection to be closed
+pub(crate) async fn respond<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    status: &str,
+    content_type: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    with_body: bool,
+) -> Result<()> {
+    let mut head = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n", status, content_type, body.len());
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    if with_body {
+        stream.write_all(body).await?;
+    }
+    stream.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // <temp>/…/site served as the root, next to a secret.txt it must not hand out
+    struct Tree {
+        dir: PathBuf,
+        root: PathBuf,
+    }
+
+    impl Tree {
+        fn new(test: &str) -> Self {
+            let dir = crate::paths::temp_root().join(format!("browser-cli-serve-{}-{}", test, std::process::id()));
+            let site = dir.join("site");
+            std::fs::create_dir_all(site.join("sub")).unwrap();
+            std::fs::write(site.join("index.html"), "<h1>home</h1>").unwrap();
+            std::fs::write(site.join("with space.txt"), "spaced").unwrap();
+            std::fs::write(site.join("sub").join("b.txt"), "b").unwrap();
+            std::fs::write(dir.join("secret.txt"), "secret").unwrap();
+            let root = site.canonicalize().unwrap();
+            Self { dir, root }
+        }
+
+        fn resolve(&self, path: &str) -> Option<PathBuf> {
+            resolve(&self.root, path)
+        }
+    }
+
+    impl Drop for Tree {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.dir).ok();
+        }
+    }
+
+    #[test]
+    fn files_inside_the_root_resolve() {
+        let tree = Tree::new("inside");
+        assert_eq!(tree.resolve("/"), Some(tree.root.clone()));
+        assert_eq!(tree.resolve("/index.html"), Some(tree.root.join("index.html")));
+        assert_eq!(tree.resolve("/./sub/b.txt"), Some(tree.root.join("sub").join("b.txt")));
+        assert_eq!(tree.resolve("/with%20space.txt"), Some(tree.root.join("with space.txt")));
+        assert_eq!(tree.resolve("/missing.txt"), None);
+    }
+
+    #[test]
+    fn parent_components_are_refused() {
+        let tree = Tree::new("parent");
+        assert_eq!(tree.resolve("/../secret.txt"), None);
+        assert_eq!(tree.resolve("/sub/../../secret.txt"), None);
+        // Even when the result would stay inside the root
+        assert_eq!(tree.resolve("/sub/../index.html"), None);
+    }
+
+    #[test]
+    fn encoded_traversal_is_refused() {
+        let tree = Tree::new("encoded");
+        assert_eq!(tree.resolve("/%2e%2e/secret.txt"), None);
+        assert_eq!(tree.resolve("/%2E%2E%2Fsecret.txt"), None);
+        assert_eq!(tree.resolve("/sub%2f..%2f..%2fsecret.txt"), None);
+        assert_eq!(tree.resolve("/..%5csecret.txt"), None);
+    }
+
+    #[test]
+    fn absolute_paths_stay_under_the_root() {
+        let tree = Tree::new("absolute");
+        let secret = tree.dir.join("secret.txt");
+        let secret = secret.to_str().unwrap();
+        assert_eq!(tree.resolve(&format!("/{}", secret)), None);
+        assert_eq!(tree.resolve(&format!("/{}", secret.replace('/', "%2f"))), None);
+        assert_eq!(tree.resolve("//index.html"), Some(tree.root.join("index.html")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_root_are_refused() {
+        let tree = Tree::new("symlink");
+        std::os::unix::fs::symlink(tree.dir.join("secret.txt"), tree.root.join("link.txt")).unwrap();
+        assert_eq!(tree.resolve("/link.txt"), None);
+    }
+
+    #[test]
+    fn percent_decoding_rejects_malformed_escapes() {
+        assert_eq!(percent_decode("/a%20b").as_deref(), Some("/a b"));
+        assert_eq!(percent_decode("/%E2%9C%93").as_deref(), Some("/✓"));
+        assert_eq!(percent_decode("/%2"), None);
+        assert_eq!(percent_decode("/%zz"), None);
+        assert_eq!(percent_decode("/%FF"), None);
+    }
+}
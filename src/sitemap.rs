@@ -0,0 +1,281 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::future::Future;
+
+// Sitemaps an index may lead to before reading stops, so a looping or runaway index can't go on forever
+pub const MAX_SITEMAPS: usize = 200;
+
+// Which pages of a sitemap `--from-sitemap` uses
+#[derive(Debug, Clone, Default)]
+pub struct SitemapFilter {
+    // Substring of the URL, or a glob over the whole URL when it contains *
+    pub pattern: Option<String>,
+    // Only pages modified on or after this day; pages without a lastmod are left out
+    pub since: Option<NaiveDate>,
+}
+
+impl SitemapFilter {
+    // YYYY-MM-DD as given to --since
+    pub fn parse_since(value: &str) -> Result<NaiveDate> {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| anyhow::anyhow!("Invalid --since '{}' (expected YYYY-MM-DD)", value))
+    }
+
+    pub fn accepts(&self, entry: &SitemapEntry) -> bool {
+        if let Some(pattern) = &self.pattern {
+            if !crate::network::pattern_matches(pattern, &entry.loc) {
+                return false;
+            }
+        }
+        match self.since {
+            Some(since) => entry.modified().is_some_and(|modified| modified >= since),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub lastmod: Option<String>,
+}
+
+impl SitemapEntry {
+    // lastmod is a W3C datetime; its day is all the filter needs
+    fn modified(&self) -> Option<NaiveDate> {
+        let lastmod = self.lastmod.as_deref()?;
+        NaiveDate::parse_from_str(lastmod.get(..10).unwrap_or(lastmod), "%Y-%m-%d").ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Sitemap {
+    // <sitemapindex>: further sitemaps to read
+    Index(Vec<SitemapEntry>),
+    // <urlset>: the pages themselves
+    UrlSet(Vec<SitemapEntry>),
+}
+
+// Page URLs the sitemap at `url` lists that pass the filter, following indexes (nested ones too) so pages
+// come in sitemap order. `fetch` gets a sitemap's XML; each sitemap is read once, and at most MAX_SITEMAPS.
+pub async fn collect<F, Fut>(url: &str, filter: &SitemapFilter, mut fetch: F) -> Result<Vec<String>>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let (mut pending, mut seen, mut urls) = (vec![url.to_string()], HashSet::new(), Vec::new());
+    while let Some(sitemap_url) = pending.pop() {
+        if !seen.insert(sitemap_url.clone()) {
+            continue;
+        }
+        if seen.len() > MAX_SITEMAPS {
+            return Err(anyhow::anyhow!("{} leads to more than {} sitemaps", url, MAX_SITEMAPS));
+        }
+        let xml = fetch(sitemap_url.clone()).await?;
+        match parse(&xml).map_err(|e| anyhow::anyhow!("{}: {}", sitemap_url, e))? {
+            // Children go on the stack last-first so they're read in the index's order
+            Sitemap::Index(children) => pending.extend(children.into_iter().rev().map(|child| child.loc)),
+            Sitemap::UrlSet(entries) => urls.extend(entries.into_iter().filter(|entry| filter.accepts(entry)).map(|entry| entry.loc)),
+        }
+    }
+    Ok(urls)
+}
+
+// Parse sitemap.xml as the sitemaps.org protocol defines it. Only <loc> and <lastmod> are read, which a
+// tag scan handles without a full XML parser.
+pub fn parse(xml: &str) -> Result<Sitemap> {
+    let (index, element) = if find_element(xml, "sitemapindex").is_some() {
+        (true, "sitemap")
+    } else if find_element(xml, "urlset").is_some() {
+        (false, "url")
+    } else {
+        return Err(anyhow::anyhow!("Not a sitemap (no <urlset> or <sitemapindex>)"));
+    };
+
+    let mut entries = Vec::new();
+    let mut rest = xml;
+    while let Some((inner, after)) = next_element(rest, element) {
+        if let Some(loc) = child_text(inner, "loc").filter(|loc| !loc.is_empty()) {
+            entries.push(SitemapEntry { loc, lastmod: child_text(inner, "lastmod") });
+        }
+        rest = after;
+    }
+    Ok(if index { Sitemap::Index(entries) } else { Sitemap::UrlSet(entries) })
+}
+
+// Position of the first <name> or <name attr…> tag (not <namespace…>)
+fn find_element(xml: &str, name: &str) -> Option<usize> {
+    let open = format!("<{}", name);
+    let mut from = 0;
+    while let Some(at) = xml[from..].find(&open) {
+        let start = from + at;
+        match xml[start + open.len()..].chars().next() {
+            Some(c) if c == '>' || c == '/' || c.is_whitespace() => return Some(start),
+            _ => from = start + open.len(),
+        }
+    }
+    None
+}
+
+// Content of the next <name>…</name> element and the text after it
+fn next_element<'a>(xml: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+    let start = find_element(xml, name)?;
+    let content = start + xml[start..].find('>')? + 1;
+    let close = format!("</{}>", name);
+    let end = content + xml[content..].find(&close)?;
+    Some((&xml[content..end], &xml[end + close.len()..]))
+}
+
+fn child_text(xml: &str, name: &str) -> Option<String> {
+    let (inner, _) = next_element(xml, name)?;
+    let inner = inner.trim();
+    let text = match inner.strip_prefix("<![CDATA[").and_then(|cdata| cdata.strip_suffix("]]>")) {
+        Some(cdata) => cdata.to_string(),
+        None => unescape(inner),
+    };
+    Some(text.trim().to_string())
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const ROOT_INDEX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://shop.test/sitemaps/products.xml</loc><lastmod>2024-03-01</lastmod></sitemap>
+  <sitemap>
+    <loc>https://shop.test/sitemaps/blog-index.xml</loc>
+  </sitemap>
+</sitemapindex>"#;
+
+    // An index inside the index, which also points back at the root
+    const BLOG_INDEX: &str = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://shop.test/sitemaps/blog-2023.xml</loc></sitemap>
+  <sitemap><loc>https://shop.test/sitemap.xml</loc></sitemap>
+  <sitemap><loc>https://shop.test/sitemaps/blog-2024.xml</loc></sitemap>
+</sitemapindex>"#;
+
+    const PRODUCTS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+  <url>
+    <loc>https://shop.test/p/1?color=red&amp;size=m</loc>
+    <lastmod>2024-02-10T08:00:00+00:00</lastmod>
+    <image:image><image:loc>https://cdn.shop.test/1.jpg</image:loc></image:image>
+  </url>
+  <url><loc><![CDATA[https://shop.test/p/2?a=1&b=2]]></loc></url>
+  <url><lastmod>2024-01-01</lastmod></url>
+</urlset>"#;
+
+    const BLOG_2023: &str = r#"<urlset><url><loc>https://shop.test/blog/old</loc><lastmod>2023-06-01</lastmod></url></urlset>"#;
+    const BLOG_2024: &str = r#"<urlset><url><loc>https://shop.test/blog/new</loc><lastmod>2024-04-01</lastmod></url></urlset>"#;
+
+    fn fixtures() -> HashMap<String, &'static str> {
+        [
+            ("https://shop.test/sitemap.xml", ROOT_INDEX),
+            ("https://shop.test/sitemaps/blog-index.xml", BLOG_INDEX),
+            ("https://shop.test/sitemaps/products.xml", PRODUCTS),
+            ("https://shop.test/sitemaps/blog-2023.xml", BLOG_2023),
+            ("https://shop.test/sitemaps/blog-2024.xml", BLOG_2024),
+        ]
+        .into_iter()
+        .map(|(url, xml)| (url.to_string(), xml))
+        .collect()
+    }
+
+    // Every page the root leads to, and the sitemaps fetched on the way
+    fn collect_fixtures(sites: &HashMap<String, &'static str>, filter: &SitemapFilter) -> (Result<Vec<String>>, Vec<String>) {
+        let mut fetched = Vec::new();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let urls = runtime.block_on(collect("https://shop.test/sitemap.xml", filter, |url| {
+            fetched.push(url.clone());
+            let xml = sites.get(&url).map(|xml| xml.to_string()).ok_or_else(|| anyhow::anyhow!("HTTP 404"));
+            async move { xml }
+        }));
+        (urls, fetched)
+    }
+
+    #[test]
+    fn urlsets_list_their_pages() {
+        let Sitemap::UrlSet(entries) = parse(PRODUCTS).unwrap() else {
+            panic!("products.xml is a urlset");
+        };
+        let locs: Vec<&str> = entries.iter().map(|entry| entry.loc.as_str()).collect();
+        assert_eq!(locs, ["https://shop.test/p/1?color=red&size=m", "https://shop.test/p/2?a=1&b=2"]);
+        assert_eq!(entries[0].lastmod.as_deref(), Some("2024-02-10T08:00:00+00:00"));
+        assert_eq!(entries[0].modified(), NaiveDate::from_ymd_opt(2024, 2, 10));
+        assert_eq!(entries[1].lastmod, None);
+    }
+
+    #[test]
+    fn indexes_list_their_sitemaps() {
+        let Sitemap::Index(entries) = parse(ROOT_INDEX).unwrap() else {
+            panic!("sitemap.xml is an index");
+        };
+        let locs: Vec<&str> = entries.iter().map(|entry| entry.loc.as_str()).collect();
+        assert_eq!(locs, ["https://shop.test/sitemaps/products.xml", "https://shop.test/sitemaps/blog-index.xml"]);
+        assert!(parse("<html><body>Not found</body></html>").is_err());
+    }
+
+    #[test]
+    fn nested_indexes_are_followed_in_order_and_read_once() {
+        let (urls, fetched) = collect_fixtures(&fixtures(), &SitemapFilter::default());
+        assert_eq!(urls.unwrap(), [
+            "https://shop.test/p/1?color=red&size=m",
+            "https://shop.test/p/2?a=1&b=2",
+            "https://shop.test/blog/old",
+            "https://shop.test/blog/new",
+        ]);
+        assert_eq!(fetched.len(), 5);
+    }
+
+    #[test]
+    fn filters_apply_to_pages_of_nested_sitemaps() {
+        let filter = SitemapFilter { pattern: Some("/blog/".to_string()), since: None };
+        assert_eq!(collect_fixtures(&fixtures(), &filter).0.unwrap(), ["https://shop.test/blog/old", "https://shop.test/blog/new"]);
+
+        let filter = SitemapFilter { pattern: None, since: Some(SitemapFilter::parse_since("2024-02-01").unwrap()) };
+        assert_eq!(
+            collect_fixtures(&fixtures(), &filter).0.unwrap(),
+            ["https://shop.test/p/1?color=red&size=m", "https://shop.test/blog/new"]
+        );
+        assert!(SitemapFilter::parse_since("02/01/2024").is_err());
+    }
+
+    #[test]
+    fn a_broken_nested_sitemap_fails_the_whole_read() {
+        let mut sites = fixtures();
+        sites.insert("https://shop.test/sitemaps/blog-2024.xml".to_string(), "<html>moved</html>");
+        let error = collect_fixtures(&sites, &SitemapFilter::default()).0.unwrap_err().to_string();
+        assert!(error.starts_with("https://shop.test/sitemaps/blog-2024.xml: Not a sitemap"), "{}", error);
+
+        sites.remove("https://shop.test/sitemaps/blog-2024.xml");
+        assert!(collect_fixtures(&sites, &SitemapFilter::default()).0.is_err());
+    }
+
+    #[test]
+    fn runaway_indexes_stop_at_the_limit() {
+        // Each index leads to the next one, forever
+        let chain = |n: usize| {
+            format!("<sitemapindex><sitemap><loc>https://shop.test/sitemap-{}.xml</loc></sitemap></sitemapindex>", n + 1)
+        };
+        let mut fetched = 0;
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let result = runtime.block_on(collect("https://shop.test/sitemap-0.xml", &SitemapFilter::default(), |_| {
+            fetched += 1;
+            let xml = chain(fetched);
+            async move { Ok(xml) }
+        }));
+        assert!(result.unwrap_err().to_string().contains("more than 200 sitemaps"));
+        assert_eq!(fetched, MAX_SITEMAPS);
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+// Output shape of `table`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TableFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl TableFormat {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!("Unknown format '{}' (expected csv or json)", name)),
+        }
+    }
+}
+
+// A table's cells as the page lays them out (see TABLE_JS): colspan and rowspan already repeated into
+// every slot they cover, so all rows line up. The first `header_rows` rows are headers.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TableGrid {
+    pub rows: Vec<Vec<String>>,
+    pub header_rows: usize,
+}
+
+// JS function reading a <table> into a TableGrid. Header rows are the <thead>'s, or else the leading rows
+// made only of <th> cells. Nested tables are left to their own cells' text.
+pub const TABLE_JS: &str = r#"(table) => {
+    const text = cell => (cell.innerText ?? cell.textContent ?? '').replace(/\s+/g, ' ').trim();
+    const rows = Array.from(table.rows);
+    const grid = [];
+    rows.forEach((row, r) => {
+        grid[r] = grid[r] || [];
+        let c = 0;
+        for (const cell of row.cells) {
+            while (grid[r][c] !== undefined) c++;
+            const value = text(cell);
+            const colspan = Math.max(1, cell.colSpan || 1);
+            // rowspan 0 stretches to the last row
+            const rowspan = cell.rowSpan === 0 ? rows.length - r : Math.max(1, cell.rowSpan || 1);
+            for (let dr = 0; dr < rowspan && r + dr < rows.length; dr++) {
+                grid[r + dr] = grid[r + dr] || [];
+                for (let dc = 0; dc < colspan; dc++) grid[r + dr][c + dc] = value;
+            }
+            c += colspan;
+        }
+    });
+    // table.rows lists the thead's rows first wherever it sits in the markup
+    let headerRows = table.tHead ? table.tHead.rows.length : 0;
+    if (!headerRows) {
+        while (headerRows < rows.length && rows[headerRows].cells.length
+            && Array.from(rows[headerRows].cells).every(cell => cell.localName === 'th')) headerRows++;
+    }
+    return { rows: grid.map(row => Array.from(row, value => value ?? '')), header_rows: headerRows };
+}"#;
+
+// A table as headers and data rows, all rows as wide as the widest
+#[derive(Debug, Clone)]
+pub struct Table {
+    // One per column; empty when the table has no header rows
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    // Stacked header rows make one header per column, e.g. "Sales / Q1" under a colspan'd "Sales"
+    pub fn from_grid(grid: TableGrid) -> Self {
+        let width = grid.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut rows: Vec<Vec<String>> = grid.rows.into_iter()
+            .map(|mut row| {
+                row.resize(width, String::new());
+                row
+            })
+            .collect();
+        let header_rows = grid.header_rows.min(rows.len());
+        let body = rows.split_off(header_rows);
+        let headers = if rows.is_empty() {
+            Vec::new()
+        } else {
+            (0..width)
+                .map(|column| {
+                    let mut parts: Vec<&str> = Vec::new();
+                    for row in &rows {
+                        let part = row[column].as_str();
+                        if !part.is_empty() && parts.last() != Some(&part) {
+                            parts.push(part);
+                        }
+                    }
+                    parts.join(" / ")
+                })
+                .collect()
+        };
+        Self { headers, rows: body }
+    }
+
+    // RFC 4180 quoting: fields with commas, quotes or line breaks quoted, quotes doubled. No trailing newline.
+    pub fn to_csv(&self) -> String {
+        let line = |row: &[String]| {
+            row.iter()
+                .map(|field| {
+                    if field.contains([',', '"', '\n', '\r']) {
+                        format!("\"{}\"", field.replace('"', "\"\""))
+                    } else {
+                        field.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        std::iter::once(&self.headers)
+            .filter(|headers| !headers.is_empty())
+            .chain(&self.rows)
+            .map(|row| line(row))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // An object per row keyed by header; blank headers become "column N" and repeated ones get a "_2"
+    // suffix. Without headers, an array per row.
+    pub fn to_json(&self) -> Value {
+        if self.headers.is_empty() {
+            return Value::from(self.rows.clone());
+        }
+        let mut keys: Vec<String> = Vec::new();
+        for (index, header) in self.headers.iter().enumerate() {
+            let base = if header.is_empty() { format!("column {}", index + 1) } else { header.clone() };
+            let mut key = base.clone();
+            let mut n = 2;
+            while keys.contains(&key) {
+                key = format!("{}_{}", base, n);
+                n += 1;
+            }
+            keys.push(key);
+        }
+        Value::Array(
+            self.rows.iter()
+                .map(|row| {
+                    let object: Map<String, Value> = keys.iter().cloned().zip(row.iter().map(|cell| Value::from(cell.as_str()))).collect();
+                    Value::Object(object)
+                })
+                .collect(),
+        )
+    }
+}